@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use crate::symbol::Symbol;
+
+/// Number of rows after which [`Table::push`] first merges the rows with equal keys
+const COMPACT_AT: usize = 1 << 20;
+
+/// Records of string keys and numeric values, stored column by column with the keys interned as
+/// [`Symbol`]s, to sum up millions of records without a `String` for each of them
+pub struct Table {
+    keys: Vec<Vec<Symbol>>,
+    values: Vec<Vec<u64>>,
+    rows: usize,
+    compact_at: usize,
+}
+
+impl Table {
+    /// Creates a table with the given number of key and value columns
+    pub fn new(key_columns: usize, value_columns: usize) -> Self {
+        Table {
+            keys: vec![Vec::new(); key_columns],
+            values: vec![Vec::new(); value_columns],
+            rows: 0,
+            compact_at: COMPACT_AT,
+        }
+    }
+
+    /// Number of rows, which can be fewer than were pushed once rows with equal keys are merged
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends a record with one key and one value per column. Once the table grows large,
+    /// rows with equal keys are merged into one by summing their values.
+    pub fn push(&mut self, keys: &[&str], values: &[u64]) {
+        assert_eq!(keys.len(), self.keys.len());
+        assert_eq!(values.len(), self.values.len());
+        for (column, key) in self.keys.iter_mut().zip(keys) {
+            column.push(Symbol::intern(key));
+        }
+        for (column, value) in self.values.iter_mut().zip(values) {
+            column.push(*value);
+        }
+        self.rows += 1;
+        if self.rows >= self.compact_at {
+            self.compact();
+            // Tables with mostly distinct keys shrink little, so don't merge again right away
+            self.compact_at = (self.rows * 2).max(COMPACT_AT);
+        }
+    }
+
+    /// Merges the rows with equal keys by summing their values
+    pub fn compact(&mut self) {
+        let all = (0..self.keys.len()).collect::<Vec<_>>();
+        let groups = self.group_ids(&all);
+        for column in &mut self.keys {
+            column.clear();
+        }
+        for column in &mut self.values {
+            column.clear();
+        }
+        self.rows = groups.len();
+        for (keys, values) in groups {
+            for (column, key) in self.keys.iter_mut().zip(keys) {
+                column.push(key);
+            }
+            for (column, value) in self.values.iter_mut().zip(values) {
+                column.push(value);
+            }
+        }
+    }
+
+    /// Sums the values of all rows grouped by the given key columns, ordered by the first value
+    /// column descending and then by the keys
+    pub fn group_by(&self, columns: &[usize]) -> Vec<(Vec<Symbol>, Vec<u64>)> {
+        let mut groups = self.group_ids(columns);
+        groups.sort_by(|(a_keys, a), (b_keys, b)| {
+            b.first().cmp(&a.first()).then_with(|| a_keys.cmp(b_keys))
+        });
+        groups
+    }
+
+    fn group_ids(&self, columns: &[usize]) -> Vec<(Vec<Symbol>, Vec<u64>)> {
+        let mut index = HashMap::<Vec<Symbol>, usize>::new();
+        let mut groups = Vec::<(Vec<Symbol>, Vec<u64>)>::new();
+        let mut keys = Vec::with_capacity(columns.len());
+        for row in 0..self.rows {
+            keys.clear();
+            keys.extend(columns.iter().map(|&column| self.keys[column][row]));
+            let group = match index.get(&keys) {
+                Some(&group) => group,
+                None => {
+                    index.insert(keys.clone(), groups.len());
+                    groups.push((keys.clone(), vec![0; self.values.len()]));
+                    groups.len() - 1
+                }
+            };
+            for (sum, column) in groups[group].1.iter_mut().zip(&self.values) {
+                *sum += column[row];
+            }
+        }
+        groups
+    }
+}
@@ -0,0 +1,141 @@
+use std::env;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::location::Location;
+
+/// The environment variable with the salt used when none is given on the command line, so that
+/// all tools give a player the same pseudonym
+pub const SALT_ENV: &str = "WORLD_STATISTICS_ANONYMIZE_SALT";
+
+#[derive(thiserror::Error, Debug)]
+pub enum AnonymizeError {
+    #[error("--anonymize needs a secret salt, pass --anonymize-salt or set {SALT_ENV}")]
+    MissingSalt,
+}
+
+/// The command line flags for sharing the output of a tool, shared by all tools printing players
+/// or coordinates
+#[derive(clap::Args, Debug)]
+pub struct AnonymizeArgs {
+    /// Replace players with pseudonyms, e.g. to share the output publicly
+    #[clap(long)]
+    pub anonymize: bool,
+
+    /// Secret key of the pseudonyms, required with --anonymize unless
+    /// $WORLD_STATISTICS_ANONYMIZE_SALT is set. The same salt gives the same pseudonyms.
+    #[clap(long, requires = "anonymize")]
+    pub anonymize_salt: Option<String>,
+
+    /// Round coordinates down to the corner of their region so that bases can't be found from
+    /// the output
+    #[clap(long)]
+    pub coarse_coordinates: bool,
+}
+
+impl AnonymizeArgs {
+    pub fn anonymizer(&self) -> Result<Anonymizer, AnonymizeError> {
+        if !self.anonymize {
+            return Ok(Anonymizer::new(None, self.coarse_coordinates));
+        }
+        let salt = match &self.anonymize_salt {
+            Some(salt) => salt.clone(),
+            None => env::var(SALT_ENV).unwrap_or_default(),
+        };
+        if salt.is_empty() {
+            return Err(AnonymizeError::MissingSalt);
+        }
+        Ok(Anonymizer::new(Some(&salt), self.coarse_coordinates))
+    }
+}
+
+/// Replaces player names and UUIDs with stable pseudonyms and optionally rounds coordinates to
+/// the region they are in, so that reports can be shared without revealing who lives where
+pub struct Anonymizer {
+    salt: Option<Vec<u8>>,
+    coarse_coordinates: bool,
+}
+
+impl Anonymizer {
+    /// Players are only replaced with a salt, which is the key of the pseudonyms and should be
+    /// kept secret, as anyone who knows it can tell which player is which by hashing their
+    /// UUIDs. The same salt gives the same pseudonyms across runs.
+    pub fn new(salt: Option<&str>, coarse_coordinates: bool) -> Self {
+        Anonymizer {
+            salt: salt.map(|salt| salt.as_bytes().to_vec()),
+            coarse_coordinates,
+        }
+    }
+
+    /// Returns a pseudonym like `player-1a2b3c4d5e6f7081` for a player's UUID, the start of
+    /// its HMAC-SHA256 keyed with the salt, or None if players aren't replaced. Pass the UUID
+    /// rather than the name where possible, since players can change their names.
+    pub fn player(&self, uuid: &str) -> Option<String> {
+        let salt = self.salt.as_ref()?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(salt).expect("HMAC takes keys of any size");
+        mac.update(uuid.to_lowercase().as_bytes());
+        let digest = mac.finalize().into_bytes();
+        let hex: String = digest[..8]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        Some(format!("player-{}", hex))
+    }
+
+    /// Rounds a block coordinate down to the corner of its region if coordinates are coarsened
+    pub fn coordinate(&self, coordinate: i32) -> i32 {
+        if self.coarse_coordinates {
+            coordinate & !511
+        } else {
+            coordinate
+        }
+    }
+
+    /// Rounds the x and z coordinates of a block position, keeping the height
+    pub fn pos(&self, [x, y, z]: [i32; 3]) -> [i32; 3] {
+        [self.coordinate(x), y, self.coordinate(z)]
+    }
+
+    pub fn location(&self, location: &mut Location) {
+        location.x = self.coordinate(location.x);
+        location.z = self.coordinate(location.z);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pseudonyms_are_keyed_with_the_salt() {
+        // test case 2 of RFC 4231
+        let anonymizer = Anonymizer::new(Some("Jefe"), false);
+        assert_eq!(
+            anonymizer.player("what do ya want for nothing?").as_deref(),
+            Some("player-5bdcc146bf60754e")
+        );
+
+        let uuid = "853c80ef-3c37-49fd-aa49-938b674adae6";
+        let other = Anonymizer::new(Some("other"), false);
+        assert_eq!(
+            anonymizer.player(uuid),
+            anonymizer.player(&uuid.to_uppercase())
+        );
+        assert_ne!(anonymizer.player(uuid), other.player(uuid));
+        assert_eq!(Anonymizer::new(None, true).player(uuid), None);
+    }
+
+    #[test]
+    fn anonymize_needs_a_salt() {
+        let args = AnonymizeArgs {
+            anonymize: true,
+            anonymize_salt: Some(String::new()),
+            coarse_coordinates: false,
+        };
+        assert!(matches!(
+            args.anonymizer(),
+            Err(AnonymizeError::MissingSalt)
+        ));
+    }
+}
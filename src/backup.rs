@@ -0,0 +1,92 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Copies of the region and `.dat` files a command is about to change, taken before their first
+/// write. Unless [`finish`](Self::finish) is called, e.g. because the command failed with an
+/// error halfway, the files are restored from the copies when the backup is dropped.
+pub struct Backup {
+    dir: PathBuf,
+    world_path: PathBuf,
+    /// Paths relative to the world directory, and whether the file existed
+    files: Vec<(PathBuf, bool)>,
+    finished: bool,
+}
+
+impl Backup {
+    /// Creates the backup in a new directory named after the current time inside `dir`, so
+    /// that backups of several runs don't overwrite each other
+    pub fn new(dir: &Path, world_path: &Path) -> Self {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        Backup {
+            dir: dir.join(time.to_string()),
+            world_path: world_path.to_path_buf(),
+            files: Vec::new(),
+            finished: false,
+        }
+    }
+
+    /// Copies the file into the backup unless it already was
+    pub fn save(&mut self, path: &Path) -> io::Result<()> {
+        let relative = path.strip_prefix(&self.world_path).unwrap_or(path);
+        if self.files.iter().any(|(saved, _)| saved == relative) {
+            return Ok(());
+        }
+        let exists = path.exists();
+        if exists {
+            let backup_path = self.dir.join(relative);
+            if let Some(parent) = backup_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(path, backup_path)?;
+        }
+        self.files.push((relative.to_path_buf(), exists));
+        Ok(())
+    }
+
+    /// Keeps the changes and the backup
+    pub fn finish(mut self) {
+        self.finished = true;
+        if !self.files.is_empty() {
+            eprintln!(
+                "backed up {} files to {}",
+                self.files.len(),
+                self.dir.display()
+            );
+        }
+    }
+
+    /// Puts back the files as they were before the first write
+    pub fn restore(&self) -> io::Result<()> {
+        for (relative, existed) in &self.files {
+            let path = self.world_path.join(relative);
+            if *existed {
+                fs::copy(self.dir.join(relative), &path)?;
+            } else if path.exists() {
+                fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Backup {
+    fn drop(&mut self) {
+        if self.finished || self.files.is_empty() {
+            return;
+        }
+        match self.restore() {
+            Ok(()) => eprintln!(
+                "restored {} files from {}",
+                self.files.len(),
+                self.dir.display()
+            ),
+            Err(e) => eprintln!("failed to restore files from {}: {}", self.dir.display(), e),
+        }
+    }
+}
@@ -0,0 +1,468 @@
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+
+use crate::version::GameVersion;
+
+/// The dye colors by name in the order of their numeric ids since 1.13, with the colors banners
+/// are drawn in
+pub const DYE_COLORS: [(&str, [u8; 3]); 16] = [
+    ("white", [0xf9, 0xff, 0xfe]),
+    ("orange", [0xf9, 0x80, 0x1d]),
+    ("magenta", [0xc7, 0x4e, 0xbd]),
+    ("light_blue", [0x3a, 0xb3, 0xda]),
+    ("yellow", [0xfe, 0xd8, 0x3d]),
+    ("lime", [0x80, 0xc7, 0x1f]),
+    ("pink", [0xf3, 0x8b, 0xaa]),
+    ("gray", [0x47, 0x4f, 0x52]),
+    ("light_gray", [0x9d, 0x9d, 0x97]),
+    ("cyan", [0x16, 0x9c, 0x9c]),
+    ("purple", [0x89, 0x32, 0xb8]),
+    ("blue", [0x3c, 0x44, 0xaa]),
+    ("brown", [0x83, 0x54, 0x32]),
+    ("green", [0x5e, 0x7c, 0x16]),
+    ("red", [0xb0, 0x2e, 0x26]),
+    ("black", [0x1d, 0x1d, 0x21]),
+];
+
+/// The codes patterns were stored as before 1.20.5, with the ids they have since
+const PATTERN_CODES: &[(&str, &str)] = &[
+    ("b", "base"),
+    ("bl", "square_bottom_left"),
+    ("br", "square_bottom_right"),
+    ("tl", "square_top_left"),
+    ("tr", "square_top_right"),
+    ("bs", "stripe_bottom"),
+    ("ts", "stripe_top"),
+    ("ls", "stripe_left"),
+    ("rs", "stripe_right"),
+    ("cs", "stripe_center"),
+    ("ms", "stripe_middle"),
+    ("drs", "stripe_downright"),
+    ("dls", "stripe_downleft"),
+    ("ss", "small_stripes"),
+    ("cr", "cross"),
+    ("sc", "straight_cross"),
+    ("bt", "triangle_bottom"),
+    ("tt", "triangle_top"),
+    ("bts", "triangles_bottom"),
+    ("tts", "triangles_top"),
+    ("ld", "diagonal_left"),
+    ("rd", "diagonal_up_right"),
+    ("lud", "diagonal_up_left"),
+    ("rud", "diagonal_right"),
+    ("mc", "circle"),
+    ("mr", "rhombus"),
+    ("vh", "half_vertical"),
+    ("hh", "half_horizontal"),
+    ("vhr", "half_vertical_right"),
+    ("hhb", "half_horizontal_bottom"),
+    ("bo", "border"),
+    ("cbo", "curly_border"),
+    ("gra", "gradient"),
+    ("gru", "gradient_up"),
+    ("bri", "bricks"),
+    ("glb", "globe"),
+    ("cre", "creeper"),
+    ("sku", "skull"),
+    ("flo", "flower"),
+    ("moj", "mojang"),
+    ("pig", "piglin"),
+    ("flw", "flow"),
+    ("gus", "guster"),
+];
+
+/// Width of the front of a banner in pixels of the game's texture
+pub const BANNER_WIDTH: usize = 20;
+/// Height of the front of a banner in pixels of the game's texture
+pub const BANNER_HEIGHT: usize = 40;
+
+/// The base color of a banner or shield and the patterns layered on top of it, which is the
+/// same for a placed banner, the banner item and a shield carrying it
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BannerDesign {
+    /// The name of the dye color, like `red`
+    pub base: String,
+    /// The pattern ids without the `minecraft:` namespace with the names of their colors,
+    /// bottom layer first
+    pub patterns: Vec<(String, String)>,
+}
+
+impl BannerDesign {
+    /// Reads the design of a banner block entity. Since 1.13 the base color is only part of the
+    /// name of the block, like `minecraft:red_wall_banner`, which has to be passed.
+    pub fn from_block_entity(
+        block_entity: &NbtCompound,
+        block_name: Option<&str>,
+        version: GameVersion,
+    ) -> Option<Self> {
+        let legacy = !version.named_block_states();
+        let base = match block_name {
+            Some(name) if !legacy => {
+                let name = name.strip_prefix("minecraft:").unwrap_or(name);
+                let color = name
+                    .strip_suffix("_wall_banner")
+                    .or_else(|| name.strip_suffix("_banner"))?;
+                color.to_string()
+            }
+            _ => color_name(block_entity.inner().get("Base")?, legacy)?,
+        };
+        Some(BannerDesign {
+            base,
+            patterns: patterns(block_entity, legacy),
+        })
+    }
+
+    /// Reads the design of a banner or a decorated shield, from `tag.BlockEntityTag` before
+    /// 1.20.5 and the `banner_patterns` and `base_color` components since. Returns None for
+    /// other items and shields without a banner.
+    pub fn from_item(item: &NbtCompound, version: GameVersion) -> Option<Self> {
+        let legacy = !version.named_block_states();
+        let id = item.get::<_, &str>("id").ok()?;
+        let id = id.strip_prefix("minecraft:").unwrap_or(id);
+        let shield = id == "shield";
+        if !shield && !id.ends_with("banner") {
+            return None;
+        }
+
+        let data = item.get::<_, &NbtCompound>(version.item_data_key()).ok();
+        if version.item_components() {
+            let base = match shield {
+                true => data?.get::<_, &str>("minecraft:base_color").ok()?,
+                false => id.strip_suffix("_banner")?,
+            };
+            let patterns = data
+                .and_then(|components| {
+                    components
+                        .get::<_, &NbtList>("minecraft:banner_patterns")
+                        .ok()
+                })
+                .map(|list| pattern_list(list, false))
+                .unwrap_or_default();
+            return Some(BannerDesign {
+                base: base.to_string(),
+                patterns,
+            });
+        }
+
+        let block_entity_tag =
+            data.and_then(|tag| tag.get::<_, &NbtCompound>("BlockEntityTag").ok());
+        let base = match id.strip_suffix("_banner") {
+            Some(color) => color.to_string(),
+            // banners had their color as damage value before 1.13
+            None if id == "banner" => color_name(item.inner().get("Damage")?, true)?,
+            None => color_name(block_entity_tag?.inner().get("Base")?, legacy)?,
+        };
+        Some(BannerDesign {
+            base,
+            patterns: block_entity_tag
+                .map(|tag| patterns(tag, legacy))
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Draws the front of the banner at `scale` times the size of the game's texture, returning
+    /// the width, height and RGB pixels row by row. Patterns added by datapacks are left out.
+    pub fn render(&self, scale: usize) -> (usize, usize, Vec<u8>) {
+        let scale = scale.max(1);
+        let mut pixels = vec![dye_color(&self.base); BANNER_WIDTH * BANNER_HEIGHT];
+        for (pattern, color) in &self.patterns {
+            let color = dye_color(color);
+            for y in 0..BANNER_HEIGHT {
+                for x in 0..BANNER_WIDTH {
+                    let alpha = coverage(pattern, x, y);
+                    if alpha > 0.0 {
+                        let pixel = &mut pixels[y * BANNER_WIDTH + x];
+                        for channel in 0..3 {
+                            pixel[channel] = (pixel[channel] as f32 * (1.0 - alpha)
+                                + color[channel] as f32 * alpha)
+                                .round() as u8;
+                        }
+                    }
+                }
+            }
+        }
+
+        let (width, height) = (BANNER_WIDTH * scale, BANNER_HEIGHT * scale);
+        let mut rgb = Vec::with_capacity(width * height * 3);
+        for y in 0..height {
+            for x in 0..width {
+                rgb.extend_from_slice(&pixels[y / scale * BANNER_WIDTH + x / scale]);
+            }
+        }
+        (width, height, rgb)
+    }
+}
+
+/// Returns the color of a dye by name, or magenta for unknown names
+pub fn dye_color(name: &str) -> [u8; 3] {
+    DYE_COLORS
+        .iter()
+        .find(|(color, _)| *color == name)
+        .map_or([0xff, 0x00, 0xff], |&(_, rgb)| rgb)
+}
+
+/// The name of a dye color stored as its name or numeric id. Before 1.13 the ids were dye
+/// damage values, which count the other way round.
+fn color_name(tag: &NbtTag, legacy: bool) -> Option<String> {
+    let id = match tag {
+        NbtTag::String(name) => return Some(name.clone()),
+        NbtTag::Int(id) => *id,
+        NbtTag::Short(id) => *id as i32,
+        NbtTag::Byte(id) => *id as i32,
+        _ => return None,
+    };
+    let id = if legacy { 15 - id } else { id };
+    Some(DYE_COLORS.get(usize::try_from(id).ok()?)?.0.to_string())
+}
+
+/// The patterns of a banner block entity or a `BlockEntityTag`
+fn patterns(compound: &NbtCompound, legacy: bool) -> Vec<(String, String)> {
+    compound
+        .get::<_, &NbtList>("patterns")
+        .or_else(|_| compound.get::<_, &NbtList>("Patterns"))
+        .map(|list| pattern_list(list, legacy))
+        .unwrap_or_default()
+}
+
+/// Reads the patterns stored as `Pattern` codes and numeric `Color`s before 1.20.5 and as
+/// `pattern` ids and `color` names since. Patterns defined inline by datapacks are named after
+/// their asset id.
+fn pattern_list(list: &NbtList, legacy: bool) -> Vec<(String, String)> {
+    list.iter_map::<&NbtCompound>()
+        .flatten()
+        .filter_map(|layer| {
+            let pattern = match layer.inner().get("pattern") {
+                Some(NbtTag::String(id)) => id.clone(),
+                Some(NbtTag::Compound(inline)) => inline.get::<_, &str>("asset_id").ok()?.into(),
+                _ => {
+                    let code = layer.get::<_, &str>("Pattern").ok()?;
+                    PATTERN_CODES
+                        .iter()
+                        .find(|(legacy_code, _)| *legacy_code == code)
+                        .map_or(code, |(_, id)| id)
+                        .to_string()
+                }
+            };
+            let pattern = pattern.strip_prefix("minecraft:").unwrap_or(&pattern);
+            let color = layer
+                .inner()
+                .get("color")
+                .or_else(|| layer.inner().get("Color"))?;
+            Some((pattern.to_string(), color_name(color, legacy)?))
+        })
+        .collect()
+}
+
+/// How much of the pixel a pattern covers, from 0 to 1. The shapes follow the game's textures
+/// closely enough to recognize a design, the emblems are simplified.
+fn coverage(pattern: &str, x: usize, y: usize) -> f32 {
+    let (w, h) = (BANNER_WIDTH as f32, BANNER_HEIGHT as f32);
+    // the center of the pixel relative to the banner, from 0 to 1
+    let fx = (x as f32 + 0.5) / w;
+    let fy = (y as f32 + 0.5) / h;
+    // the offset of the pixel from the center in pixels
+    let (dx, dy) = (x as f32 + 0.5 - w / 2.0, y as f32 + 0.5 - h / 2.0);
+    let inside = match pattern {
+        "base" => true,
+        "square_bottom_left" => x < 10 && y >= 27,
+        "square_bottom_right" => x >= 10 && y >= 27,
+        "square_top_left" => x < 10 && y < 13,
+        "square_top_right" => x >= 10 && y < 13,
+        "stripe_bottom" => y >= 27,
+        "stripe_top" => y < 13,
+        "stripe_left" => x < 7,
+        "stripe_right" => x >= 13,
+        "stripe_center" => (7..13).contains(&x),
+        "stripe_middle" => (17..23).contains(&y),
+        "stripe_downright" => (fx - fy).abs() < 0.15,
+        "stripe_downleft" => (fx + fy - 1.0).abs() < 0.15,
+        "small_stripes" => x % 4 == 1 || x % 4 == 2,
+        "cross" => (fx - fy).abs() < 0.12 || (fx + fy - 1.0).abs() < 0.12,
+        "straight_cross" => (8..12).contains(&x) || (18..22).contains(&y),
+        "triangle_bottom" => y >= 30 && dx.abs() <= (y - 29) as f32,
+        "triangle_top" => y < 10 && dx.abs() <= (10 - y) as f32,
+        "triangles_bottom" => y >= 35 && (x % 5).abs_diff(2) <= y - 35,
+        "triangles_top" => y < 5 && (x % 5).abs_diff(2) <= 4 - y,
+        "diagonal_left" => fx + fy < 1.0,
+        "diagonal_right" => fx > fy,
+        "diagonal_up_left" => fx < fy,
+        "diagonal_up_right" => fx + fy > 1.0,
+        "circle" => dx * dx + dy * dy < 25.0,
+        "rhombus" => {
+            (x as f32 + 0.5 - w / 2.0).abs() / 6.0 + (y as f32 + 0.5 - h / 2.0).abs() / 9.0 < 1.0
+        }
+        "half_vertical" => x < 10,
+        "half_vertical_right" => x >= 10,
+        "half_horizontal" => y < 20,
+        "half_horizontal_bottom" => y >= 20,
+        "border" => !(2..18).contains(&x) || !(2..38).contains(&y),
+        "curly_border" => {
+            let edge = x.min(19 - x).min(y).min(39 - y);
+            edge < 1 || (edge < 3 && (x + y) % 4 < 2)
+        }
+        "gradient" => return 1.0 - fy,
+        "gradient_up" => return fy,
+        "bricks" => y.is_multiple_of(4) || (x + (y / 4 % 2) * 4).is_multiple_of(8),
+        emblem => {
+            let Some(rows) = EMBLEMS
+                .iter()
+                .find(|(name, _)| *name == emblem)
+                .map(|e| e.1)
+            else {
+                return 0.0;
+            };
+            // the emblems sit in the upper middle of the banner
+            let (left, top) = (4, 10);
+            x >= left
+                && y >= top
+                && rows
+                    .get(y - top)
+                    .and_then(|row| row.as_bytes().get(x - left))
+                    == Some(&b'#')
+        }
+    };
+    if inside {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Simplified 12x12 pictures of the emblem patterns
+const EMBLEMS: &[(&str, [&str; 12])] = &[
+    (
+        "creeper",
+        [
+            "............",
+            ".###....###.",
+            ".###....###.",
+            ".###....###.",
+            "....####....",
+            "....####....",
+            "...######...",
+            "...######...",
+            "...##..##...",
+            "...##..##...",
+            "............",
+            "............",
+        ],
+    ),
+    (
+        "skull",
+        [
+            "...######...",
+            "..########..",
+            "..#..##..#..",
+            "..#..##..#..",
+            "..########..",
+            "...#.##.#...",
+            "............",
+            "#..........#",
+            ".##......##.",
+            "...##..##...",
+            "...##..##...",
+            ".##......##.",
+        ],
+    ),
+    (
+        "flower",
+        [
+            ".....##.....",
+            "....####....",
+            "..#.####.#..",
+            ".###.##.###.",
+            ".####..####.",
+            "..##.##.##..",
+            "..##.##.##..",
+            ".####..####.",
+            ".###.##.###.",
+            "..#.####.#..",
+            "....####....",
+            ".....##.....",
+        ],
+    ),
+    (
+        "mojang",
+        [
+            "............",
+            ".##......##.",
+            ".###....###.",
+            ".####..####.",
+            ".##.####.##.",
+            ".##..##..##.",
+            ".##......##.",
+            ".##......##.",
+            ".##......##.",
+            "............",
+            "............",
+            "............",
+        ],
+    ),
+    (
+        "globe",
+        [
+            "....####....",
+            "..##.##.##..",
+            ".#..#..#..#.",
+            ".##########.",
+            "#..#....#..#",
+            "############",
+            "#..#....#..#",
+            ".##########.",
+            ".#..#..#..#.",
+            "..##.##.##..",
+            "....####....",
+            "............",
+        ],
+    ),
+    (
+        "piglin",
+        [
+            "............",
+            "............",
+            "...######...",
+            "..########..",
+            "..#..##..#..",
+            "..#..##..#..",
+            "..########..",
+            "...######...",
+            "....#..#....",
+            "............",
+            "............",
+            "............",
+        ],
+    ),
+    (
+        "flow",
+        [
+            "############",
+            "#..........#",
+            "#.########.#",
+            "#.#......#.#",
+            "#.#.####.#.#",
+            "#.#.#..#.#.#",
+            "#.#.#.##.#.#",
+            "#.#.#....#.#",
+            "#.#.######.#",
+            "#.#........#",
+            "#.##########",
+            "#...........",
+        ],
+    ),
+    (
+        "guster",
+        [
+            "..########..",
+            ".#........#.",
+            "#..######..#",
+            "#.#......#.#",
+            "#.#.####.#.#",
+            "#.#.#..#.#..",
+            "#.#.#..#.#..",
+            "#.#..##..#..",
+            "#..#....#...",
+            ".#..####....",
+            "..#.........",
+            "...########.",
+        ],
+    ),
+];
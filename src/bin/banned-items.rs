@@ -0,0 +1,288 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::OpenOptions,
+    path::PathBuf,
+};
+
+use clap::Parser;
+use eyre::eyre;
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+use world_statistics::{
+    backup::Backup,
+    interrupt::{handle_interrupts, interrupted, EXIT_INTERRUPTED},
+    items::{contents_mut, id_and_count, stack_mut, ILLEGAL_ITEMS},
+    journal::Journal,
+    redact::Redaction,
+    region::{encode_chunk, read_chunk, region_files, RegionFile},
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, ensure_closed, warn_if_open, Source, Sources},
+};
+
+/// Entity item lists where every index is an equipment slot, so items are replaced with empty
+/// compounds instead of being removed
+const FIXED_KEYS: &[&str] = &["ArmorItems", "HandItems"];
+
+/// Finds banned items in containers and entities, including inside shulker boxes. Only prints
+/// where they are unless --remove is given. Stop the server before removing.
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Items to look for, defaults to items that can't be obtained in survival like bedrock and
+    /// command blocks
+    #[clap(long, use_value_delimiter = true)]
+    items: Vec<String>,
+
+    /// Remove the items from the region files
+    #[clap(long)]
+    remove: bool,
+
+    /// Only print which items would be removed
+    #[clap(long, requires = "remove")]
+    dry_run: bool,
+
+    /// JSON lines file to record the previous content of changed chunks to, to be reverted
+    /// with undo
+    #[clap(long)]
+    journal: Option<PathBuf>,
+
+    /// Directory to copy the changed files to first, restored from if writing fails halfway
+    #[clap(long)]
+    backup: Option<PathBuf>,
+
+    /// Write even though the world is open in a running game or server
+    #[clap(long)]
+    force: bool,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+/// An item removed from a container or entity
+struct Removal {
+    item: NbtCompound,
+    pos: [i32; 3],
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    let write = args.remove && !args.dry_run;
+    if write {
+        ensure_closed(&world_path, args.force)?;
+    } else {
+        warn_if_open(&world_path);
+    }
+
+    let banned: HashSet<String> = if args.items.is_empty() {
+        ILLEGAL_ITEMS
+            .iter()
+            .map(|name| String::from("minecraft:") + name)
+            .collect()
+    } else {
+        args.items
+            .iter()
+            .map(|name| String::from("minecraft:") + name)
+            .collect()
+    };
+
+    let mut journal = match &args.journal {
+        Some(path) if write => Some(Journal::open(path, &world_path, "banned-items")?),
+        _ => None,
+    };
+    let mut backup = match &args.backup {
+        Some(dir) if write => Some(Backup::new(dir, &world_path)),
+        _ => None,
+    };
+
+    handle_interrupts()?;
+    let redaction = Redaction::global()?;
+    let mut found = BTreeMap::<String, u64>::new();
+
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[
+        (Source::Region, "containers there"),
+        (Source::Entities, "entities there"),
+    ]);
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+
+        for source in [Source::Region, Source::Entities] {
+            if !sources.has(dim_name, source) {
+                continue;
+            }
+            let folder = source.folder();
+
+            for (region_x, region_z, path) in region_files(&dim_path.join(folder))? {
+                if interrupted() {
+                    break;
+                }
+                let file = OpenOptions::new().read(true).write(write).open(&path)?;
+                let mut region_file = RegionFile::new(file)?.with_path(&path);
+
+                let mut changed_chunks = Vec::new();
+                region_file.for_each_chunk(|(index, _, buf)| {
+                    let chunk_x = region_x * 32 + (index % 32) as i32;
+                    let chunk_z = region_z * 32 + (index / 32) as i32;
+
+                    let mut chunk = match read_chunk(buf) {
+                        Ok(chunk) => chunk,
+                        Err(e) => {
+                            eprintln!("failed to read chunk {} {}: {}", chunk_x, chunk_z, e);
+                            return;
+                        }
+                    };
+                    let removals = if source == Source::Region {
+                        strip_chunk(&mut chunk, &banned)
+                    } else {
+                        strip_entities(&mut chunk, &banned)
+                    };
+
+                    for removal in &removals {
+                        let (id, count) = id_and_count(&removal.item);
+                        let [x, y, z] = removal.pos;
+                        let (x, z) = redaction.coordinates(x, z);
+                        println!("{} {} {} {} {} {}", dim_name, x, y, z, id, count);
+                        *found.entry(id.to_string()).or_default() += count;
+                    }
+                    if !removals.is_empty() && write {
+                        changed_chunks.push((index, chunk));
+                    }
+                })?;
+
+                for (index, chunk) in changed_chunks {
+                    if let Some(backup) = &mut backup {
+                        backup.save(&path)?;
+                    }
+                    if let Some(journal) = &mut journal {
+                        journal.record_chunk(&path, &mut region_file, index)?;
+                    }
+                    region_file.write_chunk(index, &encode_chunk(&chunk)?)?;
+                }
+            }
+        }
+    }
+
+    if let Some(backup) = backup {
+        backup.finish();
+    }
+    for (id, count) in &found {
+        eprintln!("{} {}", id, count);
+    }
+    let total: u64 = found.values().sum();
+    if args.dry_run {
+        eprintln!("would remove {} items", total);
+    } else if args.remove {
+        eprintln!("removed {} items", total);
+    } else {
+        eprintln!("found {} items, run with --remove to remove them", total);
+    }
+
+    if interrupted() {
+        eprintln!("interrupted, the remaining region files were left unchanged");
+        std::process::exit(EXIT_INTERRUPTED);
+    }
+
+    Ok(())
+}
+
+/// Strips the block entities of a terrain chunk and, before 1.17, the entities stored in it
+fn strip_chunk(chunk: &mut NbtCompound, banned: &HashSet<String>) -> Vec<Removal> {
+    let version = GameVersion::of(chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+    let level = match version.level_mut(chunk) {
+        Some(level) => level,
+        None => return Vec::new(),
+    };
+    let mut removals = Vec::new();
+    if let Ok(block_entities) = level.get_mut::<_, &mut NbtList>(version.block_entities_key()) {
+        removals.extend(strip_block_entities(block_entities, banned));
+    }
+    if version.entities_in_chunk() {
+        removals.extend(strip_entities(level, banned));
+    }
+    removals
+}
+
+fn strip_block_entities(block_entities: &mut NbtList, banned: &HashSet<String>) -> Vec<Removal> {
+    let mut removals = Vec::new();
+    for block_entity in block_entities.inner_mut() {
+        let block_entity = match block_entity {
+            NbtTag::Compound(block_entity) => block_entity,
+            _ => continue,
+        };
+        let pos = ["x", "y", "z"].map(|key| block_entity.get::<_, i32>(key).unwrap_or_default());
+        if let Ok(items) = block_entity.get_mut::<_, &mut NbtList>("Items") {
+            for item in strip(items, false, banned) {
+                removals.push(Removal { item, pos });
+            }
+        }
+    }
+    removals
+}
+
+/// Strips the entities in the `Entities` list of an entities chunk, or of the level of a terrain
+/// chunk before 1.17
+fn strip_entities(chunk: &mut NbtCompound, banned: &HashSet<String>) -> Vec<Removal> {
+    let mut removals = Vec::new();
+    let entities = match chunk.get_mut::<_, &mut NbtList>("Entities") {
+        Ok(entities) => entities,
+        Err(_) => return removals,
+    };
+    for entity in entities.inner_mut() {
+        let entity = match entity {
+            NbtTag::Compound(entity) => entity,
+            _ => continue,
+        };
+        let pos = match entity.get::<_, &NbtList>("Pos") {
+            Ok(pos) => [0, 1, 2].map(|i| pos.get::<f64>(i).unwrap_or_default().floor() as i32),
+            Err(_) => [0; 3],
+        };
+
+        let mut stripped = Vec::new();
+        for key in ["Items", "Inventory", "ArmorItems", "HandItems"] {
+            if let Ok(items) = entity.get_mut::<_, &mut NbtList>(key) {
+                stripped.extend(strip(items, FIXED_KEYS.contains(&key), banned));
+            }
+        }
+        if let Ok(item) = entity.get_mut::<_, &mut NbtCompound>("Item") {
+            if banned.contains(item.get::<_, &str>("id").unwrap_or_default()) {
+                if let Some(NbtTag::Compound(item)) = entity.inner_mut().remove("Item") {
+                    stripped.push(item);
+                }
+            } else if let Some(contents) = contents_mut(item) {
+                stripped.extend(strip(contents, false, banned));
+            }
+        }
+
+        removals.extend(stripped.into_iter().map(|item| Removal { item, pos }));
+    }
+    removals
+}
+
+/// Removes the banned items from a list and the container items inside it
+fn strip(list: &mut NbtList, fixed: bool, banned: &HashSet<String>) -> Vec<NbtCompound> {
+    let mut removed = Vec::new();
+    for index in (0..list.len()).rev() {
+        let item = match &mut list.inner_mut()[index] {
+            NbtTag::Compound(entry) => stack_mut(entry),
+            _ => continue,
+        };
+        if banned.contains(item.get::<_, &str>("id").unwrap_or_default()) {
+            // takes the item out of its `{slot, item}` entry before removing the entry
+            let item = std::mem::take(item);
+            if !fixed {
+                list.inner_mut().remove(index);
+            }
+            removed.push(item);
+        } else if let Some(contents) = contents_mut(item) {
+            removed.extend(strip(contents, false, banned));
+        }
+    }
+    removed
+}
@@ -0,0 +1,298 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::BufWriter,
+    path::PathBuf,
+};
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+use serde_json::{json, Value};
+use world_statistics::{
+    banners::BannerDesign,
+    blocks::ChunkBlocks,
+    dat::read_dat,
+    items::{for_each_item, id_and_count},
+    players::{find_usercache, playerdata_files, INVENTORY_KEYS},
+    png::write_png,
+    redact::print_json,
+    region::for_each_chunk_in_dir,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, Source, Sources},
+};
+
+/// Collects every distinct banner design of placed banners, banner items and shields, and draws
+/// each as a PNG image into a folder, listing how often it occurs and where
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Folder to write the images to, created if missing
+    #[clap(short, long, default_value = "banner-gallery")]
+    output: PathBuf,
+
+    /// Minimum number of patterns of the designs to include, 0 to include plain banners
+    #[clap(long, default_value = "1")]
+    min_patterns: usize,
+
+    /// Size of an image pixel in screen pixels; the banner texture is 20x40
+    #[clap(long, default_value = "4")]
+    scale: usize,
+
+    /// Maximum number of locations to list per design
+    #[clap(long, default_value = "10")]
+    max_locations: usize,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+/// Keys of entities that hold items, besides the single `Item` of item frames
+const ENTITY_ITEM_KEYS: &[&str] = &["Items", "ArmorItems", "HandItems", "Inventory"];
+
+#[derive(Default)]
+struct Entry {
+    placed: u64,
+    items: u64,
+    shields: u64,
+    locations: Vec<Value>,
+}
+
+impl Entry {
+    fn count(&self) -> u64 {
+        self.placed + self.items + self.shields
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[
+        (Source::Region, "banners there"),
+        (
+            Source::Entities,
+            "banners and shields held by entities there",
+        ),
+        (Source::Playerdata, "player inventories"),
+    ]);
+
+    let mut designs = HashMap::<BannerDesign, Entry>::new();
+    let mut add = |design: BannerDesign, kind: &str, count: u64, location: Value| {
+        let entry = designs.entry(design).or_default();
+        match kind {
+            "placed" => entry.placed += count,
+            "shield" => entry.shields += count,
+            _ => entry.items += count,
+        }
+        if entry.locations.len() < args.max_locations {
+            entry.locations.push(location);
+        }
+    };
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+
+        if sources.has(dim_name, Source::Region) {
+            for_each_chunk_in_dir(&dim_path.join("region"), |_, _, chunk| {
+                find_in_chunk(&chunk, dim_name, &mut add);
+            })?;
+        }
+        if sources.has(dim_name, Source::Entities) {
+            for_each_chunk_in_dir(&dim_path.join("entities"), |_, _, chunk| {
+                let version =
+                    GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+                if let Ok(entities) = chunk.get::<_, &NbtList>("Entities") {
+                    for entity in entities.iter_map::<&NbtCompound>().flatten() {
+                        find_in_entity(entity, version, dim_name, &mut add);
+                    }
+                }
+            })?;
+        }
+    }
+
+    if sources.playerdata {
+        let names = find_usercache(&world_path);
+        for (uuid, path) in playerdata_files(&world_path)? {
+            let player = match read_dat(&path) {
+                Ok(player) => player,
+                Err(e) => {
+                    eprintln!("failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let version = GameVersion::of(&player).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let name = names.get(&uuid).unwrap_or(&uuid);
+            for key in INVENTORY_KEYS.iter().chain(&["equipment"]) {
+                if let Some(items) = item_list(&player, key) {
+                    let location = json!({ "player": name, "in": key });
+                    find_items(&items, version, &location, &mut add);
+                }
+            }
+        }
+    }
+
+    let mut designs: Vec<_> = designs
+        .into_iter()
+        .filter(|(design, _)| design.patterns.len() >= args.min_patterns)
+        .collect();
+    designs
+        .sort_by(|(a, a_entry), (b, b_entry)| b_entry.count().cmp(&a_entry.count()).then(a.cmp(b)));
+
+    fs::create_dir_all(&args.output)
+        .with_context(|| format!("failed to create {}", args.output.display()))?;
+    let digits = designs.len().to_string().len().max(3);
+    let mut gallery = Vec::new();
+    for (rank, (design, entry)) in designs.iter().enumerate() {
+        let image = format!("banner-{:0digits$}.png", rank + 1, digits = digits);
+        let (width, height, rgb) = design.render(args.scale);
+        let file = File::create(args.output.join(&image))?;
+        write_png(BufWriter::new(file), width, height, &rgb)?;
+
+        gallery.push(json!({
+            "image": image,
+            "count": entry.count(),
+            "placed": entry.placed,
+            "items": entry.items,
+            "shields": entry.shields,
+            "base": design.base,
+            "patterns": design
+                .patterns
+                .iter()
+                .map(|(pattern, color)| json!({ "pattern": pattern, "color": color }))
+                .collect::<Vec<_>>(),
+            "locations": entry.locations,
+        }));
+    }
+    eprintln!(
+        "{} designs drawn into {}",
+        gallery.len(),
+        args.output.display()
+    );
+    print_json(&gallery)?;
+
+    Ok(())
+}
+
+/// Adds the placed banners of a chunk and the banners and shields in its containers
+fn find_in_chunk(
+    chunk: &NbtCompound,
+    dim_name: &str,
+    add: &mut impl FnMut(BannerDesign, &str, u64, Value),
+) {
+    let version = GameVersion::of(chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+    let level = match version.level(chunk) {
+        Some(level) => level,
+        None => return,
+    };
+    // the block states are only needed for the colors of placed banners
+    let mut blocks = None;
+    if let Ok(block_entities) = level.get::<_, &NbtList>(version.block_entities_key()) {
+        for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+            let id = block_entity.get::<_, &str>("id").unwrap_or("unknown");
+            let pos: [i32; 3] =
+                ["x", "y", "z"].map(|key| block_entity.get(key).unwrap_or_default());
+            if matches!(id, "minecraft:banner" | "Banner") {
+                let blocks =
+                    blocks.get_or_insert_with(|| ChunkBlocks::from_chunk_as(chunk, version));
+                let [x, y, z] = pos;
+                let name = blocks.name(x.rem_euclid(16) as usize, y, z.rem_euclid(16) as usize);
+                if let Some(design) = BannerDesign::from_block_entity(block_entity, name, version) {
+                    add(
+                        design,
+                        "placed",
+                        1,
+                        json!({ "dimension": dim_name, "pos": pos }),
+                    );
+                }
+            }
+            if let Ok(items) = block_entity.get::<_, &NbtList>("Items") {
+                let location = json!({ "dimension": dim_name, "pos": pos, "in": id });
+                find_items(items, version, &location, add);
+            }
+        }
+    }
+    // entities were stored in the terrain chunks before 1.17
+    if version.entities_in_chunk() {
+        if let Ok(entities) = level.get::<_, &NbtList>("Entities") {
+            for entity in entities.iter_map::<&NbtCompound>().flatten() {
+                find_in_entity(entity, version, dim_name, add);
+            }
+        }
+    }
+}
+
+/// Adds the banners and shields in the list and in the containers among them
+fn find_items(
+    items: &NbtList,
+    version: GameVersion,
+    location: &Value,
+    add: &mut impl FnMut(BannerDesign, &str, u64, Value),
+) {
+    for_each_item(items, &mut |item| {
+        if let Some(design) = BannerDesign::from_item(item, version) {
+            let (id, count) = id_and_count(item);
+            let kind = if id.ends_with("shield") {
+                "shield"
+            } else {
+                "item"
+            };
+            add(design, kind, count.max(1), location.clone());
+        }
+    });
+}
+
+/// Adds the banners and shields an entity holds, like an armor stand or an item frame
+fn find_in_entity(
+    entity: &NbtCompound,
+    version: GameVersion,
+    dim_name: &str,
+    add: &mut impl FnMut(BannerDesign, &str, u64, Value),
+) {
+    let pos: Vec<i32> = entity
+        .get::<_, &NbtList>("Pos")
+        .map(|pos| {
+            pos.iter_map::<f64>()
+                .flatten()
+                .map(|v| v.floor() as i32)
+                .collect()
+        })
+        .unwrap_or_default();
+    let location = json!({
+        "dimension": dim_name,
+        "pos": pos,
+        "in": entity.get::<_, &str>("id").unwrap_or("unknown"),
+    });
+    for key in ENTITY_ITEM_KEYS.iter().chain(&["Item", "equipment"]) {
+        if let Some(items) = item_list(entity, key) {
+            find_items(&items, version, &location, add);
+        }
+    }
+}
+
+/// Returns the items under a key as a list, whether it is a list, a single item, or the
+/// `equipment` compound of entities and players since 1.21.5 which has an item per slot
+fn item_list(compound: &NbtCompound, key: &str) -> Option<NbtList> {
+    match compound.inner().get(key)? {
+        NbtTag::List(items) => Some(items.clone()),
+        NbtTag::Compound(item) if key == "Item" => Some(NbtList::from(vec![item.clone()])),
+        NbtTag::Compound(slots) => Some(NbtList::from(
+            slots
+                .inner()
+                .values()
+                .filter_map(|item| match item {
+                    NbtTag::Compound(item) => Some(item.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+        )),
+        _ => None,
+    }
+}
@@ -0,0 +1,102 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use quartz_nbt::{NbtCompound, NbtList};
+use world_statistics::{
+    biomes::ChunkBiomes,
+    region::for_each_chunk_in_dir,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open},
+};
+
+/// Reports in which biomes players build, using the time players spent in a chunk and its
+/// number of block entities as proxies
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Minimum InhabitedTime in ticks for a chunk to count as used
+    #[clap(long, default_value = "72000")]
+    min_inhabited_time: i64,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+#[derive(Default)]
+struct BiomeStats {
+    chunks: u64,
+    used_chunks: u64,
+    inhabited_time: i64,
+    block_entities: u64,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+
+    let mut stats = HashMap::<String, BiomeStats>::new();
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+
+        for_each_chunk_in_dir(&dim_path.join("region"), |_, _, chunk| {
+            let biome = match dominant_biome(&chunk) {
+                Some(biome) => biome,
+                None => return,
+            };
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let level = match version.level(&chunk) {
+                Some(level) => level,
+                None => return,
+            };
+            let inhabited_time: i64 = level.get("InhabitedTime").unwrap_or_default();
+            let block_entities = level
+                .get::<_, &NbtList>(version.block_entities_key())
+                .map_or(0, |block_entities| block_entities.len());
+
+            let stats = stats.entry(biome).or_default();
+            stats.chunks += 1;
+            if inhabited_time >= args.min_inhabited_time {
+                stats.used_chunks += 1;
+            }
+            stats.inhabited_time += inhabited_time;
+            stats.block_entities += block_entities as u64;
+        })
+        .with_context(|| format!("region folder of {} not found", dim_name))?;
+    }
+
+    let mut stats: Vec<_> = stats.into_iter().collect();
+    stats.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.block_entities));
+
+    println!("biome,chunks,used_chunks,inhabited_hours,block_entities,block_entities_per_chunk");
+    for (biome, stats) in stats {
+        println!(
+            "{},{},{},{:.1},{},{:.2}",
+            biome,
+            stats.chunks,
+            stats.used_chunks,
+            stats.inhabited_time as f64 / 72000.0,
+            stats.block_entities,
+            stats.block_entities as f64 / stats.chunks as f64
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the biome covering the most cells of the chunk, or columns before 1.15
+fn dominant_biome(chunk: &NbtCompound) -> Option<String> {
+    ChunkBiomes::from_chunk(chunk)
+        .counts()
+        .into_iter()
+        .max_by_key(|&(biome, count)| (count, std::cmp::Reverse(biome)))
+        .map(|(biome, _)| biome.to_string())
+}
@@ -0,0 +1,186 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    dat::LevelDat,
+    items::format_stack,
+    redact::print_json,
+    region::for_each_chunk_in_dir,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, Source, Sources},
+};
+
+/// Reports chunks, containers and entities outside of the world border from level.dat, e.g. left
+/// over from exploration before the border was shrunk
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Only print the chunks completely outside of the border as `x,z`, one per line, to be
+    /// passed to delete-chunks
+    #[clap(long)]
+    chunk_list: bool,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+/// The border as min and max block x/z
+struct Border {
+    min: (f64, f64),
+    max: (f64, f64),
+}
+
+impl Border {
+    fn contains(&self, x: f64, z: f64) -> bool {
+        x >= self.min.0 && x < self.max.0 && z >= self.min.1 && z < self.max.1
+    }
+
+    fn contains_chunk_part(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        let (x, z) = (chunk_x as f64 * 16.0, chunk_z as f64 * 16.0);
+        x + 16.0 > self.min.0 && x < self.max.0 && z + 16.0 > self.min.1 && z < self.max.1
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[
+        (Source::Region, "that dimension"),
+        (Source::Entities, "entities there"),
+    ]);
+    let level =
+        LevelDat::read(&world_path.join("level.dat")).context("failed to read level.dat")?;
+    let (center_x, center_z) = (level.world_border.center_x, level.world_border.center_z);
+    let size = level.world_border.size;
+
+    let mut dimensions = serde_json::Map::new();
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        if !sources.has(dim_name, Source::Region) {
+            continue;
+        }
+
+        // the nether's border is scaled down like its coordinates
+        let scale = if dim_name == "nether" { 8.0 } else { 1.0 };
+        let border = Border {
+            min: (
+                (center_x - size / 2.0) / scale,
+                (center_z - size / 2.0) / scale,
+            ),
+            max: (
+                (center_x + size / 2.0) / scale,
+                (center_z + size / 2.0) / scale,
+            ),
+        };
+
+        let mut chunks_outside = 0;
+        let mut containers = Vec::new();
+        let mut entities = Vec::new();
+        for_each_chunk_in_dir(&dim_path.join("region"), |chunk_x, chunk_z, chunk| {
+            if !border.contains_chunk_part(chunk_x, chunk_z) {
+                chunks_outside += 1;
+                if args.chunk_list {
+                    println!("{},{}", chunk_x, chunk_z);
+                }
+            }
+            if args.chunk_list {
+                return;
+            }
+
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let level = match version.level(&chunk) {
+                Some(level) => level,
+                None => return,
+            };
+            // entities were stored in the terrain chunks before 1.17
+            if version.entities_in_chunk() {
+                if let Ok(list) = level.get::<_, &NbtList>("Entities") {
+                    add_entities_outside(&mut entities, &border, list);
+                }
+            }
+            let block_entities = match level.get::<_, &NbtList>(version.block_entities_key()) {
+                Ok(block_entities) => block_entities,
+                Err(_) => return,
+            };
+            for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+                let items = match block_entity.get::<_, &NbtList>("Items") {
+                    Ok(items) if !items.is_empty() => items,
+                    _ => continue,
+                };
+                let pos =
+                    ["x", "y", "z"].map(|key| block_entity.get::<_, i32>(key).unwrap_or_default());
+                if border.contains(pos[0] as f64 + 0.5, pos[2] as f64 + 0.5) {
+                    continue;
+                }
+                let stacks: Vec<_> = items
+                    .iter_map::<&NbtCompound>()
+                    .flatten()
+                    .filter_map(format_stack)
+                    .collect();
+                containers.push(json!({
+                    "id": block_entity.get::<_, &str>("id").unwrap_or("unknown"),
+                    "pos": pos,
+                    "items": stacks,
+                }));
+            }
+        })?;
+
+        let entities_path = dim_path.join("entities");
+        if !args.chunk_list && sources.has(dim_name, Source::Entities) {
+            for_each_chunk_in_dir(&entities_path, |_, _, chunk| {
+                if let Ok(list) = chunk.get::<_, &NbtList>("Entities") {
+                    add_entities_outside(&mut entities, &border, list);
+                }
+            })?;
+        }
+
+        dimensions.insert(
+            dim_name.clone(),
+            json!({
+                "chunks_outside": chunks_outside,
+                "containers": containers,
+                "entities": entities,
+            }),
+        );
+    }
+
+    if !args.chunk_list {
+        let report = json!({
+            "border": {
+                "center": [center_x, center_z],
+                "size": size,
+            },
+            "dimensions": dimensions,
+        });
+        print_json(&report)?;
+    }
+
+    Ok(())
+}
+
+fn add_entities_outside(entities: &mut Vec<serde_json::Value>, border: &Border, list: &NbtList) {
+    for entity in list.iter_map::<&NbtCompound>().flatten() {
+        let pos = match entity.get::<_, &NbtList>("Pos") {
+            Ok(pos) => [0, 1, 2].map(|i| pos.get::<f64>(i).unwrap_or_default()),
+            Err(_) => continue,
+        };
+        if !border.contains(pos[0], pos[2]) {
+            entities.push(json!({
+                "id": entity.get::<_, &str>("id").unwrap_or("unknown"),
+                "pos": pos.map(|v| v.floor() as i32),
+            }));
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    anonymize::AnonymizeArgs,
+    claims::{Claim, ClaimSource},
+    dat::read_dat,
+    items::{for_each_item, id_and_count},
+    players::{find_usercache, playerdata_files},
+    poi::find_records,
+    redact::print_json,
+    region::for_each_chunk_in_dir,
+    text::plain_text,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, Source, Sources},
+};
+
+/// Attributes containers to the players who probably own them, by the claim they are in, player
+/// names in the container's custom name or else by the closest respawn point of a player
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Maximum distance of a container from a respawn point
+    #[clap(long, default_value = "64")]
+    radius: i32,
+
+    /// Claims of a server plugin as `griefprevention:<ClaimData dir>`,
+    /// `worldguard:<regions.yml>` or `lands:<export.json>`
+    #[clap(long)]
+    claims: Vec<ClaimSource>,
+
+    /// Also list every container with its probable owner
+    #[clap(long)]
+    containers: bool,
+
+    #[clap(flatten)]
+    anonymize: AnonymizeArgs,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+/// A player's respawn point, set by sleeping in a bed or charging a respawn anchor
+struct Spawn {
+    uuid: String,
+    dimension: String,
+    pos: [i32; 3],
+}
+
+#[derive(Default)]
+struct Owner {
+    containers: u64,
+    items: u64,
+    by_claim: u64,
+    by_name: u64,
+    by_spawn: u64,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[
+        (Source::Region, "containers there"),
+        (Source::Poi, "beds there"),
+        (Source::Playerdata, "respawn points and player names"),
+    ]);
+    let names = find_usercache(&world_path);
+    let anonymizer = args.anonymize.anonymizer()?;
+    // players are kept by UUID, or name if that is all a claim has, until they are reported
+    let player = |player: &str| {
+        anonymizer.player(player).unwrap_or_else(|| {
+            names
+                .get(player)
+                .cloned()
+                .unwrap_or_else(|| player.to_string())
+        })
+    };
+
+    let mut player_names = Vec::new();
+    let mut spawns = Vec::new();
+    if sources.playerdata {
+        for (uuid, path) in playerdata_files(&world_path)? {
+            if let Some(name) = names.get(&uuid) {
+                player_names.push((uuid.clone(), name.to_lowercase()));
+            }
+            let player = match read_dat(&path) {
+                Ok(player) => player,
+                Err(e) => {
+                    eprintln!("failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let pos = match ["SpawnX", "SpawnY", "SpawnZ"].map(|key| player.get::<_, i32>(key).ok())
+            {
+                [Some(x), Some(y), Some(z)] => [x, y, z],
+                _ => continue,
+            };
+            let dimension = match player.get::<_, &str>("SpawnDimension") {
+                Ok("minecraft:the_nether") => "nether",
+                Ok("minecraft:the_end") => "end",
+                _ => "overworld",
+            };
+            spawns.push(Spawn {
+                uuid,
+                dimension: dimension.to_string(),
+                pos,
+            });
+        }
+    }
+
+    // spawn points whose bed is gone still hint at where a base was
+    let mut beds = HashSet::new();
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        for pos in find_beds(&dim_path.join("poi"))? {
+            beds.insert((dim_name.clone(), pos));
+        }
+    }
+
+    let mut claims = Vec::<Claim>::new();
+    for source in &args.claims {
+        claims.extend(source.read().context("failed to read claims")?);
+    }
+    let mut claim_totals = BTreeMap::<&str, (Option<String>, u64, u64)>::new();
+
+    let mut owners = BTreeMap::<String, Owner>::new();
+    let mut containers = Vec::new();
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        if !sources.has(dim_name, Source::Region) {
+            continue;
+        }
+
+        for_each_chunk_in_dir(&dim_path.join("region"), |_, _, chunk| {
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let block_entities = match version
+                .level(&chunk)
+                .and_then(|level| level.get::<_, &NbtList>(version.block_entities_key()).ok())
+            {
+                Some(block_entities) => block_entities,
+                None => return,
+            };
+            for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+                let items = match block_entity.get::<_, &NbtList>("Items") {
+                    Ok(items) => items,
+                    Err(_) => continue,
+                };
+                let pos =
+                    ["x", "y", "z"].map(|key| block_entity.get::<_, i32>(key).unwrap_or_default());
+
+                let claim = claims
+                    .iter()
+                    .find(|claim| claim.contains(dim_name, pos[0], pos[2]));
+                let claim_owner = claim.and_then(|claim| claim.owner.as_ref());
+
+                let custom_name = block_entity
+                    .get::<_, &str>("CustomName")
+                    .map(plain_text)
+                    .unwrap_or_default()
+                    .to_lowercase();
+                let by_name = player_names
+                    .iter()
+                    .find(|(_, name)| contains_word(&custom_name, name));
+                let by_spawn = spawns
+                    .iter()
+                    .filter(|spawn| spawn.dimension == *dim_name)
+                    .map(|spawn| (spawn, distance(spawn.pos, pos)))
+                    .filter(|&(_, distance)| distance <= args.radius as f64)
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+                let (owner, reason) = match (claim_owner, by_name, by_spawn) {
+                    (Some(owner), _, _) => (owner.as_str(), "claim"),
+                    (None, Some((uuid, _)), _) => (uuid.as_str(), "name"),
+                    (None, None, Some((spawn, _))) => (spawn.uuid.as_str(), "spawn"),
+                    (None, None, None) => ("unknown", "none"),
+                };
+                let owner = match reason {
+                    "none" => owner.to_string(),
+                    _ => player(owner),
+                };
+
+                let mut count = 0;
+                for_each_item(items, &mut |item| count += id_and_count(item).1);
+
+                if let Some(claim) = claim {
+                    let totals = claim_totals
+                        .entry(&claim.name)
+                        .or_insert_with(|| (claim_owner.map(|owner| player(owner)), 0, 0));
+                    totals.1 += 1;
+                    totals.2 += count;
+                }
+
+                let entry = owners.entry(owner.clone()).or_default();
+                entry.containers += 1;
+                entry.items += count;
+                match reason {
+                    "claim" => entry.by_claim += 1,
+                    "name" => entry.by_name += 1,
+                    "spawn" => entry.by_spawn += 1,
+                    _ => {}
+                }
+
+                if args.containers {
+                    containers.push(json!({
+                        "dimension": dim_name,
+                        "id": block_entity.get::<_, &str>("id").unwrap_or("unknown"),
+                        "pos": anonymizer.pos(pos),
+                        "items": count,
+                        "owner": owner,
+                        "reason": reason,
+                    }));
+                }
+            }
+        })?;
+    }
+
+    let report = json!({
+        "owners": owners
+            .iter()
+            .map(|(name, owner)| (name.clone(), json!({
+                "containers": owner.containers,
+                "items": owner.items,
+                "by_claim": owner.by_claim,
+                "by_name": owner.by_name,
+                "by_spawn": owner.by_spawn,
+            })))
+            .collect::<serde_json::Map<_, _>>(),
+        "claims": claim_totals
+            .iter()
+            .map(|(name, (owner, containers, items))| (name.to_string(), json!({
+                "owner": owner,
+                "containers": containers,
+                "items": items,
+            })))
+            .collect::<serde_json::Map<_, _>>(),
+        "spawns": spawns
+            .iter()
+            .map(|spawn| json!({
+                "player": player(&spawn.uuid),
+                "dimension": spawn.dimension,
+                "pos": anonymizer.pos(spawn.pos),
+                // the respawn point is the block next to the bed
+                "bed": beds.iter().any(|(dimension, bed)| {
+                    *dimension == spawn.dimension && distance(*bed, spawn.pos) <= 2.0
+                }),
+            }))
+            .collect::<Vec<_>>(),
+        "containers": containers,
+    });
+    print_json(&report)?;
+
+    Ok(())
+}
+
+fn distance(a: [i32; 3], b: [i32; 3]) -> f64 {
+    let squared: i64 = (0..3).map(|i| (a[i] as i64 - b[i] as i64).pow(2)).sum();
+    (squared as f64).sqrt()
+}
+
+/// Whether the word occurs in the text not surrounded by other letters or digits, so that `al`
+/// doesn't match `calcite storage`
+fn contains_word(text: &str, word: &str) -> bool {
+    text.match_indices(word).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + word.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Reads the positions of beds from the POI files
+fn find_beds(poi_path: &Path) -> eyre::Result<Vec<[i32; 3]>> {
+    if !poi_path.exists() {
+        return Ok(Vec::new());
+    }
+    let beds = find_records(poi_path, "minecraft:home")?;
+    Ok(beds.into_iter().map(|record| record.pos).collect())
+}
@@ -0,0 +1,284 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use eyre::eyre;
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    forced::{read_forced_chunks, read_legacy_tickets},
+    players::find_usercache,
+    poi::find_records,
+    redact::print_json,
+    region::for_each_chunk_in_dir,
+    uuid::format_uuid,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open},
+};
+
+/// Estimates which chunks get loaded without players nearby: the 3x3 chunks around the
+/// destination of each nether portal from the POI data, the chunks of thrown ender pearls,
+/// force loaded chunks including those of Forge mods, and modded chunk loader blocks
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Block entity ids of modded chunk loaders
+    #[clap(
+        long,
+        use_value_delimiter = true,
+        default_values = &[
+            "chickenchunks:chunk_loader",
+            "chickenchunks:spot_loader",
+            "mekanism:dimensional_stabilizer",
+        ]
+    )]
+    loaders: Vec<String>,
+
+    /// Path to the world directory
+    world: String,
+}
+
+type Portal = Vec<[i32; 3]>;
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let names = find_usercache(&world_path);
+
+    let mut portals = HashMap::new();
+    for dim_name in ["overworld", "nether"] {
+        let dim_path = dimension_path(&world_path, dim_name).unwrap();
+        portals.insert(dim_name, find_portals(&dim_path.join("poi"))?);
+    }
+
+    let mut links = Vec::new();
+    for (from, to, scale, search_radius) in [
+        ("overworld", "nether", 1.0 / 8.0, 16),
+        ("nether", "overworld", 8.0, 128),
+    ] {
+        for portal in &portals[from] {
+            let [x, y, z] = portal[0];
+            let target = [
+                (x as f64 * scale).floor() as i32,
+                y,
+                (z as f64 * scale).floor() as i32,
+            ];
+            // the game picks the closest portal block within the search radius
+            let destination = portals[to]
+                .iter()
+                .flatten()
+                .filter(|pos| {
+                    (pos[0] - target[0]).abs() <= search_radius
+                        && (pos[2] - target[2]).abs() <= search_radius
+                })
+                .min_by_key(|pos| {
+                    (pos[0] - target[0]).pow(2)
+                        + (pos[1] - target[1]).pow(2)
+                        + (pos[2] - target[2]).pow(2)
+                })
+                .copied();
+
+            let center = destination.unwrap_or(target);
+            let loaded_chunks: Vec<_> = (-1..=1)
+                .flat_map(|dx| {
+                    (-1..=1).map(move |dz| [(center[0] >> 4) + dx, (center[2] >> 4) + dz])
+                })
+                .collect();
+
+            links.push(json!({
+                "dimension": from,
+                "pos": portal[0],
+                "blocks": portal.len(),
+                "destination": {
+                    "dimension": to,
+                    "pos": destination,
+                    "new_portal": destination.is_none(),
+                },
+                "loaded_chunks": loaded_chunks,
+            }));
+        }
+    }
+
+    let mut pearls = Vec::new();
+    let mut add_pearls = |dim_name: &str, chunk_x: i32, chunk_z: i32, entities: &NbtList| {
+        for entity in entities.iter_map::<&NbtCompound>().flatten() {
+            if entity.get::<_, &str>("id").ok() != Some("minecraft:ender_pearl") {
+                continue;
+            }
+            let owner = entity.get::<_, &[i32]>("Owner").ok().and_then(format_uuid);
+            let speed = entity
+                .get::<_, &NbtList>("Motion")
+                .map(|motion| {
+                    motion
+                        .iter_map::<f64>()
+                        .flatten()
+                        .map(|v| v * v)
+                        .sum::<f64>()
+                        .sqrt()
+                })
+                .unwrap_or_default();
+            let pos: Vec<i32> = entity
+                .get::<_, &NbtList>("Pos")
+                .map(|pos| {
+                    pos.iter_map::<f64>()
+                        .flatten()
+                        .map(|v| v.floor() as i32)
+                        .collect()
+                })
+                .unwrap_or_default();
+            pearls.push(json!({
+                "dimension": dim_name,
+                "pos": pos,
+                "chunk": [chunk_x, chunk_z],
+                "owner": owner.as_ref().map(|uuid| names.get(uuid).unwrap_or(uuid)),
+                // a pearl that doesn't move is held in a stasis chamber
+                "stasis": speed < 0.1,
+            }));
+        }
+    };
+    for dim_name in ["overworld", "nether", "end"] {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        let entities_path = dim_path.join("entities");
+        if entities_path.exists() {
+            for_each_chunk_in_dir(&entities_path, |chunk_x, chunk_z, chunk| {
+                if let Ok(entities) = chunk.get::<_, &NbtList>("Entities") {
+                    add_pearls(dim_name, chunk_x, chunk_z, entities);
+                }
+            })?;
+            continue;
+        }
+        // entities were stored in the terrain chunks before 1.17
+        let region_path = dim_path.join("region");
+        if !region_path.exists() {
+            continue;
+        }
+        for_each_chunk_in_dir(&region_path, |chunk_x, chunk_z, chunk| {
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            if !version.entities_in_chunk() {
+                return;
+            }
+            if let Some(entities) = version
+                .level(&chunk)
+                .and_then(|level| level.get::<_, &NbtList>("Entities").ok())
+            {
+                add_pearls(dim_name, chunk_x, chunk_z, entities);
+            }
+        })?;
+    }
+
+    let mut forced = Vec::new();
+    let mut legacy_tickets = Vec::new();
+    let mut loaders = Vec::new();
+    for dim_name in ["overworld", "nether", "end"] {
+        let dim_path = dimension_path(&world_path, dim_name).unwrap();
+
+        for chunk in read_forced_chunks(&dim_path)? {
+            forced.push(json!({
+                "dimension": dim_name,
+                "chunk": [chunk.chunk.0, chunk.chunk.1],
+                "mod": chunk.mod_id,
+                "blocks": chunk.blocks,
+                "entities": chunk.entities,
+                "ticking": chunk.ticking,
+            }));
+        }
+        for ticket in read_legacy_tickets(&dim_path)? {
+            legacy_tickets.push(json!({
+                "dimension": dim_name,
+                "owner": ticket.owner,
+                "mod": ticket.mod_id,
+                "player": ticket.player,
+                "entity": ticket
+                    .entity
+                    .map(|(uuid, (x, z))| json!({ "uuid": uuid, "chunk": [x, z] })),
+            }));
+        }
+
+        let region_path = dim_path.join("region");
+        if args.loaders.is_empty() || !region_path.exists() {
+            continue;
+        }
+        for_each_chunk_in_dir(&region_path, |chunk_x, chunk_z, chunk| {
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let block_entities = match version
+                .level(&chunk)
+                .and_then(|level| level.get::<_, &NbtList>(version.block_entities_key()).ok())
+            {
+                Some(block_entities) => block_entities,
+                None => return,
+            };
+            for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+                let id = block_entity.get::<_, &str>("id").unwrap_or_default();
+                if !args.loaders.iter().any(|loader| loader == id) {
+                    continue;
+                }
+                let pos =
+                    ["x", "y", "z"].map(|key| block_entity.get::<_, i32>(key).unwrap_or_default());
+                loaders.push(json!({
+                    "dimension": dim_name,
+                    "id": id,
+                    "pos": pos,
+                    "chunk": [chunk_x, chunk_z],
+                }));
+            }
+        })?;
+    }
+
+    let report = json!({
+        "portals": links,
+        "pearls": pearls,
+        "forced": forced,
+        "legacy_tickets": legacy_tickets,
+        "loaders": loaders,
+    });
+    print_json(&report)?;
+
+    Ok(())
+}
+
+/// Reads the nether portal blocks from the POI files and groups adjacent blocks into portals
+fn find_portals(poi_path: &Path) -> eyre::Result<Vec<Portal>> {
+    let mut blocks = HashSet::new();
+    if poi_path.exists() {
+        for record in find_records(poi_path, "minecraft:nether_portal")? {
+            blocks.insert(record.pos);
+        }
+    }
+
+    let mut blocks: Vec<[i32; 3]> = blocks.into_iter().collect();
+    blocks.sort_unstable();
+    let mut remaining: HashSet<[i32; 3]> = blocks.iter().copied().collect();
+
+    let mut portals = Vec::new();
+    for start in blocks {
+        if !remaining.remove(&start) {
+            continue;
+        }
+        let mut portal = vec![start];
+        let mut i = 0;
+        while i < portal.len() {
+            let [x, y, z] = portal[i];
+            for neighbor in [
+                [x + 1, y, z],
+                [x - 1, y, z],
+                [x, y + 1, z],
+                [x, y - 1, z],
+                [x, y, z + 1],
+                [x, y, z - 1],
+            ] {
+                if remaining.remove(&neighbor) {
+                    portal.push(neighbor);
+                }
+            }
+            i += 1;
+        }
+        portal.sort_unstable();
+        portals.push(portal);
+    }
+    Ok(portals)
+}
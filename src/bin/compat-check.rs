@@ -0,0 +1,287 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use eyre::Context;
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    biomes::ChunkBiomes,
+    blocks::ChunkBlocks,
+    chunk::Chunk,
+    entity_index::entity_uuid,
+    items::{for_each_item, id_and_count},
+    redact::print_json,
+    region::{deserialize_chunk, encode_chunk, for_each_chunk_in_dir},
+    validate::{validate_chunk, Strictness},
+    version::GameVersion,
+};
+
+/// Runs the analyses of the library on the overworld chunks of each world and reports per
+/// DataVersion which of them work, which are unsupported and which produce no or invalid output.
+/// Exits with 1 if any analysis failed.
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// World directories, or directories of worlds like the fixtures of each game version
+    #[clap(default_values = &["fixtures"])]
+    worlds: Vec<String>,
+}
+
+const ANALYSES: &[&str] = &[
+    "blocks",
+    "block_entities",
+    "items",
+    "entities",
+    "entity_uuids",
+    "biomes",
+    "chunk",
+    "validate",
+];
+
+/// Why an analysis can't handle chunks of the version, if it can't
+fn unsupported(analysis: &str, version: GameVersion) -> Option<&'static str> {
+    match analysis {
+        "blocks" | "chunk" if !version.named_block_states() => {
+            Some("numeric block ids before 1.13")
+        }
+        "validate" if !version.named_block_states() => Some("chunks before 1.13"),
+        _ => None,
+    }
+}
+
+/// Runs an analysis on a terrain chunk and returns the number of things found
+fn run(analysis: &str, chunk: &NbtCompound, version: GameVersion) -> Result<u64, String> {
+    let level = version
+        .level(chunk)
+        .ok_or_else(|| format!("no {} compound", version.level_path()))?;
+    let block_entities = level
+        .get::<_, &NbtList>(version.block_entities_key())
+        .map(|list| list.iter_map::<&NbtCompound>().flatten().collect())
+        .unwrap_or_else(|_| Vec::new());
+
+    match analysis {
+        "blocks" => {
+            let blocks = ChunkBlocks::from_chunk_as(chunk, version);
+            let mut count = 0;
+            for (_, block_states) in blocks.sections() {
+                for index in 0..4096 {
+                    match block_states.name(index) {
+                        Some("minecraft:air") => {}
+                        Some(name) if name.contains(':') => count += 1,
+                        name => return Err(format!("invalid block name {:?}", name)),
+                    }
+                }
+            }
+            Ok(count)
+        }
+        "block_entities" => {
+            for block_entity in &block_entities {
+                if block_entity.get::<_, &str>("id").is_err() {
+                    return Err("block entity without id".to_string());
+                }
+            }
+            Ok(block_entities.len() as u64)
+        }
+        "items" => {
+            let mut count = 0;
+            for block_entity in &block_entities {
+                if let Ok(items) = block_entity.get::<_, &NbtList>("Items") {
+                    for_each_item(items, &mut |item| count += id_and_count(item).1);
+                }
+            }
+            Ok(count)
+        }
+        "entities" => Ok(level
+            .get::<_, &NbtList>("Entities")
+            .map(|entities| entities.len() as u64)
+            .unwrap_or_default()),
+        "entity_uuids" => match level.get::<_, &NbtList>("Entities") {
+            Ok(entities) => count_uuids(entities),
+            Err(_) => Ok(0),
+        },
+        "biomes" => {
+            let mut count = 0;
+            for biome in ChunkBiomes::from_chunk_as(chunk, version).iter() {
+                match biome {
+                    Some(name) if name.contains(':') => count += 1,
+                    Some(name) => return Err(format!("invalid biome {}", name)),
+                    None => return Err("unknown numeric biome id".to_string()),
+                }
+            }
+            Ok(count)
+        }
+        "chunk" => {
+            // the typed chunk has to agree with the NBT it is deserialized from
+            let payload = encode_chunk(chunk).map_err(|e| e.to_string())?;
+            let typed: Chunk = deserialize_chunk(&payload).map_err(|e| e.to_string())?;
+            if typed.block_entities.len() != block_entities.len() {
+                return Err(format!(
+                    "{} of {} block entities",
+                    typed.block_entities.len(),
+                    block_entities.len()
+                ));
+            }
+            let mut count = 0;
+            for section in &typed.sections {
+                if section.block_states.is_none() {
+                    continue;
+                }
+                for index in 0..4096 {
+                    match section.block_state(index, typed.version()) {
+                        Some(state) if state.name == "minecraft:air" => {}
+                        Some(state) if state.name.contains(':') => count += 1,
+                        state => return Err(format!("invalid block state {:?}", state)),
+                    }
+                }
+            }
+            Ok(count)
+        }
+        "validate" => {
+            let position = (
+                level.get("xPos").unwrap_or_default(),
+                level.get("zPos").unwrap_or_default(),
+            );
+            match validate_chunk(chunk, position, Some(version), Strictness::Normal).first() {
+                Some(violation) => Err(violation.to_string()),
+                None => Ok(1),
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Counts the entities after checking that the UUID of each can be read
+fn count_uuids(entities: &NbtList) -> Result<u64, String> {
+    for entity in entities.iter_map::<&NbtCompound>().flatten() {
+        if entity_uuid(entity).is_none() {
+            let id = entity.get::<_, &str>("id").unwrap_or("unknown");
+            return Err(format!("{} without UUID", id));
+        }
+    }
+    Ok(entities.len() as u64)
+}
+
+/// Result of one analysis for the chunks of one version
+#[derive(Default)]
+struct Outcome {
+    count: u64,
+    error: Option<String>,
+}
+
+type Outcomes = BTreeMap<GameVersion, BTreeMap<&'static str, Outcome>>;
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let mut worlds = Vec::new();
+    for path in &args.worlds {
+        let path = PathBuf::from(path);
+        if path.join("region").exists() {
+            worlds.push(path);
+            continue;
+        }
+        let mut subdirs: Vec<_> = fs::read_dir(&path)
+            .with_context(|| format!("{} not found", path.display()))?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.join("region").exists())
+            .collect();
+        subdirs.sort();
+        worlds.extend(subdirs);
+    }
+
+    let mut report = serde_json::Map::new();
+    let mut failed = false;
+    for world in &worlds {
+        let mut versions = serde_json::Map::new();
+        for (version, outcomes) in check_world(world)? {
+            let analyses: serde_json::Map<_, _> = ANALYSES
+                .iter()
+                .map(|&analysis| {
+                    let outcome = &outcomes[analysis];
+                    let status = if let Some(reason) = unsupported(analysis, version) {
+                        format!("unsupported: {}", reason)
+                    } else if let Some(error) = &outcome.error {
+                        failed = true;
+                        format!("failed: {}", error)
+                    } else if outcome.count == 0 {
+                        failed = true;
+                        "failed: nothing found".to_string()
+                    } else {
+                        format!("ok: {}", outcome.count)
+                    };
+                    (analysis.to_string(), json!(status))
+                })
+                .collect();
+            versions.insert(version.data_version.to_string(), json!(analyses));
+        }
+        report.insert(world.display().to_string(), json!(versions));
+    }
+    print_json(&report)?;
+
+    if failed {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Runs the analyses on all overworld chunks of a world, grouped by DataVersion
+fn check_world(world: &Path) -> eyre::Result<Outcomes> {
+    let mut outcomes = Outcomes::new();
+    let mut add = |version: GameVersion, analysis: &'static str, result: Result<u64, String>| {
+        let entry = outcomes
+            .entry(version)
+            .or_insert_with(|| {
+                ANALYSES
+                    .iter()
+                    .map(|&analysis| (analysis, Outcome::default()))
+                    .collect()
+            })
+            .get_mut(analysis)
+            .unwrap();
+        match result {
+            Ok(count) => entry.count += count,
+            Err(error) => {
+                entry.error.get_or_insert(error);
+            }
+        }
+    };
+
+    for_each_chunk_in_dir(&world.join("region"), |_, _, chunk| {
+        let version = match GameVersion::of(&chunk) {
+            Some(version) => version,
+            None => return,
+        };
+        for &analysis in ANALYSES {
+            if unsupported(analysis, version).is_some() {
+                continue;
+            }
+            if (analysis == "entities" || analysis == "entity_uuids")
+                && !version.entities_in_chunk()
+            {
+                continue;
+            }
+            add(version, analysis, run(analysis, &chunk, version));
+        }
+    })
+    .context("region folder not found")?;
+
+    let entities_path = world.join("entities");
+    if entities_path.exists() {
+        for_each_chunk_in_dir(&entities_path, |_, _, chunk| {
+            if let Some(version) = GameVersion::of(&chunk) {
+                if let Ok(entities) = chunk.get::<_, &NbtList>("Entities") {
+                    add(version, "entities", Ok(entities.len() as u64));
+                    add(version, "entity_uuids", count_uuids(entities));
+                }
+            }
+        })?;
+    }
+
+    Ok(outcomes)
+}
@@ -0,0 +1,222 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, OpenOptions},
+    io,
+    path::PathBuf,
+};
+
+use clap::Parser;
+use eyre::eyre;
+use world_statistics::{
+    backup::Backup,
+    interrupt::{handle_interrupts, interrupted, EXIT_INTERRUPTED},
+    journal::Journal,
+    region::{chunk_position, encode_chunk, read_chunk, RegionFile},
+    relocate::{relocate_chunk, relocate_entities_chunk, relocate_poi_chunk},
+    world::{dimension_path, ensure_closed, warn_if_open, ChunkSelection},
+};
+
+/// Copies chunks with their entities and POI from one world to another, optionally moved by a
+/// number of chunks. Chunks that already exist in the target world are skipped unless
+/// `--overwrite` is given. Stop the server of the target world before using this.
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Only print which chunks would be copied
+    #[clap(long)]
+    dry_run: bool,
+
+    /// Chunks to move the copies by along the x axis
+    #[clap(long, default_value = "0", allow_hyphen_values = true)]
+    offset_x: i32,
+
+    /// Chunks to move the copies by along the z axis
+    #[clap(long, default_value = "0", allow_hyphen_values = true)]
+    offset_z: i32,
+
+    /// Dimension of the target world to copy to, the same as the source by default
+    #[clap(long)]
+    target_dimension: Option<String>,
+
+    /// Replace chunks that already exist in the target world
+    #[clap(long)]
+    overwrite: bool,
+
+    /// JSON lines file to record the previous content of changed chunks and files to, to be
+    /// reverted with undo
+    #[clap(long)]
+    journal: Option<PathBuf>,
+
+    /// Directory to copy the changed files to first, restored from if writing fails halfway
+    #[clap(long)]
+    backup: Option<PathBuf>,
+
+    /// Write even though the target world is open in a running game or server
+    #[clap(long)]
+    force: bool,
+
+    /// Path to the world directory to copy from
+    source: String,
+
+    /// Path to the world directory to copy to
+    target: String,
+
+    /// overworld, nether or end
+    dimension: String,
+
+    /// Chunks as `x,z` or boxes of chunks as `x1,z1..x2,z2`
+    #[clap(required = true, allow_hyphen_values = true)]
+    chunks: Vec<ChunkSelection>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let source_path = PathBuf::from(&args.source);
+    let target_path = PathBuf::from(&args.target);
+    warn_if_open(&source_path);
+    if args.dry_run {
+        warn_if_open(&target_path);
+    } else {
+        ensure_closed(&target_path, args.force)?;
+    }
+    let mut journal = args
+        .journal
+        .as_deref()
+        .map(|path| Journal::open(path, &target_path, "copy-chunks"))
+        .transpose()?;
+    let mut backup = args
+        .backup
+        .as_deref()
+        .map(|dir| Backup::new(dir, &target_path));
+    handle_interrupts()?;
+
+    let source_dim_path = dimension_path(&source_path, &args.dimension)
+        .ok_or_else(|| eyre!("unknown dimension {}", args.dimension))?;
+    let target_dimension = args.target_dimension.as_ref().unwrap_or(&args.dimension);
+    let target_dim_path = dimension_path(&target_path, target_dimension)
+        .ok_or_else(|| eyre!("unknown dimension {}", target_dimension))?;
+    let offset = (args.offset_x, args.offset_z);
+
+    let mut regions: BTreeMap<(i32, i32), Vec<usize>> = BTreeMap::new();
+    for (chunk_x, chunk_z) in args.chunks.iter().flat_map(ChunkSelection::chunks) {
+        let (region_x, region_z, index) = chunk_position(chunk_x, chunk_z);
+        regions.entry((region_x, region_z)).or_default().push(index);
+    }
+
+    let mut target_files = HashMap::<PathBuf, Option<RegionFile>>::new();
+    let mut copied = 0;
+    let mut skipped = 0;
+    for ((region_x, region_z), indices) in regions {
+        if interrupted() {
+            break;
+        }
+        for kind in ["region", "entities", "poi"] {
+            let path = source_dim_path
+                .join(kind)
+                .join(format!("r.{}.{}.mca", region_x, region_z));
+            let mut region_file = match RegionFile::open(&path) {
+                Ok(region_file) => region_file,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            for &index in &indices {
+                let payload = match region_file.read_payload(index)? {
+                    Some(payload) => payload,
+                    None => continue,
+                };
+                let chunk_x = region_x * 32 + (index % 32) as i32;
+                let chunk_z = region_z * 32 + (index / 32) as i32;
+                let (target_x, target_z) = (chunk_x + offset.0, chunk_z + offset.1);
+                let (target_region_x, target_region_z, target_index) =
+                    chunk_position(target_x, target_z);
+                let target_region_path = target_dim_path
+                    .join(kind)
+                    .join(format!("r.{}.{}.mca", target_region_x, target_region_z));
+
+                if !target_files.contains_key(&target_region_path) {
+                    if !args.dry_run {
+                        if let Some(backup) = &mut backup {
+                            backup.save(&target_region_path)?;
+                        }
+                        fs::create_dir_all(target_dim_path.join(kind))?;
+                    }
+                    let target_file = match OpenOptions::new()
+                        .read(true)
+                        .write(!args.dry_run)
+                        .create(!args.dry_run)
+                        .truncate(false)
+                        .open(&target_region_path)
+                    {
+                        Ok(file) => Some(RegionFile::create(file)?),
+                        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+                        Err(e) => return Err(e.into()),
+                    };
+                    target_files.insert(target_region_path.clone(), target_file);
+                }
+                let target_file = target_files.get_mut(&target_region_path).unwrap();
+
+                if !args.overwrite
+                    && target_file
+                        .as_ref()
+                        .is_some_and(|target_file| target_file.contains_chunk(target_index))
+                {
+                    eprintln!(
+                        "{} {} {} exists in the target world",
+                        kind, target_x, target_z
+                    );
+                    skipped += 1;
+                    continue;
+                }
+                println!(
+                    "{} {} {} -> {} {}",
+                    kind, chunk_x, chunk_z, target_x, target_z
+                );
+                copied += 1;
+                let target_file = match target_file {
+                    Some(target_file) if !args.dry_run => target_file,
+                    _ => continue,
+                };
+
+                let payload = if offset == (0, 0) {
+                    payload
+                } else {
+                    let mut chunk = read_chunk(&payload)?;
+                    match kind {
+                        "region" => relocate_chunk(&mut chunk, offset),
+                        "entities" => relocate_entities_chunk(&mut chunk, offset),
+                        _ => relocate_poi_chunk(&mut chunk, offset),
+                    }
+                    encode_chunk(&chunk)?
+                };
+                if let Some(journal) = &mut journal {
+                    journal.record_chunk(&target_region_path, target_file, target_index)?;
+                }
+                target_file.write_chunk(target_index, &payload)?;
+            }
+        }
+    }
+
+    if let Some(backup) = backup {
+        backup.finish();
+    }
+    if args.dry_run {
+        eprintln!("would copy {} chunks", copied);
+    } else {
+        eprintln!("copied {} chunks", copied);
+    }
+    if skipped > 0 {
+        eprintln!(
+            "skipped {} chunks that exist in the target world, use --overwrite to replace them",
+            skipped
+        );
+    }
+
+    if interrupted() {
+        eprintln!("interrupted, the remaining files were left unchanged");
+        std::process::exit(EXIT_INTERRUPTED);
+    }
+
+    Ok(())
+}
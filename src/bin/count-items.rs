@@ -1,38 +1,307 @@
-use std::io::{self, BufRead, BufReader};
+use std::{
+    fs,
+    io::{self, BufRead, BufReader},
+    path::PathBuf,
+    time::Duration,
+};
 
+use clap::Parser;
+use eyre::eyre;
 use indexmap::IndexMap;
-use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::{json, Value};
+use world_statistics::{
+    categories::Taxonomy,
+    format::{format_compact, format_stacks, format_thousands, stack_size, NumberLocale},
+    items::{contents_as, for_each_item_as, id_and_count_as, ILLEGAL_ITEMS},
+    redact::print_json,
+    version::GameVersion,
+};
+
+/// Counts the items of a dump-items output read from stdin
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Previous count-items output to compute deltas against
+    #[clap(long)]
+    previous: Option<String>,
+
+    /// Discord webhook url to post a summary to
+    #[clap(long)]
+    notify_discord: Option<String>,
+
+    /// JSON file written by dump-items --stats, whose scan duration is posted to Discord
+    #[clap(long)]
+    scan_stats: Option<PathBuf>,
+
+    /// Print a table with thousands separators, stacks and short numbers instead of JSON, and
+    /// use the same formatting for Discord
+    #[clap(long)]
+    human: bool,
+
+    /// Separators for --human and the template filters: en, de, fr or ch
+    #[clap(long, default_value = "en")]
+    locale: NumberLocale,
+
+    /// Render the counts with a minijinja template instead of printing JSON. The template gets
+    /// `items` and `deltas` as lists of `id`/`count` and `id`/`delta`, `total` and `types`, and
+    /// can use the `thousands`, `compact` and `stacks(id)` filters.
+    #[clap(long)]
+    template: Option<String>,
+
+    /// Sum up the counts per item category instead of per item id
+    #[clap(long, arg_enum)]
+    rollup: Option<Rollup>,
+
+    /// JSON object of item id to category overriding the built-in categories
+    #[clap(long)]
+    categories: Option<PathBuf>,
+
+    /// Read the items in the format of this DataVersion or release like 1.16.5 instead of
+    /// guessing it from each item
+    #[clap(long)]
+    assume_version: Option<GameVersion>,
+}
+
+#[derive(clap::ArgEnum, Clone, Copy, Debug)]
+enum Rollup {
+    Category,
+}
 
 fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
     let mut total_items = IndexMap::new();
 
     for line in BufReader::new(io::stdin()).lines() {
         let line = line?;
 
         let item = quartz_nbt::snbt::parse(&line)?;
-        let id = item.get::<_, &String>("id")?;
-        let count = item.get::<_, u8>("Count")?;
-        *total_items.entry(id.clone()).or_insert(0) += count as u64;
-
-        if id.ends_with("shulker_box") && item.contains_key("tag") {
-            let tag: &NbtCompound = item.get("tag")?;
-            if tag.contains_key("BlockEntityTag") {
-                let block_entity_tag: &NbtCompound = tag.get("BlockEntityTag")?;
-                if block_entity_tag.contains_key("Items") {
-                    let items: &NbtList = block_entity_tag.get("Items")?;
-                    for item in items.iter_map::<&NbtCompound>() {
-                        let item = item?;
-                        let id = item.get::<_, &String>("id")?;
-                        let count = item.get::<_, u8>("Count")?;
-                        *total_items.entry(id.clone()).or_insert(0) += count as u64;
-                    }
-                }
-            }
+        let version = args
+            .assume_version
+            .unwrap_or_else(|| GameVersion::of_item(&item));
+        let (id, count) = id_and_count_as(&item, version);
+        *total_items.entry(id.to_string()).or_insert(0) += count;
+
+        // the items inside of shulker boxes and bundles
+        if let Some(contents) = contents_as(&item, version) {
+            for_each_item_as(contents, version, &mut |item| {
+                let (id, count) = id_and_count_as(item, version);
+                *total_items.entry(id.to_string()).or_insert(0) += count;
+            });
+        }
+    }
+
+    if let Some(Rollup::Category) = args.rollup {
+        let taxonomy = match &args.categories {
+            Some(path) => Taxonomy::from_file(path)?,
+            None => Taxonomy::default(),
+        };
+        let mut categories = IndexMap::new();
+        for (id, count) in total_items {
+            *categories
+                .entry(taxonomy.category(&id).to_string())
+                .or_insert(0) += count;
         }
+        total_items = categories;
     }
 
     total_items.sort_by(|_, a, _, b| b.cmp(a));
-    println!("{}", serde_json::to_string_pretty(&total_items)?);
+
+    let previous: Option<IndexMap<String, u64>> = match &args.previous {
+        Some(path) => Some(serde_json::from_str(&fs::read_to_string(path)?)?),
+        None => None,
+    };
+
+    if let Some(path) = &args.template {
+        print!(
+            "{}",
+            render_template(
+                &fs::read_to_string(path)?,
+                &total_items,
+                previous.as_ref(),
+                args.locale
+            )?
+        );
+    } else if args.human {
+        let width = total_items.keys().map(String::len).max().unwrap_or(0);
+        for (id, count) in &total_items {
+            println!(
+                "{:width$}  {:>15}  {:>12}  {:>7}",
+                id,
+                format_thousands(*count as i64, args.locale),
+                format_stacks(*count, stack_size(id)),
+                format_compact(*count as i64, args.locale),
+                width = width
+            );
+        }
+    } else {
+        print_json(&total_items)?;
+    }
+
+    if let Some(webhook_url) = &args.notify_discord {
+        let scan_duration = match &args.scan_stats {
+            Some(path) => {
+                let stats: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+                let duration_ms = stats["duration_ms"]
+                    .as_u64()
+                    .ok_or_else(|| eyre!("{} has no duration_ms", path.display()))?;
+                Some(Duration::from_millis(duration_ms))
+            }
+            None => None,
+        };
+        let format = |value: i64| {
+            if args.human {
+                format_thousands(value, args.locale)
+            } else {
+                value.to_string()
+            }
+        };
+        notify_discord(
+            webhook_url,
+            &total_items,
+            previous.as_ref(),
+            scan_duration,
+            format,
+        )?;
+    }
 
     Ok(())
 }
+
+const DISCORD_TOP_COUNT: usize = 10;
+
+fn notify_discord(
+    webhook_url: &str,
+    total_items: &IndexMap<String, u64>,
+    previous: Option<&IndexMap<String, u64>>,
+    scan_duration: Option<Duration>,
+    format: impl Fn(i64) -> String,
+) -> eyre::Result<()> {
+    let top_items = total_items
+        .iter()
+        .take(DISCORD_TOP_COUNT)
+        .map(|(id, count)| format!("`{}` {}", id, format(*count as i64)))
+        .collect::<Vec<_>>();
+
+    let mut fields = vec![
+        json!({ "name": "Top items", "value": field_value(&top_items) }),
+        json!({ "name": "Item types", "value": format(total_items.len() as i64), "inline": true }),
+        json!({
+            "name": "Total items",
+            "value": format(total_items.values().sum::<u64>() as i64),
+            "inline": true
+        }),
+    ];
+    if let Some(duration) = scan_duration {
+        fields.push(
+            json!({ "name": "Scan duration", "value": format!("{:.1?}", duration), "inline": true }),
+        );
+    }
+
+    // illegal items of which there are more than before, or any without a previous count
+    let new_illegal_items = ILLEGAL_ITEMS
+        .iter()
+        .filter_map(|name| {
+            let id = format!("minecraft:{}", name);
+            let count = total_items.get(&id).copied().unwrap_or(0);
+            let before = previous.map_or(0, |previous| previous.get(&id).copied().unwrap_or(0));
+            (count > before).then(|| format!("`{}` +{}", id, format((count - before) as i64)))
+        })
+        .collect::<Vec<_>>();
+    fields.push(json!({
+        "name": "New illegal items found",
+        "value": field_value(&new_illegal_items),
+    }));
+
+    if let Some(previous) = previous {
+        let deltas = deltas(total_items, previous);
+        let top_deltas = deltas
+            .iter()
+            .take(DISCORD_TOP_COUNT)
+            .map(|(id, delta)| {
+                let sign = if *delta > 0 { "+" } else { "" };
+                format!("`{}` {}{}", id, sign, format(*delta))
+            })
+            .collect::<Vec<_>>();
+        fields.insert(
+            1,
+            json!({ "name": "Top deltas", "value": field_value(&top_deltas) }),
+        );
+    }
+
+    let body = json!({
+        "embeds": [{
+            "title": "World statistics",
+            "fields": fields,
+        }]
+    });
+
+    ureq::post(webhook_url)
+        .set("Content-Type", "application/json")
+        .send_string(&body.to_string())?;
+
+    Ok(())
+}
+
+fn field_value(lines: &[String]) -> String {
+    if lines.is_empty() {
+        "none".to_string()
+    } else {
+        lines.join("\n")
+    }
+}
+
+/// Returns the changed counts, largest changes first
+fn deltas<'a>(
+    total_items: &'a IndexMap<String, u64>,
+    previous: &'a IndexMap<String, u64>,
+) -> Vec<(&'a str, i64)> {
+    let mut deltas: Vec<(&str, i64)> = total_items
+        .keys()
+        .chain(previous.keys())
+        .map(|id| {
+            let current = total_items.get(id).copied().unwrap_or(0) as i64;
+            let previous = previous.get(id).copied().unwrap_or(0) as i64;
+            (id.as_str(), current - previous)
+        })
+        .filter(|&(_, delta)| delta != 0)
+        .collect();
+    deltas.sort_by_key(|&(id, delta)| (-delta.abs(), id));
+    deltas.dedup();
+    deltas
+}
+
+fn render_template(
+    template: &str,
+    total_items: &IndexMap<String, u64>,
+    previous: Option<&IndexMap<String, u64>>,
+    locale: NumberLocale,
+) -> eyre::Result<String> {
+    let mut env = minijinja::Environment::new();
+    env.add_filter("thousands", move |value: i64| {
+        format_thousands(value, locale)
+    });
+    env.add_filter("compact", move |value: i64| format_compact(value, locale));
+    env.add_filter("stacks", |count: u64, id: String| {
+        format_stacks(count, stack_size(&id))
+    });
+    env.add_template("template", template)?;
+
+    let items: Vec<_> = total_items
+        .iter()
+        .map(|(id, count)| json!({ "id": id, "count": count }))
+        .collect();
+    let deltas: Vec<_> = previous
+        .map(|previous| deltas(total_items, previous))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(id, delta)| json!({ "id": id, "delta": delta }))
+        .collect();
+
+    Ok(env.get_template("template")?.render(json!({
+        "items": items,
+        "deltas": deltas,
+        "total": total_items.values().sum::<u64>(),
+        "types": total_items.len(),
+    }))?)
+}
@@ -0,0 +1,132 @@
+use std::{collections::BTreeMap, fs::OpenOptions, io, path::PathBuf};
+
+use clap::Parser;
+use eyre::eyre;
+use world_statistics::{
+    backup::Backup,
+    interrupt::{handle_interrupts, interrupted, EXIT_INTERRUPTED},
+    journal::Journal,
+    region::{chunk_position, RegionFile},
+    world::{dimension_path, ensure_closed, warn_if_open, ChunkSelection},
+};
+
+/// Deletes chunks from the region, entities and poi files so that they are regenerated on the
+/// next load. Stop the server before using this.
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Only print which chunks would be deleted
+    #[clap(long)]
+    dry_run: bool,
+
+    /// JSON lines file to record the previous content of changed chunks and files to, to be
+    /// reverted with undo
+    #[clap(long)]
+    journal: Option<PathBuf>,
+
+    /// Directory to copy the changed files to first, restored from if writing fails halfway
+    #[clap(long)]
+    backup: Option<PathBuf>,
+
+    /// Write even though the world is open in a running game or server
+    #[clap(long)]
+    force: bool,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether or end
+    dimension: String,
+
+    /// Chunks as `x,z` or boxes of chunks as `x1,z1..x2,z2`
+    #[clap(required = true, allow_hyphen_values = true)]
+    chunks: Vec<ChunkSelection>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    if args.dry_run {
+        warn_if_open(&world_path);
+    } else {
+        ensure_closed(&world_path, args.force)?;
+    }
+    let mut journal = args
+        .journal
+        .as_deref()
+        .map(|path| Journal::open(path, &world_path, "delete-chunks"))
+        .transpose()?;
+    let mut backup = args
+        .backup
+        .as_deref()
+        .map(|dir| Backup::new(dir, &world_path));
+    handle_interrupts()?;
+    let dim_path = dimension_path(&world_path, &args.dimension)
+        .ok_or_else(|| eyre!("unknown dimension {}", args.dimension))?;
+
+    let mut regions: BTreeMap<(i32, i32), Vec<usize>> = BTreeMap::new();
+    for (chunk_x, chunk_z) in args.chunks.iter().flat_map(ChunkSelection::chunks) {
+        let (region_x, region_z, index) = chunk_position(chunk_x, chunk_z);
+        regions.entry((region_x, region_z)).or_default().push(index);
+    }
+
+    let mut deleted = 0;
+    for ((region_x, region_z), indices) in regions {
+        if interrupted() {
+            break;
+        }
+        for kind in ["region", "entities", "poi"] {
+            let path = dim_path
+                .join(kind)
+                .join(format!("r.{}.{}.mca", region_x, region_z));
+
+            let file = match OpenOptions::new()
+                .read(true)
+                .write(!args.dry_run)
+                .open(&path)
+            {
+                Ok(file) => file,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            let mut region_file = RegionFile::new(file)?;
+
+            for &index in &indices {
+                if !region_file.contains_chunk(index) {
+                    continue;
+                }
+                let chunk_x = region_x * 32 + (index % 32) as i32;
+                let chunk_z = region_z * 32 + (index / 32) as i32;
+                println!("{} {} {}", kind, chunk_x, chunk_z);
+
+                if !args.dry_run {
+                    if let Some(backup) = &mut backup {
+                        backup.save(&path)?;
+                    }
+                    if let Some(journal) = &mut journal {
+                        journal.record_chunk(&path, &mut region_file, index)?;
+                    }
+                    region_file.delete_chunk(index)?;
+                }
+                deleted += 1;
+            }
+        }
+    }
+
+    if let Some(backup) = backup {
+        backup.finish();
+    }
+    if args.dry_run {
+        eprintln!("would delete {} chunks", deleted);
+    } else {
+        eprintln!("deleted {} chunks", deleted);
+    }
+
+    if interrupted() {
+        eprintln!("interrupted, the remaining files were left unchanged");
+        std::process::exit(EXIT_INTERRUPTED);
+    }
+
+    Ok(())
+}
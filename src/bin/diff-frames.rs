@@ -0,0 +1,165 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use quartz_nbt::{NbtCompound, NbtList};
+use world_statistics::{
+    items::id_and_count,
+    redact::Redaction,
+    region::for_each_chunk_in_dir,
+    uuid::format_uuid,
+    world::{dimension_path, warn_if_open},
+};
+
+/// Lists item frames and armor stands that were removed, emptied or changed between two
+/// copies of the same world
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Path to the older world directory
+    old_world: String,
+
+    /// Path to the newer world directory
+    new_world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+const ENTITY_IDS: &[&str] = &[
+    "minecraft:item_frame",
+    "minecraft:glow_item_frame",
+    "minecraft:armor_stand",
+];
+
+struct DisplayEntity {
+    id: String,
+    pos: [i32; 3],
+    items: Vec<String>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+    warn_if_open(Path::new(&args.old_world));
+    warn_if_open(Path::new(&args.new_world));
+    let redaction = Redaction::global()?;
+
+    for dim_name in &args.dimensions {
+        let old = read_displays(&PathBuf::from(&args.old_world), dim_name)?;
+        let new = read_displays(&PathBuf::from(&args.new_world), dim_name)?;
+
+        let mut old: Vec<_> = old.into_iter().collect();
+        old.sort_by_key(|(_, display)| display.pos);
+
+        for (uuid, old_display) in old {
+            if old_display.items.is_empty() {
+                continue;
+            }
+            let [x, y, z] = old_display.pos;
+            let (x, z) = redaction.coordinates(x, z);
+            let change = match new.get(&uuid) {
+                None => "removed".to_string(),
+                Some(new_display) if new_display.items.is_empty() => "emptied".to_string(),
+                Some(new_display) if new_display.items != old_display.items => {
+                    format!("changed to {}", new_display.items.join(" "))
+                }
+                Some(_) => continue,
+            };
+            println!(
+                "{} {} {} {} {} {}: {}",
+                dim_name,
+                x,
+                y,
+                z,
+                old_display.id,
+                old_display.items.join(" "),
+                change
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads all item frames and armor stands of a dimension by UUID
+fn read_displays(
+    world_path: &Path,
+    dim_name: &str,
+) -> eyre::Result<HashMap<String, DisplayEntity>> {
+    let dim_path = dimension_path(world_path, dim_name)
+        .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+
+    let mut displays = HashMap::new();
+    let result = for_each_chunk_in_dir(&dim_path.join("entities"), |_, _, chunk| {
+        let entities = match chunk.get::<_, &NbtList>("Entities") {
+            Ok(entities) => entities,
+            Err(_) => return,
+        };
+        for entity in entities.iter_map::<&NbtCompound>().flatten() {
+            let id: &str = entity.get("id").unwrap_or_default();
+            if !ENTITY_IDS.contains(&id) {
+                continue;
+            }
+            let uuid = match entity.get::<_, &[i32]>("UUID").ok().and_then(format_uuid) {
+                Some(uuid) => uuid,
+                None => continue,
+            };
+            let pos = match entity.get::<_, &NbtList>("Pos") {
+                Ok(pos) => {
+                    let coord = |i| pos.get::<f64>(i).unwrap_or_default().floor() as i32;
+                    [coord(0), coord(1), coord(2)]
+                }
+                Err(_) => continue,
+            };
+
+            let mut items = Vec::new();
+            if let Ok(item) = entity.get::<_, &NbtCompound>("Item") {
+                items.extend(describe_item(item));
+            }
+            for key in ["HandItems", "ArmorItems"] {
+                if let Ok(list) = entity.get::<_, &NbtList>(key) {
+                    items.extend(
+                        list.iter_map::<&NbtCompound>()
+                            .flatten()
+                            .filter_map(describe_item),
+                    );
+                }
+            }
+
+            displays.insert(
+                uuid,
+                DisplayEntity {
+                    id: id.to_string(),
+                    pos,
+                    items,
+                },
+            );
+        }
+    });
+
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            eprintln!(
+                "no entities folder for {} in {}",
+                dim_name,
+                world_path.display()
+            )
+        }
+        result => result.with_context(|| format!("failed to read entities of {}", dim_name))?,
+    }
+
+    Ok(displays)
+}
+
+fn describe_item(item: &NbtCompound) -> Option<String> {
+    let (id, count) = id_and_count(item);
+    if id.is_empty() {
+        return None;
+    }
+    Some(format!("{}x{}", count.max(1), id))
+}
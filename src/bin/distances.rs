@@ -0,0 +1,273 @@
+use std::{path::PathBuf, str::FromStr};
+
+use clap::{ArgEnum, Parser};
+use eyre::eyre;
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    anonymize::AnonymizeArgs,
+    dat::read_dat,
+    location::csv_field,
+    players::{find_usercache, playerdata_files},
+    redact::{print_json, Redaction},
+    region::for_each_chunk_in_dir,
+    text::plain_text,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, BlockPos, Source, Sources},
+};
+
+/// Computes the distances between points of interest, like the respawn points of players and
+/// named containers, both in a straight line and when travelling through the nether
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    #[clap(short, long, arg_enum, default_value = "json")]
+    format: Format,
+
+    /// Additional point as `name=x,y,z` in the overworld or `name=nether:x,y,z`
+    #[clap(long, allow_hyphen_values = true)]
+    point: Vec<Point>,
+
+    /// Leave out the respawn points of players
+    #[clap(long)]
+    no_spawns: bool,
+
+    /// Leave out named containers
+    #[clap(long)]
+    no_containers: bool,
+
+    /// Only points whose name contains this text, ignoring case
+    #[clap(long)]
+    filter: Option<String>,
+
+    #[clap(flatten)]
+    anonymize: AnonymizeArgs,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug)]
+enum Format {
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Clone)]
+struct Point {
+    name: String,
+    /// `spawn`, `container` or `point` for the ones given on the command line
+    kind: &'static str,
+    dimension: String,
+    pos: [i32; 3],
+}
+
+impl FromStr for Point {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let (name, pos) = str
+            .split_once('=')
+            .ok_or_else(|| "expected name=x,y,z".to_string())?;
+        let (dimension, pos) = pos.rsplit_once(':').unwrap_or(("overworld", pos));
+        Ok(Point {
+            name: name.to_string(),
+            kind: "point",
+            dimension: dimension.to_string(),
+            pos: pos.parse::<BlockPos>()?.0,
+        })
+    }
+}
+
+impl Point {
+    /// Horizontal position in overworld coordinates, if the dimension is linked to the overworld
+    /// by nether portals
+    fn overworld_pos(&self) -> Option<(f64, f64)> {
+        let [x, _, z] = self.pos.map(f64::from);
+        match self.dimension.as_str() {
+            "overworld" => Some((x, z)),
+            "nether" => Some((x * 8.0, z * 8.0)),
+            _ => None,
+        }
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    let mut missing = Vec::new();
+    if !args.no_containers {
+        missing.push((Source::Region, "named containers there"));
+    }
+    if !args.no_spawns {
+        missing.push((Source::Playerdata, "respawn points"));
+    }
+    sources.notify_missing(&missing);
+
+    let names = find_usercache(&world_path);
+    let anonymizer = args.anonymize.anonymizer()?;
+    let redaction = Redaction::global()?;
+
+    let mut points = args.point.clone();
+
+    if !args.no_spawns && sources.playerdata {
+        for (uuid, path) in playerdata_files(&world_path)? {
+            if redaction.hides_uuid(&uuid, &names) {
+                continue;
+            }
+            let player = match read_dat(&path) {
+                Ok(player) => player,
+                Err(e) => {
+                    eprintln!("failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let pos = match ["SpawnX", "SpawnY", "SpawnZ"].map(|key| player.get::<_, i32>(key).ok())
+            {
+                [Some(x), Some(y), Some(z)] => [x, y, z],
+                _ => continue,
+            };
+            let dimension = match player.get::<_, &str>("SpawnDimension") {
+                Ok("minecraft:the_nether") => "nether",
+                Ok("minecraft:the_end") => "end",
+                _ => "overworld",
+            };
+            let name = anonymizer
+                .player(&uuid)
+                .unwrap_or_else(|| names.get(&uuid).cloned().unwrap_or(uuid));
+            points.push(Point {
+                name,
+                kind: "spawn",
+                dimension: dimension.to_string(),
+                pos,
+            });
+        }
+    }
+
+    if !args.no_containers {
+        for dim_name in &args.dimensions {
+            let dim_path = dimension_path(&world_path, dim_name)
+                .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+            if !sources.has(dim_name, Source::Region) {
+                continue;
+            }
+            for_each_chunk_in_dir(&dim_path.join("region"), |_, _, chunk| {
+                let version =
+                    GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+                let block_entities = match version
+                    .level(&chunk)
+                    .and_then(|level| level.get::<_, &NbtList>(version.block_entities_key()).ok())
+                {
+                    Some(block_entities) => block_entities,
+                    None => return,
+                };
+                for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+                    if !block_entity.contains_key("Items") {
+                        continue;
+                    }
+                    let name = match block_entity.get::<_, &str>("CustomName") {
+                        Ok(name) => plain_text(name),
+                        Err(_) => continue,
+                    };
+                    points.push(Point {
+                        name,
+                        kind: "container",
+                        dimension: dim_name.clone(),
+                        pos: ["x", "y", "z"]
+                            .map(|key| block_entity.get::<_, i32>(key).unwrap_or_default()),
+                    });
+                }
+            })?;
+        }
+    }
+
+    if let Some(filter) = &args.filter {
+        let filter = filter.to_lowercase();
+        points.retain(|point| point.name.to_lowercase().contains(&filter));
+    }
+    // distances are computed from the coordinates as reported, so that they can't be used to
+    // work out redacted or coarsened positions
+    for point in &mut points {
+        let (x, z) = redaction.coordinates(point.pos[0], point.pos[2]);
+        point.pos = anonymizer.pos([x, point.pos[1], z]);
+    }
+    points.sort_by(|a, b| (a.kind, &a.name, a.pos).cmp(&(b.kind, &b.name, b.pos)));
+
+    // whole blocks
+    let matrix = |distance: fn(&Point, &Point) -> Option<f64>| {
+        points
+            .iter()
+            .map(|a| {
+                points
+                    .iter()
+                    .map(|b| distance(a, b).map(|distance| distance.round() as i64))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+    let straight = matrix(straight_distance);
+    let nether = matrix(nether_distance);
+
+    match args.format {
+        Format::Json => {
+            let report = json!({
+                "points": points
+                    .iter()
+                    .map(|point| json!({
+                        "name": point.name,
+                        "kind": point.kind,
+                        "dimension": point.dimension,
+                        "pos": point.pos,
+                    }))
+                    .collect::<Vec<_>>(),
+                "straight": straight,
+                "nether": nether,
+            });
+            print_json(&report)?;
+        }
+        Format::Csv => {
+            println!("from,to,straight,nether");
+            for (i, a) in points.iter().enumerate() {
+                for (j, b) in points.iter().enumerate().skip(i + 1) {
+                    let cell = |distance: Option<i64>| distance.map(|d| d.to_string());
+                    println!(
+                        "{},{},{},{}",
+                        csv_field(&a.name),
+                        csv_field(&b.name),
+                        cell(straight[i][j]).unwrap_or_default(),
+                        cell(nether[i][j]).unwrap_or_default()
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The distance in blocks between points in the same dimension, or between the overworld and the
+/// nether with nether coordinates scaled up by 8
+fn straight_distance(a: &Point, b: &Point) -> Option<f64> {
+    if a.dimension == b.dimension {
+        let squared: f64 = (0..3)
+            .map(|i| (a.pos[i] as f64 - b.pos[i] as f64).powi(2))
+            .sum();
+        return Some(squared.sqrt());
+    }
+    let (a, b) = (a.overworld_pos()?, b.overworld_pos()?);
+    Some((a.0 - b.0).hypot(a.1 - b.1))
+}
+
+/// The horizontal distance walked in the nether between two points in the overworld or nether,
+/// not counting the way to and from the portals
+fn nether_distance(a: &Point, b: &Point) -> Option<f64> {
+    let (a, b) = (a.overworld_pos()?, b.overworld_pos()?);
+    Some((a.0 - b.0).hypot(a.1 - b.1) / 8.0)
+}
@@ -1,18 +1,39 @@
 use std::{
     collections::{HashMap, HashSet},
-    fs::{self, DirEntry, File},
-    io,
+    fmt, fs,
+    io::{self, BufWriter, Write},
     path::{Path, PathBuf},
-    sync::Arc,
+    process,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::Sender,
+        Arc, Condvar, Mutex,
+    },
+    time::{Duration, Instant},
 };
 
 use clap::Parser;
-use eyre::Context;
 use quartz_nbt::{NbtCompound, NbtList};
-use regex::Regex;
-use world_statistics::region::{read_chunk, RegionFile};
+use serde_json::{json, Value};
+use world_statistics::{
+    anonymize::Anonymizer,
+    checksums::{checksum, ChecksumStore},
+    dat::read_dat,
+    interrupt::{handle_interrupts, interrupted, EXIT_INTERRUPTED},
+    items::id_and_count,
+    location::{Location, LocationFormat, LocationWriter},
+    redact::Redaction,
+    region::{read_chunk, region_files, Payload, RegionFile},
+    snbt::write_compound,
+    symbol::Symbol,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{warn_if_open, World},
+};
 
-/// Dumps the items in a world line seperated in SNBT
+/// Dumps the items in a world line seperated in SNBT.
+///
+/// Exits with 2 if chunks had to be skipped, 3 if the world is invalid and 4 on IO errors. On
+/// Ctrl-C the regions being read are finished and the output is closed before exiting with 130.
 #[derive(Parser, Debug)]
 #[clap(color = clap::ColorChoice::Never)]
 struct Args {
@@ -24,14 +45,235 @@ struct Args {
     #[clap(short, long, default_value = "all")]
     block_entities: String,
 
+    /// Output format, geojson and csv include the location of each item's container
+    #[clap(long, arg_enum, default_value = "snbt")]
+    format: Format,
+
+    /// Round the locations in the geojson and csv formats down to the corner of their region,
+    /// e.g. to share them publicly
+    #[clap(long)]
+    coarse_coordinates: bool,
+
+    /// Write the chunks that failed to be read with the reasons to this JSON file
+    #[clap(long)]
+    error_report: Option<String>,
+
+    /// Write the counts and duration of the scan to this JSON file, e.g. for
+    /// count-items --scan-stats
+    #[clap(long)]
+    stats: Option<String>,
+
+    /// Maximum size of the chunk data waiting to be decoded, in MiB, shared by all dimensions
+    #[clap(long)]
+    max_memory: Option<u64>,
+
+    /// Number of threads decoding chunks, shared by all dimensions which are read concurrently
+    #[clap(long, default_value = "4")]
+    threads: usize,
+
+    /// Read chunks in the format of this DataVersion or release like 1.16.5 instead of their
+    /// own DataVersion, e.g. if it is missing
+    #[clap(long)]
+    assume_version: Option<GameVersion>,
+
+    /// JSON file to record the CRC32 checksums of the chunk payloads in, like validate-chunks.
+    /// Chunks whose payload changed since the previous scan although the game didn't save them
+    /// are reported as failed.
+    #[clap(long)]
+    checksums: Option<String>,
+
     /// Path to the world directory
     world: String,
 
-    /// overworld, nether, end, playerdata
+    /// overworld, nether, end, playerdata, or custom dimensions like mymod:mining
     #[clap(required = true)]
     sources: Vec<String>,
 }
 
+#[derive(clap::ArgEnum, Clone, Copy, Debug)]
+enum Format {
+    Snbt,
+    Geojson,
+    Csv,
+}
+
+/// An item together with the container it was found in
+struct FoundItem {
+    item: NbtCompound,
+    dimension: Symbol,
+    container_id: Symbol,
+    pos: [i32; 3],
+}
+
+impl FoundItem {
+    fn to_location(&self) -> Location {
+        let (id, count) = id_and_count(&self.item);
+        Location {
+            dimension: self.dimension.to_string(),
+            x: self.pos[0],
+            y: self.pos[1],
+            z: self.pos[2],
+            kind: self.container_id.to_string(),
+            id: id.to_string(),
+            count,
+            // the `tag` of the item before 1.20.5 and its `components` since
+            extra: match self
+                .item
+                .get::<_, &NbtCompound>(GameVersion::of_item(&self.item).item_data_key())
+            {
+                Ok(data) => data.to_string(),
+                Err(_) => String::new(),
+            },
+        }
+    }
+}
+
+/// Limits the bytes of chunk data in flight between the region reader and the workers
+#[derive(Debug)]
+struct MemoryBudget {
+    max: u64,
+    in_flight: Mutex<u64>,
+    released: Condvar,
+}
+
+impl MemoryBudget {
+    fn new(max: u64) -> Self {
+        MemoryBudget {
+            max,
+            in_flight: Mutex::new(0),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Blocks until `bytes` fit into the budget. A chunk larger than the whole budget is let
+    /// through once nothing else is in flight.
+    fn acquire(&self, bytes: u64) {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        while *in_flight > 0 && *in_flight + bytes > self.max {
+            in_flight = self.released.wait(in_flight).unwrap();
+        }
+        *in_flight += bytes;
+    }
+
+    fn release(&self, bytes: u64) {
+        *self.in_flight.lock().unwrap() -= bytes;
+        self.released.notify_all();
+    }
+}
+
+const EXIT_SKIPPED_CHUNKS: i32 = 2;
+const EXIT_INVALID_WORLD: i32 = 3;
+const EXIT_IO_ERROR: i32 = 4;
+
+#[derive(thiserror::Error, Debug)]
+#[error("invalid world: {0}")]
+struct InvalidWorld(String);
+
+/// A chunk or region file that couldn't be scanned
+#[derive(Debug)]
+struct ScanFailure {
+    region: PathBuf,
+    chunk: Option<(i32, i32)>,
+    reason: String,
+}
+
+/// Counters of what was scanned in a source, printed at the end of a run
+#[derive(Debug, Default)]
+struct ScanStats {
+    regions: AtomicU64,
+    regions_skipped: AtomicU64,
+    /// Regions left out because the scan was interrupted
+    regions_unscanned: AtomicU64,
+    /// Playerdata files read, only counted for the playerdata source
+    players: Option<AtomicU64>,
+    chunks_read: AtomicU64,
+    chunks_skipped: AtomicU64,
+    chunks_failed: AtomicU64,
+    entities: AtomicU64,
+    block_entities: AtomicU64,
+    items: AtomicU64,
+    /// Time the workers spent decoding and scanning chunks
+    worker_time: AtomicU64,
+    wall_time: AtomicU64,
+    failures: Mutex<Vec<ScanFailure>>,
+}
+
+impl ScanStats {
+    fn add(counter: &AtomicU64, value: u64) {
+        counter.fetch_add(value, Ordering::Relaxed);
+    }
+
+    fn fail(&self, region: &Path, chunk: Option<(i32, i32)>, reason: impl ToString) {
+        self.failures.lock().unwrap().push(ScanFailure {
+            region: region.to_path_buf(),
+            chunk,
+            reason: reason.to_string(),
+        });
+    }
+}
+
+impl ScanStats {
+    fn to_json(&self) -> Value {
+        let get = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        let mut stats = json!({
+            "regions": get(&self.regions),
+            "regions_skipped": get(&self.regions_skipped),
+            "regions_unscanned": get(&self.regions_unscanned),
+            "chunks_read": get(&self.chunks_read),
+            "chunks_skipped": get(&self.chunks_skipped),
+            "chunks_failed": get(&self.chunks_failed),
+            "entities": get(&self.entities),
+            "block_entities": get(&self.block_entities),
+            "items": get(&self.items),
+            "failures": self.failures.lock().unwrap().len(),
+            "wall_time_ms": get(&self.wall_time) / 1_000_000,
+            "worker_time_ms": get(&self.worker_time) / 1_000_000,
+        });
+        if let Some(players) = &self.players {
+            stats["players"] = json!(get(players));
+        }
+        stats
+    }
+}
+
+impl fmt::Display for ScanStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let get = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        if let Some(players) = &self.players {
+            return write!(
+                f,
+                "{} players, {} failed, {} items in {:.1?}",
+                get(players),
+                self.failures.lock().unwrap().len(),
+                get(&self.items),
+                Duration::from_nanos(get(&self.wall_time)),
+            );
+        }
+        if get(&self.regions_unscanned) > 0 {
+            write!(
+                f,
+                "interrupted with {} regions left, ",
+                get(&self.regions_unscanned)
+            )?;
+        }
+        write!(
+            f,
+            "{} regions ({} skipped), {} chunks read, {} skipped, {} failed, {} entities, \
+            {} block entities, {} items in {:.1?} (worker time {:.1?})",
+            get(&self.regions),
+            get(&self.regions_skipped),
+            get(&self.chunks_read),
+            get(&self.chunks_skipped),
+            get(&self.chunks_failed),
+            get(&self.entities),
+            get(&self.block_entities),
+            get(&self.items),
+            Duration::from_nanos(get(&self.wall_time)),
+            Duration::from_nanos(get(&self.worker_time)),
+        )
+    }
+}
+
 const ENTITY_IDS: &[&str] = &[
     "minecraft:item",
     "minecraft:item_frame",
@@ -56,36 +298,162 @@ const BLOCK_ENTITY_IDS: &[&str] = &[
 fn main() -> eyre::Result<()> {
     let args = Args::parse();
 
-    let world_path = PathBuf::from(args.world);
+    let started = Instant::now();
+    let mut stats = Vec::new();
+    let result = run(&args, &mut stats);
+
+    let failures: Vec<_> = stats
+        .iter()
+        .flat_map(|(source, stats): &(String, Arc<ScanStats>)| {
+            let failures = stats.failures.lock().unwrap();
+            failures
+                .iter()
+                .map(|failure| {
+                    json!({
+                        "source": source,
+                        "region": failure.region,
+                        "chunk": failure.chunk.map(|(x, z)| [x, z]),
+                        "reason": failure.reason,
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    if let Some(path) = &args.error_report {
+        fs::write(path, serde_json::to_string_pretty(&failures)?)?;
+    }
+    if let Some(path) = &args.stats {
+        let report = json!({
+            "duration_ms": started.elapsed().as_millis() as u64,
+            "interrupted": interrupted(),
+            "sources": stats
+                .iter()
+                .map(|(source, stats)| (source.clone(), stats.to_json()))
+                .collect::<serde_json::Map<_, _>>(),
+        });
+        fs::write(path, serde_json::to_string_pretty(&report)?)?;
+    }
 
-    for source in args.sources {
-        let mut split = source.split(":");
+    if let Err(e) = result {
+        eprintln!("Error: {:?}", e);
+        if e.downcast_ref::<InvalidWorld>().is_some() {
+            process::exit(EXIT_INVALID_WORLD);
+        }
+        if e.downcast_ref::<io::Error>().is_some() {
+            process::exit(EXIT_IO_ERROR);
+        }
+        process::exit(1);
+    }
+    if interrupted() {
+        eprintln!("the scan was interrupted, the output only covers part of the world");
+        process::exit(EXIT_INTERRUPTED);
+    }
+    if !failures.is_empty() {
+        eprintln!("{} chunks or region files were skipped", failures.len());
+        process::exit(EXIT_SKIPPED_CHUNKS);
+    }
 
-        let dim_name = split.next().unwrap().to_string();
-        let opts = parse_opts(split.next());
+    Ok(())
+}
 
-        match dim_name.as_str() {
-            "overworld" | "nether" | "end" => {
-                let dim_path = world_path.join(match dim_name.as_str() {
-                    "overworld" => "",
-                    "nether" => "DIM-1",
-                    "end" => "DIM1",
-                    _ => panic!(),
-                });
+fn run(args: &Args, stats: &mut Vec<(String, Arc<ScanStats>)>) -> eyre::Result<()> {
+    warn_if_open(Path::new(&args.world));
+    handle_interrupts()?;
+    let world = World::open(&args.world).map_err(|e| InvalidWorld(e.to_string()))?;
+
+    let (item_tx, item_rx) = std::sync::mpsc::channel::<FoundItem>();
+
+    let format = args.format;
+    let anonymizer = Anonymizer::new(None, args.coarse_coordinates);
+    // fail before scanning if the redaction config the location writer uses is invalid
+    Redaction::global()?;
+    let handle = std::thread::spawn(move || -> io::Result<()> {
+        match format {
+            Format::Snbt => {
+                let mut writer = BufWriter::with_capacity(1 << 20, io::stdout().lock());
+                for found in item_rx {
+                    write_compound(&mut writer, &found.item)?;
+                    writer.write_all(b"\n")?;
+                }
+                writer.flush()?;
+            }
+            Format::Geojson | Format::Csv => {
+                let format = match format {
+                    Format::Csv => LocationFormat::Csv,
+                    _ => LocationFormat::GeoJson,
+                };
+                let mut writer = LocationWriter::new(format, io::stdout().lock())?;
+                for found in item_rx {
+                    let mut location = found.to_location();
+                    anonymizer.location(&mut location);
+                    writer.write(&location)?;
+                }
+                writer.finish()?;
+            }
+        }
+        Ok(())
+    });
 
-                scan_dimension(ScanDimensionOptions {
-                    dim_name,
+    let checksums = match &args.checksums {
+        Some(path) => Some(Arc::new(Mutex::new(ChecksumStore::open(
+            Path::new(path),
+            Path::new(&args.world),
+        )?))),
+        None => None,
+    };
+
+    let memory_budget = args
+        .max_memory
+        .map(|max| Arc::new(MemoryBudget::new(max * 1024 * 1024)));
+    let (chunk_tx, chunk_rx) = crossbeam_channel::bounded::<ChunkMessage>(args.threads * 2);
+    let workers: Vec<_> = (0..args.threads)
+        .map(|_| {
+            let chunk_rx = chunk_rx.clone();
+            let item_tx = item_tx.clone();
+            std::thread::spawn(move || scan_chunks(chunk_rx, item_tx))
+        })
+        .collect();
+    drop(chunk_rx);
+
+    // the dimensions and the playerdata are read concurrently, the dimensions sharing the
+    // workers and the memory budget
+    let mut readers = Vec::new();
+    for source in &args.sources {
+        let (dim_name, opts) = split_source(source);
+        let dim_name = dim_name.to_string();
+        let opts = parse_opts(opts);
+
+        match world.dimension(&dim_name) {
+            Some(dimension) => {
+                let dim_path = dimension.path;
+
+                let source_stats = Arc::new(ScanStats::default());
+                stats.push((source.clone(), source_stats.clone()));
+                let options = ScanDimensionOptions {
+                    dim_name: Symbol::intern(&dim_name),
                     dim_path,
                     entities: parse_list(&args.entities, ENTITY_IDS),
                     block_entities: parse_list(&args.block_entities, BLOCK_ENTITY_IDS),
-                    chunk_radius: opts
-                        .get("chunk_radius")
-                        .map(|&str| str.parse().ok())
-                        .flatten(),
-                })?;
+                    chunk_radius: opts.get("chunk_radius").and_then(|&str| str.parse().ok()),
+                    assume_version: args.assume_version,
+                    checksums: checksums.clone(),
+                    memory_budget: memory_budget.clone(),
+                    stats: source_stats,
+                    started: Instant::now(),
+                };
+                let region_files = dimension_regions(&options)?;
+                let chunk_tx = chunk_tx.clone();
+                readers.push(std::thread::spawn(move || {
+                    scan_dimension(options, region_files, chunk_tx)
+                }));
             }
-            "playerdata" => {
-                scan_playerdata(ScanPlayerDataOptions {
+            None if dim_name == "playerdata" => {
+                let source_stats = Arc::new(ScanStats {
+                    players: Some(AtomicU64::new(0)),
+                    ..ScanStats::default()
+                });
+                stats.push((source.clone(), source_stats.clone()));
+                let options = ScanPlayerDataOptions {
                     inventory: if opts.is_empty() {
                         true
                     } else {
@@ -96,128 +464,316 @@ fn main() -> eyre::Result<()> {
                     } else {
                         opts.contains_key("ender_chest")
                     },
-                });
+                    world: world.clone(),
+                    item_tx: item_tx.clone(),
+                    stats: source_stats,
+                };
+                readers.push(std::thread::spawn(move || scan_playerdata(options)));
             }
-            name => panic!("unknown source: {}", name),
+            None => return Err(InvalidWorld(format!("unknown source: {}", dim_name)).into()),
         }
     }
 
+    drop(chunk_tx);
+    for reader in readers {
+        reader.join().unwrap()?;
+    }
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    if let Some(checksums) = &checksums {
+        checksums.lock().unwrap().save()?;
+    }
+    for (source, source_stats) in stats.iter() {
+        eprintln!("{}: {}", source, source_stats);
+    }
+
+    drop(item_tx);
+    handle.join().unwrap()?;
+
     Ok(())
 }
 
 #[derive(Debug)]
 pub struct ScanDimensionOptions {
-    pub dim_name: String,
+    pub dim_name: Symbol,
     pub dim_path: PathBuf,
-    pub entities: HashSet<String>,
-    pub block_entities: HashSet<String>,
+    pub entities: HashSet<Symbol>,
+    pub block_entities: HashSet<Symbol>,
     pub chunk_radius: Option<u32>,
+    /// Read the chunks in the format of this version instead of their DataVersion
+    pub assume_version: Option<GameVersion>,
+    checksums: Option<Arc<Mutex<ChecksumStore>>>,
+    memory_budget: Option<Arc<MemoryBudget>>,
+    stats: Arc<ScanStats>,
+    started: Instant,
 }
 
-fn scan_dimension(options: ScanDimensionOptions) -> eyre::Result<()> {
-    eprintln!("scanning {}", options.dim_name);
+/// A chunk read from a region file of a dimension, waiting to be decoded and scanned by a worker
+struct ChunkMessage {
+    options: Arc<ScanDimensionOptions>,
+    is_entity_chunk: bool,
+    region: PathBuf,
+    chunk_pos: (i32, i32),
+    buf: Payload,
+}
 
-    let options = Arc::new(options);
-    let region_regex = Regex::new(r"^r\.(-?\d+)\.(-?\d+)\.mca$")?;
-    let region_path = options.dim_path.join("region");
-
-    let mut region_files: Vec<(i32, i32, DirEntry)> = fs::read_dir(region_path)
-        .context("region file folder not found")?
-        .flatten()
-        .map(|entry| {
-            let filename = entry.file_name();
-            let cap = region_regex.captures(filename.to_str()?)?;
-            Some((cap[1].parse().ok()?, cap[2].parse().ok()?, entry))
-        })
-        .flatten()
-        .collect();
+/// Lists the region files of a dimension, nearest to the origin first
+fn dimension_regions(options: &ScanDimensionOptions) -> eyre::Result<Vec<(i32, i32, PathBuf)>> {
+    let mut region_files = region_files(&options.dim_path.join("region")).map_err(|e| {
+        InvalidWorld(format!(
+            "region folder of {} not readable: {}",
+            options.dim_name, e
+        ))
+    })?;
 
     region_files.sort_by_key(|(x, z, _)| (i32::max((x * 2 + 1).abs(), (z * 2 + 1).abs()), *x, *z));
+    Ok(region_files)
+}
 
-    let (chunk_tx, chunk_rx) = crossbeam_channel::bounded::<(bool, Vec<u8>)>(6);
-    let (item_tx, item_rx) = std::sync::mpsc::channel();
+/// Decodes the chunks of all dimensions and sends the items found in them
+fn scan_chunks(chunk_rx: crossbeam_channel::Receiver<ChunkMessage>, item_tx: Sender<FoundItem>) {
+    for message in chunk_rx {
+        let ChunkMessage {
+            options,
+            is_entity_chunk,
+            region,
+            chunk_pos,
+            buf,
+        } = message;
+        let stats = &options.stats;
+        let send = |item: &NbtCompound, container_id: Symbol, pos: [i32; 3]| {
+            ScanStats::add(&stats.items, 1);
+            // the writer only stops early on an error, which is returned when joining it
+            let _ = item_tx.send(FoundItem {
+                item: item.clone(),
+                dimension: options.dim_name,
+                container_id,
+                pos,
+            });
+        };
 
-    for _ in 0..4 {
-        let chunk_rx = chunk_rx.clone();
-        let item_tx = item_tx.clone();
-        let options = options.clone();
+        let chunk_started = Instant::now();
+        let chunk = read_chunk(&buf);
+        if let Some(memory_budget) = &options.memory_budget {
+            memory_budget.release(buf.len() as u64);
+        }
+        drop(buf);
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                eprintln!(
+                    "failed to read chunk {} {}: {}",
+                    chunk_pos.0, chunk_pos.1, e
+                );
+                ScanStats::add(&stats.chunks_failed, 1);
+                stats.fail(&region, Some(chunk_pos), e);
+                continue;
+            }
+        };
+        ScanStats::add(&stats.chunks_read, 1);
+
+        if let Err(reason) = scan_chunk(&chunk, is_entity_chunk, &options, &send) {
+            eprintln!(
+                "failed to scan chunk {} {}: {}",
+                chunk_pos.0, chunk_pos.1, reason
+            );
+            ScanStats::add(&stats.chunks_failed, 1);
+            stats.fail(&region, Some(chunk_pos), reason);
+        }
+        ScanStats::add(
+            &stats.worker_time,
+            chunk_started.elapsed().as_nanos() as u64,
+        );
+        // the dimension took until its last chunk was scanned
+        stats.wall_time.fetch_max(
+            options.started.elapsed().as_nanos() as u64,
+            Ordering::Relaxed,
+        );
+    }
+}
 
-        std::thread::spawn(move || {
-            for (is_entity_chunk, buf) in chunk_rx {
-                let chunk = read_chunk(&buf).unwrap();
+/// Sends the items in the selected block entities and entities of a chunk. Fails if the chunk
+/// isn't in the format of its version, after sending the items found up to there.
+fn scan_chunk(
+    chunk: &NbtCompound,
+    is_entity_chunk: bool,
+    options: &ScanDimensionOptions,
+    send: &impl Fn(&NbtCompound, Symbol, [i32; 3]),
+) -> Result<(), String> {
+    if is_entity_chunk {
+        let entities = chunk
+            .get::<_, &NbtList>("Entities")
+            .map_err(|e| format!("invalid Entities: {}", e))?;
+        return scan_entities(entities, options, send);
+    }
 
-                if is_entity_chunk {
-                    let entities = chunk
-                        .get::<_, &NbtList>("Entities")
-                        .unwrap()
-                        .iter_map::<&NbtCompound>();
-
-                    for entity in entities {
-                        let entity = entity.unwrap();
-
-                        let id: &str = entity.get("id").unwrap();
-                        if !options.entities.contains(id) {
-                            continue;
-                        }
-
-                        match id {
-                            "minecraft:item"
-                            | "minecraft:item_frame"
-                            | "minecraft:glow_item_frame" => {
-                                if entity.contains_key("Item") {
-                                    let item: &NbtCompound = entity.get("Item").unwrap();
-                                    item_tx.send(item.clone()).unwrap();
-                                }
-                            }
-                            "minecraft:chest_minecart" | "minecraft:hopper_minecart" => {
-                                if entity.contains_key("Items") {
-                                    let items: &NbtList = entity.get("Items").unwrap();
-                                    for item in items.iter_map::<&NbtCompound>() {
-                                        item_tx.send(item.unwrap().clone()).unwrap();
-                                    }
-                                }
-                            }
-                            _ => panic!(),
-                        }
-                    }
-                } else {
-                    let block_entities: &NbtList = chunk.get("block_entities").unwrap();
-                    for block_entity in block_entities.iter_map::<&NbtCompound>() {
-                        let block_entity = block_entity.unwrap();
-
-                        let id: &str = block_entity.get("id").unwrap();
-                        if options.block_entities.contains(id) && block_entity.contains_key("Items")
-                        {
-                            let items: &NbtList = block_entity.get("Items").unwrap();
-                            for item in items.iter_map::<&NbtCompound>() {
-                                item_tx.send(item.unwrap().clone()).unwrap();
-                            }
-                        }
-                    }
-                }
-            }
-        });
+    let version = options
+        .assume_version
+        .or_else(|| GameVersion::of(chunk))
+        .unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+    let level = version
+        .level(chunk)
+        .ok_or_else(|| format!("missing Level for {}", version))?;
+    let key = version.block_entities_key();
+    let block_entities = level
+        .get::<_, &NbtList>(key)
+        .map_err(|e| format!("invalid {}: {}", key, e))?;
+    for block_entity in block_entities.iter_map::<&NbtCompound>() {
+        let block_entity = block_entity.map_err(|e| format!("invalid block entity: {}", e))?;
+        ScanStats::add(&options.stats.block_entities, 1);
+
+        let id = Symbol::intern(
+            block_entity
+                .get("id")
+                .map_err(|e| format!("invalid block entity id: {}", e))?,
+        );
+        if !options.block_entities.contains(&id) || !block_entity.contains_key("Items") {
+            continue;
+        }
+        let mut pos = [0; 3];
+        for (coord, key) in pos.iter_mut().zip(["x", "y", "z"]) {
+            *coord = block_entity
+                .get(key)
+                .map_err(|e| format!("invalid position of {}: {}", id, e))?;
+        }
+        send_items(block_entity, id, pos, send)?;
     }
+    // entities were stored in the terrain chunks before 1.17
+    if version.entities_in_chunk() {
+        if let Ok(entities) = level.get::<_, &NbtList>("Entities") {
+            scan_entities(entities, options, send)?;
+        }
+    }
+    Ok(())
+}
 
-    let handle = std::thread::spawn(move || {
-        for item in item_rx {
-            println!("{}", item);
+/// Sends the items held by the selected entities
+fn scan_entities(
+    entities: &NbtList,
+    options: &ScanDimensionOptions,
+    send: &impl Fn(&NbtCompound, Symbol, [i32; 3]),
+) -> Result<(), String> {
+    for entity in entities.iter_map::<&NbtCompound>() {
+        let entity = entity.map_err(|e| format!("invalid entity: {}", e))?;
+        ScanStats::add(&options.stats.entities, 1);
+
+        let id = Symbol::intern(
+            entity
+                .get("id")
+                .map_err(|e| format!("invalid entity id: {}", e))?,
+        );
+        if !options.entities.contains(&id) {
+            continue;
         }
-    });
+        let pos = entity_pos(entity).ok_or_else(|| format!("invalid position of {}", id))?;
+        send_items(entity, id, pos, send)?;
+    }
+    Ok(())
+}
 
-    for (region_x, region_z, entry) in region_files.into_iter() {
+/// Sends the single `Item` of an item frame or dropped item and the `Items` of a container
+fn send_items(
+    container: &NbtCompound,
+    id: Symbol,
+    pos: [i32; 3],
+    send: &impl Fn(&NbtCompound, Symbol, [i32; 3]),
+) -> Result<(), String> {
+    if let Ok(item) = container.get::<_, &NbtCompound>("Item") {
+        send(item, id, pos);
+    }
+    if container.contains_key("Items") {
+        let items = container
+            .get::<_, &NbtList>("Items")
+            .map_err(|e| format!("invalid items of {}: {}", id, e))?;
+        for item in items.iter_map::<&NbtCompound>() {
+            send(
+                item.map_err(|e| format!("invalid item in {}: {}", id, e))?,
+                id,
+                pos,
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Reads the chunks of a dimension's region files and passes them to the workers
+fn scan_dimension(
+    options: ScanDimensionOptions,
+    region_files: Vec<(i32, i32, PathBuf)>,
+    chunk_tx: crossbeam_channel::Sender<ChunkMessage>,
+) -> eyre::Result<()> {
+    eprintln!("scanning {}", options.dim_name);
+    let options = Arc::new(options);
+
+    for (region_x, region_z, path) in region_files.into_iter() {
+        // regions already read are still scanned by the workers after an interrupt
+        if interrupted() {
+            ScanStats::add(&options.stats.regions_unscanned, 1);
+            continue;
+        }
         if let Some(chunk_radius) = options.chunk_radius {
             let r = (chunk_radius as i32 - 1) / 32;
             if region_x > r || region_x < -r - 1 || region_z > r || region_z < -r - 1 {
+                ScanStats::add(&options.stats.regions_skipped, 1);
                 continue;
             }
         }
+        ScanStats::add(&options.stats.regions, 1);
 
         eprintln!("processing region {} {}", region_x, region_z);
 
         let scan_region_file = |is_entity_chunk: bool, path: &Path| -> eyre::Result<()> {
-            let mut region_file = match RegionFile::new(match File::open(path) {
-                Ok(file) => file,
+            let send_chunk = |index: usize, timestamp: u32, buf: Payload| {
+                let chunk_x = region_x * 32 + (index % 32) as i32;
+                let chunk_z = region_z * 32 + (index / 32) as i32;
+
+                if let Some(chunk_radius) = options.chunk_radius {
+                    if i32::max((chunk_x * 2 + 1).abs(), (chunk_z * 2 + 1).abs()) as u32
+                        > 2 * chunk_radius
+                    {
+                        ScanStats::add(&options.stats.chunks_skipped, 1);
+                        return;
+                    }
+                }
+
+                if let Some(checksums) = &options.checksums {
+                    let checksum = checksum(&buf);
+                    let previous = checksums
+                        .lock()
+                        .unwrap()
+                        .check(path, index, timestamp, checksum);
+                    if let Some(previous) = previous {
+                        let reason = format!(
+                            "checksum changed from {:08x} to {:08x} without the chunk being saved",
+                            previous, checksum
+                        );
+                        eprintln!("chunk {} {}: {}", chunk_x, chunk_z, reason);
+                        options.stats.fail(path, Some((chunk_x, chunk_z)), reason);
+                    }
+                }
+
+                if let Some(memory_budget) = &options.memory_budget {
+                    memory_budget.acquire(buf.len() as u64);
+                }
+                chunk_tx
+                    .send(ChunkMessage {
+                        options: options.clone(),
+                        is_entity_chunk,
+                        region: path.to_path_buf(),
+                        chunk_pos: (chunk_x, chunk_z),
+                        buf,
+                    })
+                    .unwrap();
+            };
+
+            #[cfg(feature = "memmap2")]
+            let region_file = RegionFile::map(path);
+            #[cfg(not(feature = "memmap2"))]
+            let region_file = RegionFile::open(path);
+
+            let region_file = match region_file {
+                Ok(region_file) => region_file,
                 Err(e) => match e.kind() {
                     io::ErrorKind::NotFound => {
                         eprintln!(
@@ -226,45 +782,55 @@ fn scan_dimension(options: ScanDimensionOptions) -> eyre::Result<()> {
                         );
                         return Ok(());
                     }
-                    _ => return Err(e.into()),
-                },
-            }) {
-                Ok(region_file) => region_file,
-                Err(e) => match e.kind() {
                     io::ErrorKind::UnexpectedEof => {
                         eprintln!("unexpected eof while reading region file");
+                        options
+                            .stats
+                            .fail(path, None, "unexpected eof in region header");
                         return Ok(());
                     }
                     _ => return Err(e.into()),
                 },
             };
 
-            region_file.for_each_chunk(|(index, buf)| {
-                let chunk_x = region_x * 32 + (index % 32) as i32;
-                let chunk_z = region_z * 32 + (index / 32) as i32;
-
-                if let Some(chunk_radius) = options.chunk_radius {
-                    if i32::max((chunk_x * 2 + 1).abs(), (chunk_z * 2 + 1).abs()) as u32
-                        > 2 * chunk_radius
-                    {
-                        return;
+            #[cfg(feature = "memmap2")]
+            {
+                let mut region_file = region_file;
+                let mmap = region_file.get_ref().get_ref().clone();
+                let ranges: Vec<_> = region_file.payload_ranges().collect();
+                for (index, timestamp, range) in ranges {
+                    let range = range?;
+                    if world_statistics::region::is_external(&mmap.as_ref()[range.clone()]) {
+                        let payload = region_file.read_payload(index)?.unwrap();
+                        send_chunk(index, timestamp, Payload::Owned(payload));
+                    } else {
+                        send_chunk(index, timestamp, Payload::Mapped(mmap.clone(), range));
                     }
                 }
-
-                chunk_tx.send((is_entity_chunk, buf.to_vec())).unwrap();
-            })?;
+            }
+            #[cfg(not(feature = "memmap2"))]
+            {
+                let mut region_file = region_file;
+                region_file.for_each_chunk(|(index, timestamp, buf)| {
+                    send_chunk(index, timestamp, Payload::Owned(buf.to_vec()))
+                })?;
+            }
 
             Ok(())
         };
 
-        let entity_region_path = options.dim_path.join("entities").join(entry.file_name());
-        scan_region_file(false, &entry.path())?;
+        let entity_region_path = options
+            .dim_path
+            .join("entities")
+            .join(path.file_name().unwrap());
+        scan_region_file(false, &path)?;
         scan_region_file(true, &entity_region_path)?;
     }
 
-    drop(chunk_tx);
-    drop(item_tx);
-    handle.join().unwrap();
+    options.stats.wall_time.fetch_max(
+        options.started.elapsed().as_nanos() as u64,
+        Ordering::Relaxed,
+    );
 
     Ok(())
 }
@@ -273,17 +839,111 @@ fn scan_dimension(options: ScanDimensionOptions) -> eyre::Result<()> {
 pub struct ScanPlayerDataOptions {
     pub inventory: bool,
     pub ender_chest: bool,
+    pub world: World,
+    item_tx: Sender<FoundItem>,
+    stats: Arc<ScanStats>,
 }
 
-fn scan_playerdata(_options: ScanPlayerDataOptions) {
-    unimplemented!()
+/// Sends the items in the inventories and ender chests of all players, located at the player
+fn scan_playerdata(options: ScanPlayerDataOptions) -> eyre::Result<()> {
+    eprintln!("scanning playerdata");
+    let started = Instant::now();
+    let stats = &options.stats;
+
+    let files = match options.world.playerdata() {
+        Ok(files) => files,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            eprintln!("notice: no playerdata folder, skipping the items of players");
+            return Ok(());
+        }
+        Err(e) => {
+            return Err(InvalidWorld(format!("playerdata folder not readable: {}", e)).into())
+        }
+    };
+    for (_, path) in files {
+        if interrupted() {
+            break;
+        }
+        let player = match read_dat(&path) {
+            Ok(player) => player,
+            Err(e) => {
+                eprintln!("failed to read {}: {}", path.display(), e);
+                stats.fail(&path, None, e);
+                continue;
+            }
+        };
+        if let Some(players) = &stats.players {
+            ScanStats::add(players, 1);
+        }
+
+        let dimension = match player.get::<_, &str>("Dimension") {
+            Ok("minecraft:the_nether") => "nether",
+            Ok("minecraft:the_end") => "end",
+            _ => "overworld",
+        };
+        let pos = entity_pos(&player).unwrap_or_default();
+        let mut keys = Vec::new();
+        if options.inventory {
+            keys.push(("Inventory", "minecraft:player"));
+        }
+        if options.ender_chest {
+            keys.push(("EnderItems", "minecraft:ender_chest"));
+        }
+        for (key, container_id) in keys {
+            let items = match player.get::<_, &NbtList>(key) {
+                Ok(items) => items,
+                Err(_) => continue,
+            };
+            for item in items.iter_map::<&NbtCompound>().flatten() {
+                ScanStats::add(&stats.items, 1);
+                let found = FoundItem {
+                    item: item.clone(),
+                    dimension: Symbol::intern(dimension),
+                    container_id: Symbol::intern(container_id),
+                    pos,
+                };
+                // the writer only stops early on an error, which is returned when joining it
+                if options.item_tx.send(found).is_err() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    ScanStats::add(&stats.wall_time, started.elapsed().as_nanos() as u64);
+    Ok(())
 }
 
-fn parse_list(list: &str, default: &[&str]) -> HashSet<String> {
+fn entity_pos(entity: &NbtCompound) -> Option<[i32; 3]> {
+    let pos: &NbtList = entity.get("Pos").ok()?;
+    let coord = |i| Some(pos.get::<f64>(i).ok()?.floor() as i32);
+    Some([coord(0)?, coord(1)?, coord(2)?])
+}
+
+fn parse_list(list: &str, default: &[&str]) -> HashSet<Symbol> {
     if list == "all" {
-        HashSet::from_iter(default.iter().map(|str| str.to_string()))
+        HashSet::from_iter(default.iter().map(|str| Symbol::intern(str)))
     } else {
-        HashSet::from_iter(list.split(",").map(|str| String::from("minecraft:") + str))
+        HashSet::from_iter(
+            list.split(",")
+                .map(|str| Symbol::intern(&(String::from("minecraft:") + str))),
+        )
+    }
+}
+
+/// Splits a source into its name and options, like `overworld:chunk_radius=512`. The ids of
+/// custom dimensions like `mymod:mining` have a colon of their own.
+fn split_source(source: &str) -> (&str, Option<&str>) {
+    let (name, opts) = match source.split_once(':') {
+        Some(split) => split,
+        None => return (source, None),
+    };
+    if ["overworld", "nether", "end", "playerdata"].contains(&name) || name.contains('/') {
+        return (name, Some(opts));
+    }
+    match opts.split_once(':') {
+        Some((path, opts)) => (&source[..name.len() + 1 + path.len()], Some(opts)),
+        None => (source, None),
     }
 }
 
@@ -0,0 +1,151 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use quartz_nbt::{NbtCompound, NbtList};
+use world_statistics::{
+    redact::Redaction,
+    region::for_each_chunk_in_dir,
+    world::{dimension_path, warn_if_open},
+};
+
+/// Reports end cities, whether their ships still have an elytra and how far the end has been
+/// explored
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Path to the world directory
+    world: String,
+}
+
+struct EndCity {
+    chunk: (i32, i32),
+    /// Bounding boxes of the ship pieces as [min_x, min_y, min_z, max_x, max_y, max_z]
+    ships: Vec<[i32; 6]>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let dim_path =
+        dimension_path(&world_path, "end").ok_or_else(|| eyre!("end dimension not found"))?;
+
+    let mut cities = Vec::new();
+    let mut furthest: Option<(f64, i32, i32)> = None;
+
+    for_each_chunk_in_dir(&dim_path.join("region"), |chunk_x, chunk_z, chunk| {
+        let distance = f64::hypot(chunk_x as f64 * 16.0 + 8.0, chunk_z as f64 * 16.0 + 8.0);
+        if furthest.is_none_or(|(furthest, _, _)| distance > furthest) {
+            furthest = Some((distance, chunk_x, chunk_z));
+        }
+
+        let starts = match chunk
+            .get::<_, &NbtCompound>("structures")
+            .and_then(|structures| structures.get::<_, &NbtCompound>("starts"))
+        {
+            Ok(starts) => starts,
+            Err(_) => return,
+        };
+        let start = match starts.get::<_, &NbtCompound>("minecraft:end_city") {
+            Ok(start) => start,
+            Err(_) => return,
+        };
+
+        let mut ships = Vec::new();
+        if let Ok(children) = start.get::<_, &NbtList>("Children") {
+            for piece in children.iter_map::<&NbtCompound>().flatten() {
+                let template: &str = piece.get("Template").unwrap_or_default();
+                if template.ends_with("ship") {
+                    if let Ok(bb) = piece.get::<_, &[i32]>("BB") {
+                        if let Ok(bb) = bb.try_into() {
+                            ships.push(bb);
+                        }
+                    }
+                }
+            }
+        }
+
+        cities.push(EndCity {
+            chunk: (chunk_x, chunk_z),
+            ships,
+        });
+    })
+    .context("end region folder not found")?;
+
+    let mut elytra_frames = Vec::new();
+    let entities_path = dim_path.join("entities");
+    if entities_path.exists() {
+        for_each_chunk_in_dir(&entities_path, |_, _, chunk| {
+            let entities = match chunk.get::<_, &NbtList>("Entities") {
+                Ok(entities) => entities,
+                Err(_) => return,
+            };
+            for entity in entities.iter_map::<&NbtCompound>().flatten() {
+                let id: &str = entity.get("id").unwrap_or_default();
+                if !id.ends_with("item_frame") {
+                    continue;
+                }
+                let has_elytra = entity
+                    .get::<_, &NbtCompound>("Item")
+                    .and_then(|item| item.get::<_, &str>("id"))
+                    .is_ok_and(|id| id == "minecraft:elytra");
+                if let (true, Ok(pos)) = (has_elytra, entity.get::<_, &NbtList>("Pos")) {
+                    let coord = |i| pos.get::<f64>(i).unwrap_or_default().floor() as i32;
+                    elytra_frames.push([coord(0), coord(1), coord(2)]);
+                }
+            }
+        })?;
+    }
+
+    cities.sort_by_key(|city| city.chunk);
+    let redaction = Redaction::global()?;
+
+    println!("end cities: {}", cities.len());
+    println!(
+        "with ship: {}",
+        cities.iter().filter(|city| !city.ships.is_empty()).count()
+    );
+
+    let mut unlooted = 0;
+    for city in &cities {
+        let elytra = city.ships.iter().any(|bb| {
+            elytra_frames
+                .iter()
+                .any(|pos| (0..3).all(|i| (bb[i]..=bb[i + 3]).contains(&pos[i])))
+        });
+        if elytra {
+            unlooted += 1;
+        }
+        let (x, z) = redaction.coordinates(city.chunk.0 * 16, city.chunk.1 * 16);
+        println!(
+            "  region {} {} chunk {} {} (block {} {}){}{}",
+            x >> 9,
+            z >> 9,
+            x >> 4,
+            z >> 4,
+            x,
+            z,
+            if city.ships.is_empty() { "" } else { ", ship" },
+            if elytra { ", elytra" } else { "" },
+        );
+    }
+    println!("ships with elytra: {}", unlooted);
+
+    println!("elytra item frames: {}", elytra_frames.len());
+    for &[x, y, z] in &elytra_frames {
+        let (x, z) = redaction.coordinates(x, z);
+        println!("  {} {} {}", x, y, z);
+    }
+
+    if let Some((distance, chunk_x, chunk_z)) = furthest {
+        let (chunk_x, chunk_z) = redaction.chunk_coordinates(chunk_x, chunk_z);
+        println!(
+            "furthest explored: {:.0} blocks (chunk {} {})",
+            distance, chunk_x, chunk_z
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use eyre::eyre;
+use quartz_nbt::{NbtCompound, NbtList};
+use world_statistics::{
+    redact::Redaction,
+    region::for_each_chunk_in_dir,
+    world::{dimension_path, warn_if_open},
+};
+
+/// Lists stacks of entities riding each other, like mobs on mobs in a minecart, that are at
+/// least a number of entities high, highest first. Stacks like these come from stacking
+/// plugins or exploits and cause lag.
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Minimum number of entities in a stack, including the vehicle at the bottom
+    #[clap(long, default_value = "3")]
+    min_height: usize,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+struct Stack {
+    dimension: String,
+    pos: [i32; 3],
+    height: usize,
+    entities: usize,
+    /// Ids along the highest chain of passengers, bottom first
+    ids: Vec<String>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+
+    let mut stacks = Vec::new();
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        let entities_path = dim_path.join("entities");
+        let dir = if entities_path.exists() {
+            entities_path
+        } else {
+            dim_path.join("region")
+        };
+        if !dir.exists() {
+            eprintln!("skipping {}, no entities or region folder", dim_name);
+            continue;
+        }
+
+        for_each_chunk_in_dir(&dir, |_, _, chunk| {
+            let entities = chunk.get::<_, &NbtList>("Entities").or_else(|_| {
+                chunk
+                    .get::<_, &NbtCompound>("Level")
+                    .and_then(|level| level.get::<_, &NbtList>("Entities"))
+            });
+            let entities = match entities {
+                Ok(entities) => entities,
+                Err(_) => return,
+            };
+            for entity in entities.iter_map::<&NbtCompound>().flatten() {
+                let ids = highest_chain(entity);
+                if ids.len() < args.min_height {
+                    continue;
+                }
+                stacks.push(Stack {
+                    dimension: dim_name.clone(),
+                    pos: entity_pos(entity),
+                    height: ids.len(),
+                    entities: count_entities(entity),
+                    ids,
+                });
+            }
+        })?;
+    }
+
+    stacks.sort_by(|a, b| b.height.cmp(&a.height).then(b.entities.cmp(&a.entities)));
+    let redaction = Redaction::global()?;
+    for stack in &stacks {
+        let [x, y, z] = stack.pos;
+        let (x, z) = redaction.coordinates(x, z);
+        println!(
+            "{} {} {} {} height {} entities {}: {}",
+            stack.dimension,
+            x,
+            y,
+            z,
+            stack.height,
+            stack.entities,
+            stack.ids.join(" > ")
+        );
+    }
+    eprintln!("{} stacks", stacks.len());
+
+    Ok(())
+}
+
+fn passengers(entity: &NbtCompound) -> impl Iterator<Item = &NbtCompound> {
+    entity
+        .get::<_, &NbtList>("Passengers")
+        .ok()
+        .into_iter()
+        .flat_map(|passengers| passengers.iter_map::<&NbtCompound>().flatten())
+}
+
+/// Returns the ids of the longest chain of passengers starting at the entity
+fn highest_chain(entity: &NbtCompound) -> Vec<String> {
+    let mut ids = passengers(entity)
+        .map(highest_chain)
+        .max_by_key(|ids| ids.len())
+        .unwrap_or_default();
+    ids.insert(
+        0,
+        entity.get::<_, &str>("id").unwrap_or("unknown").to_string(),
+    );
+    ids
+}
+
+fn count_entities(entity: &NbtCompound) -> usize {
+    1 + passengers(entity).map(count_entities).sum::<usize>()
+}
+
+fn entity_pos(entity: &NbtCompound) -> [i32; 3] {
+    let pos: Vec<f64> = entity
+        .get::<_, &NbtList>("Pos")
+        .map(|pos| pos.iter_map().flatten().collect())
+        .unwrap_or_default();
+    let coord = |i: usize| pos.get(i).map_or(0, |v| v.floor() as i32);
+    [coord(0), coord(1), coord(2)]
+}
@@ -0,0 +1,140 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use eyre::eyre;
+use world_statistics::{
+    anonymize::{AnonymizeArgs, Anonymizer},
+    entity_index::{EntityIndex, IndexedEntity},
+    redact::Redaction,
+    region::for_each_chunk_in_dir,
+    world::{dimension_path, warn_if_open},
+};
+
+/// Indexes the UUIDs of all entities to look them up, find duplicates and resolve the entities
+/// they refer to
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(long, use_value_delimiter = true, default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+
+    #[clap(flatten)]
+    anonymize: AnonymizeArgs,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Prints the entities with the given UUIDs
+    Find { uuids: Vec<String> },
+    /// Prints every UUID used by more than one entity, exiting with 1 if there are any
+    Duplicates,
+    /// Prints owner, leash holder, thrower and vehicle references with the entities they
+    /// resolve to
+    References {
+        /// Only print references that don't resolve to an entity
+        #[clap(long)]
+        unresolved: bool,
+    },
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+
+    let mut index = EntityIndex::new();
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        let entities_path = dim_path.join("entities");
+        let dir = if entities_path.exists() {
+            entities_path
+        } else {
+            dim_path.join("region")
+        };
+        if !dir.exists() {
+            eprintln!("skipping {}, no entities or region folder", dim_name);
+            continue;
+        }
+        for_each_chunk_in_dir(&dir, |_, _, chunk| index.add_chunk(dim_name, &chunk))?;
+    }
+    eprintln!("indexed {} entities", index.len());
+    let redaction = Redaction::global()?;
+    let anonymizer = args.anonymize.anonymizer()?;
+    let describe = |entity: &IndexedEntity| describe(entity, redaction, &anonymizer);
+
+    match args.command {
+        Command::Find { uuids } => {
+            for uuid in &uuids {
+                let mut found = false;
+                for entity in index.find(uuid) {
+                    println!("{} {}", uuid, describe(entity));
+                    found = true;
+                }
+                if !found {
+                    eprintln!("{} not found", uuid);
+                }
+            }
+        }
+        Command::Duplicates => {
+            let duplicates = index.duplicates();
+            for (uuid, entities) in &duplicates {
+                for entity in entities {
+                    println!("{} {}", uuid, describe(entity));
+                }
+            }
+            eprintln!("{} duplicate UUIDs", duplicates.len());
+            if !duplicates.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Command::References { unresolved } => {
+            for reference in index.references() {
+                if unresolved && !reference.targets.is_empty()
+                    || redaction.hides_player(reference.uuid)
+                {
+                    continue;
+                }
+                let uuid = match anonymizer.player(reference.uuid) {
+                    Some(pseudonym) if reference.targets.is_empty() => pseudonym,
+                    _ => reference.uuid.to_string(),
+                };
+                let targets = if reference.targets.is_empty() {
+                    "unresolved".to_string()
+                } else {
+                    reference
+                        .targets
+                        .iter()
+                        .map(|&target| describe(target))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                println!(
+                    "{} {} {} {} -> {}",
+                    reference.from.uuid,
+                    describe(reference.from),
+                    reference.kind,
+                    uuid,
+                    targets
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn describe(entity: &IndexedEntity, redaction: &Redaction, anonymizer: &Anonymizer) -> String {
+    let [x, y, z] = entity.pos;
+    let (x, z) = redaction.coordinates(x, z);
+    let [x, y, z] = anonymizer.pos([x, y, z]);
+    format!("{} {} {} {} {}", entity.id, entity.dimension, x, y, z)
+}
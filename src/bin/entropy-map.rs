@@ -0,0 +1,147 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::{ArgEnum, Parser};
+use eyre::{eyre, Context};
+#[cfg(not(feature = "rayon"))]
+use world_statistics::region::for_each_chunk_in_dir;
+#[cfg(feature = "rayon")]
+use world_statistics::region::par_chunks;
+use world_statistics::{
+    blocks::ChunkBlocks,
+    redact::Redaction,
+    world::{dimension_path, warn_if_open},
+};
+
+/// Maps how varied the blocks of each chunk are, which tells player builds apart from untouched
+/// generated terrain, as CSV or as a grayscale image with one pixel per chunk
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    #[clap(short, long, arg_enum, default_value = "csv")]
+    format: Format,
+
+    /// Entropy in bits from which a chunk counts as built
+    #[clap(long, default_value = "3.0")]
+    min_entropy: f64,
+
+    /// Entropy drawn as white in the image
+    #[clap(long, default_value = "5.0")]
+    max_entropy: f64,
+
+    /// Only list the chunks counted as built
+    #[clap(long)]
+    only_built_chunks: bool,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether or end
+    #[clap(default_value = "overworld")]
+    dimension: String,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug)]
+enum Format {
+    Csv,
+    /// Binary PGM image, which most image viewers and converters open
+    Pgm,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let dim_path = dimension_path(&world_path, &args.dimension)
+        .ok_or_else(|| eyre!("unknown dimension {}", args.dimension))?;
+    let redaction = Redaction::global()?;
+
+    let region_path = dim_path.join("region");
+    let keep = |entropy: f64| !args.only_built_chunks || entropy >= args.min_entropy;
+
+    // decoding the blocks is most of the work, which is spread over all cores with rayon
+    #[cfg(feature = "rayon")]
+    let entropies: HashMap<_, _> = {
+        use rayon::prelude::*;
+
+        par_chunks(&region_path)
+            .with_context(|| format!("region folder of {} not found", args.dimension))?
+            .filter_map(|(chunk_x, chunk_z, chunk)| match chunk {
+                Ok(chunk) => Some((
+                    (chunk_x, chunk_z),
+                    ChunkBlocks::from_chunk(&chunk).entropy(),
+                )),
+                Err(e) => {
+                    eprintln!("failed to read chunk {} {}: {}", chunk_x, chunk_z, e);
+                    None
+                }
+            })
+            .filter(|&(_, entropy)| keep(entropy))
+            .collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let entropies = {
+        let mut entropies = HashMap::new();
+        for_each_chunk_in_dir(&region_path, |chunk_x, chunk_z, chunk| {
+            let entropy = ChunkBlocks::from_chunk(&chunk).entropy();
+            if keep(entropy) {
+                entropies.insert((chunk_x, chunk_z), entropy);
+            }
+        })
+        .with_context(|| format!("region folder of {} not found", args.dimension))?;
+        entropies
+    };
+
+    let mut stdout = io::stdout().lock();
+    match args.format {
+        Format::Csv => {
+            let mut chunks: Vec<_> = entropies.into_iter().collect();
+            chunks.sort_by_key(|&(chunk, _)| chunk);
+            writeln!(stdout, "chunk_x,chunk_z,entropy,built")?;
+            for ((chunk_x, chunk_z), entropy) in chunks {
+                let (chunk_x, chunk_z) = redaction.chunk_coordinates(chunk_x, chunk_z);
+                writeln!(
+                    stdout,
+                    "{},{},{:.2},{}",
+                    chunk_x,
+                    chunk_z,
+                    entropy,
+                    entropy >= args.min_entropy
+                )?;
+            }
+        }
+        Format::Pgm => {
+            if entropies.is_empty() {
+                return Err(eyre!("no chunks to draw"));
+            }
+            let xs = entropies.keys().map(|&(x, _)| x);
+            let zs = entropies.keys().map(|&(_, z)| z);
+            let (min_x, max_x) = (xs.clone().min().unwrap(), xs.max().unwrap());
+            let (min_z, max_z) = (zs.clone().min().unwrap(), zs.max().unwrap());
+            let (width, height) = ((max_x - min_x + 1) as usize, (max_z - min_z + 1) as usize);
+
+            // chunks that were never saved stay black
+            let mut pixels = vec![0u8; width * height];
+            for (&(chunk_x, chunk_z), &entropy) in &entropies {
+                let value = (entropy / args.max_entropy).clamp(0.0, 1.0) * 254.0;
+                let index = (chunk_z - min_z) as usize * width + (chunk_x - min_x) as usize;
+                pixels[index] = 1 + value.round() as u8;
+            }
+            // the image itself has no coordinates, only where its corner is
+            let (corner_x, corner_z) = redaction.chunk_coordinates(min_x, min_z);
+            eprintln!(
+                "{}x{} chunks, top left chunk {} {}",
+                width, height, corner_x, corner_z
+            );
+            write!(stdout, "P5\n{} {}\n255\n", width, height)?;
+            stdout.write_all(&pixels)?;
+        }
+    }
+    stdout.flush()?;
+
+    Ok(())
+}
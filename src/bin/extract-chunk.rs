@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+
+use clap::{ArgEnum, Parser};
+use eyre::{eyre, Context};
+use quartz_nbt::NbtTag;
+use world_statistics::{
+    nbt_json::nbt_to_json,
+    redact::{print_json, Redaction},
+    region::{chunk_position, read_chunk, RegionFile},
+    world::{dimension_path, warn_if_open},
+};
+
+/// Dumps the full NBT of a single chunk
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// region, entities or poi
+    #[clap(short, long, default_value = "region")]
+    kind: String,
+
+    #[clap(short, long, arg_enum, default_value = "snbt")]
+    format: Format,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether or end
+    dimension: String,
+
+    #[clap(allow_hyphen_values = true)]
+    chunk_x: i32,
+
+    #[clap(allow_hyphen_values = true)]
+    chunk_z: i32,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug)]
+enum Format {
+    Snbt,
+    Json,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let dim_path = dimension_path(&world_path, &args.dimension)
+        .ok_or_else(|| eyre!("unknown dimension {}", args.dimension))?;
+    let (region_x, region_z, index) = chunk_position(args.chunk_x, args.chunk_z);
+    let region_path = dim_path
+        .join(&args.kind)
+        .join(format!("r.{}.{}.mca", region_x, region_z));
+
+    let mut region_file = RegionFile::open(&region_path)
+        .with_context(|| format!("failed to open {:?}", region_path))?;
+
+    let payload = region_file
+        .read_payload(index)
+        .with_context(|| format!("failed to read {:?}", region_path))?
+        .ok_or_else(|| eyre!("chunk does not exist"))?;
+    let mut chunk = read_chunk(&payload)?;
+    Redaction::global()?.nbt(&mut chunk);
+
+    match args.format {
+        Format::Snbt => println!("{}", chunk.to_pretty_snbt()),
+        Format::Json => print_json(&nbt_to_json(&NbtTag::Compound(chunk)))?,
+    }
+
+    Ok(())
+}
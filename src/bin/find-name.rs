@@ -0,0 +1,228 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use eyre::eyre;
+use quartz_nbt::{NbtCompound, NbtList};
+use regex::{Regex, RegexBuilder};
+use serde_json::{json, Value};
+use world_statistics::{
+    anonymize::{AnonymizeArgs, Anonymizer},
+    dat::read_dat,
+    items::{custom_name, for_each_item},
+    players::{find_usercache, playerdata_files, INVENTORY_KEYS},
+    redact::{print_json, Redaction},
+    region::for_each_chunk_in_dir,
+    text::plain_text,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, Source, Sources},
+};
+
+/// Finds entities, containers and items whose custom name matches a regular expression, in the
+/// dimensions and in the inventories of players, with where they are
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Match regardless of upper and lower case
+    #[clap(short, long)]
+    ignore_case: bool,
+
+    /// Regular expression the plain text of the name is matched against, e.g. `^Steve's`
+    pattern: String,
+
+    #[clap(flatten)]
+    anonymize: AnonymizeArgs,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+/// Keys of entities that hold items
+const ENTITY_ITEM_KEYS: &[&str] = &["Items", "ArmorItems", "HandItems", "Inventory"];
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let pattern = RegexBuilder::new(&args.pattern)
+        .case_insensitive(args.ignore_case)
+        .build()?;
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[
+        (Source::Region, "containers there"),
+        (Source::Entities, "entities there"),
+        (Source::Playerdata, "player inventories"),
+    ]);
+
+    let anonymizer = args.anonymize.anonymizer()?;
+    let mut matches = Vec::new();
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+
+        if sources.has(dim_name, Source::Region) {
+            for_each_chunk_in_dir(&dim_path.join("region"), |_, _, chunk| {
+                let version =
+                    GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+                let level = match version.level(&chunk) {
+                    Some(level) => level,
+                    None => return,
+                };
+                if let Ok(block_entities) = level.get::<_, &NbtList>(version.block_entities_key()) {
+                    for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+                        let id = block_entity.get::<_, &str>("id").unwrap_or("unknown");
+                        let pos =
+                            anonymizer
+                                .pos(["x", "y", "z"].map(|key| {
+                                    block_entity.get::<_, i32>(key).unwrap_or_default()
+                                }));
+                        let name = block_entity.get::<_, &str>("CustomName").ok();
+                        if let Some(name) = name.and_then(|name| matching(&pattern, name)) {
+                            matches.push(json!({
+                                "kind": "container",
+                                "name": name,
+                                "id": id,
+                                "dimension": dim_name,
+                                "pos": pos,
+                            }));
+                        }
+                        if let Ok(items) = block_entity.get::<_, &NbtList>("Items") {
+                            let location = json!({
+                                "dimension": dim_name,
+                                "pos": pos,
+                                "in": id,
+                            });
+                            find_items(&pattern, items, &location, &mut matches);
+                        }
+                    }
+                }
+                // entities were stored in the terrain chunks before 1.17
+                if version.entities_in_chunk() {
+                    if let Ok(entities) = level.get::<_, &NbtList>("Entities") {
+                        for entity in entities.iter_map::<&NbtCompound>().flatten() {
+                            find_in_entity(&pattern, entity, dim_name, &anonymizer, &mut matches);
+                        }
+                    }
+                }
+            })?;
+        }
+        if sources.has(dim_name, Source::Entities) {
+            for_each_chunk_in_dir(&dim_path.join("entities"), |_, _, chunk| {
+                if let Ok(entities) = chunk.get::<_, &NbtList>("Entities") {
+                    for entity in entities.iter_map::<&NbtCompound>().flatten() {
+                        find_in_entity(&pattern, entity, dim_name, &anonymizer, &mut matches);
+                    }
+                }
+            })?;
+        }
+    }
+
+    if sources.playerdata {
+        let names = find_usercache(&world_path);
+        let redaction = Redaction::global()?;
+        for (uuid, path) in playerdata_files(&world_path)? {
+            // hidden players no longer match once they are pseudonymized
+            if redaction.hides_uuid(&uuid, &names) {
+                continue;
+            }
+            let player = match read_dat(&path) {
+                Ok(player) => player,
+                Err(e) => {
+                    eprintln!("failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let name = anonymizer
+                .player(&uuid)
+                .unwrap_or_else(|| names.get(&uuid).unwrap_or(&uuid).clone());
+            for key in INVENTORY_KEYS {
+                if let Ok(items) = player.get::<_, &NbtList>(*key) {
+                    let location = json!({ "player": name, "in": key });
+                    find_items(&pattern, items, &location, &mut matches);
+                }
+            }
+        }
+    }
+
+    print_json(&matches)?;
+
+    Ok(())
+}
+
+/// Returns the plain text of a name if it matches
+fn matching(pattern: &Regex, name: &str) -> Option<String> {
+    let name = plain_text(name);
+    pattern.is_match(&name).then_some(name)
+}
+
+/// Adds the named items in the list and in the containers among them, with the fields of the
+/// location where they are
+fn find_items(pattern: &Regex, items: &NbtList, location: &Value, matches: &mut Vec<Value>) {
+    for_each_item(items, &mut |item| {
+        if let Some(name) = custom_name(item).and_then(|name| matching(pattern, name)) {
+            let mut found = json!({
+                "kind": "item",
+                "name": name,
+                "id": item.get::<_, &str>("id").unwrap_or("unknown"),
+            });
+            if let (Value::Object(found), Value::Object(location)) = (&mut found, location) {
+                found.extend(location.clone());
+            }
+            matches.push(found);
+        }
+    });
+}
+
+/// Adds a named entity and the named items it holds
+fn find_in_entity(
+    pattern: &Regex,
+    entity: &NbtCompound,
+    dim_name: &str,
+    anonymizer: &Anonymizer,
+    matches: &mut Vec<Value>,
+) {
+    let id = entity.get::<_, &str>("id").unwrap_or("unknown");
+    let pos: Vec<i32> = entity
+        .get::<_, &NbtList>("Pos")
+        .map(|pos| {
+            pos.iter_map::<f64>()
+                .flatten()
+                .enumerate()
+                .map(|(i, v)| match i {
+                    1 => v.floor() as i32,
+                    _ => anonymizer.coordinate(v.floor() as i32),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let name = entity.get::<_, &str>("CustomName").ok();
+    if let Some(name) = name.and_then(|name| matching(pattern, name)) {
+        matches.push(json!({
+            "kind": "entity",
+            "name": name,
+            "id": id,
+            "dimension": dim_name,
+            "pos": pos,
+        }));
+    }
+    let location = json!({
+        "dimension": dim_name,
+        "pos": pos,
+        "in": id,
+    });
+    // item frames and dropped items hold a single item
+    if let Ok(item) = entity.get::<_, &NbtCompound>("Item") {
+        let items = NbtList::from(vec![item.clone()]);
+        find_items(pattern, &items, &location, matches);
+    }
+    for key in ENTITY_ITEM_KEYS {
+        if let Ok(items) = entity.get::<_, &NbtList>(*key) {
+            find_items(pattern, items, &location, matches);
+        }
+    }
+}
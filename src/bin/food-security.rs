@@ -0,0 +1,249 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use clap::Parser;
+use eyre::eyre;
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    dat::read_dat,
+    items::{contents, for_each_item, id_and_count},
+    players::playerdata_files,
+    redact::print_json,
+    region::for_each_chunk_in_dir,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, Source, Sources},
+};
+
+/// Hunger and saturation restored by eating one item. Items that poison or otherwise hurt
+/// the player are left out, cakes count all seven slices.
+const FOOD: &[(&str, f64, f64)] = &[
+    ("minecraft:apple", 4.0, 2.4),
+    ("minecraft:baked_potato", 5.0, 6.0),
+    ("minecraft:beef", 3.0, 1.8),
+    ("minecraft:beetroot", 1.0, 1.2),
+    ("minecraft:beetroot_soup", 6.0, 7.2),
+    ("minecraft:bread", 5.0, 6.0),
+    ("minecraft:cake", 14.0, 2.8),
+    ("minecraft:carrot", 3.0, 3.6),
+    ("minecraft:chicken", 2.0, 1.2),
+    ("minecraft:chorus_fruit", 4.0, 2.4),
+    ("minecraft:cod", 2.0, 0.4),
+    ("minecraft:cooked_beef", 8.0, 12.8),
+    ("minecraft:cooked_chicken", 6.0, 7.2),
+    ("minecraft:cooked_cod", 5.0, 6.0),
+    ("minecraft:cooked_mutton", 6.0, 9.6),
+    ("minecraft:cooked_porkchop", 8.0, 12.8),
+    ("minecraft:cooked_rabbit", 5.0, 6.0),
+    ("minecraft:cooked_salmon", 6.0, 9.6),
+    ("minecraft:cookie", 2.0, 0.4),
+    ("minecraft:dried_kelp", 1.0, 0.6),
+    ("minecraft:enchanted_golden_apple", 4.0, 9.6),
+    ("minecraft:glow_berries", 2.0, 0.4),
+    ("minecraft:golden_apple", 4.0, 9.6),
+    ("minecraft:golden_carrot", 6.0, 14.4),
+    ("minecraft:honey_bottle", 6.0, 1.2),
+    ("minecraft:melon_slice", 2.0, 1.2),
+    ("minecraft:mushroom_stew", 6.0, 7.2),
+    ("minecraft:mutton", 2.0, 1.2),
+    ("minecraft:porkchop", 3.0, 1.8),
+    ("minecraft:potato", 1.0, 0.6),
+    ("minecraft:pumpkin_pie", 8.0, 4.8),
+    ("minecraft:rabbit", 3.0, 1.8),
+    ("minecraft:rabbit_stew", 10.0, 12.0),
+    ("minecraft:salmon", 2.0, 0.4),
+    ("minecraft:suspicious_stew", 6.0, 7.2),
+    ("minecraft:sweet_berries", 2.0, 0.4),
+    ("minecraft:tropical_fish", 1.0, 0.2),
+];
+
+/// Sums up the food in storage and player inventories and estimates how many days it lasts
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Player counts to estimate the days of food for
+    #[clap(long, use_value_delimiter = true, default_values = &["1", "5", "10", "20"])]
+    players: Vec<u32>,
+
+    /// Hunger and saturation points one player uses up per day of play
+    #[clap(long, default_value = "60")]
+    points_per_day: f64,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+/// Food counts of one source
+#[derive(Default)]
+struct Pantry {
+    items: BTreeMap<String, u64>,
+}
+
+impl Pantry {
+    /// Adds the dropped items and the items in minecarts and other entities with an inventory
+    fn add_entities(&mut self, entities: &NbtList) {
+        for entity in entities.iter_map::<&NbtCompound>().flatten() {
+            if let Ok(item) = entity.get::<_, &NbtCompound>("Item") {
+                self.add_stack(item);
+            }
+            if let Ok(items) = entity.get::<_, &NbtList>("Items") {
+                self.add_all(items);
+            }
+        }
+    }
+
+    fn add_stack(&mut self, item: &NbtCompound) {
+        self.add(item);
+        if let Some(contents) = contents(item) {
+            self.add_all(contents);
+        }
+    }
+
+    fn add_all(&mut self, items: &NbtList) {
+        for_each_item(items, &mut |item| self.add(item));
+    }
+
+    fn add(&mut self, item: &NbtCompound) {
+        let (id, count) = id_and_count(item);
+        if food_value(id).is_some() {
+            *self.items.entry(id.to_string()).or_default() += count;
+        }
+    }
+
+    /// Total hunger and saturation points
+    fn points(&self) -> (f64, f64) {
+        self.items
+            .iter()
+            .filter_map(|(id, &count)| {
+                let (hunger, saturation) = food_value(id)?;
+                Some((hunger * count as f64, saturation * count as f64))
+            })
+            .fold((0.0, 0.0), |(a, b), (c, d)| (a + c, b + d))
+    }
+}
+
+fn food_value(id: &str) -> Option<(f64, f64)> {
+    FOOD.iter()
+        .find(|(food, _, _)| *food == id)
+        .map(|&(_, hunger, saturation)| (hunger, saturation))
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[
+        (Source::Region, "food in storage there"),
+        (Source::Entities, "item entities there"),
+        (Source::Playerdata, "player inventories"),
+    ]);
+
+    let mut storage = Pantry::default();
+    let mut entities = Pantry::default();
+    let mut players = Pantry::default();
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        if !sources.has(dim_name, Source::Region) {
+            continue;
+        }
+
+        for_each_chunk_in_dir(&dim_path.join("region"), |_, _, chunk| {
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let level = match version.level(&chunk) {
+                Some(level) => level,
+                None => return,
+            };
+            if let Ok(block_entities) = level.get::<_, &NbtList>(version.block_entities_key()) {
+                for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+                    if let Ok(items) = block_entity.get::<_, &NbtList>("Items") {
+                        storage.add_all(items);
+                    }
+                }
+            }
+            // entities were stored in the terrain chunks before 1.17
+            if version.entities_in_chunk() {
+                if let Ok(list) = level.get::<_, &NbtList>("Entities") {
+                    entities.add_entities(list);
+                }
+            }
+        })?;
+
+        let entities_path = dim_path.join("entities");
+        if !sources.has(dim_name, Source::Entities) {
+            continue;
+        }
+        for_each_chunk_in_dir(&entities_path, |_, _, chunk| {
+            if let Ok(list) = chunk.get::<_, &NbtList>("Entities") {
+                entities.add_entities(list);
+            }
+        })?;
+    }
+
+    if sources.playerdata {
+        for (_, path) in playerdata_files(&world_path)? {
+            let player = match read_dat(&path) {
+                Ok(player) => player,
+                Err(e) => {
+                    eprintln!("failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            for key in ["Inventory", "EnderItems"] {
+                if let Ok(items) = player.get::<_, &NbtList>(key) {
+                    players.add_all(items);
+                }
+            }
+        }
+    }
+
+    let mut items = BTreeMap::<&str, u64>::new();
+    let (mut hunger, mut saturation) = (0.0, 0.0);
+    let mut sources = serde_json::Map::new();
+    for (name, pantry) in [
+        ("storage", &storage),
+        ("entities", &entities),
+        ("players", &players),
+    ] {
+        for (id, count) in &pantry.items {
+            *items.entry(id).or_default() += count;
+        }
+        let (source_hunger, source_saturation) = pantry.points();
+        hunger += source_hunger;
+        saturation += source_saturation;
+        sources.insert(
+            name.to_string(),
+            json!({ "hunger": round(source_hunger), "saturation": round(source_saturation) }),
+        );
+    }
+
+    let days: Vec<_> = args
+        .players
+        .iter()
+        .map(|&count| {
+            let days = (hunger + saturation) / (args.points_per_day * count.max(1) as f64);
+            json!({ "players": count, "days": round(days) })
+        })
+        .collect();
+
+    let report = json!({
+        "hunger": round(hunger),
+        "saturation": round(saturation),
+        "sources": sources,
+        "items": items,
+        "days": days,
+    });
+    print_json(&report)?;
+
+    Ok(())
+}
+
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
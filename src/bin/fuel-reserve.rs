@@ -0,0 +1,285 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::PathBuf,
+};
+
+use clap::Parser;
+use eyre::eyre;
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    dat::read_dat,
+    items::{contents, for_each_item, id_and_count},
+    players::playerdata_files,
+    redact::print_json,
+    region::for_each_chunk_in_dir,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, Source, Sources},
+};
+
+/// Ticks it takes a furnace to smelt one item
+const SMELT_TIME: u64 = 200;
+
+const FURNACE: &str = "minecraft:furnace";
+
+/// Furnaces that smelt twice as fast, using up fuel twice as fast
+const FAST_FURNACES: &[&str] = &["minecraft:blast_furnace", "minecraft:smoker"];
+
+/// Sums up the fuel in storage and player inventories as the number of items it can smelt,
+/// and how long it lasts the furnace arrays of the world
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Number of largest furnace arrays to list
+    #[clap(long, default_value = "10")]
+    top: usize,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+/// Burn time of a fuel item in ticks
+fn burn_time(id: &str) -> Option<u64> {
+    let name = id.strip_prefix("minecraft:")?;
+    if name.starts_with("crimson_") || name.starts_with("warped_") {
+        return None;
+    }
+    match name {
+        "lava_bucket" => Some(20000),
+        "coal_block" => Some(16000),
+        "dried_kelp_block" => Some(4000),
+        "blaze_rod" => Some(2400),
+        "coal" | "charcoal" => Some(1600),
+        "stick" => Some(100),
+        "bamboo" | "scaffolding" => Some(50),
+        _ if name.ends_with("_log")
+            || name.ends_with("_wood")
+            || name.ends_with("_planks")
+            || name.ends_with("_fence")
+            || name.ends_with("_fence_gate")
+            || name == "bamboo_block" =>
+        {
+            Some(300)
+        }
+        _ if name.ends_with("_slab") && name.contains("wood") => Some(150),
+        _ => None,
+    }
+}
+
+/// Fuel counts of one source
+#[derive(Default)]
+struct Bunker {
+    items: BTreeMap<String, u64>,
+}
+
+impl Bunker {
+    /// Adds the dropped items and the items in minecarts and other entities with an inventory
+    fn add_entities(&mut self, entities: &NbtList) {
+        for entity in entities.iter_map::<&NbtCompound>().flatten() {
+            if let Ok(item) = entity.get::<_, &NbtCompound>("Item") {
+                self.add_stack(item);
+            }
+            if let Ok(items) = entity.get::<_, &NbtList>("Items") {
+                self.add_all(items);
+            }
+        }
+    }
+
+    fn add_stack(&mut self, item: &NbtCompound) {
+        self.add(item);
+        if let Some(contents) = contents(item) {
+            self.add_all(contents);
+        }
+    }
+
+    fn add_all(&mut self, items: &NbtList) {
+        for_each_item(items, &mut |item| self.add(item));
+    }
+
+    fn add(&mut self, item: &NbtCompound) {
+        let (id, count) = id_and_count(item);
+        if burn_time(id).is_some() {
+            *self.items.entry(id.to_string()).or_default() += count;
+        }
+    }
+
+    fn burn_time(&self) -> u64 {
+        self.items
+            .iter()
+            .filter_map(|(id, count)| Some(burn_time(id)? * count))
+            .sum()
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[
+        (Source::Region, "fuel and furnaces there"),
+        (Source::Entities, "item entities there"),
+        (Source::Playerdata, "player inventories"),
+    ]);
+
+    let mut storage = Bunker::default();
+    let mut entities = Bunker::default();
+    let mut players = Bunker::default();
+    let mut furnaces = HashMap::<(String, [i32; 3]), String>::new();
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        if !sources.has(dim_name, Source::Region) {
+            continue;
+        }
+
+        for_each_chunk_in_dir(&dim_path.join("region"), |_, _, chunk| {
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let level = match version.level(&chunk) {
+                Some(level) => level,
+                None => return,
+            };
+            // entities were stored in the terrain chunks before 1.17
+            if version.entities_in_chunk() {
+                if let Ok(list) = level.get::<_, &NbtList>("Entities") {
+                    entities.add_entities(list);
+                }
+            }
+            let block_entities = match level.get::<_, &NbtList>(version.block_entities_key()) {
+                Ok(block_entities) => block_entities,
+                Err(_) => return,
+            };
+            for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+                if let Ok(items) = block_entity.get::<_, &NbtList>("Items") {
+                    storage.add_all(items);
+                }
+                let id: &str = block_entity.get("id").unwrap_or_default();
+                if id == FURNACE || FAST_FURNACES.contains(&id) {
+                    let pos = ["x", "y", "z"]
+                        .map(|key| block_entity.get::<_, i32>(key).unwrap_or_default());
+                    furnaces.insert((dim_name.clone(), pos), id.to_string());
+                }
+            }
+        })?;
+
+        let entities_path = dim_path.join("entities");
+        if !sources.has(dim_name, Source::Entities) {
+            continue;
+        }
+        for_each_chunk_in_dir(&entities_path, |_, _, chunk| {
+            if let Ok(list) = chunk.get::<_, &NbtList>("Entities") {
+                entities.add_entities(list);
+            }
+        })?;
+    }
+
+    if sources.playerdata {
+        for (_, path) in playerdata_files(&world_path)? {
+            let player = match read_dat(&path) {
+                Ok(player) => player,
+                Err(e) => {
+                    eprintln!("failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            for key in ["Inventory", "EnderItems"] {
+                if let Ok(items) = player.get::<_, &NbtList>(key) {
+                    players.add_all(items);
+                }
+            }
+        }
+    }
+
+    let mut items = BTreeMap::<&str, u64>::new();
+    let mut total_burn_time = 0;
+    let mut sources = serde_json::Map::new();
+    for (name, bunker) in [
+        ("storage", &storage),
+        ("entities", &entities),
+        ("players", &players),
+    ] {
+        for (id, count) in &bunker.items {
+            *items.entry(id).or_default() += count;
+        }
+        total_burn_time += bunker.burn_time();
+        sources.insert(name.to_string(), json!(bunker.burn_time() / SMELT_TIME));
+    }
+    let smeltable = total_burn_time / SMELT_TIME;
+
+    let mut arrays = furnace_arrays(&furnaces);
+    arrays.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(b[0])));
+
+    let mut furnace_counts = BTreeMap::<&str, u64>::new();
+    for id in furnaces.values() {
+        *furnace_counts.entry(id).or_default() += 1;
+    }
+
+    let report = json!({
+        "burn_time": total_burn_time,
+        "smeltable_items": smeltable,
+        "sources": sources,
+        "items": items,
+        "furnaces": furnace_counts,
+        "arrays": arrays
+            .iter()
+            .take(args.top)
+            .map(|array| {
+                // items per second of the whole array
+                let speed: f64 = array
+                    .iter()
+                    .map(|key| if furnaces[*key] == FURNACE { 0.1 } else { 0.2 })
+                    .sum();
+                let (dimension, pos) = array[0];
+                json!({
+                    "dimension": dimension,
+                    "pos": pos,
+                    "size": array.len(),
+                    "hours": (smeltable as f64 / speed / 3600.0 * 10.0).round() / 10.0,
+                })
+            })
+            .collect::<Vec<_>>(),
+    });
+    print_json(&report)?;
+
+    Ok(())
+}
+
+/// Groups furnaces touching each other, also diagonally, into arrays sorted by position
+fn furnace_arrays(furnaces: &HashMap<(String, [i32; 3]), String>) -> Vec<Vec<&(String, [i32; 3])>> {
+    let mut keys: Vec<_> = furnaces.keys().collect();
+    keys.sort();
+
+    let mut seen = HashSet::new();
+    let mut arrays = Vec::new();
+    for &start in &keys {
+        if !seen.insert(start) {
+            continue;
+        }
+        let mut array = vec![start];
+        let mut stack = vec![start];
+        while let Some((dimension, [x, y, z])) = stack.pop() {
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let neighbor = (dimension.clone(), [x + dx, y + dy, z + dz]);
+                        if let Some((key, _)) = furnaces.get_key_value(&neighbor) {
+                            if seen.insert(key) {
+                                array.push(key);
+                                stack.push(key);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        array.sort();
+        arrays.push(array);
+    }
+    arrays
+}
@@ -0,0 +1,199 @@
+use std::{collections::HashMap, path::Path};
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use quartz_nbt::NbtCompound;
+use serde_json::json;
+use world_statistics::{
+    blocks::ChunkBlocks,
+    redact::print_json,
+    region::for_each_chunk_in_dir,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open},
+};
+
+/// Compares two backups of a world and lists the chunks near player builds that gained a lot of
+/// flowing lava or water, fire or obsidian in between, most suspicious first, to inspect them
+/// for griefing
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Minimum increase of any kind of block for a chunk to be listed
+    #[clap(long, default_value = "16")]
+    min_increase: u64,
+
+    /// Minimum InhabitedTime in ticks for a chunk to count as part of a build
+    #[clap(long, default_value = "72000")]
+    min_inhabited_time: i64,
+
+    /// Distance in chunks to a build within which chunks are checked
+    #[clap(long, default_value = "1")]
+    build_radius: i32,
+
+    /// Maximum number of chunks to list
+    #[clap(long, default_value = "100")]
+    limit: usize,
+
+    /// Path to the older world directory
+    old_world: String,
+
+    /// Path to the newer world directory
+    new_world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+/// The kinds of blocks counted, with how much an increase of one block adds to the score
+const KINDS: [(&str, u64); 4] = [
+    ("flowing_lava", 4),
+    ("fire", 4),
+    ("obsidian", 2),
+    ("flowing_water", 1),
+];
+
+/// The counts of each kind of block in a chunk and its InhabitedTime
+struct ChunkCounts {
+    counts: [u64; KINDS.len()],
+    inhabited_time: i64,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let old_world = Path::new(&args.old_world);
+    let new_world = Path::new(&args.new_world);
+    warn_if_open(new_world);
+
+    let mut flagged = Vec::new();
+    for dim_name in &args.dimensions {
+        let old = read_counts(old_world, dim_name)?;
+        let new = read_counts(new_world, dim_name)?;
+
+        for (&(chunk_x, chunk_z), new_chunk) in &new {
+            // chunks generated since the old backup have natural lava and water of their own
+            let old_chunk = match old.get(&(chunk_x, chunk_z)) {
+                Some(old_chunk) => old_chunk,
+                None => continue,
+            };
+            let increases: Vec<u64> = (0..KINDS.len())
+                .map(|i| new_chunk.counts[i].saturating_sub(old_chunk.counts[i]))
+                .collect();
+            if increases
+                .iter()
+                .all(|&increase| increase < args.min_increase)
+            {
+                continue;
+            }
+
+            let radius = args.build_radius;
+            let build_time = (-radius..=radius)
+                .flat_map(|dx| (-radius..=radius).map(move |dz| (chunk_x + dx, chunk_z + dz)))
+                .filter_map(|chunk| new.get(&chunk))
+                .map(|chunk| chunk.inhabited_time)
+                .max()
+                .unwrap_or_default();
+            if build_time < args.min_inhabited_time {
+                continue;
+            }
+
+            let score: u64 = KINDS
+                .iter()
+                .zip(&increases)
+                .map(|((_, weight), increase)| weight * increase)
+                .sum();
+            flagged.push((score, dim_name, chunk_x, chunk_z, increases, build_time));
+        }
+    }
+    flagged.sort_by(|a, b| b.0.cmp(&a.0).then((a.1, a.2, a.3).cmp(&(b.1, b.2, b.3))));
+    flagged.truncate(args.limit);
+
+    let report: Vec<_> = flagged
+        .iter()
+        .map(
+            |(score, dim_name, chunk_x, chunk_z, increases, build_time)| {
+                json!({
+                    "dimension": dim_name,
+                    "chunk": [chunk_x, chunk_z],
+                    "score": score,
+                    "increases": KINDS
+                        .iter()
+                        .zip(increases)
+                        .filter(|(_, &increase)| increase > 0)
+                        .map(|((kind, _), increase)| (kind.to_string(), json!(increase)))
+                        .collect::<serde_json::Map<_, _>>(),
+                    "build_inhabited_hours": (*build_time as f64 / 72000.0 * 10.0).round() / 10.0,
+                })
+            },
+        )
+        .collect();
+    print_json(&report)?;
+
+    Ok(())
+}
+
+/// Counts the blocks of each kind in all chunks of a dimension
+fn read_counts(
+    world_path: &Path,
+    dim_name: &str,
+) -> eyre::Result<HashMap<(i32, i32), ChunkCounts>> {
+    let dim_path = dimension_path(world_path, dim_name)
+        .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+    let mut chunks = HashMap::new();
+    for_each_chunk_in_dir(&dim_path.join("region"), |chunk_x, chunk_z, chunk| {
+        let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+        let inhabited_time = version
+            .level(&chunk)
+            .and_then(|level| level.get("InhabitedTime").ok())
+            .unwrap_or_default();
+
+        let mut counts = [0; KINDS.len()];
+        for (_, block_states) in ChunkBlocks::from_chunk_as(&chunk, version).sections() {
+            let kinds: Vec<Option<usize>> =
+                block_states.palette().iter().map(|s| kind(s)).collect();
+            if kinds.iter().all(Option::is_none) {
+                continue;
+            }
+            for (kind, count) in kinds.iter().zip(block_states.counts()) {
+                if let Some(kind) = kind {
+                    counts[*kind] += count;
+                }
+            }
+        }
+        chunks.insert(
+            (chunk_x, chunk_z),
+            ChunkCounts {
+                counts,
+                inhabited_time,
+            },
+        );
+    })
+    .with_context(|| {
+        format!(
+            "region folder of {} not found in {}",
+            dim_name,
+            world_path.display()
+        )
+    })?;
+    Ok(chunks)
+}
+
+/// Returns the index into [`KINDS`] of a block state
+fn kind(state: &NbtCompound) -> Option<usize> {
+    let name = state.get::<_, &str>("Name").ok()?;
+    // source blocks have level 0, flowing blocks the distance to their source
+    let flowing = || {
+        state
+            .get::<_, &NbtCompound>("Properties")
+            .and_then(|properties| properties.get::<_, &str>("level"))
+            .is_ok_and(|level| level != "0")
+    };
+    match name {
+        "minecraft:lava" if flowing() => Some(0),
+        "minecraft:fire" | "minecraft:soul_fire" => Some(1),
+        "minecraft:obsidian" => Some(2),
+        "minecraft:water" if flowing() => Some(3),
+        _ => None,
+    }
+}
@@ -0,0 +1,189 @@
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use clap::Parser;
+use eyre::eyre;
+use indexmap::IndexMap;
+use world_statistics::{
+    dat::LevelDat,
+    history::scan,
+    region::{region_files, RegionFile},
+    world::{dimension_path, warn_if_open},
+};
+
+/// Reconstructs the growth of a world from a directory of backups by measuring each of them,
+/// printing one CSV row per backup in the order they were last played
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// What to measure: size, chunks, players and items. Items decodes every chunk and is
+    /// much slower than the others.
+    #[clap(
+        long,
+        arg_enum,
+        use_value_delimiter = true,
+        default_values = &["size", "chunks", "players"]
+    )]
+    metrics: Vec<Metric>,
+
+    /// Item ids to add a column with their total count for, also decoding every chunk
+    #[clap(long, use_value_delimiter = true)]
+    items: Vec<String>,
+
+    /// Directory with one world directory per backup
+    backups: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Metric {
+    /// Bytes of all files of the world
+    Size,
+    /// Chunks in the region files
+    Chunks,
+    /// Players with a playerdata file
+    Players,
+    /// Items in containers and player inventories
+    Items,
+}
+
+fn main() -> eyre::Result<()> {
+    let mut args = Args::parse();
+    for id in &mut args.items {
+        if !id.contains(':') {
+            *id = String::from("minecraft:") + id;
+        }
+    }
+
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(&args.backups)?.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            eprintln!(
+                "notice: skipping {}, extract archives first",
+                path.display()
+            );
+            continue;
+        }
+        match find_world(&path) {
+            Some(world_path) => backups.push((timestamp(&world_path)?, path, world_path)),
+            None => eprintln!("notice: no level.dat in {}, skipping it", path.display()),
+        }
+    }
+    backups.sort();
+
+    let mut header = vec!["timestamp".to_string(), "backup".to_string()];
+    header.extend(
+        args.metrics
+            .iter()
+            .map(|metric| format!("{:?}", metric).to_lowercase()),
+    );
+    header.extend(args.items.iter().cloned());
+    println!("{}", header.join(","));
+
+    for (timestamp, path, world_path) in &backups {
+        warn_if_open(world_path);
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let mut row = vec![timestamp.to_string(), name.into_owned()];
+
+        let counts = if args.metrics.contains(&Metric::Items) || !args.items.is_empty() {
+            scan(world_path, &args.dimensions)?.0
+        } else {
+            IndexMap::new()
+        };
+        for metric in &args.metrics {
+            let value = match metric {
+                Metric::Size => dir_size(world_path)?,
+                Metric::Chunks => count_chunks(world_path, &args.dimensions)?,
+                Metric::Players => count_players(world_path)?,
+                Metric::Items => counts.values().sum(),
+            };
+            row.push(value.to_string());
+        }
+        for id in &args.items {
+            row.push(counts.get(id).copied().unwrap_or(0).to_string());
+        }
+        println!("{}", row.join(","));
+    }
+
+    Ok(())
+}
+
+/// Returns the backup directory if it is a world, or a world directory inside of it
+fn find_world(path: &Path) -> Option<PathBuf> {
+    if path.join("level.dat").exists() {
+        return Some(path.to_path_buf());
+    }
+    fs::read_dir(path)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| path.join("level.dat").exists())
+}
+
+/// When the backed up world was last played as unix timestamp, from level.dat or its
+/// modification time
+fn timestamp(world_path: &Path) -> eyre::Result<u64> {
+    let level_dat = world_path.join("level.dat");
+    if let Some(last_played) = LevelDat::read(&level_dat)
+        .ok()
+        .and_then(|level| level.last_played)
+    {
+        return Ok(last_played as u64 / 1000);
+    }
+    Ok(fs::metadata(&level_dat)?
+        .modified()?
+        .duration_since(UNIX_EPOCH)?
+        .as_secs())
+}
+
+fn dir_size(path: &Path) -> Result<u64, io::Error> {
+    let mut size = 0;
+    for entry in fs::read_dir(path)?.flatten() {
+        let metadata = entry.metadata()?;
+        size += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(size)
+}
+
+fn count_chunks(world_path: &Path, dimensions: &[String]) -> eyre::Result<u64> {
+    let mut chunks = 0;
+    for dim_name in dimensions {
+        let dim_path = dimension_path(world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        let files = match region_files(&dim_path.join("region")) {
+            Ok(files) => files,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        for (_, _, path) in files {
+            let region_file = RegionFile::new(File::open(path)?)?;
+            chunks += (0..1024)
+                .filter(|&index| region_file.contains_chunk(index))
+                .count() as u64;
+        }
+    }
+    Ok(chunks)
+}
+
+fn count_players(world_path: &Path) -> Result<u64, io::Error> {
+    match fs::read_dir(world_path.join("playerdata")) {
+        Ok(entries) => Ok(entries
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "dat"))
+            .count() as u64),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,191 @@
+use std::{io, path::Path, time::Duration};
+
+use clap::{Parser, Subcommand};
+use indexmap::IndexSet;
+use world_statistics::{
+    history::{find_anomalies, scan, Anomaly, AnomalyThresholds, Snapshot, SnapshotStore},
+    world::warn_if_open,
+};
+
+/// Keeps a timestamped history of count-items snapshots
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Directory the snapshots are stored in
+    store: String,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Stores the count-items output read from stdin as a new snapshot
+    Record {
+        #[clap(flatten)]
+        retention: Retention,
+    },
+    /// Counts the items in containers and player inventories of a world and stores them as a
+    /// new snapshot
+    Scan {
+        #[clap(flatten)]
+        retention: Retention,
+
+        /// Path to the world
+        world: String,
+
+        /// overworld, nether, end
+        #[clap(default_values = &["overworld", "nether", "end"])]
+        dimensions: Vec<String>,
+    },
+    /// Removes old snapshots
+    Prune {
+        #[clap(flatten)]
+        retention: Retention,
+    },
+    /// Prints the counts of the given items over time as CSV
+    Export {
+        /// Item ids to export, all items if none are given
+        items: Vec<String>,
+    },
+    /// Flags unusual increases of items and stack fingerprints between the last two snapshots
+    Anomalies {
+        /// Minimum absolute increase of an item count
+        #[clap(long, default_value = "64")]
+        min_increase: u64,
+
+        /// Minimum absolute increase of the items with the same stack fingerprint, such as
+        /// copies of a renamed or enchanted item. Fingerprints are only recorded by scan.
+        #[clap(long, default_value = "64")]
+        min_fingerprint_increase: u64,
+
+        /// Minimum relative increase in percent
+        #[clap(long, default_value = "100")]
+        min_increase_percent: f64,
+
+        /// Minimum deviation from earlier changes in standard deviations
+        #[clap(long, default_value = "3")]
+        min_z_score: f64,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct Retention {
+    /// Number of snapshots to keep
+    #[clap(long)]
+    keep: Option<usize>,
+
+    /// Maximum age of snapshots in days
+    #[clap(long)]
+    max_age_days: Option<u64>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+    let store = SnapshotStore::open(&args.store)?;
+
+    match args.command {
+        Command::Record { retention } => {
+            let snapshot: Snapshot = serde_json::from_reader(io::stdin())?;
+            let timestamp = store.record(&snapshot, None)?;
+            eprintln!("recorded snapshot {}", timestamp);
+            prune(&store, &retention)?;
+        }
+        Command::Scan {
+            retention,
+            world,
+            dimensions,
+        } => {
+            warn_if_open(Path::new(&world));
+            let (snapshot, fingerprints) = scan(Path::new(&world), &dimensions)?;
+            let timestamp = store.record(&snapshot, Some(&fingerprints))?;
+            eprintln!("recorded snapshot {}", timestamp);
+            prune(&store, &retention)?;
+        }
+        Command::Prune { retention } => prune(&store, &retention)?,
+        Command::Export { items } => export(&store, items)?,
+        Command::Anomalies {
+            min_increase,
+            min_fingerprint_increase,
+            min_increase_percent,
+            min_z_score,
+        } => {
+            let snapshots: Vec<Snapshot> = store
+                .load_all()?
+                .into_iter()
+                .map(|(_, snapshot)| snapshot)
+                .collect();
+
+            let mut thresholds = AnomalyThresholds {
+                min_increase,
+                min_increase_percent,
+                min_z_score,
+            };
+            print_anomalies(&find_anomalies(&snapshots, &thresholds));
+
+            // only the latest run of snapshots with fingerprints, since those recorded without
+            // them would make every fingerprint look new
+            let mut fingerprints = Vec::new();
+            for timestamp in store.timestamps()?.into_iter().rev() {
+                match store.load_fingerprints(timestamp)? {
+                    Some(snapshot) => fingerprints.push(snapshot),
+                    None => break,
+                }
+            }
+            fingerprints.reverse();
+            thresholds.min_increase = min_fingerprint_increase;
+            print_anomalies(&find_anomalies(&fingerprints, &thresholds));
+        }
+    }
+
+    Ok(())
+}
+
+fn print_anomalies(anomalies: &[Anomaly]) {
+    for anomaly in anomalies {
+        print!("{} {} -> {}", anomaly.id, anomaly.previous, anomaly.current);
+        if let Some(z_score) = anomaly.z_score {
+            print!(" (z-score {:.1})", z_score);
+        }
+        println!();
+    }
+}
+
+fn prune(store: &SnapshotStore, retention: &Retention) -> eyre::Result<()> {
+    let max_age = retention
+        .max_age_days
+        .map(|days| Duration::from_secs(days * 24 * 60 * 60));
+    for timestamp in store.prune(retention.keep, max_age)? {
+        eprintln!("removed snapshot {}", timestamp);
+    }
+    Ok(())
+}
+
+fn export(store: &SnapshotStore, mut items: Vec<String>) -> eyre::Result<()> {
+    let snapshots = store.load_all()?;
+
+    if items.is_empty() {
+        let ids: IndexSet<&String> = snapshots
+            .iter()
+            .flat_map(|(_, snapshot)| snapshot.keys())
+            .collect();
+        items = ids.into_iter().cloned().collect();
+    } else {
+        for id in &mut items {
+            if !id.contains(':') {
+                *id = String::from("minecraft:") + id;
+            }
+        }
+    }
+
+    println!("timestamp,{}", items.join(","));
+    for (timestamp, snapshot) in &snapshots {
+        let counts: Vec<String> = items
+            .iter()
+            .map(|id| snapshot.get(id).copied().unwrap_or(0).to_string())
+            .collect();
+        println!("{},{}", timestamp, counts.join(","));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    items::{contents, custom_name, id_and_count, stacks, stored_enchantments},
+    redact::print_json,
+    region::for_each_chunk_in_dir,
+    text::plain_text,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open},
+};
+
+/// Catalogs enchanted books by enchantment and level, listing the containers they are in
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+#[derive(Default)]
+struct Entry {
+    books: u64,
+    /// Number of books per container, keyed by dimension, position and container name
+    containers: BTreeMap<(String, [i32; 3], String), u64>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let mut catalog = BTreeMap::<(String, i16), Entry>::new();
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+
+        for_each_chunk_in_dir(&dim_path.join("region"), |_, _, chunk| {
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let block_entities = match version
+                .level(&chunk)
+                .and_then(|level| level.get::<_, &NbtList>(version.block_entities_key()).ok())
+            {
+                Some(block_entities) => block_entities,
+                None => return,
+            };
+            for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+                let items = match block_entity.get::<_, &NbtList>("Items") {
+                    Ok(items) => items,
+                    Err(_) => continue,
+                };
+                let pos = ["x", "y", "z"].map(|key| block_entity.get(key).unwrap_or_default());
+                let container = container_name(block_entity);
+                let mut add = |enchantment: &str, level: i16, count: u64, container: &str| {
+                    let entry = catalog.entry((enchantment.to_string(), level)).or_default();
+                    entry.books += count;
+                    *entry
+                        .containers
+                        .entry((dim_name.clone(), pos, container.to_string()))
+                        .or_default() += count;
+                };
+
+                for item in items.iter_map::<&NbtCompound>().flatten() {
+                    for (enchantment, level, count) in book_enchantments(item) {
+                        add(enchantment, level, count, &container);
+                    }
+                    // books in shulker boxes are listed under the shulker box's name
+                    if let Some(box_items) = contents(item) {
+                        let container = format!("{} in {}", item_name(item), container);
+                        for item in stacks(box_items) {
+                            for (enchantment, level, count) in book_enchantments(item) {
+                                add(enchantment, level, count, &container);
+                            }
+                        }
+                    }
+                }
+            }
+        })
+        .context("region folder not found")?;
+    }
+
+    let report: Vec<_> = catalog
+        .iter()
+        .map(|((enchantment, level), entry)| {
+            json!({
+                "enchantment": enchantment,
+                "level": level,
+                "books": entry.books,
+                "containers": entry
+                    .containers
+                    .iter()
+                    .map(|((dimension, pos, name), count)| json!({
+                        "dimension": dimension,
+                        "pos": pos,
+                        "name": name,
+                        "books": count,
+                    }))
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    print_json(&report)?;
+
+    Ok(())
+}
+
+/// Returns the enchantments of an enchanted book with the stack's count
+fn book_enchantments(item: &NbtCompound) -> Vec<(&str, i16, u64)> {
+    let (id, count) = id_and_count(item);
+    if id != "minecraft:enchanted_book" {
+        return Vec::new();
+    }
+    stored_enchantments(item)
+        .into_iter()
+        .map(|(enchantment, level)| (enchantment, level, count))
+        .collect()
+}
+
+/// The custom name of a container or its id
+fn container_name(block_entity: &NbtCompound) -> String {
+    match block_entity.get::<_, &str>("CustomName") {
+        Ok(name) => plain_text(name),
+        Err(_) => block_entity.get("id").unwrap_or("unknown").to_string(),
+    }
+}
+
+/// The custom name of an item or its id
+fn item_name(item: &NbtCompound) -> String {
+    match custom_name(item) {
+        Some(name) => plain_text(name),
+        None => item.get("id").unwrap_or("unknown").to_string(),
+    }
+}
@@ -0,0 +1,242 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    path::PathBuf,
+};
+
+use clap::Parser;
+use eyre::eyre;
+use serde_json::{json, Value};
+use world_statistics::{
+    blocks::ChunkBlocks,
+    redact::print_json,
+    region::for_each_chunk_in_dir,
+    world::{dimension_path, warn_if_open, Source, Sources},
+};
+
+/// Measures the rail lines and packed and blue ice roads of each dimension: their total length,
+/// the longest routes and the stations along the rails
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Number of longest routes to list per dimension
+    #[clap(long, default_value = "5")]
+    top: usize,
+
+    /// Rail lines and ice roads shorter than this many blocks are left out, like the rails of
+    /// farms
+    #[clap(long, default_value = "32")]
+    min_length: usize,
+
+    /// Ice wider than this on average is not a road, like the ice of icebergs
+    #[clap(long, default_value = "5")]
+    max_road_width: usize,
+
+    /// Detector and activator rails at most this far apart belong to the same station
+    #[clap(long, default_value = "8")]
+    station_radius: i32,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+const RAILS: &[&str] = &[
+    "minecraft:rail",
+    "minecraft:powered_rail",
+    "minecraft:detector_rail",
+    "minecraft:activator_rail",
+];
+const STATION_RAILS: &[&str] = &["minecraft:detector_rail", "minecraft:activator_rail"];
+const ROAD_ICE: &[&str] = &["minecraft:packed_ice", "minecraft:blue_ice"];
+
+/// A connected group of blocks
+struct Component {
+    blocks: Vec<[i32; 3]>,
+}
+
+impl Component {
+    /// The longest side of the bounding box, which is the length of straight roads
+    fn extent(&self) -> usize {
+        let side = |axis: usize| {
+            let values = self.blocks.iter().map(|pos| pos[axis]);
+            (values.clone().max().unwrap_or(0) - values.min().unwrap_or(0) + 1) as usize
+        };
+        side(0).max(side(2))
+    }
+
+    /// The two blocks furthest apart along the longest side
+    fn ends(&self) -> [[i32; 3]; 2] {
+        let axis = if self.spread(0) >= self.spread(2) {
+            0
+        } else {
+            2
+        };
+        let min = self.blocks.iter().min_by_key(|pos| (pos[axis], pos[1]));
+        let max = self.blocks.iter().max_by_key(|pos| (pos[axis], pos[1]));
+        [*min.unwrap(), *max.unwrap()]
+    }
+
+    fn spread(&self, axis: usize) -> i32 {
+        let values = self.blocks.iter().map(|pos| pos[axis]);
+        values.clone().max().unwrap_or(0) - values.min().unwrap_or(0)
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[(Source::Region, "rails and ice roads there")]);
+
+    let mut report = serde_json::Map::new();
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        if !sources.has(dim_name, Source::Region) {
+            continue;
+        }
+
+        let mut rails = HashSet::new();
+        let mut station_rails = HashSet::new();
+        let mut ice = HashSet::new();
+        for_each_chunk_in_dir(&dim_path.join("region"), |chunk_x, chunk_z, chunk| {
+            let blocks = ChunkBlocks::from_chunk(&chunk);
+            for (section_y, block_states) in blocks.sections() {
+                let wanted = |name: &str| RAILS.contains(&name) || ROAD_ICE.contains(&name);
+                if !block_states
+                    .palette()
+                    .iter()
+                    .any(|state| state.get::<_, &str>("Name").is_ok_and(wanted))
+                {
+                    continue;
+                }
+                for ([x, y, z], state) in block_states.iter() {
+                    let name = state.get::<_, &str>("Name").unwrap_or_default();
+                    let pos = [
+                        chunk_x * 16 + x as i32,
+                        section_y * 16 + y as i32,
+                        chunk_z * 16 + z as i32,
+                    ];
+                    if RAILS.contains(&name) {
+                        rails.insert(pos);
+                        if STATION_RAILS.contains(&name) {
+                            station_rails.insert(pos);
+                        }
+                    } else if ROAD_ICE.contains(&name) {
+                        ice.insert(pos);
+                    }
+                }
+            }
+        })?;
+
+        // rails connect to the rails next to them, on slopes also one block up or down
+        let mut lines = components(&rails, 1, 1, false);
+        lines.retain(|line| line.blocks.len() >= args.min_length);
+        lines.sort_by_key(|line| std::cmp::Reverse(line.blocks.len()));
+        let on_lines: HashSet<_> = lines.iter().flat_map(|line| &line.blocks).collect();
+        station_rails.retain(|pos| on_lines.contains(pos));
+        let stations = components(
+            &station_rails,
+            args.station_radius,
+            args.station_radius,
+            true,
+        );
+
+        let mut roads = components(&ice, 1, 1, false);
+        roads.retain(|road| {
+            let length = road.extent();
+            length >= args.min_length && road.blocks.len() <= length * args.max_road_width
+        });
+        roads.sort_by_key(|road| std::cmp::Reverse(road.extent()));
+
+        let route = |component: &Component, length: usize| {
+            json!({
+                "length": length,
+                "positions": component.ends(),
+            })
+        };
+        report.insert(
+            dim_name.clone(),
+            json!({
+                "rails": {
+                    "length": lines.iter().map(|line| line.blocks.len()).sum::<usize>(),
+                    "lines": lines.len(),
+                    "longest": lines
+                        .iter()
+                        .take(args.top)
+                        .map(|line| route(line, line.blocks.len()))
+                        .collect::<Vec<_>>(),
+                    "stations": stations
+                        .iter()
+                        .map(|station| json!({
+                            "pos": center(&station.blocks),
+                            "rails": station.blocks.len(),
+                        }))
+                        .collect::<Vec<_>>(),
+                },
+                "ice_roads": {
+                    "length": roads.iter().map(Component::extent).sum::<usize>(),
+                    "blocks": roads.iter().map(|road| road.blocks.len()).sum::<usize>(),
+                    "roads": roads.len(),
+                    "longest": roads
+                        .iter()
+                        .take(args.top)
+                        .map(|road| route(road, road.extent()))
+                        .collect::<Vec<_>>(),
+                },
+            }),
+        );
+    }
+    print_json(&Value::Object(report))?;
+
+    Ok(())
+}
+
+/// Groups the blocks into components of blocks at most `horizontal` blocks apart on x and z and
+/// `vertical` blocks on y, also diagonally on x and z if `diagonal` is set
+fn components(
+    blocks: &HashSet<[i32; 3]>,
+    horizontal: i32,
+    vertical: i32,
+    diagonal: bool,
+) -> Vec<Component> {
+    let mut seen = HashSet::new();
+    let mut components = Vec::new();
+    let mut sorted: Vec<_> = blocks.iter().copied().collect();
+    sorted.sort();
+    for start in sorted {
+        if !seen.insert(start) {
+            continue;
+        }
+        let mut component = Vec::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(pos @ [x, y, z]) = queue.pop_front() {
+            component.push(pos);
+            for dx in -horizontal..=horizontal {
+                for dz in -horizontal..=horizontal {
+                    if !diagonal && dx != 0 && dz != 0 {
+                        continue;
+                    }
+                    for dy in -vertical..=vertical {
+                        let next = [x + dx, y + dy, z + dz];
+                        if blocks.contains(&next) && seen.insert(next) {
+                            queue.push_back(next);
+                        }
+                    }
+                }
+            }
+        }
+        components.push(Component { blocks: component });
+    }
+    components
+}
+
+fn center(blocks: &[[i32; 3]]) -> [i32; 3] {
+    let len = blocks.len().max(1) as i64;
+    [0, 1, 2].map(|axis| (blocks.iter().map(|pos| pos[axis] as i64).sum::<i64>() / len) as i32)
+}
@@ -0,0 +1,146 @@
+use std::{fs, path::PathBuf};
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use world_statistics::{
+    backup::Backup,
+    journal::Journal,
+    region::{chunk_position, Compression, RegionWriter},
+    version::GameVersion,
+    world::{dimension_path, ensure_closed, warn_if_open},
+};
+
+/// Writes a chunk edited as SNBT back into its region file, creating the file if it doesn't
+/// exist. Stop the server before using this.
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// region, entities or poi
+    #[clap(short, long, default_value = "region")]
+    kind: String,
+
+    /// Compress the chunk with gzip instead of zlib
+    #[clap(long, conflicts_with = "uncompressed")]
+    gzip: bool,
+
+    /// Store the chunk uncompressed, which the game only reads since 1.20.5
+    #[clap(long)]
+    uncompressed: bool,
+
+    /// Only print where the chunk would be written
+    #[clap(long)]
+    dry_run: bool,
+
+    /// JSON lines file to record the previous content of changed chunks and files to, to be
+    /// reverted with undo
+    #[clap(long)]
+    journal: Option<PathBuf>,
+
+    /// Directory to copy the changed files to first, restored from if writing fails halfway
+    #[clap(long)]
+    backup: Option<PathBuf>,
+
+    /// Write even though the world is open in a running game or server
+    #[clap(long)]
+    force: bool,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether or end
+    dimension: String,
+
+    #[clap(allow_hyphen_values = true)]
+    chunk_x: i32,
+
+    #[clap(allow_hyphen_values = true)]
+    chunk_z: i32,
+
+    /// SNBT file as written by extract-chunk
+    file: String,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let chunk = quartz_nbt::snbt::parse(&fs::read_to_string(&args.file)?)?;
+    if args.uncompressed {
+        match GameVersion::of(&chunk) {
+            Some(version) if version.uncompressed_chunks() => {}
+            Some(version) => {
+                return Err(eyre!(
+                    "the game only reads uncompressed chunks since 1.20.5, the chunk has {}",
+                    version
+                ))
+            }
+            None => {
+                return Err(eyre!(
+                    "the chunk has no DataVersion, the game only reads uncompressed chunks since 1.20.5"
+                ))
+            }
+        }
+    }
+
+    let world_path = PathBuf::from(&args.world);
+    if args.dry_run {
+        warn_if_open(&world_path);
+    } else {
+        ensure_closed(&world_path, args.force)?;
+    }
+    let mut journal = args
+        .journal
+        .as_deref()
+        .map(|path| Journal::open(path, &world_path, "inject-chunk"))
+        .transpose()?;
+    let mut backup = args
+        .backup
+        .as_deref()
+        .map(|dir| Backup::new(dir, &world_path));
+    let dim_path = dimension_path(&world_path, &args.dimension)
+        .ok_or_else(|| eyre!("unknown dimension {}", args.dimension))?;
+    let (region_x, region_z, index) = chunk_position(args.chunk_x, args.chunk_z);
+    let region_path = dim_path
+        .join(&args.kind)
+        .join(format!("r.{}.{}.mca", region_x, region_z));
+
+    let compression = if args.gzip {
+        Compression::Gzip
+    } else if args.uncompressed {
+        Compression::None
+    } else {
+        Compression::Zlib
+    };
+
+    if args.dry_run {
+        eprintln!(
+            "would write chunk {} {} to {}",
+            args.chunk_x,
+            args.chunk_z,
+            region_path.display()
+        );
+        return Ok(());
+    }
+
+    if let Some(backup) = &mut backup {
+        backup.save(&region_path)?;
+    }
+    let mut writer = RegionWriter::open(&region_path)
+        .with_context(|| format!("failed to open {:?}", region_path))?
+        .compression(compression);
+    if let Some(journal) = &mut journal {
+        journal.record_chunk(&region_path, writer.region_file(), index)?;
+    }
+    writer.write_chunk(index, &chunk)?;
+    if let Some(backup) = backup {
+        backup.finish();
+    }
+
+    eprintln!(
+        "wrote chunk {} {} to {}",
+        args.chunk_x,
+        args.chunk_z,
+        region_path.display()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,232 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use clap::Parser;
+use eyre::eyre;
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    blocks::ChunkBlocks,
+    redact::print_json_line,
+    region::for_each_chunk_in_dir,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, Source, Sources},
+};
+
+/// Ranks chunks by how likely they contain a lag machine, combining entity, hopper, observer and
+/// piston counts, pending block ticks and falling blocks into a weighted score
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Number of chunks to list
+    #[clap(long, default_value = "50")]
+    top: usize,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+/// A signal with the count up to which it's considered normal and the weight of each one above
+struct Signal {
+    name: &'static str,
+    allowance: u64,
+    weight: f64,
+}
+
+const SIGNALS: &[Signal] = &[
+    Signal {
+        name: "entities",
+        allowance: 24,
+        weight: 1.0,
+    },
+    Signal {
+        name: "falling blocks",
+        allowance: 0,
+        weight: 5.0,
+    },
+    Signal {
+        name: "items",
+        allowance: 16,
+        weight: 0.5,
+    },
+    Signal {
+        name: "hoppers",
+        allowance: 8,
+        weight: 2.0,
+    },
+    Signal {
+        name: "observers",
+        allowance: 4,
+        weight: 1.0,
+    },
+    Signal {
+        name: "pistons",
+        allowance: 4,
+        weight: 1.0,
+    },
+    Signal {
+        name: "pending ticks",
+        allowance: 100,
+        weight: 0.1,
+    },
+];
+
+#[derive(Default)]
+struct ChunkSignals([u64; 7]);
+
+impl ChunkSignals {
+    fn add(&mut self, name: &str, count: u64) {
+        let i = SIGNALS
+            .iter()
+            .position(|signal| signal.name == name)
+            .unwrap();
+        self.0[i] += count;
+    }
+
+    fn score(&self) -> (f64, Vec<String>) {
+        let mut score = 0.0;
+        let mut reasons = Vec::new();
+        for (signal, count) in SIGNALS.iter().zip(self.0) {
+            if count > signal.allowance {
+                score += (count - signal.allowance) as f64 * signal.weight;
+                reasons.push(format!("{} {}", count, signal.name));
+            }
+        }
+        (score, reasons)
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[
+        (Source::Region, "that dimension"),
+        (Source::Entities, "entities there"),
+    ]);
+    let mut chunks = HashMap::<(String, i32, i32), ChunkSignals>::new();
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        if !sources.has(dim_name, Source::Region) {
+            continue;
+        }
+
+        for_each_chunk_in_dir(&dim_path.join("region"), |chunk_x, chunk_z, chunk| {
+            let signals = chunks
+                .entry((dim_name.clone(), chunk_x, chunk_z))
+                .or_default();
+
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let level = match version.level(&chunk) {
+                Some(level) => level,
+                None => return,
+            };
+            if let Ok(block_entities) = level.get::<_, &NbtList>(version.block_entities_key()) {
+                let hoppers = block_entities
+                    .iter_map::<&NbtCompound>()
+                    .flatten()
+                    .filter(|block_entity| {
+                        block_entity.get::<_, &str>("id").ok() == Some("minecraft:hopper")
+                    })
+                    .count();
+                signals.add("hoppers", hoppers as u64);
+            }
+            // entities were stored in the terrain chunks before 1.17
+            if version.entities_in_chunk() {
+                if let Ok(entities) = level.get::<_, &NbtList>("Entities") {
+                    add_entities(signals, entities);
+                }
+            }
+            for key in ["block_ticks", "fluid_ticks"] {
+                if let Ok(ticks) = chunk.get::<_, &NbtList>(key) {
+                    signals.add("pending ticks", ticks.len() as u64);
+                }
+            }
+
+            for (_, block_states) in ChunkBlocks::from_chunk(&chunk).sections() {
+                let palette_names: Vec<_> = block_states
+                    .palette()
+                    .iter()
+                    .map(|state| state.get::<_, &str>("Name").unwrap_or_default())
+                    .collect();
+                if !palette_names
+                    .iter()
+                    .any(|name| block_signal(name).is_some())
+                {
+                    continue;
+                }
+                for i in 0..4096 {
+                    if let Some(signal) = palette_names
+                        .get(block_states.palette_index(i))
+                        .and_then(|name| block_signal(name))
+                    {
+                        signals.add(signal, 1);
+                    }
+                }
+            }
+        })?;
+
+        let entities_path = dim_path.join("entities");
+        if !sources.has(dim_name, Source::Entities) {
+            continue;
+        }
+        for_each_chunk_in_dir(&entities_path, |chunk_x, chunk_z, chunk| {
+            let entities = match chunk.get::<_, &NbtList>("Entities") {
+                Ok(entities) => entities,
+                Err(_) => return,
+            };
+            let signals = chunks
+                .entry((dim_name.clone(), chunk_x, chunk_z))
+                .or_default();
+            add_entities(signals, entities);
+        })?;
+    }
+
+    let mut scores: Vec<_> = chunks
+        .iter()
+        .map(|(chunk, signals)| (chunk, signals.score()))
+        .filter(|(_, (score, _))| *score > 0.0)
+        .collect();
+    scores.sort_by(|(a_chunk, (a, _)), (b_chunk, (b, _))| {
+        b.total_cmp(a).then_with(|| a_chunk.cmp(b_chunk))
+    });
+
+    for ((dimension, chunk_x, chunk_z), (score, reasons)) in scores.into_iter().take(args.top) {
+        print_json_line(&json!({
+            "dimension": dimension,
+            "chunk": [chunk_x, chunk_z],
+            "block": [chunk_x * 16 + 8, chunk_z * 16 + 8],
+            "score": score,
+            "reasons": reasons,
+        }))?;
+    }
+
+    Ok(())
+}
+
+/// Adds the entity signals of the entities in a chunk
+fn add_entities(signals: &mut ChunkSignals, entities: &NbtList) {
+    signals.add("entities", entities.len() as u64);
+    for entity in entities.iter_map::<&NbtCompound>().flatten() {
+        match entity.get::<_, &str>("id").unwrap_or_default() {
+            "minecraft:falling_block" => signals.add("falling blocks", 1),
+            "minecraft:item" => signals.add("items", 1),
+            _ => {}
+        }
+    }
+}
+
+fn block_signal(name: &str) -> Option<&'static str> {
+    match name {
+        "minecraft:observer" => Some("observers"),
+        "minecraft:piston" | "minecraft:sticky_piston" => Some("pistons"),
+        _ => None,
+    }
+}
@@ -0,0 +1,273 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use clap::{ArgEnum, Parser};
+use quartz_nbt::NbtList;
+use serde_json::{json, Value};
+use world_statistics::{
+    anonymize::AnonymizeArgs,
+    dat::read_dat,
+    items::{for_each_item, id_and_count},
+    players::{
+        find_usercache, play_time, playerdata_files, read_usercache, stat, stats_files,
+        INVENTORY_KEYS,
+    },
+    redact::{print_json, Redaction},
+    world::warn_if_open,
+};
+
+/// Ranks the players by statistics and by the value of their inventories and ender chests, as
+/// JSON or CSV for community websites
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    #[clap(short, long, arg_enum, default_value = "json")]
+    format: Format,
+
+    /// Metrics to rank by, all if none are given
+    #[clap(short, long, arg_enum, use_value_delimiter = true)]
+    metrics: Vec<Metric>,
+
+    /// Number of players per metric
+    #[clap(long, default_value = "10")]
+    top: usize,
+
+    /// JSON object of item ids to their value for the wealth score, instead of counting
+    /// diamonds, emeralds, netherite and their blocks
+    #[clap(long)]
+    values: Option<PathBuf>,
+
+    /// usercache.json to resolve player names, defaults to the one next to the world
+    #[clap(long)]
+    usercache: Option<String>,
+
+    #[clap(flatten)]
+    anonymize: AnonymizeArgs,
+
+    /// Path to the world directory
+    world: String,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug)]
+enum Format {
+    Json,
+    Csv,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+enum Metric {
+    DiamondsMined,
+    Wealth,
+    Playtime,
+    Deaths,
+    DistanceFlown,
+}
+
+impl Metric {
+    const ALL: &'static [Metric] = &[
+        Metric::DiamondsMined,
+        Metric::Wealth,
+        Metric::Playtime,
+        Metric::Deaths,
+        Metric::DistanceFlown,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Metric::DiamondsMined => "diamonds_mined",
+            Metric::Wealth => "wealth",
+            Metric::Playtime => "playtime_hours",
+            Metric::Deaths => "deaths",
+            Metric::DistanceFlown => "distance_flown_km",
+        }
+    }
+
+    /// Reads the metric from a stats file, or None for metrics that don't come from stats
+    fn value_in(self, stats: &Value) -> Option<f64> {
+        let custom = |key| stat(stats, "minecraft:custom", key);
+        Some(match self {
+            Metric::DiamondsMined => {
+                (stat(stats, "minecraft:mined", "minecraft:diamond_ore")
+                    + stat(stats, "minecraft:mined", "minecraft:deepslate_diamond_ore"))
+                    as f64
+            }
+            Metric::Playtime => play_time(stats).unwrap_or_default() as f64 / 72000.0,
+            Metric::Deaths => custom("minecraft:deaths") as f64,
+            Metric::DistanceFlown => custom("minecraft:aviate_one_cm") as f64 / 100_000.0,
+            Metric::Wealth => return None,
+        })
+    }
+}
+
+const DEFAULT_VALUES: &[(&str, f64)] = &[
+    ("minecraft:diamond", 1.0),
+    ("minecraft:diamond_block", 9.0),
+    ("minecraft:emerald", 0.25),
+    ("minecraft:emerald_block", 2.25),
+    ("minecraft:netherite_ingot", 4.0),
+    ("minecraft:netherite_scrap", 1.0),
+    ("minecraft:netherite_block", 36.0),
+    ("minecraft:ancient_debris", 1.0),
+];
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let mut names = match &args.usercache {
+        Some(path) => read_usercache(Path::new(path))?,
+        None => find_usercache(&world_path),
+    };
+    let values: HashMap<String, f64> = match &args.values {
+        Some(path) => serde_json::from_str(&fs::read_to_string(path)?)?,
+        None => DEFAULT_VALUES
+            .iter()
+            .map(|&(id, value)| (id.to_string(), value))
+            .collect(),
+    };
+    let metrics = if args.metrics.is_empty() {
+        Metric::ALL.to_vec()
+    } else {
+        args.metrics.clone()
+    };
+
+    // values of each metric by player UUID
+    let mut scores = HashMap::<&str, HashMap<String, f64>>::new();
+
+    if metrics.iter().any(|&metric| metric != Metric::Wealth) {
+        for (uuid, path) in existing(stats_files(&world_path), "stats")? {
+            let stats: Value = match read_json(&path) {
+                Ok(stats) => stats,
+                Err(e) => {
+                    eprintln!("skipping {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            for &metric in &metrics {
+                if let Some(value) = metric.value_in(&stats) {
+                    scores
+                        .entry(metric.name())
+                        .or_default()
+                        .insert(uuid.clone(), value);
+                }
+            }
+        }
+    }
+
+    if metrics.contains(&Metric::Wealth) {
+        let wealth = scores.entry(Metric::Wealth.name()).or_default();
+        for (uuid, path) in existing(playerdata_files(&world_path), "playerdata")? {
+            let player = match read_dat(&path) {
+                Ok(player) => player,
+                Err(e) => {
+                    eprintln!("skipping {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let mut score = 0.0;
+            for key in INVENTORY_KEYS {
+                if let Ok(items) = player.get::<_, &NbtList>(*key) {
+                    for_each_item(items, &mut |item| {
+                        let (id, count) = id_and_count(item);
+                        score += values.get(id).copied().unwrap_or_default() * count as f64;
+                    });
+                }
+            }
+            wealth.insert(uuid, score);
+        }
+    }
+
+    let redaction = Redaction::global()?;
+    let mut leaderboards: Vec<(&str, Vec<(String, f64)>)> = metrics
+        .iter()
+        .map(|metric| {
+            let mut ranking: Vec<(String, f64)> = scores
+                .remove(metric.name())
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(uuid, value)| *value > 0.0 && !redaction.hides_uuid(uuid, &names))
+                .collect();
+            ranking.sort_by(|(a_uuid, a), (b_uuid, b)| b.total_cmp(a).then(a_uuid.cmp(b_uuid)));
+            ranking.truncate(args.top);
+            (metric.name(), ranking)
+        })
+        .collect();
+
+    let anonymizer = args.anonymize.anonymizer()?;
+    if args.anonymize.anonymize {
+        for (_, ranking) in &mut leaderboards {
+            for (uuid, _) in ranking {
+                *uuid = anonymizer.player(uuid).unwrap_or_default();
+            }
+        }
+        names.clear();
+    }
+    let name = |uuid: &str| names.get(uuid).map(String::as_str);
+    match args.format {
+        Format::Json => {
+            let output: serde_json::Map<String, Value> = leaderboards
+                .iter()
+                .map(|(metric, ranking)| {
+                    let entries = ranking
+                        .iter()
+                        .enumerate()
+                        .map(|(i, (uuid, value))| {
+                            json!({
+                                "rank": i + 1,
+                                "uuid": uuid,
+                                "name": name(uuid),
+                                "value": round(*value),
+                            })
+                        })
+                        .collect();
+                    (metric.to_string(), Value::Array(entries))
+                })
+                .collect();
+            print_json(&output)?;
+        }
+        Format::Csv => {
+            println!("metric,rank,uuid,name,value");
+            for (metric, ranking) in &leaderboards {
+                for (i, (uuid, value)) in ranking.iter().enumerate() {
+                    println!(
+                        "{},{},{},{},{}",
+                        metric,
+                        i + 1,
+                        uuid,
+                        name(uuid).unwrap_or_default(),
+                        round(*value)
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Treats a missing folder like an empty one
+fn existing(
+    files: Result<Vec<(String, PathBuf)>, io::Error>,
+    folder: &str,
+) -> Result<Vec<(String, PathBuf)>, io::Error> {
+    match files {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            eprintln!("skipping {}, folder not found", folder);
+            Ok(Vec::new())
+        }
+        files => files,
+    }
+}
+
+fn read_json(path: &Path) -> eyre::Result<Value> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Rounds to two decimals for display
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
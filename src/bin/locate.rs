@@ -0,0 +1,273 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use clap::{Parser, Subcommand};
+use eyre::{eyre, Context};
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+use world_statistics::{
+    biomes::SectionBiomes,
+    redact::Redaction,
+    region::{read_chunk, region_files, RegionFile},
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, BlockPos},
+};
+
+/// Finds the biomes and structures nearest to a position in the chunks saved so far, without
+/// generating any terrain, e.g. while the server is offline or in an old backup
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end, or a custom dimension like mymod:mining
+    #[clap(long, default_value = "overworld")]
+    dimension: String,
+
+    /// Number of results to list, nearest first
+    #[clap(long, default_value = "1")]
+    count: usize,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Finds the nearest 4x4x4 cells of a biome, like cherry_grove. Biomes are only searched in
+    /// chunks saved since 1.18.
+    Biome {
+        biome: String,
+
+        /// Position to search from as x,y,z
+        #[clap(allow_hyphen_values = true)]
+        from: BlockPos,
+    },
+    /// Finds the nearest starts of a structure, like minecraft:village_plains, or village for
+    /// all kinds of villages
+    Structure {
+        structure: String,
+
+        /// Position to search from as x,y,z
+        #[clap(allow_hyphen_values = true)]
+        from: BlockPos,
+    },
+}
+
+/// Something found, with its squared distance to the position searched from
+struct Found {
+    distance_sq: i64,
+    name: String,
+    pos: [i32; 3],
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let dim_path = dimension_path(&world_path, &args.dimension)
+        .ok_or_else(|| eyre!("unknown dimension {}", args.dimension))?;
+
+    let (query, from) = match &args.command {
+        Command::Biome { biome, from } => (normalize(biome), from.0),
+        Command::Structure { structure, from } => (normalize(structure), from.0),
+    };
+    let distance_sq = |x: i32, y: Option<i32>, z: i32| {
+        let dy = y.map_or(0, |y| y - from[1]) as i64;
+        (x - from[0]) as i64 * (x - from[0]) as i64
+            + dy * dy
+            + (z - from[2]) as i64 * (z - from[2]) as i64
+    };
+
+    // search the region files nearest first, until none can have anything nearer
+    let mut regions = region_files(&dim_path.join("region"))
+        .with_context(|| format!("region folder of {} not found", args.dimension))?;
+    let region_distance_sq = |region_x: i32, region_z: i32| {
+        let nearest = |from: i32, region: i32| from.clamp(region * 512, region * 512 + 511);
+        distance_sq(nearest(from[0], region_x), None, nearest(from[2], region_z))
+    };
+    regions.sort_by_key(|&(region_x, region_z, _)| region_distance_sq(region_x, region_z));
+
+    let mut found: Vec<Found> = Vec::new();
+    let mut seen_starts = HashSet::new();
+    let mut old_chunks = 0;
+    for (region_x, region_z, path) in regions {
+        if found.len() >= args.count
+            && region_distance_sq(region_x, region_z) > found[args.count - 1].distance_sq
+        {
+            break;
+        }
+
+        let mut region_file = RegionFile::open(&path)?;
+        region_file.for_each_chunk(|(index, _, buf)| {
+            let chunk_x = region_x * 32 + (index % 32) as i32;
+            let chunk_z = region_z * 32 + (index / 32) as i32;
+            let chunk = match read_chunk(buf) {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    eprintln!("failed to read chunk {} {}: {}", chunk_x, chunk_z, e);
+                    return;
+                }
+            };
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+
+            match &args.command {
+                Command::Biome { .. } => {
+                    if version.data_version < DATA_VERSION_1_18 {
+                        old_chunks += 1;
+                        return;
+                    }
+                    // only the nearest cell of each chunk, so that the results are different
+                    // places rather than neighbouring cells
+                    let nearest = biome_cells(&chunk, chunk_x, chunk_z)
+                        .filter(|(biome, _)| normalize(biome) == query)
+                        .map(|(biome, [x, y, z])| Found {
+                            distance_sq: distance_sq(x, Some(y), z),
+                            name: biome.to_string(),
+                            pos: [x, y, z],
+                        })
+                        .min_by_key(|found| found.distance_sq);
+                    found.extend(nearest);
+                }
+                Command::Structure { .. } => {
+                    for (name, start_x, start_z) in structure_starts(&chunk, version) {
+                        if !matches_structure(&normalize(&name), &query)
+                            || !seen_starts.insert((name.clone(), start_x, start_z))
+                        {
+                            continue;
+                        }
+                        let (x, z) = (start_x * 16 + 8, start_z * 16 + 8);
+                        found.push(Found {
+                            distance_sq: distance_sq(x, None, z),
+                            name,
+                            pos: [x, from[1], z],
+                        });
+                    }
+                }
+            }
+        })?;
+        found.sort_by(|a, b| a.distance_sq.cmp(&b.distance_sq).then(a.pos.cmp(&b.pos)));
+    }
+
+    if old_chunks > 0 {
+        eprintln!(
+            "skipped {} chunks saved before 1.18, which store biomes as numeric ids",
+            old_chunks
+        );
+    }
+    if found.is_empty() {
+        return Err(eyre!("no {} found in the saved chunks", query));
+    }
+    let redaction = Redaction::global()?;
+    for found in found.iter().take(args.count) {
+        let [x, y, z] = found.pos;
+        // the distance is to the redacted position, so that it doesn't give the exact one away
+        let (x, z) = redaction.coordinates(x, z);
+        let distance_sq = match args.command {
+            Command::Biome { .. } => distance_sq(x, Some(y), z),
+            Command::Structure { .. } => distance_sq(x, None, z),
+        };
+        let y = match args.command {
+            Command::Biome { .. } => y.to_string(),
+            Command::Structure { .. } => "~".to_string(),
+        };
+        println!(
+            "{} {} {} {} ({:.0} blocks)",
+            found.name,
+            x,
+            y,
+            z,
+            (distance_sq as f64).sqrt()
+        );
+    }
+
+    Ok(())
+}
+
+/// Removes the `minecraft:` namespace and lowercases the names of structures from before 1.13
+fn normalize(name: &str) -> String {
+    name.strip_prefix("minecraft:")
+        .unwrap_or(name)
+        .to_lowercase()
+}
+
+/// Whether a structure like `village_plains` is the structure searched for, or one of its kinds
+fn matches_structure(name: &str, query: &str) -> bool {
+    name == query
+        || name
+            .strip_prefix(query)
+            .is_some_and(|kind| kind.starts_with('_'))
+}
+
+/// Returns the biome and center of every 4x4x4 cell of a chunk saved since 1.18
+fn biome_cells(
+    chunk: &NbtCompound,
+    chunk_x: i32,
+    chunk_z: i32,
+) -> impl Iterator<Item = (&str, [i32; 3])> {
+    let sections = chunk
+        .get::<_, &NbtList>("sections")
+        .map(|sections| sections.iter_map::<&NbtCompound>().flatten().collect())
+        .unwrap_or_else(|_| Vec::new());
+    sections.into_iter().flat_map(move |section| {
+        let y = section.get::<_, i8>("Y").unwrap_or_default() as i32;
+        let biomes = SectionBiomes::from_section(section);
+        biomes.into_iter().flat_map(move |biomes| {
+            (0..64).map(move |i| {
+                let (cell_x, cell_y, cell_z) = (i % 4, i / 16, i / 4 % 4);
+                let pos = [
+                    chunk_x * 16 + cell_x as i32 * 4 + 2,
+                    y * 16 + cell_y as i32 * 4 + 2,
+                    chunk_z * 16 + cell_z as i32 * 4 + 2,
+                ];
+                (biomes.get(i), pos)
+            })
+        })
+    })
+}
+
+/// Returns the name and start chunk of the structures a chunk holds the start of or references.
+/// References point to the start chunk, which finds structures whose start chunk isn't saved.
+fn structure_starts(chunk: &NbtCompound, version: GameVersion) -> Vec<(String, i32, i32)> {
+    let (structures_key, starts_key) = if version.nested_block_states() {
+        ("structures", "starts")
+    } else {
+        ("Structures", "Starts")
+    };
+    let structures = match version
+        .level(chunk)
+        .and_then(|level| level.get::<_, &NbtCompound>(structures_key).ok())
+    {
+        Some(structures) => structures,
+        None => return Vec::new(),
+    };
+
+    let mut starts = Vec::new();
+    if let Ok(compound) = structures.get::<_, &NbtCompound>(starts_key) {
+        for (name, start) in compound.inner() {
+            let start = match start {
+                NbtTag::Compound(start) => start,
+                _ => continue,
+            };
+            if start
+                .get::<_, &str>("id")
+                .map_or(true, |id| id == "INVALID")
+            {
+                continue;
+            }
+            if let (Ok(x), Ok(z)) = (start.get("ChunkX"), start.get("ChunkZ")) {
+                starts.push((name.clone(), x, z));
+            }
+        }
+    }
+    if let Ok(references) = structures.get::<_, &NbtCompound>("References") {
+        for (name, chunks) in references.inner() {
+            if let NbtTag::LongArray(chunks) = chunks {
+                for &chunk in chunks {
+                    starts.push((name.clone(), chunk as i32, (chunk >> 32) as i32));
+                }
+            }
+        }
+    }
+    starts
+}
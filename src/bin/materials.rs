@@ -0,0 +1,142 @@
+use std::{collections::BTreeMap, fs::File, io, path::PathBuf};
+
+use clap::Parser;
+use eyre::eyre;
+use indexmap::IndexMap;
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    blocks::{BlockStates, AIR_BLOCKS},
+    items::id_and_count_as,
+    redact::print_json,
+    region::{chunk_position, read_chunk, RegionFile},
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, BlockPos},
+};
+
+/// Counts the blocks and container contents inside a box, as a bill of materials
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether or end
+    dimension: String,
+
+    /// First corner as x,y,z
+    #[clap(allow_hyphen_values = true)]
+    from: BlockPos,
+
+    /// Second corner as x,y,z
+    #[clap(allow_hyphen_values = true)]
+    to: BlockPos,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let dim_path = dimension_path(&world_path, &args.dimension)
+        .ok_or_else(|| eyre!("unknown dimension {}", args.dimension))?;
+
+    let min = [0, 1, 2].map(|i| args.from.0[i].min(args.to.0[i]));
+    let max = [0, 1, 2].map(|i| args.from.0[i].max(args.to.0[i]));
+    let contains = |x: i32, y: i32, z: i32| {
+        (min[0]..=max[0]).contains(&x)
+            && (min[1]..=max[1]).contains(&y)
+            && (min[2]..=max[2]).contains(&z)
+    };
+
+    let mut regions: BTreeMap<(i32, i32), Vec<(usize, usize)>> = BTreeMap::new();
+    for chunk_x in (min[0] >> 4)..=(max[0] >> 4) {
+        for chunk_z in (min[2] >> 4)..=(max[2] >> 4) {
+            let (region_x, region_z, index) = chunk_position(chunk_x, chunk_z);
+            regions
+                .entry((region_x, region_z))
+                .or_default()
+                .push((index % 32, index / 32));
+        }
+    }
+
+    let mut blocks = IndexMap::<String, u64>::new();
+    let mut items = IndexMap::<String, u64>::new();
+
+    for ((region_x, region_z), coords) in regions {
+        let path = dim_path
+            .join("region")
+            .join(format!("r.{}.{}.mca", region_x, region_z));
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        let payloads = RegionFile::new(file)?
+            .with_path(&path)
+            .read_chunks_at(&coords)?;
+        for (&(local_x, local_z), payload) in coords.iter().zip(payloads) {
+            let chunk = match payload {
+                Some(buf) => read_chunk(&buf)?,
+                None => continue,
+            };
+            let chunk_x = region_x * 32 + local_x as i32;
+            let chunk_z = region_z * 32 + local_z as i32;
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let level = match version.level(&chunk) {
+                Some(level) => level,
+                None => continue,
+            };
+
+            if let Ok(sections) = level.get::<_, &NbtList>(version.sections_key()) {
+                for section in sections.iter_map::<&NbtCompound>().flatten() {
+                    let section_y = match section.get::<_, i8>("Y") {
+                        Ok(y) => y as i32,
+                        Err(_) => continue,
+                    };
+                    let block_states = match BlockStates::from_section_as(section, version) {
+                        Some(block_states) => block_states,
+                        None => continue,
+                    };
+                    for i in 0..4096 {
+                        let x = chunk_x * 16 + (i % 16) as i32;
+                        let z = chunk_z * 16 + (i / 16 % 16) as i32;
+                        let y = section_y * 16 + (i / 256) as i32;
+                        if !contains(x, y, z) {
+                            continue;
+                        }
+                        match block_states.name(i) {
+                            Some(name) if !AIR_BLOCKS.contains(&name) => {
+                                *blocks.entry(name.to_string()).or_default() += 1;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+
+            if let Ok(block_entities) = level.get::<_, &NbtList>(version.block_entities_key()) {
+                for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+                    let pos = ["x", "y", "z"].map(|key| block_entity.get::<_, i32>(key).ok());
+                    match pos {
+                        [Some(x), Some(y), Some(z)] if contains(x, y, z) => {}
+                        _ => continue,
+                    }
+                    if let Ok(list) = block_entity.get::<_, &NbtList>("Items") {
+                        for item in list.iter_map::<&NbtCompound>().flatten() {
+                            let (id, count) = id_and_count_as(item, version);
+                            *items.entry(id.to_string()).or_default() += count;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    blocks.sort_by(|_, a, _, b| b.cmp(a));
+    items.sort_by(|_, a, _, b| b.cmp(a));
+    print_json(&json!({ "blocks": blocks, "items": items }))?;
+
+    Ok(())
+}
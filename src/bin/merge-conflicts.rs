@@ -0,0 +1,139 @@
+use std::{cmp::Ordering, path::PathBuf};
+
+use clap::Parser;
+use eyre::eyre;
+use serde_json::{json, Value};
+use world_statistics::{
+    chunk::Chunk,
+    redact::print_json,
+    region::{chunk_position, deserialize_chunk, region_files, RegionFile},
+    region_cache::RegionCache,
+    world::{dimension_path, warn_if_open, Source, Sources},
+};
+
+/// Reports the chunks that exist in both worlds, with the InhabitedTime, LastUpdate and
+/// DataVersion of either copy, to decide which to keep before copying chunks between them
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Chunks the source chunks would be moved by along the x axis, as for copy-chunks
+    #[clap(long, default_value = "0", allow_hyphen_values = true)]
+    offset_x: i32,
+
+    /// Chunks the source chunks would be moved by along the z axis, as for copy-chunks
+    #[clap(long, default_value = "0", allow_hyphen_values = true)]
+    offset_z: i32,
+
+    /// Path to the world directory to copy from
+    source: String,
+
+    /// Path to the world directory to copy to
+    target: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let source_path = PathBuf::from(&args.source);
+    let target_path = PathBuf::from(&args.target);
+    warn_if_open(&source_path);
+    warn_if_open(&target_path);
+    let sources = Sources::detect(&source_path, &args.dimensions);
+    sources.notify_missing(&[(Source::Region, "that dimension")]);
+
+    let mut dimensions = serde_json::Map::new();
+    for dim_name in &args.dimensions {
+        let source_dim_path = dimension_path(&source_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        let target_dim_path = dimension_path(&target_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        if !sources.has(dim_name, Source::Region) {
+            continue;
+        }
+
+        // a source region overlaps at most four target regions
+        let target_files = RegionCache::new(target_dim_path.join("region"), 4);
+        let mut source_chunks = 0;
+        let mut conflicts = Vec::new();
+        let mut more_inhabited = (0, 0);
+        for (region_x, region_z, path) in region_files(&source_dim_path.join("region"))? {
+            let mut region_file = RegionFile::open(&path)?;
+            for index in 0..1024 {
+                if !region_file.contains_chunk(index) {
+                    continue;
+                }
+                source_chunks += 1;
+                let chunk_x = region_x * 32 + (index % 32) as i32;
+                let chunk_z = region_z * 32 + (index / 32) as i32;
+                let (target_x, target_z) = (chunk_x + args.offset_x, chunk_z + args.offset_z);
+                let (target_region_x, target_region_z, target_index) =
+                    chunk_position(target_x, target_z);
+
+                let target_file = match target_files.region(target_region_x, target_region_z)? {
+                    Some(target_file) => target_file,
+                    None => continue,
+                };
+                let mut target_file = target_file.lock().unwrap();
+                if !target_file.contains_chunk(target_index) {
+                    continue;
+                }
+
+                let source = chunk_summary(&mut region_file, index)?;
+                let target = chunk_summary(&mut target_file, target_index)?;
+                match source["inhabited_time"]
+                    .as_i64()
+                    .cmp(&target["inhabited_time"].as_i64())
+                {
+                    Ordering::Greater => more_inhabited.0 += 1,
+                    Ordering::Less => more_inhabited.1 += 1,
+                    Ordering::Equal => {}
+                }
+                conflicts.push(json!({
+                    "chunk": [chunk_x, chunk_z],
+                    "target_chunk": [target_x, target_z],
+                    "source": source,
+                    "target": target,
+                }));
+            }
+        }
+
+        dimensions.insert(
+            dim_name.clone(),
+            json!({
+                "source_chunks": source_chunks,
+                "conflicts": conflicts.len(),
+                "more_inhabited_in_source": more_inhabited.0,
+                "more_inhabited_in_target": more_inhabited.1,
+                "chunks": conflicts,
+            }),
+        );
+    }
+
+    print_json(&dimensions)?;
+
+    Ok(())
+}
+
+/// Reads the DataVersion, InhabitedTime, LastUpdate, status and number of block entities of a
+/// chunk, or the error decoding it
+fn chunk_summary(region_file: &mut RegionFile, index: usize) -> eyre::Result<Value> {
+    let chunk: Chunk = match region_file
+        .read_payload(index)?
+        .map(|buf| deserialize_chunk(&buf))
+    {
+        Some(Ok(chunk)) => chunk,
+        Some(Err(e)) => return Ok(json!({ "error": e.to_string() })),
+        None => return Ok(Value::Null),
+    };
+    Ok(json!({
+        "data_version": chunk.data_version,
+        "inhabited_time": chunk.inhabited_time,
+        "last_update": chunk.last_update,
+        "status": chunk.status,
+        "block_entities": chunk.block_entities.len(),
+    }))
+}
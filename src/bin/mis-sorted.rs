@@ -0,0 +1,87 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, BufRead},
+};
+
+use clap::Parser;
+use serde_json::{json, Value};
+use world_statistics::redact::print_json_line;
+
+/// Reads slot-map output from stdin and flags containers where nearly all slots hold one item but
+/// a few hold something else, the usual sign of an overflowing sorter. A container named after
+/// an item, like "Cobblestone", is expected to hold only that item.
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Minimum share of occupied slots holding the dominant item
+    #[clap(long, default_value = "0.9")]
+    min_share: f64,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let container: Value = serde_json::from_str(&line)?;
+        let slots: BTreeMap<String, String> = match container.get("slots") {
+            Some(slots) => serde_json::from_value(slots.clone())?,
+            None => continue,
+        };
+        if slots.is_empty() {
+            continue;
+        }
+
+        let ids: BTreeMap<&str, &str> = slots
+            .iter()
+            .map(|(slot, stack)| {
+                let id = stack.split_once(' ').map_or(stack.as_str(), |(_, id)| id);
+                (slot.as_str(), id)
+            })
+            .collect();
+
+        let mut slot_counts = HashMap::<&str, usize>::new();
+        for id in ids.values() {
+            *slot_counts.entry(id).or_default() += 1;
+        }
+        let dominant = slot_counts
+            .iter()
+            .max_by(|(a_id, a), (b_id, b)| a.cmp(b).then_with(|| b_id.cmp(a_id)))
+            .map(|(id, _)| *id)
+            .unwrap();
+
+        let named = container
+            .get("name")
+            .and_then(Value::as_str)
+            .map(|name| String::from("minecraft:") + &name.trim().to_lowercase().replace(' ', "_"));
+        let (expected, by_name) = match &named {
+            Some(named) if slot_counts.contains_key(named.as_str()) => (named.as_str(), true),
+            _ => (dominant, false),
+        };
+
+        let share = slot_counts[expected] as f64 / ids.len() as f64;
+        if share == 1.0 || (!by_name && share < args.min_share) {
+            continue;
+        }
+
+        let offending: BTreeMap<_, _> = ids
+            .iter()
+            .filter(|(_, id)| **id != expected)
+            .map(|(slot, _)| (slot.parse::<i8>().unwrap_or_default(), &slots[*slot]))
+            .collect();
+
+        print_json_line(&json!({
+            "dimension": container.get("dimension"),
+            "pos": container.get("pos"),
+            "name": container.get("name"),
+            "expected": expected,
+            "share": share,
+            "offending": offending,
+        }))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,241 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use clap::Parser;
+use eyre::eyre;
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    blocks::ChunkBlocks,
+    dat::read_dat,
+    items::{contents, for_each_item, id_and_count},
+    players::playerdata_files,
+    redact::print_json,
+    region::for_each_chunk_in_dir,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, Source, Sources},
+};
+
+/// Lists the blocks, items, entities and block entities of namespaces other than `minecraft`,
+/// e.g. left over after removing mods, and where they are
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Namespaces to ignore besides minecraft
+    #[clap(long, use_value_delimiter = true)]
+    ignore: Vec<String>,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+/// Counts of one namespace
+#[derive(Default)]
+struct Namespace {
+    blocks: BTreeMap<String, u64>,
+    items: BTreeMap<String, u64>,
+    entities: BTreeMap<String, u64>,
+    block_entities: BTreeMap<String, u64>,
+    /// Occurrences per region, or `playerdata`
+    locations: BTreeMap<String, u64>,
+}
+
+struct Residue {
+    ignore: Vec<String>,
+    namespaces: BTreeMap<String, Namespace>,
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Block,
+    Item,
+    Entity,
+    BlockEntity,
+}
+
+impl Residue {
+    fn add(&mut self, kind: Kind, id: &str, count: u64, location: &str) {
+        let namespace = match id.split_once(':') {
+            Some((namespace, _)) => namespace,
+            None => return,
+        };
+        if namespace == "minecraft" || self.ignore.iter().any(|ignore| ignore == namespace) {
+            return;
+        }
+        let entry = self.namespaces.entry(namespace.to_string()).or_default();
+        let counts = match kind {
+            Kind::Block => &mut entry.blocks,
+            Kind::Item => &mut entry.items,
+            Kind::Entity => &mut entry.entities,
+            Kind::BlockEntity => &mut entry.block_entities,
+        };
+        *counts.entry(id.to_string()).or_default() += count;
+        *entry.locations.entry(location.to_string()).or_default() += count;
+    }
+
+    fn add_items(&mut self, items: &NbtList, location: &str) {
+        for_each_item(items, &mut |item| {
+            let (id, count) = id_and_count(item);
+            self.add(Kind::Item, id, count, location);
+        });
+    }
+
+    fn add_stack(&mut self, item: &NbtCompound, location: &str) {
+        let (id, count) = id_and_count(item);
+        self.add(Kind::Item, id, count, location);
+        if let Some(contents) = contents(item) {
+            self.add_items(contents, location);
+        }
+    }
+
+    fn add_entity(&mut self, entity: &NbtCompound, location: &str) {
+        self.add(
+            Kind::Entity,
+            entity.get("id").unwrap_or_default(),
+            1,
+            location,
+        );
+        if let Ok(item) = entity.get::<_, &NbtCompound>("Item") {
+            self.add_stack(item, location);
+        }
+        for key in ["Items", "Inventory", "ArmorItems", "HandItems"] {
+            if let Ok(items) = entity.get::<_, &NbtList>(key) {
+                self.add_items(items, location);
+            }
+        }
+        if let Ok(passengers) = entity.get::<_, &NbtList>("Passengers") {
+            for passenger in passengers.iter_map::<&NbtCompound>().flatten() {
+                self.add_entity(passenger, location);
+            }
+        }
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[
+        (Source::Region, "blocks and block entities there"),
+        (Source::Entities, "entities there"),
+        (Source::Playerdata, "player inventories"),
+    ]);
+    let mut residue = Residue {
+        ignore: args.ignore.clone(),
+        namespaces: BTreeMap::new(),
+    };
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        if !sources.has(dim_name, Source::Region) {
+            continue;
+        }
+
+        for_each_chunk_in_dir(&dim_path.join("region"), |chunk_x, chunk_z, chunk| {
+            let location = format!("{} r.{}.{}", dim_name, chunk_x >> 5, chunk_z >> 5);
+
+            let blocks = ChunkBlocks::from_chunk(&chunk);
+            for (_, block_states) in blocks.sections() {
+                let modded: Vec<bool> = block_states
+                    .palette()
+                    .iter()
+                    .map(|state| {
+                        !state
+                            .get::<_, &str>("Name")
+                            .unwrap_or_default()
+                            .starts_with("minecraft:")
+                    })
+                    .collect();
+                if !modded.contains(&true) {
+                    continue;
+                }
+                let counts = block_states.counts();
+                for (state, count) in block_states.palette().iter().zip(counts) {
+                    if count > 0 {
+                        let name = state.get("Name").unwrap_or_default();
+                        residue.add(Kind::Block, name, count, &location);
+                    }
+                }
+            }
+
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let level = match version.level(&chunk) {
+                Some(level) => level,
+                None => return,
+            };
+            if let Ok(block_entities) = level.get::<_, &NbtList>(version.block_entities_key()) {
+                for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+                    let id = block_entity.get("id").unwrap_or_default();
+                    residue.add(Kind::BlockEntity, id, 1, &location);
+                    if let Ok(items) = block_entity.get::<_, &NbtList>("Items") {
+                        residue.add_items(items, &location);
+                    }
+                }
+            }
+            // entities were stored in the terrain chunks before 1.17
+            if version.entities_in_chunk() {
+                if let Ok(entities) = level.get::<_, &NbtList>("Entities") {
+                    for entity in entities.iter_map::<&NbtCompound>().flatten() {
+                        residue.add_entity(entity, &location);
+                    }
+                }
+            }
+        })?;
+
+        let entities_path = dim_path.join("entities");
+        if !sources.has(dim_name, Source::Entities) {
+            continue;
+        }
+        for_each_chunk_in_dir(&entities_path, |chunk_x, chunk_z, chunk| {
+            let location = format!("{} r.{}.{}", dim_name, chunk_x >> 5, chunk_z >> 5);
+            if let Ok(entities) = chunk.get::<_, &NbtList>("Entities") {
+                for entity in entities.iter_map::<&NbtCompound>().flatten() {
+                    residue.add_entity(entity, &location);
+                }
+            }
+        })?;
+    }
+
+    if sources.playerdata {
+        for (_, path) in playerdata_files(&world_path)? {
+            let player = match read_dat(&path) {
+                Ok(player) => player,
+                Err(e) => {
+                    eprintln!("failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            for key in ["Inventory", "EnderItems"] {
+                if let Ok(items) = player.get::<_, &NbtList>(key) {
+                    residue.add_items(items, "playerdata");
+                }
+            }
+        }
+    }
+
+    let report: serde_json::Map<_, _> = residue
+        .namespaces
+        .iter()
+        .map(|(name, namespace)| {
+            (
+                name.clone(),
+                json!({
+                    "blocks": namespace.blocks,
+                    "items": namespace.items,
+                    "entities": namespace.entities,
+                    "block_entities": namespace.block_entities,
+                    "locations": namespace.locations,
+                }),
+            )
+        })
+        .collect();
+    print_json(&report)?;
+
+    Ok(())
+}
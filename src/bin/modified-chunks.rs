@@ -0,0 +1,203 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use eyre::eyre;
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    blocks::ChunkBlocks,
+    redact::print_json,
+    region::for_each_chunk_in_dir,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, Source, Sources},
+};
+
+/// Estimates which chunks players modified, without needing the seed to compare against freshly
+/// generated terrain: a chunk counts as modified if players spent a while in it, it has block
+/// entities world generation doesn't place, or it contains blocks world generation doesn't place
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Minimum InhabitedTime in ticks for a chunk to count as modified
+    #[clap(long, default_value = "72000")]
+    min_inhabited_time: i64,
+
+    /// Blocks that indicate players built in a chunk
+    #[clap(
+        long,
+        use_value_delimiter = true,
+        default_values = &[
+            "minecraft:crafting_table",
+            "minecraft:torch",
+            "minecraft:wall_torch",
+            "minecraft:hopper",
+            "minecraft:observer",
+            "minecraft:comparator",
+            "minecraft:shulker_box",
+            "minecraft:ender_chest",
+        ]
+    )]
+    blocks: Vec<String>,
+
+    /// Block entities placed by world generation, which don't indicate players built in a chunk
+    #[clap(
+        long,
+        use_value_delimiter = true,
+        default_values = &[
+            "minecraft:chest",
+            "minecraft:trapped_chest",
+            "minecraft:barrel",
+            "minecraft:mob_spawner",
+            "minecraft:spawner",
+            "minecraft:trial_spawner",
+            "minecraft:vault",
+            "minecraft:bed",
+            "minecraft:bell",
+            "minecraft:lectern",
+            "minecraft:furnace",
+            "minecraft:smoker",
+            "minecraft:blast_furnace",
+            "minecraft:brewing_stand",
+            "minecraft:campfire",
+            "minecraft:dispenser",
+            "minecraft:beehive",
+            "minecraft:bee_nest",
+            "minecraft:sculk_sensor",
+            "minecraft:sculk_catalyst",
+            "minecraft:sculk_shrieker",
+            "minecraft:brushable_block",
+            "minecraft:decorated_pot",
+            "minecraft:end_gateway",
+            "minecraft:end_portal",
+        ]
+    )]
+    natural_block_entities: Vec<String>,
+
+    /// Only print the chunks as `x,z`, one per line
+    #[clap(long)]
+    chunk_list: bool,
+
+    /// List the chunks not estimated as modified instead, e.g. to be passed to delete-chunks
+    #[clap(long, requires = "chunk-list")]
+    unmodified: bool,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[(Source::Region, "that dimension")]);
+
+    let mut dimensions = serde_json::Map::new();
+    let (mut total_chunks, mut total_modified) = (0u64, 0u64);
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        if !sources.has(dim_name, Source::Region) {
+            continue;
+        }
+
+        let mut chunks = 0u64;
+        let mut modified = 0u64;
+        let mut by_reason = [0u64; 3];
+        for_each_chunk_in_dir(&dim_path.join("region"), |chunk_x, chunk_z, chunk| {
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let level = match version.level(&chunk) {
+                Some(level) => level,
+                None => return,
+            };
+            chunks += 1;
+
+            let reasons = [
+                level.get::<_, i64>("InhabitedTime").unwrap_or_default() >= args.min_inhabited_time,
+                level
+                    .get::<_, &NbtList>(version.block_entities_key())
+                    .is_ok_and(|block_entities| {
+                        block_entities
+                            .iter_map::<&NbtCompound>()
+                            .flatten()
+                            .any(|block_entity| !is_natural(&args, block_entity))
+                    }),
+                contains_blocks(&ChunkBlocks::from_chunk_as(&chunk, version), &args.blocks),
+            ];
+            for (count, reason) in by_reason.iter_mut().zip(reasons) {
+                *count += reason as u64;
+            }
+            let is_modified = reasons.contains(&true);
+            modified += is_modified as u64;
+
+            if args.chunk_list && is_modified != args.unmodified {
+                println!("{},{}", chunk_x, chunk_z);
+            }
+        })?;
+
+        total_chunks += chunks;
+        total_modified += modified;
+        dimensions.insert(
+            dim_name.clone(),
+            json!({
+                "chunks": chunks,
+                "modified": modified,
+                "modified_share": share(modified, chunks),
+                "inhabited": by_reason[0],
+                "with_placed_block_entities": by_reason[1],
+                "with_placed_blocks": by_reason[2],
+            }),
+        );
+    }
+
+    if !args.chunk_list {
+        let report = json!({
+            "chunks": total_chunks,
+            "modified": total_modified,
+            "modified_share": share(total_modified, total_chunks),
+            "dimensions": dimensions,
+        });
+        print_json(&report)?;
+    }
+
+    Ok(())
+}
+
+fn is_natural(args: &Args, block_entity: &NbtCompound) -> bool {
+    let id = block_entity.get::<_, &str>("id").unwrap_or_default();
+    args.natural_block_entities
+        .iter()
+        .any(|natural| natural == id || natural.strip_prefix("minecraft:") == Some(id))
+}
+
+/// Whether any of the blocks is in the chunk, checking the palettes before the block states
+fn contains_blocks(blocks: &ChunkBlocks, names: &[String]) -> bool {
+    blocks.sections().iter().any(|(_, block_states)| {
+        let indices: Vec<usize> = block_states
+            .palette()
+            .iter()
+            .enumerate()
+            .filter(|(_, state)| {
+                state
+                    .get::<_, &str>("Name")
+                    .is_ok_and(|name| names.iter().any(|known| known == name))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        !indices.is_empty()
+            && (0..4096).any(|index| indices.contains(&block_states.palette_index(index)))
+    })
+}
+
+fn share(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
@@ -0,0 +1,128 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use quartz_nbt::NbtCompound;
+use regex::Regex;
+use world_statistics::{
+    dat::read_dat,
+    nbt_path::NbtPath,
+    redact::Redaction,
+    region::{read_chunk, region_coords, RegionFile},
+    world::warn_if_open,
+};
+
+/// Prints all values matching an NBT path in the chunks and .dat files of a world
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Only search files whose path relative to the world matches this regex
+    #[clap(short, long)]
+    filter: Option<String>,
+
+    /// Path to the world directory
+    world: String,
+
+    /// e.g. block_entities[*].Items[*].tag.display.Name
+    path: NbtPath,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(args.world);
+    warn_if_open(&world_path);
+    let filter = args.filter.map(|filter| Regex::new(&filter)).transpose()?;
+    let redaction = Redaction::global()?;
+
+    let mut files = Vec::new();
+    collect_files(&world_path, &mut files)?;
+
+    for path in files {
+        let relative_path = path.strip_prefix(&world_path)?.to_string_lossy();
+        if let Some(filter) = &filter {
+            if !filter.is_match(&relative_path) {
+                continue;
+            }
+        }
+
+        if path.extension().is_some_and(|ext| ext == "dat") {
+            let mut nbt = match read_dat(&path) {
+                Ok(nbt) => nbt,
+                Err(e) => {
+                    eprintln!("failed to read {}: {}", relative_path, e);
+                    continue;
+                }
+            };
+            redaction.nbt(&mut nbt);
+            for tag in args.path.query(&nbt) {
+                println!("{} {}", relative_path, tag);
+            }
+        } else {
+            query_region_file(&path, &relative_path, &args.path, redaction)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn query_region_file(
+    path: &Path,
+    relative_path: &str,
+    nbt_path: &NbtPath,
+    redaction: &Redaction,
+) -> eyre::Result<()> {
+    let (region_x, region_z) = match region_coords(path) {
+        Some(coords) => coords,
+        None => return Ok(()),
+    };
+
+    let mut region_file = RegionFile::open(path)?;
+    region_file.for_each_chunk(|(index, _, buf)| {
+        let chunk_x = region_x * 32 + (index % 32) as i32;
+        let chunk_z = region_z * 32 + (index / 32) as i32;
+
+        let mut chunk: NbtCompound = match read_chunk(buf) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                eprintln!(
+                    "failed to read chunk {} {} in {}: {}",
+                    chunk_x, chunk_z, relative_path, e
+                );
+                return;
+            }
+        };
+
+        redaction.nbt(&mut chunk);
+        let (chunk_x, chunk_z) = redaction.chunk_coordinates(chunk_x, chunk_z);
+        for tag in nbt_path.query(&chunk) {
+            println!("{} {} {} {}", relative_path, chunk_x, chunk_z, tag);
+        }
+    })?;
+
+    Ok(())
+}
+
+/// Collects the .dat and region files of the world in a stable order
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> eyre::Result<()> {
+    let mut entries: Vec<_> = fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext == "dat" || ext == "mca")
+        {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use std::{collections::BTreeSet, path::PathBuf};
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use world_statistics::{
+    blocks::ChunkBlocks,
+    redact::Redaction,
+    region::for_each_chunk_in_dir,
+    world::{dimension_path, warn_if_open},
+};
+
+/// Estimates the length of the nether highways along the four axis directions by looking for
+/// walkable corridors (two blocks of air above a solid floor) close to the axes
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Maximum distance of a corridor from the axis
+    #[clap(long, default_value = "4")]
+    axis_width: i32,
+
+    /// Lowest floor level to consider
+    #[clap(long, default_value = "10")]
+    min_y: i32,
+
+    /// Highest floor level to consider, the nether roof is at 128
+    #[clap(long, default_value = "129")]
+    max_y: i32,
+
+    /// Path to the world directory
+    world: String,
+}
+
+const DIRECTIONS: [&str; 4] = ["+x", "-x", "+z", "-z"];
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let dim_path =
+        dimension_path(&world_path, "nether").ok_or_else(|| eyre!("nether dimension not found"))?;
+
+    // distances from the origin along each direction that have a corridor close to the axis
+    let mut covered: [BTreeSet<i32>; 4] = Default::default();
+    let width = args.axis_width;
+
+    for_each_chunk_in_dir(&dim_path.join("region"), |chunk_x, chunk_z, chunk| {
+        let near_x_axis = (chunk_z * 16 - width..chunk_z * 16 + 16 + width).contains(&0);
+        let near_z_axis = (chunk_x * 16 - width..chunk_x * 16 + 16 + width).contains(&0);
+        if !near_x_axis && !near_z_axis {
+            return;
+        }
+
+        let blocks = ChunkBlocks::from_chunk(&chunk);
+        for local_x in 0..16 {
+            for local_z in 0..16 {
+                let x = chunk_x * 16 + local_x as i32;
+                let z = chunk_z * 16 + local_z as i32;
+                let direction = if z.abs() <= width && x != 0 {
+                    if x > 0 {
+                        0
+                    } else {
+                        1
+                    }
+                } else if x.abs() <= width && z != 0 {
+                    if z > 0 {
+                        2
+                    } else {
+                        3
+                    }
+                } else {
+                    continue;
+                };
+
+                let air_at = |y| blocks.name(local_x, y, local_z).is_none_or(is_air);
+                let is_corridor = (args.min_y..=args.max_y).any(|y| {
+                    let floor = blocks.name(local_x, y - 1, local_z);
+                    floor.is_some_and(|floor| !is_air(floor) && !floor.ends_with("lava"))
+                        && air_at(y)
+                        && air_at(y + 1)
+                });
+                if is_corridor {
+                    covered[direction].insert(if direction < 2 { x.abs() } else { z.abs() });
+                }
+            }
+        }
+    })
+    .context("nether region folder not found")?;
+
+    let redaction = Redaction::global()?;
+    for (i, (direction, covered)) in DIRECTIONS.iter().zip(&covered).enumerate() {
+        let mut longest = (0, 0, 0);
+        let mut run_start = 0;
+        let mut last = None;
+        for &distance in covered {
+            if last != Some(distance - 1) {
+                run_start = distance;
+            }
+            if distance - run_start + 1 > longest.0 {
+                longest = (distance - run_start + 1, run_start, distance);
+            }
+            last = Some(distance);
+        }
+        // distances along the negative directions are rounded like negative coordinates
+        let redact = |distance: i32| match i % 2 {
+            0 => redaction.coordinates(distance, 0).0,
+            _ => -redaction.coordinates(-distance, 0).0,
+        };
+
+        println!(
+            "{}: {} blocks of corridor, longest stretch {} blocks ({} to {})",
+            direction,
+            covered.len(),
+            longest.0,
+            redact(longest.1),
+            redact(longest.2)
+        );
+    }
+
+    Ok(())
+}
+
+fn is_air(name: &str) -> bool {
+    matches!(
+        name,
+        "minecraft:air" | "minecraft:cave_air" | "minecraft:void_air"
+    )
+}
@@ -0,0 +1,212 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    blocks::ChunkBlocks,
+    dat::read_dat,
+    players::{find_usercache, playerdata_files},
+    redact::print_json,
+    region::for_each_chunk_in_dir,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, Source, Sources},
+};
+
+/// Lowest y above the nether's bedrock roof
+const ROOF_Y: i32 = 128;
+
+/// Blocks generated above the roof
+const NATURAL: &[&str] = &[
+    "minecraft:air",
+    "minecraft:cave_air",
+    "minecraft:void_air",
+    "minecraft:bedrock",
+];
+
+/// Finds players, entities, block entities and placed blocks above the nether roof and holes in
+/// the top and bottom bedrock layers
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Maximum number of holes and chunks with placed blocks to list
+    #[clap(long, default_value = "100")]
+    limit: usize,
+
+    /// Path to the world directory
+    world: String,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let dim_path = dimension_path(&world_path, "nether").ok_or_else(|| eyre!("no nether"))?;
+    let sources = Sources::detect(&world_path, &["nether".to_string()]);
+    sources.notify_missing(&[
+        (Source::Entities, "entities above the roof"),
+        (Source::Playerdata, "players above the roof"),
+    ]);
+
+    let mut block_entities = Vec::new();
+    let mut entities = Vec::new();
+    let mut placed_blocks = Vec::new();
+    let mut holes = Vec::new();
+    let mut total_placed = 0;
+    let mut total_holes = 0;
+
+    for_each_chunk_in_dir(&dim_path.join("region"), |chunk_x, chunk_z, chunk| {
+        let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+        let level = match version.level(&chunk) {
+            Some(level) => level,
+            None => return,
+        };
+        if let Ok(list) = level.get::<_, &NbtList>(version.block_entities_key()) {
+            for block_entity in list.iter_map::<&NbtCompound>().flatten() {
+                let pos =
+                    ["x", "y", "z"].map(|key| block_entity.get::<_, i32>(key).unwrap_or_default());
+                if pos[1] >= ROOF_Y {
+                    block_entities.push(json!({
+                        "id": block_entity.get::<_, &str>("id").unwrap_or("unknown"),
+                        "pos": pos,
+                    }));
+                }
+            }
+        }
+        // entities were stored in the terrain chunks before 1.17
+        if version.entities_in_chunk() {
+            if let Ok(list) = level.get::<_, &NbtList>("Entities") {
+                add_entities_above_roof(&mut entities, list);
+            }
+        }
+
+        let blocks = ChunkBlocks::from_chunk(&chunk);
+
+        // blocks above the roof, counted per chunk with the first one as an example
+        let mut placed = 0;
+        let mut example = None;
+        for (section_y, block_states) in blocks.sections() {
+            if section_y * 16 < ROOF_Y
+                || block_states
+                    .palette()
+                    .iter()
+                    .all(|state| NATURAL.contains(&state.get("Name").unwrap_or_default()))
+            {
+                continue;
+            }
+            for index in 0..4096 {
+                let name = block_states.name(index).unwrap_or_default();
+                if NATURAL.contains(&name) {
+                    continue;
+                }
+                placed += 1;
+                if example.is_none() {
+                    let pos = [
+                        chunk_x * 16 + (index % 16) as i32,
+                        section_y * 16 + (index / 256) as i32,
+                        chunk_z * 16 + (index / 16 % 16) as i32,
+                    ];
+                    example = Some((name, pos));
+                }
+            }
+        }
+        if let Some((name, pos)) = example {
+            total_placed += placed;
+            if placed_blocks.len() < args.limit {
+                placed_blocks.push(json!({
+                    "chunk": [chunk_x, chunk_z],
+                    "count": placed,
+                    "example": name,
+                    "pos": pos,
+                }));
+            }
+        }
+
+        // the top of the roof and the bottom of the floor are always bedrock
+        for (layer, y) in [("roof", ROOF_Y - 1), ("floor", 0)] {
+            for z in 0..16 {
+                for x in 0..16 {
+                    let name = match blocks.name(x, y, z) {
+                        Some(name) if name != "minecraft:bedrock" => name,
+                        _ => continue,
+                    };
+                    total_holes += 1;
+                    if holes.len() < args.limit {
+                        holes.push(json!({
+                            "layer": layer,
+                            "pos": [chunk_x * 16 + x as i32, y, chunk_z * 16 + z as i32],
+                            "block": name,
+                        }));
+                    }
+                }
+            }
+        }
+    })
+    .context("region folder not found")?;
+
+    let entities_path = dim_path.join("entities");
+    if sources.has("nether", Source::Entities) {
+        for_each_chunk_in_dir(&entities_path, |_, _, chunk| {
+            if let Ok(list) = chunk.get::<_, &NbtList>("Entities") {
+                add_entities_above_roof(&mut entities, list);
+            }
+        })?;
+    }
+
+    let names = find_usercache(&world_path);
+    let mut players = Vec::new();
+    if sources.playerdata {
+        for (uuid, path) in playerdata_files(&world_path)? {
+            let player = match read_dat(&path) {
+                Ok(player) => player,
+                Err(e) => {
+                    eprintln!("failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if player.get::<_, &str>("Dimension").ok() != Some("minecraft:the_nether") {
+                continue;
+            }
+            let pos = match player.get::<_, &NbtList>("Pos") {
+                Ok(pos) => [0, 1, 2].map(|i| pos.get::<f64>(i).unwrap_or_default()),
+                Err(_) => continue,
+            };
+            if pos[1] >= ROOF_Y as f64 {
+                players.push(json!({
+                    "name": names.get(&uuid).cloned().unwrap_or(uuid),
+                    "pos": pos.map(|v| v.floor() as i32),
+                }));
+            }
+        }
+    }
+
+    let report = json!({
+        "players": players,
+        "entities": entities,
+        "block_entities": block_entities,
+        "placed_blocks": total_placed,
+        "placed_block_chunks": placed_blocks,
+        "bedrock_holes": total_holes,
+        "holes": holes,
+    });
+    print_json(&report)?;
+
+    Ok(())
+}
+
+fn add_entities_above_roof(entities: &mut Vec<serde_json::Value>, list: &NbtList) {
+    for entity in list.iter_map::<&NbtCompound>().flatten() {
+        let pos = match entity.get::<_, &NbtList>("Pos") {
+            Ok(pos) => [0, 1, 2].map(|i| pos.get::<f64>(i).unwrap_or_default()),
+            Err(_) => continue,
+        };
+        if pos[1] >= ROOF_Y as f64 {
+            entities.push(json!({
+                "id": entity.get::<_, &str>("id").unwrap_or("unknown"),
+                "pos": pos.map(|v| v.floor() as i32),
+            }));
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use eyre::eyre;
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    anonymize::AnonymizeArgs,
+    players::{find_usercache, read_usercache},
+    redact::{print_json, Redaction},
+    region::for_each_chunk_in_dir,
+    text::plain_text,
+    uuid::format_uuid,
+    world::{dimension_path, warn_if_open},
+};
+
+/// Lists all tamed animals grouped by their owner's name as JSON
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// usercache.json to resolve owner names, defaults to the one next to the world
+    #[clap(long)]
+    usercache: Option<String>,
+
+    /// Only pets of this owner, by name or UUID
+    #[clap(long)]
+    owner: Option<String>,
+
+    #[clap(flatten)]
+    anonymize: AnonymizeArgs,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let names = match &args.usercache {
+        Some(path) => read_usercache(Path::new(path))?,
+        None => find_usercache(&world_path),
+    };
+
+    let anonymizer = args.anonymize.anonymizer()?;
+    let redaction = Redaction::global()?;
+
+    let mut owners = BTreeMap::<String, Vec<_>>::new();
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        let entities_path = dim_path.join("entities");
+        if !entities_path.exists() {
+            eprintln!("skipping {}, no entities folder", dim_name);
+            continue;
+        }
+
+        for_each_chunk_in_dir(&entities_path, |_, _, chunk| {
+            let entities = match chunk.get::<_, &NbtList>("Entities") {
+                Ok(entities) => entities,
+                Err(_) => return,
+            };
+            for entity in entities.iter_map::<&NbtCompound>().flatten() {
+                let uuid = match entity.get::<_, &[i32]>("Owner").ok().and_then(format_uuid) {
+                    Some(uuid) => uuid,
+                    None => continue,
+                };
+                if redaction.hides_uuid(&uuid, &names) {
+                    continue;
+                }
+                let owner = names.get(&uuid).cloned().unwrap_or_else(|| uuid.clone());
+                if let Some(filter) = &args.owner {
+                    if *filter != owner && *filter != uuid {
+                        continue;
+                    }
+                }
+
+                let mut pos: Vec<i32> = entity
+                    .get::<_, &NbtList>("Pos")
+                    .map(|pos| {
+                        pos.iter_map::<f64>()
+                            .flatten()
+                            .map(|v| v.floor() as i32)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let name = entity.get::<_, &str>("CustomName").ok().map(plain_text);
+
+                if let [x, _, z] = pos.as_mut_slice() {
+                    *x = anonymizer.coordinate(*x);
+                    *z = anonymizer.coordinate(*z);
+                }
+                let (owner, uuid) = match anonymizer.player(&uuid) {
+                    Some(pseudonym) => (pseudonym.clone(), pseudonym),
+                    None => (owner, uuid),
+                };
+                owners.entry(owner).or_default().push(json!({
+                    "id": entity.get::<_, &str>("id").unwrap_or("unknown"),
+                    "name": name,
+                    "dimension": dim_name,
+                    "pos": pos,
+                    "owner_uuid": uuid,
+                }));
+            }
+        })?;
+    }
+
+    print_json(&owners)?;
+
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use clap::{ArgEnum, Parser};
+use serde_json::Value;
+use world_statistics::{
+    anonymize::AnonymizeArgs,
+    players::{find_usercache, play_time, read_usercache},
+    redact::Redaction,
+    world::warn_if_open,
+};
+
+/// Estimates daily playtime per player from the stats files of several copies of a world,
+/// using the modification time of each stats file as the time of the measurement
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    #[clap(short, long, arg_enum, default_value = "csv")]
+    format: Format,
+
+    /// usercache.json to resolve player names, defaults to the one next to the worlds
+    #[clap(long)]
+    usercache: Option<String>,
+
+    #[clap(flatten)]
+    anonymize: AnonymizeArgs,
+
+    /// Paths to the world directories, e.g. dated backups
+    #[clap(required = true)]
+    worlds: Vec<String>,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug)]
+enum Format {
+    Csv,
+    Prometheus,
+}
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let mut names = HashMap::new();
+    match &args.usercache {
+        Some(path) => names = read_usercache(Path::new(path))?,
+        None => {
+            for world in &args.worlds {
+                names.extend(find_usercache(Path::new(world)));
+            }
+        }
+    }
+
+    // observations of (unix time, play time in ticks) per player
+    let mut observations = HashMap::<String, Vec<(u64, u64)>>::new();
+    for world in &args.worlds {
+        warn_if_open(Path::new(world));
+        let stats_path = PathBuf::from(world).join("stats");
+        let entries = match fs::read_dir(&stats_path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                eprintln!("skipping {}: {}", stats_path.display(), e);
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let uuid = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(uuid) if path.extension().is_some_and(|ext| ext == "json") => uuid.to_string(),
+                _ => continue,
+            };
+            let time = entry
+                .metadata()?
+                .modified()?
+                .duration_since(UNIX_EPOCH)?
+                .as_secs();
+            match read_play_time(&path) {
+                Some(play_time) => observations
+                    .entry(uuid)
+                    .or_default()
+                    .push((time, play_time)),
+                None => eprintln!("no play time in {}", path.display()),
+            }
+        }
+    }
+
+    let redaction = Redaction::global()?;
+    observations.retain(|uuid, _| !redaction.hides_uuid(uuid, &names));
+
+    let anonymizer = args.anonymize.anonymizer()?;
+    if args.anonymize.anonymize {
+        observations = observations
+            .into_iter()
+            .map(|(uuid, observations)| (anonymizer.player(&uuid).unwrap_or(uuid), observations))
+            .collect();
+        names.clear();
+    }
+
+    let mut players: Vec<_> = observations.into_iter().collect();
+    players.sort();
+
+    match args.format {
+        Format::Csv => {
+            println!("date,uuid,name,hours");
+            for (uuid, mut observations) in players {
+                observations.sort_unstable();
+                observations.dedup_by_key(|(time, _)| *time);
+                let name = names.get(&uuid).map(String::as_str).unwrap_or_default();
+                for (day, ticks) in daily_play_time(&observations) {
+                    println!(
+                        "{},{},{},{:.2}",
+                        format_date(day),
+                        uuid,
+                        name,
+                        ticks / 72000.0
+                    );
+                }
+            }
+        }
+        Format::Prometheus => {
+            println!("# TYPE minecraft_player_play_time_seconds gauge");
+            for (uuid, observations) in players {
+                let (_, ticks) = observations.iter().max().unwrap();
+                let name = names.get(&uuid).map(String::as_str).unwrap_or_default();
+                println!(
+                    "minecraft_player_play_time_seconds{{uuid=\"{}\",name=\"{}\"}} {}",
+                    uuid,
+                    name,
+                    ticks / 20
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn read_play_time(path: &Path) -> Option<u64> {
+    let stats: Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    play_time(&stats)
+}
+
+/// Spreads the play time gained between two observations evenly over the days in between
+fn daily_play_time(observations: &[(u64, u64)]) -> BTreeMap<u64, f64> {
+    let mut days = BTreeMap::new();
+    for pair in observations.windows(2) {
+        let ((start, start_ticks), (end, end_ticks)) = (pair[0], pair[1]);
+        let ticks = end_ticks.saturating_sub(start_ticks) as f64;
+
+        let mut time = start;
+        while time < end {
+            let day = time / SECONDS_PER_DAY;
+            let day_end = u64::min((day + 1) * SECONDS_PER_DAY, end);
+            *days.entry(day).or_default() += ticks * (day_end - time) as f64 / (end - start) as f64;
+            time = day_end;
+        }
+    }
+    days
+}
+
+/// Formats days since the unix epoch as YYYY-MM-DD
+fn format_date(days: u64) -> String {
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
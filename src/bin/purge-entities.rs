@@ -0,0 +1,288 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::{self, OpenOptions},
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+use world_statistics::{
+    backup::Backup,
+    entity_index::owner_uuid,
+    interrupt::{handle_interrupts, interrupted, EXIT_INTERRUPTED},
+    journal::Journal,
+    region::{encode_chunk, read_chunk, region_files, RegionFile},
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, ensure_closed, warn_if_open, ChunkSelection},
+};
+
+/// Removes entities matching all given filters from the entities region files, or from the
+/// terrain region files before 1.17. Only prints what would be removed unless --apply is given.
+/// Stop the server before applying.
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Actually rewrite the region files
+    #[clap(long)]
+    apply: bool,
+
+    /// Only print what would be removed, the default without --apply
+    #[clap(long, conflicts_with = "apply")]
+    dry_run: bool,
+
+    /// item, zombie, etc.
+    #[clap(short, long, default_value = "all")]
+    types: String,
+
+    /// Minimum value of the entity's Age tag in ticks
+    #[clap(long)]
+    min_age: Option<i32>,
+
+    /// Only entities in these chunks, as `x,z` or `x1,z1..x2,z2`
+    #[clap(long, allow_hyphen_values = true)]
+    chunks: Vec<ChunkSelection>,
+
+    /// Only tamed entities whose owner has no playerdata
+    #[clap(long)]
+    missing_owner: bool,
+
+    /// Number of matching entities to keep per chunk
+    #[clap(long, default_value = "0")]
+    keep_per_chunk: usize,
+
+    /// JSON lines file to record the previous content of changed chunks and files to, to be
+    /// reverted with undo
+    #[clap(long)]
+    journal: Option<PathBuf>,
+
+    /// Directory to copy the changed files to first, restored from if writing fails halfway
+    #[clap(long)]
+    backup: Option<PathBuf>,
+
+    /// Write even though the world is open in a running game or server
+    #[clap(long)]
+    force: bool,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether or end
+    dimension: String,
+}
+
+struct Filter {
+    types: Option<HashSet<String>>,
+    min_age: Option<i32>,
+    chunks: Vec<ChunkSelection>,
+    known_players: Option<HashSet<String>>,
+}
+
+impl Filter {
+    fn matches(&self, entity: &NbtCompound) -> bool {
+        if let Some(types) = &self.types {
+            match entity.get::<_, &str>("id") {
+                Ok(id) if types.contains(id) => {}
+                _ => return false,
+            }
+        }
+
+        if let Some(min_age) = self.min_age {
+            let age = match entity.inner().get("Age") {
+                Some(NbtTag::Short(age)) => *age as i32,
+                Some(NbtTag::Int(age)) => *age,
+                _ => return false,
+            };
+            if age < min_age {
+                return false;
+            }
+        }
+
+        if let Some(known_players) = &self.known_players {
+            match owner_uuid(entity) {
+                Some(owner) if !known_players.contains(&owner) => {}
+                _ => return false,
+            }
+        }
+
+        true
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    if args.apply {
+        ensure_closed(&world_path, args.force)?;
+    } else {
+        warn_if_open(&world_path);
+    }
+    let mut journal = args
+        .journal
+        .as_deref()
+        .map(|path| Journal::open(path, &world_path, "purge-entities"))
+        .transpose()?;
+    let mut backup = args
+        .backup
+        .as_deref()
+        .map(|dir| Backup::new(dir, &world_path));
+    handle_interrupts()?;
+    let dim_path = dimension_path(&world_path, &args.dimension)
+        .ok_or_else(|| eyre!("unknown dimension {}", args.dimension))?;
+
+    let filter = Filter {
+        types: (args.types != "all").then(|| {
+            args.types
+                .split(',')
+                .map(|str| String::from("minecraft:") + str)
+                .collect()
+        }),
+        min_age: args.min_age,
+        chunks: args.chunks,
+        known_players: if args.missing_owner {
+            Some(known_players(&world_path)?)
+        } else {
+            None
+        },
+    };
+
+    let mut purged = BTreeMap::<String, usize>::new();
+
+    // entities were stored in the terrain chunks before 1.17
+    let entities_path = dim_path.join("entities");
+    let in_region = !entities_path.exists();
+    let folder = if in_region {
+        dim_path.join("region")
+    } else {
+        entities_path
+    };
+    for (region_x, region_z, path) in
+        region_files(&folder).context("entities or region folder not found")?
+    {
+        if interrupted() {
+            break;
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(args.apply)
+            .open(&path)?;
+        let mut region_file = RegionFile::new(file)?.with_path(&path);
+
+        let mut changed_chunks = Vec::new();
+        region_file.for_each_chunk(|(index, _, buf)| {
+            let chunk_x = region_x * 32 + (index % 32) as i32;
+            let chunk_z = region_z * 32 + (index / 32) as i32;
+
+            if !filter.chunks.is_empty()
+                && !filter
+                    .chunks
+                    .iter()
+                    .any(|selection| selection.contains(chunk_x, chunk_z))
+            {
+                return;
+            }
+
+            let mut chunk = match read_chunk(buf) {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    eprintln!("failed to read chunk {} {}: {}", chunk_x, chunk_z, e);
+                    return;
+                }
+            };
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let entities = if in_region {
+                if !version.entities_in_chunk() {
+                    return;
+                }
+                version
+                    .level_mut(&mut chunk)
+                    .and_then(|level| level.get_mut::<_, &mut NbtList>("Entities").ok())
+            } else {
+                chunk.get_mut::<_, &mut NbtList>("Entities").ok()
+            };
+            let entities = match entities {
+                Some(entities) => entities,
+                None => return,
+            };
+
+            let mut kept = Vec::new();
+            let mut matching = 0;
+            let mut removed = false;
+            for entity in std::mem::take(entities.inner_mut()) {
+                let matches = match &entity {
+                    NbtTag::Compound(entity) => filter.matches(entity),
+                    _ => false,
+                };
+                if matches {
+                    matching += 1;
+                }
+                if !matches || matching <= args.keep_per_chunk {
+                    kept.push(entity);
+                    continue;
+                }
+
+                removed = true;
+                if let NbtTag::Compound(entity) = &entity {
+                    let id = entity.get::<_, &str>("id").unwrap_or("unknown");
+                    let pos = entity
+                        .get::<_, &NbtList>("Pos")
+                        .map(|pos| pos.to_string())
+                        .unwrap_or_default();
+                    println!("{} {}", id, pos);
+                    *purged.entry(id.to_string()).or_default() += 1;
+                }
+            }
+
+            *entities.inner_mut() = kept;
+            if removed && args.apply {
+                changed_chunks.push((index, chunk));
+            }
+        })?;
+
+        for (index, chunk) in changed_chunks {
+            if let Some(backup) = &mut backup {
+                backup.save(&path)?;
+            }
+            if let Some(journal) = &mut journal {
+                journal.record_chunk(&path, &mut region_file, index)?;
+            }
+            region_file.write_chunk(index, &encode_chunk(&chunk)?)?;
+        }
+    }
+
+    if let Some(backup) = backup {
+        backup.finish();
+    }
+    for (id, count) in &purged {
+        eprintln!("{} {}", id, count);
+    }
+    let total: usize = purged.values().sum();
+    if args.apply {
+        eprintln!("removed {} entities", total);
+    } else {
+        eprintln!(
+            "would remove {} entities, run with --apply to remove them",
+            total
+        );
+    }
+
+    if interrupted() {
+        eprintln!("interrupted, the remaining files were left unchanged");
+        std::process::exit(EXIT_INTERRUPTED);
+    }
+
+    Ok(())
+}
+
+/// Returns the UUIDs of all players with a playerdata file
+fn known_players(world_path: &Path) -> eyre::Result<HashSet<String>> {
+    Ok(fs::read_dir(world_path.join("playerdata"))
+        .context("playerdata folder not found")?
+        .flatten()
+        .filter_map(|entry| {
+            let filename = entry.file_name();
+            Some(filename.to_str()?.strip_suffix(".dat")?.to_string())
+        })
+        .collect())
+}
@@ -0,0 +1,199 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+use clap::Parser;
+use eyre::eyre;
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    dat::read_dat,
+    items::{contents, for_each_item, id_and_count},
+    players::{find_usercache, playerdata_files},
+    redact::print_json,
+    region::for_each_chunk_in_dir,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, Source, Sources},
+};
+
+const RAIDERS: &[&str] = &[
+    "minecraft:pillager",
+    "minecraft:vindicator",
+    "minecraft:evoker",
+    "minecraft:ravager",
+    "minecraft:witch",
+    "minecraft:illusioner",
+];
+
+/// Counts totems of undying in storage and player inventories and where raiders are
+/// concentrated, to estimate the output of raid farms
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Number of chunks with the most raiders to list
+    #[clap(long, default_value = "10")]
+    top: usize,
+
+    /// Item to count instead of totems
+    #[clap(long, default_value = "totem_of_undying")]
+    item: String,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[
+        (Source::Region, "that dimension"),
+        (Source::Entities, "entities there"),
+        (Source::Playerdata, "player statistics"),
+    ]);
+    let item_id = String::from("minecraft:") + &args.item;
+
+    let mut storage = 0;
+    let mut entities = 0;
+    let mut raiders = BTreeMap::<String, u64>::new();
+    let mut raider_chunks = HashMap::<(String, i32, i32), u64>::new();
+
+    let mut add_entities = |dim_name: &str, chunk_x: i32, chunk_z: i32, list: &NbtList| {
+        for entity in list.iter_map::<&NbtCompound>().flatten() {
+            let id: &str = entity.get("id").unwrap_or_default();
+            if RAIDERS.contains(&id) {
+                *raiders.entry(id.to_string()).or_default() += 1;
+                *raider_chunks
+                    .entry((dim_name.to_string(), chunk_x, chunk_z))
+                    .or_default() += 1;
+            }
+
+            if let Ok(item) = entity.get::<_, &NbtCompound>("Item") {
+                entities += count_stack(item, &item_id);
+            }
+            for key in ["Items", "ArmorItems", "HandItems"] {
+                if let Ok(items) = entity.get::<_, &NbtList>(key) {
+                    entities += count_in(items, &item_id);
+                }
+            }
+        }
+    };
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        if !sources.has(dim_name, Source::Region) {
+            continue;
+        }
+
+        for_each_chunk_in_dir(&dim_path.join("region"), |chunk_x, chunk_z, chunk| {
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let level = match version.level(&chunk) {
+                Some(level) => level,
+                None => return,
+            };
+            if let Ok(block_entities) = level.get::<_, &NbtList>(version.block_entities_key()) {
+                for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+                    if let Ok(items) = block_entity.get::<_, &NbtList>("Items") {
+                        storage += count_in(items, &item_id);
+                    }
+                }
+            }
+            // entities were stored in the terrain chunks before 1.17
+            if version.entities_in_chunk() {
+                if let Ok(list) = level.get::<_, &NbtList>("Entities") {
+                    add_entities(dim_name, chunk_x, chunk_z, list);
+                }
+            }
+        })?;
+
+        let entities_path = dim_path.join("entities");
+        if !sources.has(dim_name, Source::Entities) {
+            continue;
+        }
+        for_each_chunk_in_dir(&entities_path, |chunk_x, chunk_z, chunk| {
+            if let Ok(list) = chunk.get::<_, &NbtList>("Entities") {
+                add_entities(dim_name, chunk_x, chunk_z, list);
+            }
+        })?;
+    }
+
+    let names = find_usercache(&world_path);
+    let mut players = BTreeMap::<String, u64>::new();
+    if sources.playerdata {
+        for (uuid, path) in playerdata_files(&world_path)? {
+            let player = match read_dat(&path) {
+                Ok(player) => player,
+                Err(e) => {
+                    eprintln!("failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let count: u64 = ["Inventory", "EnderItems"]
+                .into_iter()
+                .filter_map(|key| player.get::<_, &NbtList>(key).ok())
+                .map(|items| count_in(items, &item_id))
+                .sum();
+            if count > 0 {
+                players.insert(names.get(&uuid).cloned().unwrap_or(uuid), count);
+            }
+        }
+    }
+
+    let mut top_chunks: Vec<_> = raider_chunks.into_iter().collect();
+    top_chunks.sort_by(|(a_key, a), (b_key, b)| b.cmp(a).then_with(|| a_key.cmp(b_key)));
+    top_chunks.truncate(args.top);
+
+    let player_total: u64 = players.values().sum();
+    let report = json!({
+        "item": item_id,
+        "total": storage + entities + player_total,
+        "storage": storage,
+        "entities": entities,
+        "players": player_total,
+        "by_player": players,
+        "raiders": raiders,
+        "raider_chunks": top_chunks
+            .iter()
+            .map(|((dimension, x, z), count)| json!({
+                "dimension": dimension,
+                "chunk": [x, z],
+                "count": count,
+            }))
+            .collect::<Vec<_>>(),
+    });
+    print_json(&report)?;
+
+    Ok(())
+}
+
+/// Counts the item in a stack including the contents of shulker boxes
+fn count_stack(item: &NbtCompound, item_id: &str) -> u64 {
+    let mut total = 0;
+    let (id, count) = id_and_count(item);
+    if id == item_id {
+        total += count;
+    }
+    if let Some(contents) = contents(item) {
+        total += count_in(contents, item_id);
+    }
+    total
+}
+
+fn count_in(items: &NbtList, item_id: &str) -> u64 {
+    let mut total = 0;
+    for_each_item(items, &mut |item| {
+        let (id, count) = id_and_count(item);
+        if id == item_id {
+            total += count;
+        }
+    });
+    total
+}
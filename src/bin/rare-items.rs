@@ -0,0 +1,203 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use clap::Parser;
+use eyre::eyre;
+use indexmap::IndexMap;
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    anonymize::AnonymizeArgs,
+    dat::read_dat,
+    items::{contents, for_each_item, id_and_count},
+    players::{find_usercache, playerdata_files},
+    redact::{print_json, Redaction},
+    region::for_each_chunk_in_dir,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, Source, Sources},
+};
+
+/// Reports how many elytra, shulker shells and boxes, netherite items, heavy cores, nether stars
+/// and beacons exist, per player and per region file area
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    #[clap(flatten)]
+    anonymize: AnonymizeArgs,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+/// Returns the rare resource category of an item id
+fn category(id: &str) -> Option<&'static str> {
+    let name = id.strip_prefix("minecraft:")?;
+    Some(match name {
+        "elytra" => "elytra",
+        "shulker_shell" => "shulker_shell",
+        "heavy_core" => "heavy_core",
+        "nether_star" => "nether_star",
+        "beacon" => "beacon",
+        _ if name.ends_with("shulker_box") => "shulker_box",
+        _ if name.starts_with("netherite_") || name == "ancient_debris" => "netherite",
+        _ => return None,
+    })
+}
+
+#[derive(Default)]
+struct Distribution {
+    total: u64,
+    players: BTreeMap<String, u64>,
+    areas: BTreeMap<String, u64>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[
+        (Source::Region, "containers there"),
+        (Source::Entities, "entities there"),
+        (Source::Playerdata, "player inventories"),
+    ]);
+    let mut categories = IndexMap::<&str, Distribution>::new();
+    for name in [
+        "elytra",
+        "shulker_shell",
+        "shulker_box",
+        "netherite",
+        "heavy_core",
+        "nether_star",
+        "beacon",
+    ] {
+        categories.insert(name, Distribution::default());
+    }
+
+    let mut count_area = |area: &str, item: &NbtCompound| {
+        let (id, count) = id_and_count(item);
+        if let Some(category) = category(id) {
+            let distribution = categories.get_mut(category).unwrap();
+            distribution.total += count;
+            *distribution.areas.entry(area.to_string()).or_default() += count;
+        }
+    };
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        if !sources.has(dim_name, Source::Region) {
+            continue;
+        }
+        let area = |chunk_x: i32, chunk_z: i32| {
+            format!("{} r.{}.{}", dim_name, chunk_x >> 5, chunk_z >> 5)
+        };
+
+        for_each_chunk_in_dir(&dim_path.join("region"), |chunk_x, chunk_z, chunk| {
+            let area = area(chunk_x, chunk_z);
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let level = match version.level(&chunk) {
+                Some(level) => level,
+                None => return,
+            };
+            if let Ok(block_entities) = level.get::<_, &NbtList>(version.block_entities_key()) {
+                for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+                    if let Ok(items) = block_entity.get::<_, &NbtList>("Items") {
+                        for_each_item(items, &mut |item| count_area(&area, item));
+                    }
+                }
+            }
+            // entities were stored in the terrain chunks before 1.17
+            if version.entities_in_chunk() {
+                if let Ok(entities) = level.get::<_, &NbtList>("Entities") {
+                    count_entities(entities, &mut |item| count_area(&area, item));
+                }
+            }
+        })?;
+
+        let entities_path = dim_path.join("entities");
+        if !sources.has(dim_name, Source::Entities) {
+            continue;
+        }
+        for_each_chunk_in_dir(&entities_path, |chunk_x, chunk_z, chunk| {
+            let area = area(chunk_x, chunk_z);
+            if let Ok(entities) = chunk.get::<_, &NbtList>("Entities") {
+                count_entities(entities, &mut |item| count_area(&area, item));
+            }
+        })?;
+    }
+
+    let names = find_usercache(&world_path);
+    let anonymizer = args.anonymize.anonymizer()?;
+    let redaction = Redaction::global()?;
+    if sources.playerdata {
+        for (uuid, path) in playerdata_files(&world_path)? {
+            let player = match read_dat(&path) {
+                Ok(player) => player,
+                Err(e) => {
+                    eprintln!("failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            // hidden players still count towards the totals
+            let name = match anonymizer.player(&uuid) {
+                _ if redaction.hides_uuid(&uuid, &names) => None,
+                Some(pseudonym) => Some(pseudonym),
+                None => Some(names.get(&uuid).unwrap_or(&uuid).clone()),
+            };
+            for key in ["Inventory", "EnderItems"] {
+                if let Ok(items) = player.get::<_, &NbtList>(key) {
+                    for_each_item(items, &mut |item| {
+                        let (id, count) = id_and_count(item);
+                        if let Some(category) = category(id) {
+                            let distribution = categories.get_mut(category).unwrap();
+                            distribution.total += count;
+                            if let Some(name) = &name {
+                                *distribution.players.entry(name.clone()).or_default() += count;
+                            }
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    let report: IndexMap<_, _> = categories
+        .iter()
+        .map(|(name, distribution)| {
+            (
+                name,
+                json!({
+                    "total": distribution.total,
+                    "players": distribution.players,
+                    "areas": distribution.areas,
+                }),
+            )
+        })
+        .collect();
+    print_json(&report)?;
+
+    Ok(())
+}
+
+/// Calls `f` with the dropped items, inventories and equipment of the entities, including the
+/// contents of dropped shulker boxes
+fn count_entities<F: FnMut(&NbtCompound)>(entities: &NbtList, f: &mut F) {
+    for entity in entities.iter_map::<&NbtCompound>().flatten() {
+        if let Ok(item) = entity.get::<_, &NbtCompound>("Item") {
+            f(item);
+            if let Some(contents) = contents(item) {
+                for_each_item(contents, f);
+            }
+        }
+        for key in ["Items", "ArmorItems", "HandItems"] {
+            if let Ok(items) = entity.get::<_, &NbtList>(key) {
+                for_each_item(items, f);
+            }
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, OpenOptions},
+    path::PathBuf,
+};
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use quartz_nbt::{NbtCompound, NbtTag};
+use world_statistics::{
+    backup::Backup,
+    dat::{read_dat, write_dat},
+    interrupt::{handle_interrupts, interrupted, EXIT_INTERRUPTED},
+    journal::Journal,
+    players::playerdata_files,
+    region::{encode_chunk, read_chunk, region_files, RegionFile},
+    world::{dimension_path, ensure_closed, warn_if_open, Source, Sources},
+};
+
+/// Renames block, item, entity and block entity ids in the region files and playerdata using a
+/// JSON object of old to new ids. Only prints how often each id would be renamed unless --apply
+/// is given. Stop the server before applying.
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Actually rewrite the region and playerdata files
+    #[clap(long)]
+    apply: bool,
+
+    /// Only print what would be renamed, the default without --apply
+    #[clap(long, conflicts_with = "apply")]
+    dry_run: bool,
+
+    /// JSON file mapping old ids to new ids
+    mapping: PathBuf,
+
+    /// JSON lines file to record the previous content of changed chunks and files to, to be
+    /// reverted with undo
+    #[clap(long)]
+    journal: Option<PathBuf>,
+
+    /// Directory to copy the changed files to first, restored from if writing fails halfway
+    #[clap(long)]
+    backup: Option<PathBuf>,
+
+    /// Write even though the world is open in a running game or server
+    #[clap(long)]
+    force: bool,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+/// Replaces the `id` and `Name` (block states) strings found in the mapping anywhere in the
+/// compound, and returns the number of replacements
+fn remap(
+    compound: &mut NbtCompound,
+    mapping: &HashMap<String, String>,
+    counts: &mut BTreeMap<String, u64>,
+) -> u64 {
+    let mut replaced = 0;
+    for (key, tag) in compound.inner_mut() {
+        match tag {
+            NbtTag::String(value) if key == "id" || key == "Name" => {
+                if let Some(new_id) = mapping.get(value.as_str()) {
+                    *counts.entry(value.clone()).or_default() += 1;
+                    *value = new_id.clone();
+                    replaced += 1;
+                }
+            }
+            NbtTag::Compound(compound) => replaced += remap(compound, mapping, counts),
+            NbtTag::List(list) => {
+                for tag in list.inner_mut() {
+                    if let NbtTag::Compound(compound) = tag {
+                        replaced += remap(compound, mapping, counts);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    replaced
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    if args.apply {
+        ensure_closed(&world_path, args.force)?;
+    } else {
+        warn_if_open(&world_path);
+    }
+    let mut journal = args
+        .journal
+        .as_deref()
+        .map(|path| Journal::open(path, &world_path, "remap-ids"))
+        .transpose()?;
+    let mut backup = args
+        .backup
+        .as_deref()
+        .map(|dir| Backup::new(dir, &world_path));
+    handle_interrupts()?;
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[
+        (Source::Region, "blocks and block entities there"),
+        (Source::Entities, "entities there"),
+        (Source::Playerdata, "player inventories"),
+    ]);
+    let mapping: HashMap<String, String> =
+        serde_json::from_str(&fs::read_to_string(&args.mapping)?)
+            .context("failed to read mapping")?;
+
+    let mut counts = BTreeMap::<String, u64>::new();
+    let mut changed_files = 0;
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+
+        for source in [Source::Region, Source::Entities] {
+            if !sources.has(dim_name, source) {
+                continue;
+            }
+
+            for (region_x, region_z, path) in region_files(&dim_path.join(source.folder()))? {
+                if interrupted() {
+                    break;
+                }
+                let file = OpenOptions::new()
+                    .read(true)
+                    .write(args.apply)
+                    .open(&path)?;
+                let mut region_file = RegionFile::new(file)?.with_path(&path);
+
+                let mut changed_chunks = Vec::new();
+                region_file.for_each_chunk(|(index, _, buf)| {
+                    let mut chunk = match read_chunk(buf) {
+                        Ok(chunk) => chunk,
+                        Err(e) => {
+                            let chunk_x = region_x * 32 + (index % 32) as i32;
+                            let chunk_z = region_z * 32 + (index / 32) as i32;
+                            eprintln!("failed to read chunk {} {}: {}", chunk_x, chunk_z, e);
+                            return;
+                        }
+                    };
+                    if remap(&mut chunk, &mapping, &mut counts) > 0 && args.apply {
+                        changed_chunks.push((index, chunk));
+                    }
+                })?;
+
+                if !changed_chunks.is_empty() {
+                    changed_files += 1;
+                }
+                for (index, chunk) in changed_chunks {
+                    if let Some(backup) = &mut backup {
+                        backup.save(&path)?;
+                    }
+                    if let Some(journal) = &mut journal {
+                        journal.record_chunk(&path, &mut region_file, index)?;
+                    }
+                    region_file.write_chunk(index, &encode_chunk(&chunk)?)?;
+                }
+            }
+        }
+    }
+
+    if sources.playerdata {
+        for (_, path) in playerdata_files(&world_path)? {
+            if interrupted() {
+                break;
+            }
+            let mut player = match read_dat(&path) {
+                Ok(player) => player,
+                Err(e) => {
+                    eprintln!("failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if remap(&mut player, &mapping, &mut counts) > 0 && args.apply {
+                if let Some(backup) = &mut backup {
+                    backup.save(&path)?;
+                }
+                if let Some(journal) = &mut journal {
+                    journal.record_file(&path)?;
+                }
+                write_dat(&path, &player)?;
+                changed_files += 1;
+            }
+        }
+    }
+
+    if let Some(backup) = backup {
+        backup.finish();
+    }
+    for (id, count) in &counts {
+        println!("{} -> {} {}", id, mapping[id], count);
+    }
+    let total: u64 = counts.values().sum();
+    if args.apply {
+        eprintln!("renamed {} ids in {} files", total, changed_files);
+    } else {
+        eprintln!(
+            "would rename {} ids, run with --apply to rename them",
+            total
+        );
+    }
+
+    if interrupted() {
+        eprintln!("interrupted, the remaining files were left unchanged");
+        std::process::exit(EXIT_INTERRUPTED);
+    }
+
+    Ok(())
+}
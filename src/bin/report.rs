@@ -0,0 +1,392 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use eyre::eyre;
+use indexmap::IndexMap;
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::{json, Value};
+use world_statistics::{
+    aggregate::Table,
+    biomes::SectionBiomes,
+    blocks::ChunkBlocks,
+    items::{for_each_item, id_and_count},
+    redact::print_json,
+    region::for_each_chunk_in_dir,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, Source, Sources},
+};
+
+/// Produces several reports about a world in one go. A planning phase first works out what has
+/// to be read and decoded for the requested reports, so that each folder of a dimension is
+/// scanned only once no matter how many reports use it.
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Reports to produce: chunks, items, block-entities, blocks, biomes and entities. Blocks
+    /// decodes the block states of every section and is much slower than the others.
+    #[clap(
+        long,
+        arg_enum,
+        use_value_delimiter = true,
+        default_values = &["chunks", "items", "entities"]
+    )]
+    reports: Vec<Report>,
+
+    /// Only print the plan with the amount of data each pass reads, without scanning
+    #[clap(long)]
+    plan: bool,
+
+    /// Only count chunks whose blocks are varied enough to be built by players, see
+    /// `entropy-map`. Decodes the block states of every section like the blocks report.
+    #[clap(long)]
+    only_built_chunks: bool,
+
+    /// Entropy in bits from which a chunk counts as built
+    #[clap(long, default_value = "3.0")]
+    min_entropy: f64,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Report {
+    /// Number of chunks and their total inhabited time
+    Chunks,
+    /// Items in containers
+    Items,
+    /// Block entities by id
+    BlockEntities,
+    /// Blocks by name
+    Blocks,
+    /// Biomes by 4x4x4 cell
+    Biomes,
+    /// Entities by id
+    Entities,
+}
+
+/// The parts of a chunk a report reads, from which the planner decides what each pass decodes
+#[derive(Debug, Clone, Copy, Default)]
+struct Needs {
+    /// The terrain chunks in the region folder
+    region: bool,
+    /// The palette indices of all block states, the most expensive part to decode
+    block_states: bool,
+    /// The biome palettes of the sections
+    biomes: bool,
+    /// The entity chunks, or the entities in terrain chunks before 1.17
+    entities: bool,
+}
+
+impl Report {
+    fn name(self) -> &'static str {
+        match self {
+            Report::Chunks => "chunks",
+            Report::Items => "items",
+            Report::BlockEntities => "block_entities",
+            Report::Blocks => "blocks",
+            Report::Biomes => "biomes",
+            Report::Entities => "entities",
+        }
+    }
+
+    fn needs(self) -> Needs {
+        let region = Needs {
+            region: true,
+            ..Needs::default()
+        };
+        match self {
+            Report::Chunks | Report::Items | Report::BlockEntities => region,
+            Report::Blocks => Needs {
+                block_states: true,
+                ..region
+            },
+            Report::Biomes => Needs {
+                biomes: true,
+                ..region
+            },
+            Report::Entities => Needs {
+                entities: true,
+                ..Needs::default()
+            },
+        }
+    }
+}
+
+/// The passes over one dimension that together produce all requested reports
+struct Plan {
+    /// Scan the region folder, decoding what the union of the reports needs
+    region: Option<Needs>,
+    /// Scan the entities folder
+    entities: bool,
+    /// Bytes of the region files that are read
+    region_bytes: u64,
+    /// Bytes of the entity region files that are read
+    entities_bytes: u64,
+}
+
+impl Plan {
+    fn new(
+        reports: &[Report],
+        only_built_chunks: bool,
+        dim_path: &Path,
+        has_region: bool,
+        has_entities: bool,
+    ) -> Self {
+        let mut needs = Needs::default();
+        for report in reports {
+            let report = report.needs();
+            needs.region |= report.region;
+            needs.block_states |= report.block_states;
+            needs.biomes |= report.biomes;
+            needs.entities |= report.entities;
+        }
+        // which chunks are built is decided from their blocks, also for the entities in them
+        if only_built_chunks {
+            needs.region = true;
+            needs.block_states = true;
+        }
+        // Worlds from before 1.17 have no entities folder and keep the entities in the terrain
+        // chunks, which are then read for them as well
+        let entities = needs.entities && has_entities;
+        needs.entities &= !has_entities;
+        needs.region |= needs.entities;
+
+        let region = (needs.region && has_region).then_some(needs);
+        Plan {
+            region,
+            entities,
+            region_bytes: if region.is_some() {
+                dir_size(&dim_path.join("region"))
+            } else {
+                0
+            },
+            entities_bytes: if entities {
+                dir_size(&dim_path.join("entities"))
+            } else {
+                0
+            },
+        }
+    }
+
+    fn describe(&self, dim_name: &str) -> String {
+        let mut passes = Vec::new();
+        if let Some(needs) = self.region {
+            let mut decoded = vec!["chunk data"];
+            if needs.block_states {
+                decoded.push("block states");
+            }
+            if needs.biomes {
+                decoded.push("biomes");
+            }
+            if needs.entities {
+                decoded.push("legacy entities");
+            }
+            passes.push(format!(
+                "region ({:.1} MiB, {})",
+                self.region_bytes as f64 / 1048576.0,
+                decoded.join(", ")
+            ));
+        }
+        if self.entities {
+            passes.push(format!(
+                "entities ({:.1} MiB)",
+                self.entities_bytes as f64 / 1048576.0
+            ));
+        }
+        if passes.is_empty() {
+            format!("{}: nothing to scan", dim_name)
+        } else {
+            format!("{}: {}", dim_name, passes.join(" + "))
+        }
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+    let reports = &args.reports;
+    let has = |report| reports.contains(&report);
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[(Source::Region, "that dimension")]);
+
+    let mut plans = Vec::new();
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        let plan = Plan::new(
+            reports,
+            args.only_built_chunks,
+            &dim_path,
+            sources.has(dim_name, Source::Region),
+            sources.has(dim_name, Source::Entities),
+        );
+        eprintln!("plan: {}", plan.describe(dim_name));
+        plans.push((dim_name, dim_path, plan));
+    }
+    if args.plan {
+        return Ok(());
+    }
+
+    // One record per counted thing as dimension, report and id with the count, summed up at
+    // the end
+    let mut table = Table::new(3, 1);
+    for (dim_name, dim_path, plan) in &plans {
+        let mut add = |report: Report, id: &str, count: u64| {
+            table.push(&[dim_name, report.name(), id], &[count]);
+        };
+
+        let mut built_chunks = HashSet::new();
+        if let Some(needs) = plan.region {
+            for_each_chunk_in_dir(&dim_path.join("region"), |chunk_x, chunk_z, chunk| {
+                let version =
+                    GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+                let level = match version.level(&chunk) {
+                    Some(level) => level,
+                    None => return,
+                };
+                let blocks = needs
+                    .block_states
+                    .then(|| ChunkBlocks::from_chunk_as(&chunk, version));
+                if args.only_built_chunks {
+                    if blocks.as_ref().unwrap().entropy() < args.min_entropy {
+                        return;
+                    }
+                    built_chunks.insert((chunk_x, chunk_z));
+                }
+
+                if has(Report::Chunks) {
+                    let inhabited_time = level.get::<_, i64>("InhabitedTime").unwrap_or(0);
+                    add(Report::Chunks, "chunks", 1);
+                    add(
+                        Report::Chunks,
+                        "inhabited_time",
+                        inhabited_time.max(0) as u64,
+                    );
+                }
+
+                if has(Report::Items) || has(Report::BlockEntities) {
+                    if let Ok(block_entities) =
+                        level.get::<_, &NbtList>(version.block_entities_key())
+                    {
+                        for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+                            match block_entity.get::<_, &str>("id") {
+                                Ok(id) if has(Report::BlockEntities) => {
+                                    add(Report::BlockEntities, id, 1)
+                                }
+                                _ => {}
+                            }
+                            match block_entity.get::<_, &NbtList>("Items") {
+                                Ok(items) if has(Report::Items) => {
+                                    for_each_item(items, &mut |item| {
+                                        let (id, count) = id_and_count(item);
+                                        add(Report::Items, id, count);
+                                    })
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+
+                if needs.entities && version.entities_in_chunk() {
+                    if let Ok(entities) = level.get::<_, &NbtList>("Entities") {
+                        count_entities(entities, &mut add);
+                    }
+                }
+
+                if let Some(blocks) = blocks.as_ref().filter(|_| has(Report::Blocks)) {
+                    for (_, block_states) in blocks.sections() {
+                        let counts = block_states.counts();
+                        for (state, count) in block_states.palette().iter().zip(counts) {
+                            if count == 0 {
+                                continue;
+                            }
+                            if let Ok(name) = state.get::<_, &str>("Name") {
+                                add(Report::Blocks, name, count);
+                            }
+                        }
+                    }
+                }
+
+                if needs.biomes {
+                    if let Ok(sections) = level.get::<_, &NbtList>(version.sections_key()) {
+                        for section in sections.iter_map::<&NbtCompound>().flatten() {
+                            if let Some(biomes) = SectionBiomes::from_section(section) {
+                                for i in 0..64 {
+                                    add(Report::Biomes, biomes.get(i), 1);
+                                }
+                            }
+                        }
+                    }
+                }
+            })?;
+        }
+
+        if plan.entities {
+            for_each_chunk_in_dir(&dim_path.join("entities"), |chunk_x, chunk_z, chunk| {
+                if args.only_built_chunks && !built_chunks.contains(&(chunk_x, chunk_z)) {
+                    return;
+                }
+                if let Ok(entities) = chunk.get::<_, &NbtList>("Entities") {
+                    count_entities(entities, &mut add);
+                }
+            })?;
+        }
+    }
+
+    let mut output = IndexMap::<&str, IndexMap<&str, Value>>::new();
+    for (dim_name, _, _) in &plans {
+        let dimension = output.entry(dim_name).or_default();
+        for report in reports {
+            let result = match report {
+                Report::Chunks => json!({ "chunks": 0, "inhabited_time": 0 }),
+                _ => json!({}),
+            };
+            dimension.insert(report.name(), result);
+        }
+    }
+    for (keys, values) in table.group_by(&[0, 1, 2]) {
+        if let Some(Value::Object(counts)) = output
+            .get_mut(keys[0].as_str())
+            .and_then(|dimension| dimension.get_mut(keys[1].as_str()))
+        {
+            counts.insert(keys[2].to_string(), json!(values[0]));
+        }
+    }
+
+    print_json(&output)?;
+
+    Ok(())
+}
+
+fn count_entities(entities: &NbtList, add: &mut impl FnMut(Report, &str, u64)) {
+    for entity in entities.iter_map::<&NbtCompound>().flatten() {
+        if let Ok(id) = entity.get::<_, &str>("id") {
+            add(Report::Entities, id, 1);
+        }
+    }
+}
+
+/// Bytes of the files in a directory, 0 if it can't be read
+fn dir_size(path: &Path) -> u64 {
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
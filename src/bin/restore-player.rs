@@ -0,0 +1,121 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use quartz_nbt::NbtCompound;
+use world_statistics::{
+    backup::Backup,
+    dat::{read_dat, write_dat},
+    journal::Journal,
+    players::{find_usercache, inventory_of, InventoryStore, INVENTORY_KEYS},
+    world::{ensure_closed, warn_if_open},
+};
+
+/// Writes a player's inventory and ender chest from a snapshot of snapshot-players back into
+/// their playerdata. The current items are saved as a new snapshot first. The player has to be
+/// offline, or the server stopped.
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// JSON lines file to record the previous content of changed chunks and files to, to be
+    /// reverted with undo
+    #[clap(long)]
+    journal: Option<PathBuf>,
+
+    /// Directory to copy the changed files to first, restored from if writing fails halfway
+    #[clap(long)]
+    backup: Option<PathBuf>,
+
+    /// Write even though the world is open in a running game or server, if the player is
+    /// offline
+    #[clap(long)]
+    force: bool,
+
+    /// Path to the world directory
+    world: String,
+
+    /// Directory the snapshots are stored in
+    store: String,
+
+    /// UUID or name of the player
+    player: String,
+
+    /// Timestamp of the snapshot, lists the snapshots containing the player if omitted
+    timestamp: Option<u64>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    if args.timestamp.is_some() {
+        ensure_closed(&world_path, args.force)?;
+    } else {
+        warn_if_open(&world_path);
+    }
+    let mut journal = args
+        .journal
+        .as_deref()
+        .map(|path| Journal::open(path, &world_path, "restore-player"))
+        .transpose()?;
+    let mut backup = args
+        .backup
+        .as_deref()
+        .map(|dir| Backup::new(dir, &world_path));
+    let store = InventoryStore::open(&args.store)?;
+
+    let uuid = find_usercache(&world_path)
+        .into_iter()
+        .find_map(|(uuid, name)| name.eq_ignore_ascii_case(&args.player).then_some(uuid))
+        .unwrap_or_else(|| args.player.clone());
+
+    let timestamp = match args.timestamp {
+        Some(timestamp) => timestamp,
+        None => {
+            for timestamp in store.timestamps()? {
+                if store.load(timestamp)?.contains_key(&uuid) {
+                    println!("{}", timestamp);
+                }
+            }
+            return Ok(());
+        }
+    };
+
+    let snapshot = store
+        .load(timestamp)
+        .with_context(|| format!("no snapshot {}", timestamp))?;
+    let inventory: &NbtCompound = snapshot
+        .get(&uuid)
+        .map_err(|_| eyre!("player {} is not in snapshot {}", uuid, timestamp))?;
+
+    let path = world_path.join("playerdata").join(format!("{}.dat", uuid));
+    let mut player =
+        read_dat(&path).with_context(|| format!("failed to read {}", path.display()))?;
+
+    let mut current = NbtCompound::new();
+    current.insert(uuid.as_str(), inventory_of(&player));
+    let saved = store.record(&current)?;
+    eprintln!("saved the current items as snapshot {}", saved);
+
+    for key in INVENTORY_KEYS {
+        match inventory.inner().get(*key) {
+            Some(tag) => player.insert(*key, tag.clone()),
+            None => {
+                player.inner_mut().remove(*key);
+            }
+        }
+    }
+    if let Some(backup) = &mut backup {
+        backup.save(&path)?;
+    }
+    if let Some(journal) = &mut journal {
+        journal.record_file(&path)?;
+    }
+    write_dat(&path, &player)?;
+    eprintln!("restored {} to snapshot {}", uuid, timestamp);
+    if let Some(backup) = backup {
+        backup.finish();
+    }
+
+    Ok(())
+}
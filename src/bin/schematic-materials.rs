@@ -0,0 +1,189 @@
+use std::{fs, path::Path};
+
+use clap::Parser;
+use eyre::eyre;
+use indexmap::IndexMap;
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+use serde_json::json;
+use world_statistics::{blocks::AIR_BLOCKS, dat::read_dat, redact::print_json};
+
+/// Sums up the blocks needed for the .schem and .litematic files in a directory
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// count-items output of the items already in storage
+    #[clap(long)]
+    storage: Option<String>,
+
+    /// Directory containing the schematics
+    dir: String,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let mut paths: Vec<_> = fs::read_dir(&args.dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "schem" || ext == "litematic")
+        })
+        .collect();
+    paths.sort();
+
+    let mut files = IndexMap::new();
+    let mut total = IndexMap::<String, u64>::new();
+
+    for path in paths {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let mut blocks = match count_blocks(&path) {
+            Ok(blocks) => blocks,
+            Err(e) => {
+                eprintln!("failed to read {}: {}", name, e);
+                continue;
+            }
+        };
+        blocks.retain(|name, _| !AIR_BLOCKS.contains(&name.as_str()));
+        blocks.sort_by(|_, a, _, b| b.cmp(a));
+
+        for (id, count) in &blocks {
+            *total.entry(id.clone()).or_default() += count;
+        }
+        files.insert(name, blocks);
+    }
+    total.sort_by(|_, a, _, b| b.cmp(a));
+
+    let mut output = json!({ "total": total });
+    if let Some(storage) = args.storage {
+        let storage: IndexMap<String, u64> = serde_json::from_str(&fs::read_to_string(storage)?)?;
+        let mut missing: IndexMap<String, u64> = total
+            .iter()
+            .map(|(id, &count)| {
+                (
+                    id.clone(),
+                    count.saturating_sub(storage.get(id).copied().unwrap_or(0)),
+                )
+            })
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        missing.sort_by(|_, a, _, b| b.cmp(a));
+        output["missing"] = json!(missing);
+    }
+    output["files"] = json!(files);
+
+    print_json(&output)?;
+
+    Ok(())
+}
+
+/// Counts the block names of a Sponge schematic (version 2 or 3) or a Litematica schematic
+fn count_blocks(path: &Path) -> eyre::Result<IndexMap<String, u64>> {
+    let root = read_dat(path)?;
+
+    if path.extension().is_some_and(|ext| ext == "litematic") {
+        let mut blocks = IndexMap::new();
+        let regions: &NbtCompound = root.get("Regions")?;
+        for region in regions.inner().values() {
+            if let NbtTag::Compound(region) = region {
+                count_litematic_region(region, &mut blocks)?;
+            }
+        }
+        return Ok(blocks);
+    }
+
+    // version 3 nests the palette and data under Schematic.Blocks
+    let (palette, data): (&NbtCompound, &[i8]) = match root.get::<_, &NbtCompound>("Schematic") {
+        Ok(schematic) => {
+            let blocks: &NbtCompound = schematic.get("Blocks")?;
+            (blocks.get("Palette")?, blocks.get("Data")?)
+        }
+        Err(_) => (root.get("Palette")?, root.get("BlockData")?),
+    };
+
+    let mut names = vec![""; palette.len()];
+    for (name, index) in palette.inner() {
+        if let NbtTag::Int(index) = index {
+            if let Some(slot) = names.get_mut(*index as usize) {
+                *slot = block_name(name);
+            }
+        }
+    }
+
+    let mut counts = vec![0u64; names.len()];
+    let mut value = 0usize;
+    let mut shift = 0;
+    for &byte in data {
+        value |= ((byte as u8 & 0x7f) as usize) << shift;
+        if byte as u8 & 0x80 != 0 {
+            shift += 7;
+            continue;
+        }
+        if let Some(count) = counts.get_mut(value) {
+            *count += 1;
+        }
+        value = 0;
+        shift = 0;
+    }
+
+    let mut blocks = IndexMap::new();
+    for (name, count) in names.into_iter().zip(counts) {
+        if count > 0 {
+            *blocks.entry(name.to_string()).or_default() += count;
+        }
+    }
+    Ok(blocks)
+}
+
+fn count_litematic_region(
+    region: &NbtCompound,
+    blocks: &mut IndexMap<String, u64>,
+) -> eyre::Result<()> {
+    let size: &NbtCompound = region.get("Size")?;
+    let volume = ["x", "y", "z"]
+        .iter()
+        .map(|key| Ok(size.get::<_, i32>(*key)?.unsigned_abs() as usize))
+        .product::<eyre::Result<usize>>()?;
+
+    let palette = region
+        .get::<_, &NbtList>("BlockStatePalette")?
+        .iter_map::<&NbtCompound>()
+        .map(|state| Ok(state?.get::<_, &str>("Name")?))
+        .collect::<eyre::Result<Vec<_>>>()?;
+    let data: &[i64] = region.get("BlockStates")?;
+    if palette.is_empty() {
+        return Err(eyre!("empty block state palette"));
+    }
+
+    // litematica packs the indices tightly, so values can span two longs
+    let bits = usize::max(
+        2,
+        (usize::BITS - (palette.len() - 1).leading_zeros()) as usize,
+    );
+    let mask = (1u64 << bits) - 1;
+
+    let mut counts = vec![0u64; palette.len()];
+    for i in 0..volume {
+        let bit = i * bits;
+        let (long, offset) = (bit / 64, bit % 64);
+        let mut value = data.get(long).copied().unwrap_or(0) as u64 >> offset;
+        if offset + bits > 64 {
+            value |= (data.get(long + 1).copied().unwrap_or(0) as u64) << (64 - offset);
+        }
+        if let Some(count) = counts.get_mut((value & mask) as usize) {
+            *count += 1;
+        }
+    }
+
+    for (name, count) in palette.into_iter().zip(counts) {
+        if count > 0 {
+            *blocks.entry(name.to_string()).or_default() += count;
+        }
+    }
+    Ok(())
+}
+
+/// Strips the block state properties from a palette key like `minecraft:oak_stairs[facing=east]`
+fn block_name(state: &str) -> &str {
+    state.split('[').next().unwrap()
+}
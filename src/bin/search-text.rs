@@ -0,0 +1,347 @@
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use eyre::eyre;
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    anonymize::AnonymizeArgs,
+    dat::read_dat,
+    items::{book_content, for_each_item},
+    players::{find_usercache, playerdata_files, INVENTORY_KEYS},
+    redact::{print_json, Redaction},
+    region::{read_chunk, region_files, RegionFile},
+    text::sign_lines,
+    text_index::{words, TextEntry, TextIndex},
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, Source, Sources},
+};
+
+/// Searches the text of signs and of books in containers, on lecterns, in item frames and in
+/// player inventories. The text is kept in an index file between runs, which only rescans the
+/// files that changed since.
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// JSON file the index is kept in
+    #[clap(long, default_value = "text-index.json")]
+    index: PathBuf,
+
+    /// Scan the whole world again instead of only the files that changed
+    #[clap(long)]
+    rebuild: bool,
+
+    /// Print the whole text of each match instead of an excerpt around the first word
+    #[clap(long)]
+    full_text: bool,
+
+    /// Maximum number of matches to print
+    #[clap(long, default_value = "100")]
+    limit: usize,
+
+    #[clap(flatten)]
+    anonymize: AnonymizeArgs,
+
+    /// Words that all have to appear, regardless of case. A word ending with `*` matches all
+    /// words starting with it, e.g. `"king* castle"`.
+    query: String,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+/// Characters of context printed before and after the first matching word
+const EXCERPT_CONTEXT: usize = 60;
+
+/// Keys of entities that hold items, besides the single `Item` of item frames
+const ENTITY_ITEM_KEYS: &[&str] = &["Items", "ArmorItems", "HandItems", "Inventory"];
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[
+        (Source::Region, "signs and books there"),
+        (Source::Entities, "books in item frames there"),
+        (Source::Playerdata, "books in player inventories"),
+    ]);
+
+    let mut index = TextIndex::open(&args.index, &world_path)?;
+    if args.rebuild {
+        index.clear();
+    }
+
+    let mut scanned = 0;
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        for (folder, source) in [("region", Source::Region), ("entities", Source::Entities)] {
+            if !sources.has(dim_name, source) {
+                continue;
+            }
+            for (_, _, path) in region_files(&dim_path.join(folder))? {
+                if index.is_current(&path) {
+                    continue;
+                }
+                let entries = scan_region(&path, dim_name, source == Source::Entities)?;
+                index.update_file(&path, entries);
+                scanned += 1;
+            }
+        }
+    }
+    if sources.playerdata {
+        for (uuid, path) in playerdata_files(&world_path)? {
+            if index.is_current(&path) {
+                continue;
+            }
+            let player = match read_dat(&path) {
+                Ok(player) => player,
+                Err(e) => {
+                    eprintln!("failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let mut entries = Vec::new();
+            for key in INVENTORY_KEYS {
+                if let Ok(items) = player.get::<_, &NbtList>(*key) {
+                    let location = TextEntry {
+                        player: Some(uuid.clone()),
+                        container: Some(key.to_string()),
+                        ..Default::default()
+                    };
+                    find_books(items, &location, &mut entries);
+                }
+            }
+            index.update_file(&path, entries);
+            scanned += 1;
+        }
+    }
+    index.remove_missing();
+    index.save()?;
+    eprintln!(
+        "scanned {} changed files, {} signs and books indexed",
+        scanned,
+        index.len()
+    );
+
+    let names = find_usercache(&world_path);
+    let anonymizer = args.anonymize.anonymizer()?;
+    let redaction = Redaction::global()?;
+    // authors are only stored by name, pseudonymized by their UUID where the usercache has it
+    let author = |name: &str| {
+        let uuid = names
+            .iter()
+            .find(|(_, known)| known.eq_ignore_ascii_case(name))
+            .map_or(name, |(uuid, _)| uuid);
+        anonymizer.player(uuid).unwrap_or_else(|| name.to_string())
+    };
+    let query_words = words(&args.query.replace('*', ""));
+    let matches: Vec<_> = index
+        .search(&args.query)
+        .into_iter()
+        // the index may also hold dimensions scanned by earlier runs
+        .filter(|entry| {
+            entry
+                .dimension
+                .as_ref()
+                .is_none_or(|dimension| args.dimensions.contains(dimension))
+        })
+        // hidden players no longer match once they are pseudonymized
+        .filter(|entry| {
+            !entry
+                .player
+                .as_ref()
+                .is_some_and(|uuid| redaction.hides_uuid(uuid, &names))
+                && !entry
+                    .author
+                    .as_ref()
+                    .is_some_and(|author| redaction.hides_player(author))
+        })
+        .take(args.limit)
+        .map(|entry| {
+            let text = match args.full_text {
+                true => entry.text.clone(),
+                false => excerpt(&entry.text, &query_words),
+            };
+            let mut found = json!({ "kind": entry.kind });
+            let author = entry.author.as_deref().map(author);
+            let fields = [
+                ("title", entry.title.as_ref()),
+                ("author", author.as_ref()),
+                ("dimension", entry.dimension.as_ref()),
+                ("in", entry.container.as_ref()),
+            ];
+            for (key, value) in fields {
+                if let Some(value) = value {
+                    found[key] = json!(value);
+                }
+            }
+            if let Some(pos) = entry.pos {
+                found["pos"] = json!(anonymizer.pos(pos));
+            }
+            if let Some(uuid) = &entry.player {
+                found["player"] = match anonymizer.player(uuid) {
+                    Some(pseudonym) => json!(pseudonym),
+                    None => json!(names.get(uuid).unwrap_or(uuid)),
+                };
+            }
+            found["text"] = json!(text);
+            found
+        })
+        .collect();
+    print_json(&matches)?;
+
+    Ok(())
+}
+
+/// Collects the signs and books in the chunks of a region file, or the books held by the
+/// entities of an entities file
+fn scan_region(path: &Path, dim_name: &str, entities: bool) -> eyre::Result<Vec<TextEntry>> {
+    let mut entries = Vec::new();
+    let mut region_file = RegionFile::open(path)?;
+    region_file.for_each_chunk(|(index, _, buf)| {
+        let chunk = match read_chunk(buf) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                eprintln!(
+                    "failed to read chunk {} of {}: {}",
+                    index,
+                    path.display(),
+                    e
+                );
+                return;
+            }
+        };
+        if entities {
+            if let Ok(entities) = chunk.get::<_, &NbtList>("Entities") {
+                for entity in entities.iter_map::<&NbtCompound>().flatten() {
+                    find_in_entity(entity, dim_name, &mut entries);
+                }
+            }
+            return;
+        }
+
+        let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+        let level = match version.level(&chunk) {
+            Some(level) => level,
+            None => return,
+        };
+        if let Ok(block_entities) = level.get::<_, &NbtList>(version.block_entities_key()) {
+            for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+                let id = block_entity.get::<_, &str>("id").unwrap_or("unknown");
+                let pos = ["x", "y", "z"].map(|key| block_entity.get(key).unwrap_or_default());
+                let location = TextEntry {
+                    dimension: Some(dim_name.to_string()),
+                    pos: Some(pos),
+                    container: Some(id.to_string()),
+                    ..Default::default()
+                };
+                if let Some(lines) = sign_lines(block_entity) {
+                    let text = lines.join("\n");
+                    if !text.trim().is_empty() {
+                        entries.push(TextEntry {
+                            kind: "sign".to_string(),
+                            container: None,
+                            text,
+                            ..location.clone()
+                        });
+                    }
+                }
+                // lecterns hold a single book
+                if let Ok(book) = block_entity.get::<_, &NbtCompound>("Book") {
+                    let items = NbtList::from(vec![book.clone()]);
+                    find_books(&items, &location, &mut entries);
+                }
+                if let Ok(items) = block_entity.get::<_, &NbtList>("Items") {
+                    find_books(items, &location, &mut entries);
+                }
+            }
+        }
+        // entities were stored in the terrain chunks before 1.17
+        if version.entities_in_chunk() {
+            if let Ok(entities) = level.get::<_, &NbtList>("Entities") {
+                for entity in entities.iter_map::<&NbtCompound>().flatten() {
+                    find_in_entity(entity, dim_name, &mut entries);
+                }
+            }
+        }
+    })?;
+    Ok(entries)
+}
+
+/// Adds the books an entity holds, like an item frame or a chest minecart
+fn find_in_entity(entity: &NbtCompound, dim_name: &str, entries: &mut Vec<TextEntry>) {
+    let pos = entity.get::<_, &NbtList>("Pos").ok().and_then(|pos| {
+        let pos: Vec<f64> = pos.iter_map().flatten().collect();
+        Some([pos.first()?, pos.get(1)?, pos.get(2)?].map(|v| v.floor() as i32))
+    });
+    let location = TextEntry {
+        dimension: Some(dim_name.to_string()),
+        pos,
+        container: Some(entity.get::<_, &str>("id").unwrap_or("unknown").to_string()),
+        ..Default::default()
+    };
+    if let Ok(item) = entity.get::<_, &NbtCompound>("Item") {
+        let items = NbtList::from(vec![item.clone()]);
+        find_books(&items, &location, entries);
+    }
+    for key in ENTITY_ITEM_KEYS {
+        if let Ok(items) = entity.get::<_, &NbtList>(*key) {
+            find_books(items, &location, entries);
+        }
+    }
+}
+
+/// Adds the written books and books and quill in the list and in the containers among them
+fn find_books(items: &NbtList, location: &TextEntry, entries: &mut Vec<TextEntry>) {
+    for_each_item(items, &mut |item| {
+        let book = match book_content(item) {
+            Some(book) => book,
+            None => return,
+        };
+        let text = book.pages.join("\n");
+        if text.trim().is_empty() && book.title.is_none() {
+            return;
+        }
+        entries.push(TextEntry {
+            kind: "book".to_string(),
+            title: book.title,
+            author: book.author,
+            text,
+            ..location.clone()
+        });
+    });
+}
+
+/// Cuts the text down to the surroundings of the first of the words it contains, on a single
+/// line
+fn excerpt(text: &str, query_words: &[String]) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let lowercase = text.to_lowercase();
+    // lowercasing can change the length of some characters, then the excerpt starts at the
+    // beginning
+    let first = query_words
+        .iter()
+        .filter_map(|word| lowercase.find(word.as_str()))
+        .min()
+        .filter(|_| lowercase.len() == text.len())
+        .unwrap_or(0);
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let center = chars.partition_point(|&(i, _)| i < first);
+    let start = center.saturating_sub(EXCERPT_CONTEXT);
+    let end = (center + EXCERPT_CONTEXT).min(chars.len());
+    let mut excerpt: String = chars[start..end].iter().map(|&(_, c)| c).collect();
+    if start > 0 {
+        excerpt.insert(0, '…');
+    }
+    if end < chars.len() {
+        excerpt.push('…');
+    }
+    excerpt
+}
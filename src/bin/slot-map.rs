@@ -0,0 +1,93 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    items::format_stack,
+    redact::Redaction,
+    region::for_each_chunk_in_dir,
+    text::plain_text,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open},
+};
+
+/// Exports which slot of every container holds what, one JSON object per container and line
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// chest, barrel, etc.
+    #[clap(short, long, default_value = "all")]
+    block_entities: String,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let ids: Option<Vec<String>> = (args.block_entities != "all").then(|| {
+        args.block_entities
+            .split(',')
+            .map(|str| String::from("minecraft:") + str)
+            .collect()
+    });
+    let redaction = Redaction::global()?;
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+
+        for_each_chunk_in_dir(&dim_path.join("region"), |_, _, chunk| {
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let block_entities = match version
+                .level(&chunk)
+                .and_then(|level| level.get::<_, &NbtList>(version.block_entities_key()).ok())
+            {
+                Some(block_entities) => block_entities,
+                None => return,
+            };
+            for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+                let id: &str = block_entity.get("id").unwrap_or_default();
+                if ids.as_ref().is_some_and(|ids| !ids.iter().any(|i| i == id)) {
+                    continue;
+                }
+                let items = match block_entity.get::<_, &NbtList>("Items") {
+                    Ok(items) => items,
+                    Err(_) => continue,
+                };
+
+                let mut slots = BTreeMap::new();
+                for item in items.iter_map::<&NbtCompound>().flatten() {
+                    let slot: i8 = item.get("Slot").unwrap_or_default();
+                    if let Some(stack) = format_stack(item) {
+                        slots.insert(slot, stack);
+                    }
+                }
+
+                let pos =
+                    ["x", "y", "z"].map(|key| block_entity.get::<_, i32>(key).unwrap_or_default());
+                let mut container = json!({
+                    "dimension": dim_name,
+                    "pos": pos,
+                    "id": id,
+                    "name": block_entity.get::<_, &str>("CustomName").ok().map(plain_text),
+                    "slots": slots,
+                });
+                redaction.json(&mut container);
+                println!("{}", container);
+            }
+        })
+        .context("region folder not found")?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use eyre::Context;
+use quartz_nbt::NbtCompound;
+use world_statistics::{
+    dat::read_dat,
+    players::{inventory_of, playerdata_files, InventoryStore},
+    world::warn_if_open,
+};
+
+/// Archives the inventory and ender chest of every player, to be restored with restore-player.
+/// Prints the timestamp of the snapshot.
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Path to the world directory
+    world: String,
+
+    /// Directory the snapshots are stored in
+    store: String,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let store = InventoryStore::open(&args.store)?;
+
+    let mut inventories = NbtCompound::new();
+    for (uuid, path) in playerdata_files(&world_path).context("playerdata folder not found")? {
+        match read_dat(&path) {
+            Ok(player) => inventories.insert(uuid, inventory_of(&player)),
+            Err(e) => eprintln!("failed to read {}: {}", path.display(), e),
+        }
+    }
+
+    let timestamp = store.record(&inventories)?;
+    eprintln!("saved {} players", inventories.len());
+    println!("{}", timestamp);
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use std::{
+    fs,
+    io::{self, Read},
+};
+
+use clap::{ArgEnum, Parser};
+use eyre::eyre;
+use quartz_nbt::NbtTag;
+use serde_json::Value;
+use world_statistics::nbt_json::{json_to_nbt, nbt_to_json};
+
+/// Formats, converts and validates SNBT, one value per line
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    #[clap(arg_enum)]
+    mode: Mode,
+
+    /// Treat every input as a single value instead of one value per line
+    #[clap(short, long)]
+    whole: bool,
+
+    /// Files to read, stdin if none are given
+    files: Vec<String>,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug)]
+enum Mode {
+    Pretty,
+    Minify,
+    ToJson,
+    FromJson,
+    Validate,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let inputs = if args.files.is_empty() {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        vec![("stdin".to_string(), input)]
+    } else {
+        args.files
+            .iter()
+            .map(|path| Ok((path.clone(), fs::read_to_string(path)?)))
+            .collect::<Result<_, io::Error>>()?
+    };
+
+    let mut invalid = 0;
+    for (name, input) in &inputs {
+        let values: Vec<(usize, &str)> = if args.whole {
+            vec![(1, input.as_str())]
+        } else {
+            input
+                .lines()
+                .enumerate()
+                .map(|(i, line)| (i + 1, line))
+                .filter(|(_, line)| !line.trim().is_empty())
+                .collect()
+        };
+
+        for (line, value) in values {
+            match convert(args.mode, value) {
+                Ok(Some(output)) => println!("{}", output),
+                Ok(None) => {}
+                Err(e) => {
+                    eprintln!("{}:{}: {}", name, line, e);
+                    invalid += 1;
+                }
+            }
+        }
+    }
+
+    if invalid > 0 {
+        return Err(eyre!("{} invalid values", invalid));
+    }
+
+    Ok(())
+}
+
+fn convert(mode: Mode, input: &str) -> eyre::Result<Option<String>> {
+    if let Mode::FromJson = mode {
+        let value: Value = serde_json::from_str(input)?;
+        let tag = json_to_nbt(&value).ok_or_else(|| eyre!("null has no NBT representation"))?;
+        return match tag {
+            NbtTag::Compound(compound) => Ok(Some(compound.to_snbt())),
+            _ => Err(eyre!("expected a JSON object")),
+        };
+    }
+
+    let compound = quartz_nbt::snbt::parse(input)?;
+    Ok(match mode {
+        Mode::Pretty => Some(compound.to_pretty_snbt()),
+        Mode::Minify => Some(compound.to_snbt()),
+        Mode::ToJson => Some(nbt_to_json(&NbtTag::Compound(compound)).to_string()),
+        Mode::Validate | Mode::FromJson => None,
+    })
+}
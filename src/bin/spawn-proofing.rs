@@ -0,0 +1,197 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use quartz_nbt::NbtCompound;
+use serde_json::json;
+use world_statistics::{
+    blocks::ChunkBlocks,
+    light::ChunkLight,
+    redact::print_json,
+    region::for_each_chunk_in_selection,
+    world::{dimension_path, warn_if_open, BlockPos, ChunkSelection},
+};
+
+/// Finds the spaces around a point where hostile mobs can spawn: dark blocks with a solid top and
+/// room above, to check spawn-proofing around farms and AFK spots
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Radius of the sphere around the center to check
+    #[clap(long, default_value = "128")]
+    radius: i32,
+
+    /// Mobs don't spawn closer than this to the player
+    #[clap(long, default_value = "24")]
+    min_distance: i32,
+
+    /// Maximum number of positions to list
+    #[clap(long, default_value = "100")]
+    limit: usize,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether or end
+    dimension: String,
+
+    /// Center as x,y,z, e.g. the AFK spot
+    #[clap(allow_hyphen_values = true)]
+    center: BlockPos,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let dim_path = dimension_path(&world_path, &args.dimension)
+        .ok_or_else(|| eyre!("unknown dimension {}", args.dimension))?;
+
+    let [center_x, center_y, center_z] = args.center.0;
+    let radius = args.radius;
+    let selection = ChunkSelection {
+        min: ((center_x - radius) >> 4, (center_z - radius) >> 4),
+        max: ((center_x + radius) >> 4, (center_z + radius) >> 4),
+    };
+    let in_range = |x: i32, y: i32, z: i32| {
+        let distance_sq = (x - center_x).pow(2) + (y - center_y).pow(2) + (z - center_z).pow(2);
+        distance_sq <= radius.pow(2) && distance_sq >= args.min_distance.pow(2)
+    };
+
+    let mut spawnable = 0u64;
+    let mut by_y = BTreeMap::<i32, u64>::new();
+    let mut positions = Vec::new();
+
+    for_each_chunk_in_selection(
+        &dim_path.join("region"),
+        &selection,
+        |chunk_x, chunk_z, chunk| {
+            let blocks = ChunkBlocks::from_chunk(&chunk);
+            let block_light = ChunkLight::from_chunk(&chunk, "BlockLight");
+            let sky_light = ChunkLight::from_chunk(&chunk, "SkyLight");
+
+            for z in 0..16 {
+                for x in 0..16 {
+                    let (world_x, world_z) = (chunk_x * 16 + x as i32, chunk_z * 16 + z as i32);
+                    for y in center_y - radius..=center_y + radius {
+                        if !in_range(world_x, y, world_z) {
+                            continue;
+                        }
+                        let floor = match blocks.get(x, y - 1, z) {
+                            Some(floor) if has_spawnable_top(floor) => floor,
+                            _ => continue,
+                        };
+                        if !blocks.get(x, y, z).is_some_and(is_passable)
+                            || !blocks.get(x, y + 1, z).is_some_and(is_passable)
+                        {
+                            continue;
+                        }
+                        // sections without block light data have never been lit
+                        if block_light.get(x, y, z).unwrap_or(0) > 0 {
+                            continue;
+                        }
+                        if sky_light.get(x, y, z).is_some_and(|light| light > 7) {
+                            continue;
+                        }
+
+                        spawnable += 1;
+                        *by_y.entry(y).or_default() += 1;
+                        if positions.len() < args.limit {
+                            positions.push(json!({
+                                "pos": [world_x, y, world_z],
+                                "floor": floor.get::<_, &str>("Name").unwrap_or_default(),
+                            }));
+                        }
+                    }
+                }
+            }
+        },
+    )
+    .context("region folder not found")?;
+
+    let report = json!({
+        "spawnable": spawnable,
+        "by_y": by_y,
+        "positions": positions,
+    });
+    print_json(&report)?;
+
+    Ok(())
+}
+
+fn property<'a>(state: &'a NbtCompound, key: &str) -> Option<&'a str> {
+    state
+        .get::<_, &NbtCompound>("Properties")
+        .ok()?
+        .get(key)
+        .ok()
+}
+
+/// Whether mobs can stand on top of the block
+fn has_spawnable_top(state: &NbtCompound) -> bool {
+    let name = state.get::<_, &str>("Name").unwrap_or_default();
+    let name = name.strip_prefix("minecraft:").unwrap_or(name);
+
+    if name.ends_with("_slab") {
+        return property(state, "type") != Some("bottom");
+    }
+    if name.ends_with("_stairs") {
+        return property(state, "half") == Some("top");
+    }
+    if is_passable(state) {
+        return false;
+    }
+    // blocks without a full solid top, and magma which only fire-immune mobs spawn on
+    const NOT_SOLID: &[&str] = &[
+        "carpet",
+        "glass",
+        "leaves",
+        "fence",
+        "wall",
+        "rail",
+        "button",
+        "pressure_plate",
+        "torch",
+        "sign",
+        "banner",
+        "door",
+        "pane",
+        "bars",
+        "lantern",
+        "chain",
+        "_bed",
+        "water",
+        "lava",
+        "barrier",
+        "farmland",
+        "dirt_path",
+        "magma_block",
+        "scaffolding",
+        "sapling",
+        "flower",
+        "candle",
+        "head",
+        "skull",
+        "campfire",
+        "rod",
+        "hopper",
+        "piston_head",
+    ];
+    name == "snow_block" || !(name == "snow" || NOT_SOLID.iter().any(|part| name.contains(part)))
+}
+
+/// Whether a mob can spawn inside of the block
+fn is_passable(state: &NbtCompound) -> bool {
+    matches!(
+        state.get::<_, &str>("Name").unwrap_or_default(),
+        "minecraft:air"
+            | "minecraft:cave_air"
+            | "minecraft:void_air"
+            | "minecraft:grass"
+            | "minecraft:short_grass"
+            | "minecraft:fern"
+            | "minecraft:dead_bush"
+            | "minecraft:vine"
+    )
+}
@@ -0,0 +1,204 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use clap::Parser;
+use eyre::eyre;
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::json;
+use world_statistics::{
+    items::{for_each_item, format_stack, id_and_count, stored_enchantments},
+    redact::print_json,
+    region::for_each_chunk_in_selection,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::{dimension_path, warn_if_open, BlockPos, ChunkSelection},
+};
+
+const WANTED_ENCHANTMENTS: &[&str] = &[
+    "mending",
+    "unbreaking",
+    "efficiency",
+    "fortune",
+    "silk_touch",
+    "protection",
+    "feather_falling",
+    "sharpness",
+    "looting",
+    "power",
+    "depth_strider",
+    "respiration",
+    "aqua_affinity",
+];
+
+/// Lists the trades of the villagers in a trading hall, whether the containers in the hall hold
+/// what they cost, and which wanted enchantments no villager sells
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Enchantments that should be for sale, defaults to the usual librarian trades
+    #[clap(long, use_value_delimiter = true)]
+    want: Vec<String>,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether or end
+    dimension: String,
+
+    /// First corner of the hall as x,y,z
+    #[clap(allow_hyphen_values = true)]
+    from: BlockPos,
+
+    /// Second corner of the hall as x,y,z
+    #[clap(allow_hyphen_values = true)]
+    to: BlockPos,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let dim_path = dimension_path(&world_path, &args.dimension)
+        .ok_or_else(|| eyre!("unknown dimension {}", args.dimension))?;
+
+    let min = [0, 1, 2].map(|i| args.from.0[i].min(args.to.0[i]));
+    let max = [0, 1, 2].map(|i| args.from.0[i].max(args.to.0[i]));
+    let contains = |pos: [i32; 3]| (0..3).all(|i| (min[i]..=max[i]).contains(&pos[i]));
+    let selection = ChunkSelection {
+        min: (min[0] >> 4, min[2] >> 4),
+        max: (max[0] >> 4, max[2] >> 4),
+    };
+
+    // the villagers are only rated once the stock of the whole selection is known
+    let mut villager_entities = Vec::new();
+    let mut add_villagers = |entities: &NbtList| {
+        for entity in entities.iter_map::<&NbtCompound>().flatten() {
+            if entity.get::<_, &str>("id").ok() != Some("minecraft:villager") {
+                continue;
+            }
+            villager_entities.push(entity.clone());
+        }
+    };
+
+    let mut stock = BTreeMap::<String, u64>::new();
+    let mut entities_in_chunks = false;
+    for_each_chunk_in_selection(&dim_path.join("region"), &selection, |_, _, chunk| {
+        let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+        let level = match version.level(&chunk) {
+            Some(level) => level,
+            None => return,
+        };
+        // entities were stored in the terrain chunks before 1.17
+        if version.entities_in_chunk() {
+            entities_in_chunks = true;
+            if let Ok(entities) = level.get::<_, &NbtList>("Entities") {
+                add_villagers(entities);
+            }
+        }
+        let block_entities = match level.get::<_, &NbtList>(version.block_entities_key()) {
+            Ok(block_entities) => block_entities,
+            Err(_) => return,
+        };
+        for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+            let pos = ["x", "y", "z"].map(|key| block_entity.get(key).unwrap_or_default());
+            if !contains(pos) {
+                continue;
+            }
+            if let Ok(items) = block_entity.get::<_, &NbtList>("Items") {
+                for_each_item(items, &mut |item| {
+                    let (id, count) = id_and_count(item);
+                    *stock.entry(id.to_string()).or_default() += count;
+                });
+            }
+        }
+    })?;
+
+    let entities_path = dim_path.join("entities");
+    if entities_path.exists() {
+        for_each_chunk_in_selection(&entities_path, &selection, |_, _, chunk| {
+            if let Ok(entities) = chunk.get::<_, &NbtList>("Entities") {
+                add_villagers(entities);
+            }
+        })?;
+    } else if !entities_in_chunks {
+        return Err(eyre!("entities folder not found"));
+    }
+
+    let mut villagers = Vec::new();
+    let mut offered = BTreeSet::new();
+    for entity in &villager_entities {
+        let pos = match entity.get::<_, &NbtList>("Pos") {
+            Ok(pos) => [0, 1, 2].map(|i| pos.get::<f64>(i).unwrap_or_default().floor() as i32),
+            Err(_) => continue,
+        };
+        if !contains(pos) {
+            continue;
+        }
+
+        let mut trades = Vec::new();
+        if let Ok(recipes) = entity
+            .get::<_, &NbtCompound>("Offers")
+            .and_then(|offers| offers.get::<_, &NbtList>("Recipes"))
+        {
+            for recipe in recipes.iter_map::<&NbtCompound>().flatten() {
+                let item = |key: &str| recipe.get::<_, &NbtCompound>(key).ok();
+                let in_stock = ["buy", "buyB"].iter().all(|key| match item(key) {
+                    Some(item) => {
+                        let (id, count) = id_and_count(item);
+                        id == "minecraft:air" || stock.get(id).copied().unwrap_or(0) >= count
+                    }
+                    None => true,
+                });
+                let uses: i32 = recipe.get("uses").unwrap_or_default();
+                let max_uses: i32 = recipe.get("maxUses").unwrap_or_default();
+
+                let mut enchantments = Vec::new();
+                for (id, level) in item("sell").map(stored_enchantments).unwrap_or_default() {
+                    enchantments.push(format!("{} {}", id, level));
+                    offered.insert(id.to_string());
+                }
+
+                trades.push(json!({
+                    "buy": item("buy").and_then(format_stack),
+                    "buy_b": item("buyB").and_then(format_stack),
+                    "sell": item("sell").and_then(format_stack),
+                    "enchantments": enchantments,
+                    "in_stock": in_stock,
+                    "locked": uses >= max_uses,
+                }));
+            }
+        }
+
+        let villager_data = entity.get::<_, &NbtCompound>("VillagerData").ok();
+        villagers.push(json!({
+            "pos": pos,
+            "profession": villager_data.and_then(|data| data.get::<_, &str>("profession").ok()),
+            "level": villager_data.and_then(|data| data.get::<_, i32>("level").ok()),
+            "trades": trades,
+        }));
+    }
+
+    let wanted: Vec<String> = if args.want.is_empty() {
+        WANTED_ENCHANTMENTS
+            .iter()
+            .map(|str| String::from("minecraft:") + str)
+            .collect()
+    } else {
+        args.want
+            .iter()
+            .map(|str| String::from("minecraft:") + str)
+            .collect()
+    };
+    let missing: Vec<_> = wanted.iter().filter(|id| !offered.contains(*id)).collect();
+
+    let report = json!({
+        "villagers": villagers,
+        "stock": stock,
+        "missing_enchantments": missing,
+    });
+    print_json(&report)?;
+
+    Ok(())
+}
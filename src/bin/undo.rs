@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use world_statistics::{journal, world::ensure_closed};
+
+/// Reverts the changes recorded with `--journal` by purge-entities, delete-chunks, inject-chunk,
+/// remap-ids, validate-chunks, banned-items, copy-chunks or restore-player, newest first. Stop
+/// the server before using this.
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Write even though the world is open in a running game or server
+    #[clap(long)]
+    force: bool,
+
+    /// Journal file written with --journal
+    journal: PathBuf,
+
+    /// Path to the world directory
+    world: String,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    ensure_closed(&world_path, args.force)?;
+
+    let count = journal::undo(&args.journal, &world_path)?;
+    eprintln!("reverted {} changes", count);
+
+    Ok(())
+}
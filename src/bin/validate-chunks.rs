@@ -0,0 +1,270 @@
+use std::{collections::HashMap, fs, fs::OpenOptions, path::PathBuf};
+
+use clap::Parser;
+use eyre::eyre;
+use world_statistics::{
+    backup::Backup,
+    checksums::{checksum, ChecksumStore},
+    interrupt::{handle_interrupts, interrupted, EXIT_INTERRUPTED},
+    journal::Journal,
+    region::{encode_chunk, read_chunk, region_files, RegionFile},
+    validate::{repair_block_states, validate_chunk, validate_entities_chunk, Strictness},
+    version::GameVersion,
+    world::{dimension_path, ensure_closed, warn_if_open, Source, Sources},
+};
+
+/// Checks the chunks against the format the game expects for their DataVersion: required tags,
+/// their types, long array lengths and palette indices. Prints one line per problem and exits
+/// with 1 if there were any.
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Fill sections with inconsistent block states with air and replace blocks of unknown
+    /// namespaces, printing every change. Stop the server before repairing.
+    #[clap(long)]
+    repair: bool,
+
+    /// Only print what would be repaired
+    #[clap(long, requires = "repair")]
+    dry_run: bool,
+
+    /// Namespaces of the blocks to keep when repairing
+    #[clap(long, use_value_delimiter = true, default_values = &["minecraft"])]
+    namespaces: Vec<String>,
+
+    /// JSON object of block ids to replacement blocks used when repairing instead of air
+    #[clap(long)]
+    replace: Option<PathBuf>,
+
+    /// Check chunks against the format of this DataVersion or release like 1.16.5 instead of
+    /// their own DataVersion, e.g. if it is missing
+    #[clap(long)]
+    assume_version: Option<GameVersion>,
+
+    /// Also report deviations from what the game writes itself that it still loads, like tags
+    /// added by mods or server software and missing tags it doesn't need
+    #[clap(long, conflicts_with = "lenient")]
+    strict: bool,
+
+    /// Only report what the game fails to load, accepting numbers of another type than it writes
+    /// like some performance mods store them
+    #[clap(long)]
+    lenient: bool,
+
+    /// JSON lines file to record the previous content of changed chunks and files to, to be
+    /// reverted with undo
+    #[clap(long)]
+    journal: Option<PathBuf>,
+
+    /// Directory to copy the changed files to first, restored from if writing fails halfway
+    #[clap(long)]
+    backup: Option<PathBuf>,
+
+    /// JSON file to record the CRC32 checksums of the chunk payloads in. Chunks whose payload
+    /// changed since the previous run although the game didn't save them are reported as
+    /// invalid.
+    #[clap(long)]
+    checksums: Option<PathBuf>,
+
+    /// Write even though the world is open in a running game or server
+    #[clap(long)]
+    force: bool,
+
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    if args.repair && !args.dry_run {
+        ensure_closed(&world_path, args.force)?;
+    } else {
+        warn_if_open(&world_path);
+    }
+    let mut journal = args
+        .journal
+        .as_deref()
+        .map(|path| Journal::open(path, &world_path, "validate-chunks"))
+        .transpose()?;
+    let mut backup = args
+        .backup
+        .as_deref()
+        .map(|dir| Backup::new(dir, &world_path));
+    let mut checksums = args
+        .checksums
+        .as_deref()
+        .map(|path| ChecksumStore::open(path, &world_path))
+        .transpose()?;
+    handle_interrupts()?;
+
+    let replacements: HashMap<String, String> = match &args.replace {
+        Some(path) => serde_json::from_str(&fs::read_to_string(path)?)?,
+        None => HashMap::new(),
+    };
+    let replacement = |name: &str| {
+        if let Some(new_name) = replacements.get(name) {
+            return Some(new_name.clone());
+        }
+        let namespace = name
+            .split_once(':')
+            .map_or("minecraft", |(namespace, _)| namespace);
+        (!args.namespaces.iter().any(|known| known == namespace))
+            .then(|| "minecraft:air".to_string())
+    };
+
+    let strictness = if args.strict {
+        Strictness::Strict
+    } else if args.lenient {
+        Strictness::Lenient
+    } else {
+        Strictness::Normal
+    };
+
+    let mut chunks = 0;
+    let mut repaired_chunks = 0;
+    let mut invalid_chunks = 0;
+
+    let sources = Sources::detect(&world_path, &args.dimensions);
+    sources.notify_missing(&[
+        (Source::Region, "chunks there"),
+        (Source::Entities, "entities there"),
+    ]);
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+
+        for source in [Source::Region, Source::Entities] {
+            if !sources.has(dim_name, source) {
+                continue;
+            }
+            let folder = source.folder();
+
+            for (region_x, region_z, path) in region_files(&dim_path.join(folder))? {
+                if interrupted() {
+                    break;
+                }
+                let repair = args.repair && folder == "region";
+                let mut region_file = match OpenOptions::new()
+                    .read(true)
+                    .write(repair && !args.dry_run)
+                    .open(&path)
+                    .and_then(RegionFile::new)
+                    .map(|region_file| region_file.with_path(&path))
+                {
+                    Ok(region_file) => region_file,
+                    Err(e) => {
+                        println!("{} {}: {}", dim_name, path.display(), e);
+                        invalid_chunks += 1;
+                        continue;
+                    }
+                };
+                let mut changed_chunks = Vec::new();
+                region_file.for_each_chunk(|(index, timestamp, buf)| {
+                    let chunk_x = region_x * 32 + (index % 32) as i32;
+                    let chunk_z = region_z * 32 + (index / 32) as i32;
+                    chunks += 1;
+
+                    if let Some(checksums) = &mut checksums {
+                        let checksum = checksum(buf);
+                        if let Some(previous) = checksums.check(&path, index, timestamp, checksum)
+                        {
+                            println!(
+                                "{} {} {} {}: checksum changed from {:08x} to {:08x} without the chunk being saved",
+                                dim_name, folder, chunk_x, chunk_z, previous, checksum
+                            );
+                            invalid_chunks += 1;
+                        }
+                    }
+
+                    let mut chunk = match read_chunk(buf) {
+                        Ok(chunk) => chunk,
+                        Err(e) => {
+                            println!("{} {} {} {}: {}", dim_name, folder, chunk_x, chunk_z, e);
+                            invalid_chunks += 1;
+                            return;
+                        }
+                    };
+                    let violations = if folder == "region" {
+                        validate_chunk(&chunk, (chunk_x, chunk_z), args.assume_version, strictness)
+                    } else {
+                        validate_entities_chunk(&chunk, (chunk_x, chunk_z), strictness)
+                    };
+                    for violation in &violations {
+                        println!(
+                            "{} {} {} {} {}",
+                            dim_name, folder, chunk_x, chunk_z, violation
+                        );
+                    }
+                    if !violations.is_empty() {
+                        invalid_chunks += 1;
+                    }
+
+                    let version = args.assume_version.or_else(|| GameVersion::of(&chunk));
+                    if repair && version.is_some_and(|version| version.nested_block_states()) {
+                        let repairs = repair_block_states(&mut chunk, replacement);
+                        for repair in &repairs {
+                            eprintln!("{} {} {} {}", dim_name, chunk_x, chunk_z, repair);
+                        }
+                        if !repairs.is_empty() {
+                            changed_chunks.push((index, chunk));
+                        }
+                    }
+                })?;
+
+                if args.dry_run {
+                    repaired_chunks += changed_chunks.len();
+                    continue;
+                }
+                for (index, chunk) in changed_chunks {
+                    if let Some(backup) = &mut backup {
+                        backup.save(&path)?;
+                    }
+                    if let Some(journal) = &mut journal {
+                        journal.record_chunk(&path, &mut region_file, index)?;
+                    }
+                    let payload = encode_chunk(&chunk)?;
+                    region_file.write_chunk(index, &payload)?;
+                    if let Some(checksums) = &mut checksums {
+                        checksums.check(
+                            &path,
+                            index,
+                            region_file.timestamp(index),
+                            checksum(&payload),
+                        );
+                    }
+                    repaired_chunks += 1;
+                }
+            }
+        }
+    }
+
+    if let Some(backup) = backup {
+        backup.finish();
+    }
+    if let Some(checksums) = checksums {
+        checksums.save()?;
+    }
+    eprintln!("{} of {} chunks are invalid", invalid_chunks, chunks);
+    if args.dry_run {
+        eprintln!("would repair {} chunks", repaired_chunks);
+    } else if args.repair {
+        eprintln!("repaired {} chunks", repaired_chunks);
+    }
+    if interrupted() {
+        eprintln!("interrupted, the remaining files were left unchanged");
+        std::process::exit(EXIT_INTERRUPTED);
+    }
+
+    if invalid_chunks > 0 {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use eyre::{eyre, Context};
+use quartz_nbt::{NbtCompound, NbtList};
+use serde_json::{json, Value};
+use world_statistics::{
+    dat::LevelDat,
+    items::format_stack,
+    redact::print_json,
+    region::for_each_chunk_in_dir,
+    uuid::format_uuid,
+    world::{dimension_path, warn_if_open},
+};
+
+/// Reports the wandering trader spawn state from level.dat and where the trader and its llamas
+/// are, including the trader's offers, as JSON
+#[derive(Parser, Debug)]
+#[clap(color = clap::ColorChoice::Never)]
+struct Args {
+    /// Path to the world directory
+    world: String,
+
+    /// overworld, nether, end
+    #[clap(default_values = &["overworld", "nether", "end"])]
+    dimensions: Vec<String>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let world_path = PathBuf::from(&args.world);
+    warn_if_open(&world_path);
+    let level =
+        LevelDat::read(&world_path.join("level.dat")).context("failed to read level.dat")?;
+    let data = &level.data;
+    let trader_id = data
+        .get::<_, &[i32]>("WanderingTraderId")
+        .ok()
+        .and_then(format_uuid);
+
+    let mut traders = Vec::new();
+    let mut llamas = Vec::new();
+
+    for dim_name in &args.dimensions {
+        let dim_path = dimension_path(&world_path, dim_name)
+            .ok_or_else(|| eyre!("unknown dimension {}", dim_name))?;
+        let entities_path = dim_path.join("entities");
+        if !entities_path.exists() {
+            continue;
+        }
+
+        for_each_chunk_in_dir(&entities_path, |_, _, chunk| {
+            let entities = match chunk.get::<_, &NbtList>("Entities") {
+                Ok(entities) => entities,
+                Err(_) => return,
+            };
+            for entity in entities.iter_map::<&NbtCompound>().flatten() {
+                let id: &str = entity.get("id").unwrap_or_default();
+                if id != "minecraft:wandering_trader" && id != "minecraft:trader_llama" {
+                    continue;
+                }
+
+                let uuid = entity.get::<_, &[i32]>("UUID").ok().and_then(format_uuid);
+                let pos: Vec<i32> = entity
+                    .get::<_, &NbtList>("Pos")
+                    .map(|pos| {
+                        pos.iter_map::<f64>()
+                            .flatten()
+                            .map(|v| v.floor() as i32)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let mut value = json!({
+                    "uuid": uuid,
+                    "dimension": dim_name,
+                    "pos": pos,
+                    "despawn_delay": entity.get::<_, i32>("DespawnDelay").ok(),
+                });
+
+                if id == "minecraft:wandering_trader" {
+                    value["current"] = json!(uuid.is_some() && uuid == trader_id);
+                    value["offers"] = offers(entity);
+                    traders.push(value);
+                } else {
+                    llamas.push(value);
+                }
+            }
+        })?;
+    }
+
+    let report = json!({
+        "spawn": {
+            "trader_id": trader_id,
+            "spawn_delay": data.get::<_, i32>("WanderingTraderSpawnDelay").ok(),
+            "spawn_chance": data.get::<_, i32>("WanderingTraderSpawnChance").ok(),
+        },
+        "traders": traders,
+        "llamas": llamas,
+    });
+    print_json(&report)?;
+
+    Ok(())
+}
+
+fn offers(entity: &NbtCompound) -> Value {
+    let recipes = match entity
+        .get::<_, &NbtCompound>("Offers")
+        .and_then(|offers| offers.get::<_, &NbtList>("Recipes"))
+    {
+        Ok(recipes) => recipes,
+        Err(_) => return json!([]),
+    };
+
+    recipes
+        .iter_map::<&NbtCompound>()
+        .flatten()
+        .map(|recipe| {
+            let stack = |key: &str| {
+                recipe
+                    .get::<_, &NbtCompound>(key)
+                    .ok()
+                    .and_then(format_stack)
+            };
+            json!({
+                "buy": stack("buy"),
+                "buy_b": stack("buyB"),
+                "sell": stack("sell"),
+                "uses": recipe.get::<_, i32>("uses").ok(),
+                "max_uses": recipe.get::<_, i32>("maxUses").ok(),
+            })
+        })
+        .collect()
+}
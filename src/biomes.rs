@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+
+use crate::version::{BiomeEncoding, GameVersion, DATA_VERSION_1_18};
+
+/// The biomes of a chunk section as stored in `sections[].biomes` since 1.18, with one entry
+/// per 4x4x4 cell
+pub struct SectionBiomes<'a> {
+    palette: Vec<&'a str>,
+    data: &'a [i64],
+    bits: usize,
+}
+
+impl<'a> SectionBiomes<'a> {
+    pub fn from_section(section: &'a NbtCompound) -> Option<Self> {
+        let biomes: &NbtCompound = section.get("biomes").ok()?;
+        let palette = biomes
+            .get::<_, &NbtList>("palette")
+            .ok()?
+            .iter_map::<&str>()
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+        if palette.is_empty() {
+            return None;
+        }
+        let data: &[i64] = biomes.get("data").unwrap_or(&[]);
+
+        let bits = (usize::BITS - (palette.len() - 1).leading_zeros()) as usize;
+
+        Some(SectionBiomes {
+            palette,
+            data,
+            bits,
+        })
+    }
+
+    pub fn palette(&self) -> &[&'a str] {
+        &self.palette
+    }
+
+    /// Returns the palette index of the cell at `(y * 4 + z) * 4 + x`
+    pub fn palette_index(&self, index: usize) -> usize {
+        if self.data.is_empty() || self.bits == 0 {
+            return 0;
+        }
+        let per_long = 64 / self.bits;
+        let long = self.data.get(index / per_long).copied().unwrap_or(0) as u64;
+        ((long >> (index % per_long * self.bits)) & ((1 << self.bits) - 1)) as usize
+    }
+
+    /// Returns the biome of the cell at `(y * 4 + z) * 4 + x`
+    pub fn get(&self, index: usize) -> &'a str {
+        self.palette
+            .get(self.palette_index(index))
+            .copied()
+            .unwrap_or(self.palette[0])
+    }
+
+    /// Returns the section local x, y and z of all 64 cells, in units of cells, and their biome
+    pub fn iter(&self) -> impl Iterator<Item = ([usize; 3], &'a str)> + '_ {
+        (0..64).map(|index| ([index % 4, index / 16, index / 4 % 4], self.get(index)))
+    }
+
+    /// Counts the cells of each palette entry, in the order of the palette
+    pub fn counts(&self) -> Vec<u64> {
+        let mut counts = vec![0; self.palette.len()];
+        for index in 0..64 {
+            if let Some(count) = counts.get_mut(self.palette_index(index)) {
+                *count += 1;
+            }
+        }
+        counts
+    }
+}
+
+/// The biomes of a whole chunk, from the palettes of its sections since 1.18 or from the numeric
+/// ids in `Level.Biomes` before
+pub struct ChunkBiomes<'a> {
+    encoding: BiomeEncoding,
+    sections: Vec<(i32, SectionBiomes<'a>)>,
+    ids: Vec<i32>,
+}
+
+impl<'a> ChunkBiomes<'a> {
+    /// Reads the biomes in the format of the chunk's DataVersion, assuming 1.18 if it is missing
+    pub fn from_chunk(chunk: &'a NbtCompound) -> Self {
+        let version = GameVersion::of(chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+        Self::from_chunk_as(chunk, version)
+    }
+
+    /// Reads the biomes in the format of the given version
+    pub fn from_chunk_as(chunk: &'a NbtCompound, version: GameVersion) -> Self {
+        let encoding = version.biome_encoding();
+        let level = version.level(chunk);
+        let mut sections = Vec::new();
+        let mut ids = Vec::new();
+        match encoding {
+            BiomeEncoding::Palette => {
+                if let Some(list) =
+                    level.and_then(|level| level.get::<_, &NbtList>("sections").ok())
+                {
+                    for section in list.iter_map::<&NbtCompound>().flatten() {
+                        let y = section.get::<_, i8>("Y").unwrap_or_default() as i32;
+                        if let Some(biomes) = SectionBiomes::from_section(section) {
+                            sections.push((y, biomes));
+                        }
+                    }
+                }
+            }
+            // 1.12 stored one byte per column, later versions ints
+            _ => match level.and_then(|level| level.inner().get("Biomes")) {
+                Some(NbtTag::IntArray(array)) => ids = array.clone(),
+                Some(NbtTag::ByteArray(array)) => {
+                    ids = array.iter().map(|&id| id as u8 as i32).collect()
+                }
+                _ => {}
+            },
+        }
+        ChunkBiomes {
+            encoding,
+            sections,
+            ids,
+        }
+    }
+
+    pub fn encoding(&self) -> BiomeEncoding {
+        self.encoding
+    }
+
+    /// The sections with biomes since 1.18
+    pub fn sections(&self) -> &[(i32, SectionBiomes<'a>)] {
+        &self.sections
+    }
+
+    /// Returns the biome at chunk local x and z and absolute y. Biomes stored as numeric ids
+    /// that aren't vanilla biomes, e.g. of mods, are None.
+    pub fn get(&self, x: usize, y: i32, z: usize) -> Option<&'a str> {
+        match self.encoding {
+            BiomeEncoding::Palette => {
+                let section_y = y.div_euclid(16);
+                let (_, biomes) = self.sections.iter().find(|(y, _)| *y == section_y)?;
+                let cell_y = y.rem_euclid(16) as usize / 4;
+                Some(biomes.get((cell_y * 4 + z / 4) * 4 + x / 4))
+            }
+            BiomeEncoding::Cells => {
+                let cell_y = usize::try_from(y / 4).ok()?;
+                legacy_biome_name(*self.ids.get((cell_y * 4 + z / 4) * 4 + x / 4)?)
+            }
+            BiomeEncoding::Columns => legacy_biome_name(*self.ids.get(z * 16 + x)?),
+        }
+    }
+
+    /// Returns the biome of every stored entry: the 4x4x4 cells since 1.15 and the block
+    /// columns before
+    pub fn iter(&self) -> impl Iterator<Item = Option<&'a str>> + '_ {
+        let sections = self
+            .sections
+            .iter()
+            .flat_map(|(_, biomes)| biomes.iter().map(|(_, biome)| Some(biome)));
+        sections.chain(self.ids.iter().map(|&id| legacy_biome_name(id)))
+    }
+
+    /// Counts the stored entries of each biome, see [`iter`](Self::iter)
+    pub fn counts(&self) -> HashMap<&'a str, u64> {
+        let mut counts = HashMap::new();
+        for biome in self.iter().flatten() {
+            *counts.entry(biome).or_default() += 1;
+        }
+        counts
+    }
+}
+
+/// Returns the name of a numeric biome id used before 1.18, with the names of 1.17
+pub fn legacy_biome_name(id: i32) -> Option<&'static str> {
+    Some(match id {
+        0 => "minecraft:ocean",
+        1 => "minecraft:plains",
+        2 => "minecraft:desert",
+        3 => "minecraft:mountains",
+        4 => "minecraft:forest",
+        5 => "minecraft:taiga",
+        6 => "minecraft:swamp",
+        7 => "minecraft:river",
+        8 => "minecraft:nether_wastes",
+        9 => "minecraft:the_end",
+        10 => "minecraft:frozen_ocean",
+        11 => "minecraft:frozen_river",
+        12 => "minecraft:snowy_tundra",
+        13 => "minecraft:snowy_mountains",
+        14 => "minecraft:mushroom_fields",
+        15 => "minecraft:mushroom_field_shore",
+        16 => "minecraft:beach",
+        17 => "minecraft:desert_hills",
+        18 => "minecraft:wooded_hills",
+        19 => "minecraft:taiga_hills",
+        20 => "minecraft:mountain_edge",
+        21 => "minecraft:jungle",
+        22 => "minecraft:jungle_hills",
+        23 => "minecraft:jungle_edge",
+        24 => "minecraft:deep_ocean",
+        25 => "minecraft:stone_shore",
+        26 => "minecraft:snowy_beach",
+        27 => "minecraft:birch_forest",
+        28 => "minecraft:birch_forest_hills",
+        29 => "minecraft:dark_forest",
+        30 => "minecraft:snowy_taiga",
+        31 => "minecraft:snowy_taiga_hills",
+        32 => "minecraft:giant_tree_taiga",
+        33 => "minecraft:giant_tree_taiga_hills",
+        34 => "minecraft:wooded_mountains",
+        35 => "minecraft:savanna",
+        36 => "minecraft:savanna_plateau",
+        37 => "minecraft:badlands",
+        38 => "minecraft:wooded_badlands_plateau",
+        39 => "minecraft:badlands_plateau",
+        40 => "minecraft:small_end_islands",
+        41 => "minecraft:end_midlands",
+        42 => "minecraft:end_highlands",
+        43 => "minecraft:end_barrens",
+        44 => "minecraft:warm_ocean",
+        45 => "minecraft:lukewarm_ocean",
+        46 => "minecraft:cold_ocean",
+        47 => "minecraft:deep_warm_ocean",
+        48 => "minecraft:deep_lukewarm_ocean",
+        49 => "minecraft:deep_cold_ocean",
+        50 => "minecraft:deep_frozen_ocean",
+        127 => "minecraft:the_void",
+        129 => "minecraft:sunflower_plains",
+        130 => "minecraft:desert_lakes",
+        131 => "minecraft:gravelly_mountains",
+        132 => "minecraft:flower_forest",
+        133 => "minecraft:taiga_mountains",
+        134 => "minecraft:swamp_hills",
+        140 => "minecraft:ice_spikes",
+        149 => "minecraft:modified_jungle",
+        151 => "minecraft:modified_jungle_edge",
+        155 => "minecraft:tall_birch_forest",
+        156 => "minecraft:tall_birch_hills",
+        157 => "minecraft:dark_forest_hills",
+        158 => "minecraft:snowy_taiga_mountains",
+        160 => "minecraft:giant_spruce_taiga",
+        161 => "minecraft:giant_spruce_taiga_hills",
+        162 => "minecraft:modified_gravelly_mountains",
+        163 => "minecraft:shattered_savanna",
+        164 => "minecraft:shattered_savanna_plateau",
+        165 => "minecraft:eroded_badlands",
+        166 => "minecraft:modified_wooded_badlands_plateau",
+        167 => "minecraft:modified_badlands_plateau",
+        168 => "minecraft:bamboo_jungle",
+        169 => "minecraft:bamboo_jungle_hills",
+        170 => "minecraft:soul_sand_valley",
+        171 => "minecraft:crimson_forest",
+        172 => "minecraft:warped_forest",
+        173 => "minecraft:basalt_deltas",
+        174 => "minecraft:dripstone_caves",
+        175 => "minecraft:lush_caves",
+        _ => return None,
+    })
+}
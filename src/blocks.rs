@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+
+use quartz_nbt::{NbtCompound, NbtList};
+
+use crate::version::{GameVersion, DATA_VERSION_1_18};
+
+/// Number of blocks in a 16x16x16 chunk section
+pub const SECTION_BLOCKS: usize = 4096;
+
+/// The kinds of air
+pub const AIR_BLOCKS: &[&str] = &["minecraft:air", "minecraft:cave_air", "minecraft:void_air"];
+
+/// Sections with fewer blocks than this that aren't air are left out of
+/// [`ChunkBlocks::entropy`], as a handful of different blocks says little
+const MIN_ENTROPY_BLOCKS: u64 = 64;
+
+/// The block states of a 16x16x16 chunk section as stored in `sections[].block_states`, or in
+/// `Level.Sections[]` before 1.18
+pub struct BlockStates<'a> {
+    palette: Vec<&'a NbtCompound>,
+    data: &'a [i64],
+    bits: usize,
+    spanning: bool,
+}
+
+impl<'a> BlockStates<'a> {
+    pub fn from_section(section: &'a NbtCompound) -> Option<Self> {
+        Self::from_section_as(section, GameVersion::new(DATA_VERSION_1_18))
+    }
+
+    /// Reads the block states of a section in the format of the given version
+    pub fn from_section_as(section: &'a NbtCompound, version: GameVersion) -> Option<Self> {
+        let block_states: &NbtCompound = if version.nested_block_states() {
+            section.get("block_states").ok()?
+        } else {
+            section
+        };
+        let palette = block_states
+            .get::<_, &NbtList>(version.palette_key())
+            .ok()?
+            .iter_map::<&NbtCompound>()
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+        if palette.is_empty() {
+            return None;
+        }
+        let data: &[i64] = block_states
+            .get(version.block_states_data_key())
+            .unwrap_or(&[]);
+
+        let bits = block_state_bits(palette.len());
+
+        Some(BlockStates {
+            palette,
+            data,
+            bits,
+            spanning: version.spanning_block_states(),
+        })
+    }
+
+    pub fn palette(&self) -> &[&'a NbtCompound] {
+        &self.palette
+    }
+
+    /// Returns the palette index of the block at `(y * 16 + z) * 16 + x`
+    pub fn palette_index(&self, index: usize) -> usize {
+        packed_index(self.data, self.bits, self.spanning, index)
+    }
+
+    /// Returns the block state (`Name` and `Properties`) at `(y * 16 + z) * 16 + x`
+    pub fn get(&self, index: usize) -> Option<&'a NbtCompound> {
+        self.palette.get(self.palette_index(index)).copied()
+    }
+
+    /// Returns the block name at `(y * 16 + z) * 16 + x`
+    pub fn name(&self, index: usize) -> Option<&'a str> {
+        self.get(index)?.get("Name").ok()
+    }
+
+    /// Returns the palette indices of all blocks, in the order of `(y * 16 + z) * 16 + x`
+    pub fn palette_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..SECTION_BLOCKS).map(|index| self.palette_index(index))
+    }
+
+    /// Returns the section local x, y and z and the block state of all blocks, skipping indices
+    /// outside of the palette
+    pub fn iter(&self) -> impl Iterator<Item = ([usize; 3], &'a NbtCompound)> + '_ {
+        self.palette_indices()
+            .enumerate()
+            .filter_map(|(index, palette_index)| {
+                let state = self.palette.get(palette_index)?;
+                Some(([index % 16, index / 256, index / 16 % 16], *state))
+            })
+    }
+
+    /// Counts the blocks of each palette entry, in the order of the palette
+    pub fn counts(&self) -> Vec<u64> {
+        let mut counts = vec![0; self.palette.len()];
+        for palette_index in self.palette_indices() {
+            if let Some(count) = counts.get_mut(palette_index) {
+                *count += 1;
+            }
+        }
+        counts
+    }
+
+    /// The Shannon entropy in bits of the block names other than air, or None if there are too
+    /// few of them
+    pub fn entropy(&self) -> Option<f64> {
+        if self.palette.len() == 1 {
+            return Some(0.0);
+        }
+        let mut by_name = HashMap::<&str, u64>::new();
+        for (state, count) in self.palette.iter().zip(self.counts()) {
+            let name = state.get::<_, &str>("Name").unwrap_or_default();
+            if count > 0 && !AIR_BLOCKS.contains(&name) {
+                *by_name.entry(name).or_default() += count;
+            }
+        }
+        let total: u64 = by_name.values().sum();
+        if total < MIN_ENTROPY_BLOCKS {
+            return None;
+        }
+        Some(
+            by_name
+                .values()
+                .map(|&count| {
+                    let p = count as f64 / total as f64;
+                    -p * p.log2()
+                })
+                .sum(),
+        )
+    }
+}
+
+/// The block states of all sections of a chunk
+pub struct ChunkBlocks<'a> {
+    sections: Vec<(i32, BlockStates<'a>)>,
+}
+
+impl<'a> ChunkBlocks<'a> {
+    /// Reads the block states in the format of the chunk's DataVersion, assuming 1.18 if it is
+    /// missing
+    pub fn from_chunk(chunk: &'a NbtCompound) -> Self {
+        let version = GameVersion::of(chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+        Self::from_chunk_as(chunk, version)
+    }
+
+    /// Reads the block states in the format of the given version
+    pub fn from_chunk_as(chunk: &'a NbtCompound, version: GameVersion) -> Self {
+        let sections = version
+            .level(chunk)
+            .and_then(|level| level.get::<_, &NbtList>(version.sections_key()).ok())
+            .map(|sections| {
+                sections
+                    .iter_map::<&NbtCompound>()
+                    .flatten()
+                    .filter_map(|section| {
+                        let y = section.get::<_, i8>("Y").ok()? as i32;
+                        Some((y, BlockStates::from_section_as(section, version)?))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        ChunkBlocks { sections }
+    }
+
+    pub fn sections(&self) -> &[(i32, BlockStates<'a>)] {
+        &self.sections
+    }
+
+    /// Returns the block state at chunk local x and z and absolute y
+    pub fn get(&self, x: usize, y: i32, z: usize) -> Option<&'a NbtCompound> {
+        let section_y = y.div_euclid(16);
+        let (_, block_states) = self.sections.iter().find(|(y, _)| *y == section_y)?;
+        block_states.get((y.rem_euclid(16) as usize * 16 + z) * 16 + x)
+    }
+
+    /// Returns the block name at chunk local x and z and absolute y
+    pub fn name(&self, x: usize, y: i32, z: usize) -> Option<&'a str> {
+        self.get(x, y, z)?.get("Name").ok()
+    }
+
+    /// A score of how varied the blocks of the chunk are, the highest
+    /// [entropy](BlockStates::entropy) of any section. Generated terrain is mostly stone, dirt
+    /// or water and scores below 3 bits, while builds mix many kinds of blocks in one place and
+    /// score higher.
+    pub fn entropy(&self) -> f64 {
+        self.sections
+            .iter()
+            .filter_map(|(_, block_states)| block_states.entropy())
+            .fold(0.0, f64::max)
+    }
+
+    /// Returns the chunk local x and z, absolute y and block state of all blocks, section by
+    /// section in the order they are stored
+    pub fn iter(&self) -> impl Iterator<Item = (usize, i32, usize, &'a NbtCompound)> + '_ {
+        self.sections.iter().flat_map(|(section_y, block_states)| {
+            block_states
+                .iter()
+                .map(move |([x, y, z], state)| (x, section_y * 16 + y as i32, z, state))
+        })
+    }
+}
+
+/// Bits per block state index for a palette of the given length
+pub(crate) fn block_state_bits(palette_len: usize) -> usize {
+    usize::max(
+        4,
+        (usize::BITS - palette_len.saturating_sub(1).leading_zeros()) as usize,
+    )
+}
+
+/// Reads the `index`th value of `bits` bits from the packed longs of a paletted container.
+/// Before 1.16 values could span two longs.
+pub(crate) fn packed_index(data: &[i64], bits: usize, spanning: bool, index: usize) -> usize {
+    if data.is_empty() {
+        return 0;
+    }
+    let mask = (1 << bits) - 1;
+    if spanning {
+        let bit = index * bits;
+        let mut value = data.get(bit / 64).copied().unwrap_or(0) as u64 >> (bit % 64);
+        if bit % 64 + bits > 64 {
+            let next = data.get(bit / 64 + 1).copied().unwrap_or(0) as u64;
+            value |= next << (64 - bit % 64);
+        }
+        return (value & mask) as usize;
+    }
+    let per_long = 64 / bits;
+    let long = data.get(index / per_long).copied().unwrap_or(0) as u64;
+    ((long >> (index % per_long * bits)) & mask) as usize
+}
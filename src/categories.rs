@@ -0,0 +1,125 @@
+use std::{collections::HashMap, fs, io, path::Path};
+
+/// Maps item ids to coarse categories for summary reports
+#[derive(Debug, Default, Clone)]
+pub struct Taxonomy {
+    overrides: HashMap<String, String>,
+}
+
+impl Taxonomy {
+    /// Reads a JSON object of item id to category, which takes precedence over the built-in
+    /// categories
+    pub fn from_file(path: &Path) -> Result<Self, io::Error> {
+        let overrides = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Ok(Taxonomy { overrides })
+    }
+
+    pub fn category<'a>(&'a self, id: &str) -> &'a str {
+        match self.overrides.get(id) {
+            Some(category) => category,
+            None => builtin_category(id),
+        }
+    }
+}
+
+/// Returns the built-in category of an item id, or `other`
+pub fn builtin_category(id: &str) -> &'static str {
+    let name = id.strip_prefix("minecraft:").unwrap_or(id);
+    match name {
+        "diamond"
+        | "diamond_block"
+        | "emerald"
+        | "emerald_block"
+        | "netherite_ingot"
+        | "netherite_block"
+        | "netherite_scrap"
+        | "ancient_debris"
+        | "gold_ingot"
+        | "gold_block"
+        | "nether_star"
+        | "beacon"
+        | "elytra"
+        | "totem_of_undying"
+        | "enchanted_golden_apple"
+        | "shulker_shell"
+        | "heavy_core"
+        | "enchanted_book" => "valuables",
+        "rotten_flesh" | "poisonous_potato" | "spider_eye" | "bone" | "string" | "arrow"
+        | "gunpowder" | "wheat_seeds" | "beetroot_seeds" | "dead_bush" | "stick" => "junk",
+        "redstone" | "redstone_block" | "redstone_torch" | "repeater" | "comparator"
+        | "observer" | "piston" | "sticky_piston" | "dispenser" | "dropper" | "hopper"
+        | "lever" | "target" | "daylight_detector" | "tripwire_hook" | "note_block"
+        | "slime_block" | "honey_block" | "tnt" | "redstone_lamp" | "crafter" => "redstone",
+        _ if name.ends_with("_button")
+            || name.ends_with("_pressure_plate")
+            || name.ends_with("rail") =>
+        {
+            "redstone"
+        }
+        "bow" | "crossbow" | "trident" | "shield" | "fishing_rod" | "flint_and_steel"
+        | "shears" | "mace" => "tools",
+        _ if name.ends_with("_sword")
+            || name.ends_with("_pickaxe")
+            || name.ends_with("_axe")
+            || name.ends_with("_shovel")
+            || name.ends_with("_hoe") =>
+        {
+            "tools"
+        }
+        "turtle_helmet" => "armor",
+        _ if name.ends_with("_helmet")
+            || name.ends_with("_chestplate")
+            || name.ends_with("_leggings")
+            || name.ends_with("_boots")
+            || name.ends_with("_horse_armor") =>
+        {
+            "armor"
+        }
+        "bread" | "apple" | "golden_apple" | "golden_carrot" | "carrot" | "potato"
+        | "baked_potato" | "beetroot" | "beetroot_soup" | "mushroom_stew" | "rabbit_stew"
+        | "suspicious_stew" | "melon_slice" | "sweet_berries" | "glow_berries" | "cookie"
+        | "pumpkin_pie" | "cake" | "honey_bottle" | "dried_kelp" | "chorus_fruit" | "beef"
+        | "porkchop" | "chicken" | "mutton" | "rabbit" | "cod" | "salmon" | "tropical_fish" => {
+            "food"
+        }
+        _ if name.starts_with("cooked_") => "food",
+        _ if name.ends_with("_planks")
+            || name.ends_with("_log")
+            || name.ends_with("_wood")
+            || name.ends_with("_stairs")
+            || name.ends_with("_slab")
+            || name.ends_with("_wall")
+            || name.ends_with("_bricks")
+            || name.ends_with("_concrete")
+            || name.ends_with("_terracotta")
+            || name.ends_with("_glass")
+            || name.ends_with("_wool")
+            || name.contains("stone")
+            || name.contains("deepslate")
+            || matches!(
+                name,
+                "dirt"
+                    | "sand"
+                    | "red_sand"
+                    | "gravel"
+                    | "glass"
+                    | "bricks"
+                    | "terracotta"
+                    | "netherrack"
+                    | "basalt"
+                    | "blackstone"
+                    | "tuff"
+                    | "calcite"
+                    | "quartz_block"
+                    | "prismarine"
+                    | "purpur_block"
+                    | "andesite"
+                    | "diorite"
+                    | "granite"
+            ) =>
+        {
+            "building blocks"
+        }
+        _ => "other",
+    }
+}
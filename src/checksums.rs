@@ -0,0 +1,71 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Computes the CRC32 checksum of a chunk payload
+pub fn checksum(payload: &[u8]) -> u32 {
+    crc32fast::hash(payload)
+}
+
+/// A JSON file of the checksums of chunk payloads together with the timestamps of the chunks
+/// they were computed for, kept between runs to notice payloads that changed although the game
+/// didn't save them. Paths are stored relative to the world directory.
+#[derive(Debug)]
+pub struct ChecksumStore {
+    path: PathBuf,
+    world_path: PathBuf,
+    regions: BTreeMap<String, BTreeMap<usize, (u32, u32)>>,
+}
+
+impl ChecksumStore {
+    /// Loads the checksums recorded by previous runs, or starts with none if the file doesn't
+    /// exist yet
+    pub fn open(path: &Path, world_path: &Path) -> Result<Self, io::Error> {
+        let regions = match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(ChecksumStore {
+            path: path.to_path_buf(),
+            world_path: world_path.to_path_buf(),
+            regions,
+        })
+    }
+
+    /// Records the checksum of a chunk payload. Returns the previously recorded checksum if it
+    /// differs although the chunk's timestamp is still the same.
+    pub fn check(
+        &mut self,
+        region_path: &Path,
+        index: usize,
+        timestamp: u32,
+        checksum: u32,
+    ) -> Option<u32> {
+        let region_path = region_path
+            .strip_prefix(&self.world_path)
+            .unwrap_or(region_path)
+            .to_string_lossy()
+            .into_owned();
+        let previous = self
+            .regions
+            .entry(region_path)
+            .or_default()
+            .insert(index, (timestamp, checksum));
+        match previous {
+            Some((previous_timestamp, previous_checksum))
+                if previous_timestamp == timestamp && previous_checksum != checksum =>
+            {
+                Some(previous_checksum)
+            }
+            _ => None,
+        }
+    }
+
+    /// Writes the checksums back to the file
+    pub fn save(&self) -> Result<(), io::Error> {
+        fs::write(&self.path, serde_json::to_string(&self.regions)?)
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::{BTreeMap, HashMap};
+
+use quartz_nbt::NbtCompound;
+use serde::Deserialize;
+
+use crate::{
+    blocks::{block_state_bits, packed_index},
+    version::{GameVersion, DATA_VERSION_1_18},
+};
+
+/// A terrain chunk, deserialized with [`deserialize_chunk`](crate::region::deserialize_chunk).
+/// Chunks from before 1.18 are read from their `Level` compound and their sections from the
+/// keys used back then, so that the same fields work for all versions since 1.13.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "RawChunk")]
+pub struct Chunk {
+    pub data_version: Option<i32>,
+    pub x: i32,
+    pub z: i32,
+    /// The generation status, like `minecraft:full`, or `full` before 1.20
+    pub status: Option<String>,
+    /// Ticks players spent near the chunk, summed over all players
+    pub inhabited_time: i64,
+    /// Game tick of the last save
+    pub last_update: i64,
+    pub sections: Vec<Section>,
+    pub block_entities: Vec<NbtCompound>,
+    /// The entities of proto chunks since 1.17, or of all chunks before
+    pub entities: Vec<NbtCompound>,
+    /// Packed heights by heightmap, like `MOTION_BLOCKING` or `WORLD_SURFACE`
+    pub heightmaps: HashMap<String, Vec<i64>>,
+}
+
+impl Chunk {
+    /// The chunk's version, assuming 1.18 if it has no DataVersion
+    pub fn version(&self) -> GameVersion {
+        GameVersion::new(self.data_version.unwrap_or(DATA_VERSION_1_18))
+    }
+
+    /// Whether the chunk is fully generated
+    pub fn is_full(&self) -> bool {
+        matches!(self.status.as_deref(), Some("full" | "minecraft:full"))
+    }
+
+    pub fn section(&self, y: i8) -> Option<&Section> {
+        self.sections.iter().find(|section| section.y == y)
+    }
+
+    /// Returns the block state at chunk local x and z and absolute y
+    pub fn block_state(&self, x: usize, y: i32, z: usize) -> Option<&BlockState> {
+        let section = self.section(y.div_euclid(16) as i8)?;
+        section.block_state(
+            (y.rem_euclid(16) as usize * 16 + z) * 16 + x,
+            self.version(),
+        )
+    }
+}
+
+/// A 16x16x16 section of a chunk
+#[derive(Debug, Clone)]
+pub struct Section {
+    pub y: i8,
+    /// None for sections without blocks and before 1.13, when blocks had numeric ids
+    pub block_states: Option<PalettedContainer<BlockState>>,
+    /// One biome per 4x4x4 cell, since 1.18
+    pub biomes: Option<PalettedContainer<String>>,
+}
+
+impl Section {
+    /// Returns the block state at `(y * 16 + z) * 16 + x`. The version decides whether indices
+    /// may span two longs.
+    pub fn block_state(&self, index: usize, version: GameVersion) -> Option<&BlockState> {
+        let block_states = self.block_states.as_ref()?;
+        let bits = block_state_bits(block_states.palette.len());
+        let spanning = version.spanning_block_states();
+        block_states
+            .palette
+            .get(packed_index(&block_states.data, bits, spanning, index))
+    }
+
+    /// Returns the biome of the cell at `(y * 4 + z) * 4 + x`
+    pub fn biome(&self, index: usize) -> Option<&str> {
+        let biomes = self.biomes.as_ref()?;
+        let bits = (usize::BITS - biomes.palette.len().saturating_sub(1).leading_zeros()) as usize;
+        let palette_index = match bits {
+            0 => 0,
+            bits => packed_index(&biomes.data, bits, false, index),
+        };
+        biomes
+            .palette
+            .get(palette_index)
+            .or(biomes.palette.first())
+            .map(String::as_str)
+    }
+}
+
+/// A palette with the indices into it packed into longs, which are left out if the palette has
+/// a single entry
+#[derive(Debug, Clone, Deserialize)]
+pub struct PalettedContainer<T> {
+    pub palette: Vec<T>,
+    #[serde(default)]
+    pub data: Vec<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct BlockState {
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Properties", default)]
+    pub properties: BTreeMap<String, String>,
+}
+
+/// The chunk as stored, with the chunk data in `Level` before 1.18
+#[derive(Deserialize)]
+struct RawChunk {
+    #[serde(rename = "DataVersion")]
+    data_version: Option<i32>,
+    #[serde(rename = "Level")]
+    level: Option<Box<RawChunk>>,
+    #[serde(rename = "xPos", default)]
+    x: i32,
+    #[serde(rename = "zPos", default)]
+    z: i32,
+    #[serde(rename = "Status")]
+    status: Option<String>,
+    #[serde(rename = "InhabitedTime", default)]
+    inhabited_time: i64,
+    #[serde(rename = "LastUpdate", default)]
+    last_update: i64,
+    #[serde(alias = "Sections", default)]
+    sections: Vec<RawSection>,
+    #[serde(alias = "TileEntities", default)]
+    block_entities: Vec<NbtCompound>,
+    #[serde(alias = "Entities", default)]
+    entities: Vec<NbtCompound>,
+    #[serde(rename = "Heightmaps", default)]
+    heightmaps: HashMap<String, Vec<i64>>,
+}
+
+#[derive(Deserialize)]
+struct RawSection {
+    #[serde(rename = "Y")]
+    y: i8,
+    block_states: Option<PalettedContainer<BlockState>>,
+    biomes: Option<PalettedContainer<String>>,
+    /// The palette and indices of the block states before 1.18
+    #[serde(rename = "Palette")]
+    palette: Option<Vec<BlockState>>,
+    #[serde(rename = "BlockStates", default)]
+    data: Vec<i64>,
+}
+
+impl From<RawChunk> for Chunk {
+    fn from(mut raw: RawChunk) -> Self {
+        let data_version = raw.data_version;
+        let level = match raw.level.take() {
+            Some(level) => *level,
+            None => raw,
+        };
+        Chunk {
+            data_version,
+            x: level.x,
+            z: level.z,
+            status: level.status,
+            inhabited_time: level.inhabited_time,
+            last_update: level.last_update,
+            sections: level
+                .sections
+                .into_iter()
+                .map(|section| Section {
+                    y: section.y,
+                    block_states: section.block_states.or_else(|| {
+                        Some(PalettedContainer {
+                            palette: section.palette?,
+                            data: section.data,
+                        })
+                    }),
+                    biomes: section.biomes,
+                })
+                .collect(),
+            block_entities: level.block_entities,
+            entities: level.entities,
+            heightmaps: level.heightmaps,
+        }
+    }
+}
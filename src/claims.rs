@@ -0,0 +1,255 @@
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde_json::Value;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ClaimsError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("{0}: {1}")]
+    Invalid(PathBuf, String),
+}
+
+/// The blocks covered by a claim
+#[derive(Debug, Clone)]
+pub enum ClaimArea {
+    /// Inclusive min and max block x/z
+    Box { min: (i32, i32), max: (i32, i32) },
+    /// Block x/z corners of a polygon
+    Polygon(Vec<(i32, i32)>),
+    /// Claimed chunks
+    Chunks(HashSet<(i32, i32)>),
+}
+
+/// A claim or protected region of a server plugin
+#[derive(Debug, Clone)]
+pub struct Claim {
+    pub name: String,
+    /// UUID or name of the owner
+    pub owner: Option<String>,
+    /// overworld, nether or end
+    pub dimension: String,
+    pub area: ClaimArea,
+}
+
+impl Claim {
+    pub fn contains(&self, dimension: &str, x: i32, z: i32) -> bool {
+        if self.dimension != dimension {
+            return false;
+        }
+        match &self.area {
+            ClaimArea::Box { min, max } => {
+                (min.0..=max.0).contains(&x) && (min.1..=max.1).contains(&z)
+            }
+            ClaimArea::Polygon(points) => polygon_contains(points, x as f64 + 0.5, z as f64 + 0.5),
+            ClaimArea::Chunks(chunks) => chunks.contains(&(x >> 4, z >> 4)),
+        }
+    }
+}
+
+/// Where to read claims from, given as `griefprevention:<ClaimData dir>`,
+/// `worldguard:<worlds/<world>/regions.yml>` or `lands:<export.json>`
+#[derive(Debug, Clone)]
+pub enum ClaimSource {
+    GriefPrevention(PathBuf),
+    WorldGuard(PathBuf),
+    Lands(PathBuf),
+}
+
+impl FromStr for ClaimSource {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let (kind, path) = str
+            .split_once(':')
+            .ok_or_else(|| "expected <plugin>:<path>".to_string())?;
+        let path = PathBuf::from(path);
+        match kind {
+            "griefprevention" => Ok(ClaimSource::GriefPrevention(path)),
+            "worldguard" => Ok(ClaimSource::WorldGuard(path)),
+            "lands" => Ok(ClaimSource::Lands(path)),
+            _ => Err(format!(
+                "unknown claims plugin {}, expected griefprevention, worldguard or lands",
+                kind
+            )),
+        }
+    }
+}
+
+impl ClaimSource {
+    pub fn read(&self) -> Result<Vec<Claim>, ClaimsError> {
+        match self {
+            ClaimSource::GriefPrevention(dir) => read_griefprevention(dir),
+            ClaimSource::WorldGuard(path) => read_worldguard(path),
+            ClaimSource::Lands(path) => read_lands(path),
+        }
+    }
+}
+
+/// Maps a Bukkit world folder name like `world_nether` to the dimension
+fn world_dimension(world: &str) -> &'static str {
+    if world.ends_with("_nether") {
+        "nether"
+    } else if world.ends_with("_the_end") {
+        "end"
+    } else {
+        "overworld"
+    }
+}
+
+/// Reads the claims of GriefPrevention's `ClaimData` directory, one YAML file per claim with the
+/// corners given as `world;x;y;z`
+pub fn read_griefprevention(dir: &Path) -> Result<Vec<Claim>, ClaimsError> {
+    let mut paths: Vec<_> = fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "yml"))
+        .collect();
+    paths.sort();
+
+    let mut claims = Vec::new();
+    for path in paths {
+        let data: Value = serde_yaml::from_str(&fs::read_to_string(&path)?)?;
+        let invalid = |message: &str| ClaimsError::Invalid(path.clone(), message.to_string());
+
+        let corner = |key: &str| -> Result<(String, i32, i32), ClaimsError> {
+            let value = data[key]
+                .as_str()
+                .ok_or_else(|| invalid("missing corner"))?;
+            let parts: Vec<&str> = value.split(';').collect();
+            match parts[..] {
+                [world, x, _, z] => Ok((
+                    world.to_string(),
+                    x.parse().map_err(|_| invalid("invalid corner"))?,
+                    z.parse().map_err(|_| invalid("invalid corner"))?,
+                )),
+                _ => Err(invalid("invalid corner")),
+            }
+        };
+        let (world, min_x, min_z) = corner("Lesser Boundary Corner")?;
+        let (_, max_x, max_z) = corner("Greater Boundary Corner")?;
+
+        claims.push(Claim {
+            name: path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or_default()
+                .to_string(),
+            // administrative claims have an empty owner
+            owner: data["Owner"]
+                .as_str()
+                .filter(|owner| !owner.is_empty())
+                .map(str::to_string),
+            dimension: world_dimension(&world).to_string(),
+            area: ClaimArea::Box {
+                min: (min_x.min(max_x), min_z.min(max_z)),
+                max: (min_x.max(max_x), min_z.max(max_z)),
+            },
+        });
+    }
+    Ok(claims)
+}
+
+/// Reads the cuboid and polygon regions of a WorldGuard `regions.yml`. The world is taken from
+/// the name of the directory containing the file.
+pub fn read_worldguard(path: &Path) -> Result<Vec<Claim>, ClaimsError> {
+    let data: Value = serde_yaml::from_str(&fs::read_to_string(path)?)?;
+    let world = path
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .and_then(|name| name.to_str())
+        .unwrap_or("world");
+
+    let regions = match data["regions"].as_object() {
+        Some(regions) => regions,
+        None => return Ok(Vec::new()),
+    };
+
+    let point = |value: &Value| -> Option<(i32, i32)> {
+        Some((value["x"].as_i64()? as i32, value["z"].as_i64()? as i32))
+    };
+
+    let mut claims = Vec::new();
+    for (name, region) in regions {
+        let area = match region["type"].as_str() {
+            Some("cuboid") => match (point(&region["min"]), point(&region["max"])) {
+                (Some(min), Some(max)) => ClaimArea::Box { min, max },
+                _ => {
+                    return Err(ClaimsError::Invalid(
+                        path.to_path_buf(),
+                        format!("region {} has no min and max", name),
+                    ))
+                }
+            },
+            Some("poly2d") => ClaimArea::Polygon(
+                region["points"]
+                    .as_array()
+                    .map(|points| points.iter().filter_map(point).collect())
+                    .unwrap_or_default(),
+            ),
+            // the global region has no area
+            _ => continue,
+        };
+        let owners = &region["owners"];
+        let owner = owners["unique-ids"][0]
+            .as_str()
+            .or_else(|| owners["players"][0].as_str())
+            .map(str::to_string);
+        claims.push(Claim {
+            name: name.clone(),
+            owner,
+            dimension: world_dimension(world).to_string(),
+            area,
+        });
+    }
+    Ok(claims)
+}
+
+/// Reads lands exported as a JSON array of objects with `name`, `owner`, `world` and `chunks`
+/// as a list of `[x, z]`
+pub fn read_lands(path: &Path) -> Result<Vec<Claim>, ClaimsError> {
+    let data: Vec<Value> = serde_json::from_str(&fs::read_to_string(path)?)?;
+    Ok(data
+        .iter()
+        .map(|land| Claim {
+            name: land["name"].as_str().unwrap_or_default().to_string(),
+            owner: land["owner"].as_str().map(str::to_string),
+            dimension: world_dimension(land["world"].as_str().unwrap_or("world")).to_string(),
+            area: ClaimArea::Chunks(
+                land["chunks"]
+                    .as_array()
+                    .map(|chunks| {
+                        chunks
+                            .iter()
+                            .filter_map(|chunk| {
+                                Some((chunk[0].as_i64()? as i32, chunk[1].as_i64()? as i32))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            ),
+        })
+        .collect())
+}
+
+/// Even-odd rule point in polygon test
+fn polygon_contains(points: &[(i32, i32)], x: f64, z: f64) -> bool {
+    let mut inside = false;
+    for (i, &(x1, z1)) in points.iter().enumerate() {
+        let (x2, z2) = points[(i + 1) % points.len()];
+        let (x1, z1, x2, z2) = (x1 as f64, z1 as f64, x2 as f64, z2 as f64);
+        if (z1 > z) != (z2 > z) && x < (x2 - x1) * (z - z1) / (z2 - z1) + x1 {
+            inside = !inside;
+        }
+    }
+    inside
+}
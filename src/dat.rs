@@ -0,0 +1,351 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use quartz_nbt::{
+    io::{Flavor, NbtIoError},
+    NbtCompound, NbtList, NbtReprError, NbtTag,
+};
+
+use crate::items::map_id;
+
+/// Reads a `.dat` file, which is usually gzip compressed but may also be uncompressed
+pub fn read_dat(path: &Path) -> Result<NbtCompound, NbtIoError> {
+    let buf = fs::read(path)?;
+    let flavor = if buf.starts_with(&[0x1f, 0x8b]) {
+        Flavor::GzCompressed
+    } else {
+        Flavor::Uncompressed
+    };
+    Ok(quartz_nbt::io::read_nbt(&mut io::Cursor::new(buf), flavor)?.0)
+}
+
+/// Writes a gzip compressed `.dat` file
+pub fn write_dat(path: &Path, compound: &NbtCompound) -> Result<(), NbtIoError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    quartz_nbt::io::write_nbt(&mut writer, None, compound, Flavor::GzCompressed)?;
+    Ok(())
+}
+
+/// An error reading a `.dat` file into one of the typed structs
+#[derive(thiserror::Error, Debug)]
+pub enum DatError {
+    #[error(transparent)]
+    Io(#[from] NbtIoError),
+    #[error(transparent)]
+    Repr(#[from] NbtReprError),
+}
+
+/// The settings and state of a world from its `level.dat`
+#[derive(Debug, Clone)]
+pub struct LevelDat {
+    pub data_version: Option<i32>,
+    /// The name of the game version that last saved the world, like `1.20.4`, since 1.9
+    pub version_name: Option<String>,
+    pub level_name: Option<String>,
+    /// From `WorldGenSettings` since 1.16 and `RandomSeed` before
+    pub seed: Option<i64>,
+    /// The game rules by name, with their values as the game stores them, like `true` or `3`
+    pub game_rules: BTreeMap<String, String>,
+    pub world_border: WorldBorder,
+    /// The world spawn in the overworld
+    pub spawn: [i32; 3],
+    /// The ids of the enabled datapacks like `vanilla` or `file/my_pack.zip`, since 1.13
+    pub enabled_datapacks: Vec<String>,
+    pub disabled_datapacks: Vec<String>,
+    /// When the world was last saved, in milliseconds since the unix epoch
+    pub last_played: Option<i64>,
+    /// The whole `Data` compound, for the tags without a field of their own
+    pub data: NbtCompound,
+}
+
+/// The world border in the overworld, which the nether's is scaled down from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldBorder {
+    pub center_x: f64,
+    pub center_z: f64,
+    /// Width of the square in blocks
+    pub size: f64,
+    /// Damage per block outside of the safe zone per second
+    pub damage_per_block: f64,
+    /// Blocks outside of the border in which players take no damage
+    pub safe_zone: f64,
+    pub warning_blocks: f64,
+    pub warning_time: f64,
+}
+
+impl Default for WorldBorder {
+    fn default() -> Self {
+        WorldBorder {
+            center_x: 0.0,
+            center_z: 0.0,
+            size: 59999968.0,
+            damage_per_block: 0.2,
+            safe_zone: 5.0,
+            warning_blocks: 5.0,
+            warning_time: 15.0,
+        }
+    }
+}
+
+impl LevelDat {
+    /// Reads a `level.dat` file
+    pub fn read(path: &Path) -> Result<Self, DatError> {
+        Self::from_nbt(&read_dat(path)?)
+    }
+
+    /// Reads the fields from the root compound of a `level.dat`
+    pub fn from_nbt(root: &NbtCompound) -> Result<Self, DatError> {
+        let data = root.get::<_, &NbtCompound>("Data")?.clone();
+        let string = |compound: &NbtCompound, key: &str| {
+            compound.get::<_, &str>(key).ok().map(str::to_string)
+        };
+
+        let seed = data
+            .get::<_, &NbtCompound>("WorldGenSettings")
+            .and_then(|settings| settings.get::<_, i64>("seed"))
+            .or_else(|_| data.get::<_, i64>("RandomSeed"))
+            .ok();
+
+        let game_rules = data
+            .get::<_, &NbtCompound>("GameRules")
+            .map(|rules| {
+                rules
+                    .inner()
+                    .iter()
+                    .filter_map(|(name, value)| {
+                        let value = match value {
+                            NbtTag::String(value) => value.clone(),
+                            NbtTag::Byte(value) => (*value != 0).to_string(),
+                            NbtTag::Int(value) => value.to_string(),
+                            _ => return None,
+                        };
+                        Some((name.clone(), value))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let default_border = WorldBorder::default();
+        let border = |key: &str, default: f64| data.get::<_, f64>(key).unwrap_or(default);
+        let world_border = WorldBorder {
+            center_x: border("BorderCenterX", default_border.center_x),
+            center_z: border("BorderCenterZ", default_border.center_z),
+            size: border("BorderSize", default_border.size),
+            damage_per_block: border("BorderDamagePerBlock", default_border.damage_per_block),
+            safe_zone: border("BorderSafeZone", default_border.safe_zone),
+            warning_blocks: border("BorderWarningBlocks", default_border.warning_blocks),
+            warning_time: border("BorderWarningTime", default_border.warning_time),
+        };
+
+        // the spawn became a compound with the position as an int array in 1.21.9
+        let spawn = match data
+            .get::<_, &NbtCompound>("spawn")
+            .and_then(|spawn| spawn.get::<_, &[i32]>("pos"))
+        {
+            Ok(&[x, y, z]) => [x, y, z],
+            _ => ["SpawnX", "SpawnY", "SpawnZ"].map(|key| data.get(key).unwrap_or_default()),
+        };
+
+        let datapacks = |key: &str| -> Vec<String> {
+            data.get::<_, &NbtCompound>("DataPacks")
+                .and_then(|packs| packs.get::<_, &NbtList>(key))
+                .map(|packs| {
+                    packs
+                        .iter_map::<&str>()
+                        .flatten()
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        Ok(LevelDat {
+            data_version: data.get("DataVersion").ok(),
+            version_name: data
+                .get::<_, &NbtCompound>("Version")
+                .ok()
+                .and_then(|version| string(version, "Name")),
+            level_name: string(&data, "LevelName"),
+            seed,
+            game_rules,
+            world_border,
+            spawn,
+            enabled_datapacks: datapacks("Enabled"),
+            disabled_datapacks: datapacks("Disabled"),
+            last_played: data.get("LastPlayed").ok(),
+            data,
+        })
+    }
+
+    /// Returns the value of a game rule like `doDaylightCycle`
+    pub fn game_rule(&self, name: &str) -> Option<&str> {
+        self.game_rules.get(name).map(String::as_str)
+    }
+}
+
+/// Width and height of a map in pixels
+pub const MAP_SIZE: usize = 128;
+
+/// The colors of the map base colors by their index, which the map colors are four shades of.
+/// Index 0 is transparent.
+const MAP_BASE_COLORS: [[u8; 3]; 62] = [
+    [0, 0, 0],
+    [127, 178, 56],
+    [247, 233, 163],
+    [199, 199, 199],
+    [255, 0, 0],
+    [160, 160, 255],
+    [167, 167, 167],
+    [0, 124, 0],
+    [255, 255, 255],
+    [164, 168, 184],
+    [151, 109, 77],
+    [112, 112, 112],
+    [64, 64, 255],
+    [143, 119, 72],
+    [255, 252, 245],
+    [216, 127, 51],
+    [178, 76, 216],
+    [102, 153, 216],
+    [229, 229, 51],
+    [127, 204, 25],
+    [242, 127, 165],
+    [76, 76, 76],
+    [153, 153, 153],
+    [76, 127, 153],
+    [127, 63, 178],
+    [51, 76, 178],
+    [102, 76, 51],
+    [102, 127, 51],
+    [153, 51, 51],
+    [25, 25, 25],
+    [250, 238, 77],
+    [92, 219, 213],
+    [74, 128, 255],
+    [0, 217, 58],
+    [129, 86, 49],
+    [112, 2, 0],
+    [209, 177, 161],
+    [159, 82, 36],
+    [149, 87, 108],
+    [112, 108, 138],
+    [186, 133, 36],
+    [103, 117, 53],
+    [160, 77, 78],
+    [57, 41, 35],
+    [135, 107, 98],
+    [87, 92, 92],
+    [122, 73, 88],
+    [76, 62, 92],
+    [76, 50, 35],
+    [76, 82, 42],
+    [142, 60, 46],
+    [37, 22, 16],
+    [189, 48, 49],
+    [148, 63, 97],
+    [92, 25, 29],
+    [22, 126, 134],
+    [58, 142, 140],
+    [86, 44, 62],
+    [20, 180, 133],
+    [100, 100, 100],
+    [216, 175, 147],
+    [127, 167, 150],
+];
+
+/// The color transparent map pixels are drawn in, like the paper of an empty map
+pub const MAP_BACKGROUND: [u8; 3] = [0xd6, 0xbe, 0x96];
+
+/// Returns the RGB color of a map color, or None for transparent and unknown colors. The lower
+/// two bits select one of four shades of the base color in the upper bits.
+pub fn map_color(color: u8) -> Option<[u8; 3]> {
+    let base = MAP_BASE_COLORS
+        .get(color as usize >> 2)
+        .filter(|_| color >> 2 != 0)?;
+    let shade = [180, 220, 255, 135][color as usize & 3];
+    Some(base.map(|channel| (channel as u32 * shade / 255) as u8))
+}
+
+/// A map from `data/map_<id>.dat`, which filled map items refer to by their id
+#[derive(Debug, Clone)]
+pub struct MapData {
+    /// The id of the dimension like `minecraft:the_nether`, which was a number before 1.16
+    pub dimension: String,
+    pub center_x: i32,
+    pub center_z: i32,
+    /// From 0 for one block per pixel to 4 for 16x16 blocks per pixel
+    pub scale: i8,
+    /// Whether the map was locked in a cartography table, like most map art
+    pub locked: bool,
+    /// One color per pixel row by row, see [`map_color`]
+    pub colors: Vec<u8>,
+}
+
+impl MapData {
+    /// Reads a map file
+    pub fn read(path: &Path) -> Result<Self, DatError> {
+        Self::from_nbt(&read_dat(path)?)
+    }
+
+    /// Reads the map with the given id from the `data` folder of a world
+    pub fn read_id(world_path: &Path, id: i32) -> Result<Self, DatError> {
+        Self::read(&map_path(world_path, id))
+    }
+
+    /// Reads the map a filled map item shows, or returns None if it isn't a filled map
+    pub fn for_item(world_path: &Path, item: &NbtCompound) -> Option<Result<Self, DatError>> {
+        Some(Self::read_id(world_path, map_id(item)?))
+    }
+
+    /// Reads the fields from the root compound of a map file
+    pub fn from_nbt(root: &NbtCompound) -> Result<Self, DatError> {
+        let data = root.get::<_, &NbtCompound>("data")?;
+        let dimension = match data.inner().get("dimension") {
+            Some(NbtTag::String(id)) => id.clone(),
+            Some(NbtTag::Byte(-1)) | Some(NbtTag::Int(-1)) => "minecraft:the_nether".to_string(),
+            Some(NbtTag::Byte(1)) | Some(NbtTag::Int(1)) => "minecraft:the_end".to_string(),
+            _ => "minecraft:overworld".to_string(),
+        };
+        let mut colors: Vec<u8> = data
+            .get::<_, &[i8]>("colors")
+            .map(|colors| colors.iter().map(|&color| color as u8).collect())
+            .unwrap_or_default();
+        colors.resize(MAP_SIZE * MAP_SIZE, 0);
+        Ok(MapData {
+            dimension,
+            center_x: data.get("xCenter").unwrap_or_default(),
+            center_z: data.get("zCenter").unwrap_or_default(),
+            scale: data.get("scale").unwrap_or_default(),
+            locked: data.get::<_, bool>("locked").unwrap_or_default(),
+            colors,
+        })
+    }
+
+    /// Number of blocks along each side of a pixel
+    pub fn blocks_per_pixel(&self) -> i32 {
+        1 << self.scale.clamp(0, 4)
+    }
+
+    /// The block x and z of the north west corner of the area the map shows
+    pub fn corner(&self) -> (i32, i32) {
+        let half = MAP_SIZE as i32 / 2 * self.blocks_per_pixel();
+        (self.center_x - half, self.center_z - half)
+    }
+
+    /// Returns the RGB pixels row by row, with transparent pixels in [`MAP_BACKGROUND`]
+    pub fn render(&self) -> Vec<u8> {
+        self.colors
+            .iter()
+            .flat_map(|&color| map_color(color).unwrap_or(MAP_BACKGROUND))
+            .collect()
+    }
+}
+
+/// The path of the map file with the given id
+pub fn map_path(world_path: &Path, id: i32) -> PathBuf {
+    world_path.join("data").join(format!("map_{}.dat", id))
+}
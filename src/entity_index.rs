@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use quartz_nbt::{NbtCompound, NbtList};
+
+use crate::uuid::{format_uuid, format_uuid_longs};
+
+/// An entity found while building an [`EntityIndex`]
+#[derive(Debug, Clone)]
+pub struct IndexedEntity {
+    pub uuid: String,
+    pub id: String,
+    pub dimension: String,
+    pub pos: [i32; 3],
+    /// UUID of the entity this one is riding
+    pub vehicle: Option<String>,
+    /// UUIDs this entity refers to, by the tag they are stored in
+    pub references: Vec<(&'static str, String)>,
+}
+
+/// A reference from one entity to another, see [`EntityIndex::references`]
+pub struct Reference<'a> {
+    pub from: &'a IndexedEntity,
+    /// Tag the reference is stored in, or `vehicle` for riding entities
+    pub kind: &'static str,
+    pub uuid: &'a str,
+    /// The entities with the UUID, empty if it doesn't belong to an entity, e.g. for players
+    pub targets: Vec<&'a IndexedEntity>,
+}
+
+/// Every entity of a world by its UUID, to look entities up, find duplicate UUIDs and resolve
+/// the UUIDs entities refer to
+#[derive(Default)]
+pub struct EntityIndex {
+    entities: Vec<IndexedEntity>,
+    by_uuid: HashMap<String, Vec<usize>>,
+}
+
+impl EntityIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the entities of a chunk from the entities folder, or of a region chunk from before
+    /// 1.17, including their passengers
+    pub fn add_chunk(&mut self, dimension: &str, chunk: &NbtCompound) {
+        let entities = chunk.get::<_, &NbtList>("Entities").or_else(|_| {
+            chunk
+                .get::<_, &NbtCompound>("Level")
+                .and_then(|level| level.get::<_, &NbtList>("Entities"))
+        });
+        if let Ok(entities) = entities {
+            for entity in entities.iter_map::<&NbtCompound>().flatten() {
+                self.add_entity(dimension, entity, None);
+            }
+        }
+    }
+
+    fn add_entity(&mut self, dimension: &str, entity: &NbtCompound, vehicle: Option<&str>) {
+        let Some(uuid) = entity_uuid(entity) else {
+            return;
+        };
+
+        if let Ok(passengers) = entity.get::<_, &NbtList>("Passengers") {
+            for passenger in passengers.iter_map::<&NbtCompound>().flatten() {
+                self.add_entity(dimension, passenger, Some(&uuid));
+            }
+        }
+
+        let pos = entity
+            .get::<_, &NbtList>("Pos")
+            .ok()
+            .and_then(|pos| {
+                let pos: Vec<f64> = pos.iter_map().flatten().collect();
+                Some([pos.first()?, pos.get(1)?, pos.get(2)?].map(|v| v.floor() as i32))
+            })
+            .unwrap_or_default();
+
+        let mut references = Vec::new();
+        for tag in ["Owner", "Thrower", "Leash", "leash"] {
+            let uuid = entity
+                .get::<_, &[i32]>(tag)
+                .ok()
+                .or_else(|| {
+                    entity
+                        .get::<_, &NbtCompound>(tag)
+                        .and_then(|compound| compound.get::<_, &[i32]>("UUID"))
+                        .ok()
+                })
+                .and_then(format_uuid);
+            if let Some(uuid) = uuid {
+                references.push((tag, uuid));
+            }
+        }
+        if let Ok(owner) = entity.get::<_, &str>("OwnerUUID") {
+            if !owner.is_empty() {
+                references.push(("OwnerUUID", owner.to_string()));
+            }
+        }
+
+        self.by_uuid
+            .entry(uuid.clone())
+            .or_default()
+            .push(self.entities.len());
+        self.entities.push(IndexedEntity {
+            uuid,
+            id: entity.get::<_, &str>("id").unwrap_or("unknown").to_string(),
+            dimension: dimension.to_string(),
+            pos,
+            vehicle: vehicle.map(str::to_string),
+            references,
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Returns the entities with the UUID, more than one if it's duplicated
+    pub fn find(&self, uuid: &str) -> impl Iterator<Item = &IndexedEntity> {
+        self.by_uuid
+            .get(uuid)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.entities[i])
+    }
+
+    /// Returns the UUIDs that belong to more than one entity, which makes the game fail to load
+    /// the later ones, sorted by UUID
+    pub fn duplicates(&self) -> Vec<(&str, Vec<&IndexedEntity>)> {
+        let mut duplicates: Vec<_> = self
+            .by_uuid
+            .iter()
+            .filter(|(_, indices)| indices.len() > 1)
+            .map(|(uuid, indices)| {
+                let entities = indices.iter().map(|&i| &self.entities[i]).collect();
+                (uuid.as_str(), entities)
+            })
+            .collect();
+        duplicates.sort_by_key(|(uuid, _)| *uuid);
+        duplicates
+    }
+
+    /// Returns the owner, leash holder, thrower and vehicle references of all entities
+    pub fn references(&self) -> impl Iterator<Item = Reference<'_>> {
+        self.entities.iter().flat_map(move |entity| {
+            let vehicle = entity.vehicle.as_deref().map(|uuid| ("vehicle", uuid));
+            let tags = entity
+                .references
+                .iter()
+                .map(|(kind, uuid)| (*kind, uuid.as_str()));
+            vehicle
+                .into_iter()
+                .chain(tags)
+                .map(move |(kind, uuid)| Reference {
+                    from: entity,
+                    kind,
+                    uuid,
+                    targets: self.find(uuid).collect(),
+                })
+        })
+    }
+}
+
+/// Returns the UUID of the player owning a tamed entity, from the int array `Owner` since 1.16 or
+/// the string `OwnerUUID` before
+pub fn owner_uuid(entity: &NbtCompound) -> Option<String> {
+    if let Ok(owner) = entity.get::<_, &[i32]>("Owner") {
+        return format_uuid(owner);
+    }
+    let owner = entity
+        .get::<_, &str>("OwnerUUID")
+        .or_else(|_| entity.get::<_, &str>("Owner"))
+        .ok()?;
+    // untamed entities have an empty string, and very old ones the owner's name
+    (owner.len() == 36 && owner.chars().filter(|&c| c == '-').count() == 4)
+        .then(|| owner.to_lowercase())
+}
+
+/// Returns the UUID of an entity from its `UUID` tag, or `UUIDMost` and `UUIDLeast` before 1.16
+pub fn entity_uuid(entity: &NbtCompound) -> Option<String> {
+    if let Ok(uuid) = entity.get::<_, &[i32]>("UUID") {
+        return format_uuid(uuid);
+    }
+    let most = entity.get::<_, i64>("UUIDMost").ok()?;
+    let least = entity.get::<_, i64>("UUIDLeast").ok()?;
+    Some(format_uuid_longs(most, least))
+}
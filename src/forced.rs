@@ -0,0 +1,164 @@
+use std::path::Path;
+
+use quartz_nbt::{io::NbtIoError, NbtCompound, NbtList};
+
+use crate::{dat::read_dat, uuid::format_uuid};
+
+/// A chunk force loaded by the `/forceload` command or by a mod through Forge's chunk manager
+#[derive(Debug, Clone)]
+pub struct ForcedChunk {
+    pub chunk: (i32, i32),
+    /// The mod holding the ticket, `None` for `/forceload`
+    pub mod_id: Option<String>,
+    /// Positions of the blocks holding tickets
+    pub blocks: Vec<[i32; 3]>,
+    /// UUIDs of the entities holding tickets
+    pub entities: Vec<String>,
+    /// Whether the chunk is also ticked like a chunk near a player
+    pub ticking: bool,
+}
+
+/// A chunk loading ticket of Forge before 1.13, from `forcedchunks.dat`. The chunks are not
+/// stored, mods force them again from their ticket data when the world loads.
+#[derive(Debug, Clone)]
+pub struct LegacyTicket {
+    pub owner: String,
+    pub mod_id: String,
+    pub player: Option<String>,
+    /// UUID and chunk of the entity the ticket is bound to
+    pub entity: Option<(String, (i32, i32))>,
+}
+
+/// Unpacks a chunk position stored as a long with x in the lower and z in the upper 32 bits
+fn unpack_chunk(value: i64) -> (i32, i32) {
+    (value as i32, (value >> 32) as i32)
+}
+
+/// Reads the force loaded chunks of a dimension from `data/chunks.dat`, including the tickets
+/// Forge stores under `ForgeForced`. Returns an empty list if the file doesn't exist.
+pub fn read_forced_chunks(dim_path: &Path) -> Result<Vec<ForcedChunk>, NbtIoError> {
+    let path = dim_path.join("data/chunks.dat");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let root = read_dat(&path)?;
+    let data = match root.get::<_, &NbtCompound>("data") {
+        Ok(data) => data,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let mut chunks: Vec<ForcedChunk> = data
+        .get::<_, &[i64]>("Forced")
+        .unwrap_or(&[])
+        .iter()
+        .map(|&value| ForcedChunk {
+            chunk: unpack_chunk(value),
+            mod_id: None,
+            blocks: Vec::new(),
+            entities: Vec::new(),
+            ticking: true,
+        })
+        .collect();
+
+    let mods = match data.get::<_, &NbtList>("ForgeForced") {
+        Ok(mods) => mods,
+        Err(_) => return Ok(chunks),
+    };
+    for entry in mods.iter_map::<&NbtCompound>().flatten() {
+        let mod_id = entry.get::<_, &str>("Mod").unwrap_or("unknown");
+        let forced = match entry.get::<_, &NbtList>("ModForced") {
+            Ok(forced) => forced,
+            Err(_) => continue,
+        };
+        for forced in forced.iter_map::<&NbtCompound>().flatten() {
+            let list = |key: &str| {
+                forced
+                    .get::<_, &NbtList>(key)
+                    .map(|list| {
+                        list.iter_map::<&NbtCompound>()
+                            .flatten()
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default()
+            };
+            let block_pos = |pos: &NbtCompound| {
+                ["X", "Y", "Z"].map(|key| pos.get::<_, i32>(key).unwrap_or_default())
+            };
+
+            let ticking_blocks = list("TickingBlocks");
+            let ticking_entities = forced
+                .get::<_, &NbtList>("TickingEntities")
+                .map(|list| list.len())
+                .unwrap_or_default();
+            let mut blocks: Vec<_> = list("Blocks").into_iter().map(block_pos).collect();
+            blocks.extend(ticking_blocks.iter().copied().map(block_pos));
+            let entities = ["Entities", "TickingEntities"]
+                .iter()
+                .filter_map(|key| forced.get::<_, &NbtList>(*key).ok())
+                .flat_map(|list| {
+                    list.iter_map::<&[i32]>()
+                        .flatten()
+                        .filter_map(format_uuid)
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+
+            chunks.push(ForcedChunk {
+                chunk: unpack_chunk(forced.get("Chunk").unwrap_or_default()),
+                mod_id: Some(mod_id.to_string()),
+                blocks,
+                entities,
+                ticking: !ticking_blocks.is_empty() || ticking_entities > 0,
+            });
+        }
+    }
+    Ok(chunks)
+}
+
+/// Reads the tickets of Forge's `forcedchunks.dat` from before 1.13. Returns an empty list if
+/// the file doesn't exist.
+pub fn read_legacy_tickets(dim_path: &Path) -> Result<Vec<LegacyTicket>, NbtIoError> {
+    let path = dim_path.join("forcedchunks.dat");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let root = read_dat(&path)?;
+
+    let mut tickets = Vec::new();
+    let owners = root.get::<_, &NbtList>("TicketList");
+    for owner in owners
+        .iter()
+        .flat_map(|owners| owners.iter_map::<&NbtCompound>().flatten())
+    {
+        let owner_name = owner.get::<_, &str>("Owner").unwrap_or("unknown");
+        let list = match owner.get::<_, &NbtList>("Tickets") {
+            Ok(list) => list,
+            Err(_) => continue,
+        };
+        for ticket in list.iter_map::<&NbtCompound>().flatten() {
+            // type 1 tickets are bound to an entity
+            let entity = (ticket.get::<_, i8>("Type").ok() == Some(1)).then(|| {
+                let msb = ticket.get::<_, i64>("PersistentIDMSB").unwrap_or_default();
+                let lsb = ticket.get::<_, i64>("PersistentIDLSB").unwrap_or_default();
+                let ints = [msb >> 32, msb, lsb >> 32, lsb].map(|int| int as i32);
+                (
+                    format_uuid(&ints).unwrap_or_default(),
+                    (
+                        ticket.get::<_, i32>("chunkX").unwrap_or_default(),
+                        ticket.get::<_, i32>("chunkZ").unwrap_or_default(),
+                    ),
+                )
+            });
+            tickets.push(LegacyTicket {
+                owner: owner_name.to_string(),
+                mod_id: ticket
+                    .get::<_, &str>("ModId")
+                    .unwrap_or(owner_name)
+                    .to_string(),
+                player: ticket.get::<_, &str>("Player").ok().map(str::to_string),
+                entity,
+            });
+        }
+    }
+    Ok(tickets)
+}
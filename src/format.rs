@@ -0,0 +1,106 @@
+use std::str::FromStr;
+
+/// Separators used for formatting numbers for humans
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberLocale {
+    pub thousands: char,
+    pub decimal: char,
+}
+
+impl FromStr for NumberLocale {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let (thousands, decimal) = match str {
+            "en" => (',', '.'),
+            "de" | "nl" | "it" | "es" => ('.', ','),
+            "fr" | "pl" | "ru" => ('\u{202f}', ','),
+            "ch" => ('\'', '.'),
+            _ => return Err(format!("unknown locale {}, expected en, de, fr or ch", str)),
+        };
+        Ok(NumberLocale { thousands, decimal })
+    }
+}
+
+impl Default for NumberLocale {
+    fn default() -> Self {
+        NumberLocale {
+            thousands: ',',
+            decimal: '.',
+        }
+    }
+}
+
+/// Formats a number with thousands separators, like `1,234,567`
+pub fn format_thousands(value: i64, locale: NumberLocale) -> String {
+    let digits = value.unsigned_abs().to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+    if value < 0 {
+        formatted.push('-');
+    }
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(locale.thousands);
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// Formats a number with a suffix and one decimal, like `1.2M`
+pub fn format_compact(value: i64, locale: NumberLocale) -> String {
+    let abs = value.unsigned_abs() as f64;
+    let (scaled, suffix) = match abs {
+        _ if abs >= 1e9 => (abs / 1e9, "B"),
+        _ if abs >= 1e6 => (abs / 1e6, "M"),
+        _ if abs >= 1e3 => (abs / 1e3, "k"),
+        _ => return value.to_string(),
+    };
+    let sign = if value < 0 { "-" } else { "" };
+    let number = format!("{:.1}", scaled).replace('.', &locale.decimal.to_string());
+    format!("{}{}{}", sign, number, suffix)
+}
+
+/// Formats an item count as full stacks and remainder, like `27s + 12`. Items that don't stack
+/// are just counted.
+pub fn format_stacks(count: u64, stack_size: u64) -> String {
+    if stack_size <= 1 {
+        return count.to_string();
+    }
+    let (stacks, remainder) = (count / stack_size, count % stack_size);
+    match (stacks, remainder) {
+        (0, remainder) => remainder.to_string(),
+        (stacks, 0) => format!("{}s", stacks),
+        (stacks, remainder) => format!("{}s + {}", stacks, remainder),
+    }
+}
+
+/// The maximum stack size of the more common items that don't stack to 64
+pub fn stack_size(id: &str) -> u64 {
+    let name = id.strip_prefix("minecraft:").unwrap_or(id);
+    match name {
+        "ender_pearl" | "egg" | "snowball" | "honey_bottle" | "armor_stand" | "bucket"
+        | "written_book" => 16,
+        _ if name.ends_with("_sign") || name.ends_with("_banner") => 16,
+        "enchanted_book" | "totem_of_undying" | "elytra" | "saddle" | "potion" | "cake"
+        | "trident" | "shield" | "bow" | "crossbow" | "writable_book" => 1,
+        _ if name.ends_with("shulker_box")
+            || name.ends_with("_bucket")
+            || name.ends_with("_bed")
+            || name.ends_with("_boat")
+            || name.ends_with("_minecart")
+            || name.ends_with("_sword")
+            || name.ends_with("_pickaxe")
+            || name.ends_with("_axe")
+            || name.ends_with("_shovel")
+            || name.ends_with("_hoe")
+            || name.ends_with("_helmet")
+            || name.ends_with("_chestplate")
+            || name.ends_with("_leggings")
+            || name.ends_with("_boots") =>
+        {
+            1
+        }
+        _ => 64,
+    }
+}
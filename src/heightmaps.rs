@@ -0,0 +1,74 @@
+use quartz_nbt::{NbtCompound, NbtTag};
+
+use crate::{
+    blocks::packed_index,
+    version::{GameVersion, DATA_VERSION_1_18},
+};
+
+/// The heights of the 16x16 block columns of a chunk from one of its `Heightmaps`, like
+/// `MOTION_BLOCKING` or `WORLD_SURFACE`. A height is the y of the block above the highest block
+/// the heightmap counts, so the bottom of the world for empty columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heightmap {
+    heights: Vec<i32>,
+}
+
+impl Heightmap {
+    /// Reads a heightmap in the format of the chunk's DataVersion, assuming 1.18 if it is missing
+    pub fn from_chunk(chunk: &NbtCompound, name: &str) -> Option<Self> {
+        let version = GameVersion::of(chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+        Self::from_chunk_as(chunk, name, version)
+    }
+
+    /// Reads a heightmap in the format of the given version. Before 1.13 chunks only had the
+    /// single `HeightMap` of the lowest block with full sky light, which is returned for any name.
+    pub fn from_chunk_as(chunk: &NbtCompound, name: &str, version: GameVersion) -> Option<Self> {
+        let level = version.level(chunk)?;
+        if !version.named_block_states() {
+            let heights: &[i32] = level.get("HeightMap").ok()?;
+            return (heights.len() == 256).then(|| Heightmap {
+                heights: heights.to_vec(),
+            });
+        }
+        let data: &[i64] = level
+            .get::<_, &NbtCompound>("Heightmaps")
+            .ok()?
+            .get(name)
+            .ok()?;
+        // the bottom of the world is the lowest section since 1.18
+        let min_y = match level.inner().get("yPos") {
+            Some(NbtTag::Int(y)) => y * 16,
+            _ if version.nested_block_states() => -64,
+            _ => 0,
+        };
+        Self::unpack(data, min_y, version)
+    }
+
+    /// Unpacks the 256 heights from the longs of a heightmap. The number of bits per height
+    /// depends on the height of the dimension and is worked out from the number of longs.
+    pub fn unpack(data: &[i64], min_y: i32, version: GameVersion) -> Option<Self> {
+        let spanning = version.spanning_block_states();
+        let bits = if spanning {
+            data.len() * 64 / 256
+        } else {
+            64 / 256usize.div_ceil(data.len().max(1))
+        };
+        if data.is_empty() || bits == 0 || bits > 32 {
+            return None;
+        }
+        let heights = (0..256)
+            .map(|index| packed_index(data, bits, spanning, index) as i32 + min_y)
+            .collect();
+        Some(Heightmap { heights })
+    }
+
+    /// Returns the height at chunk local x and z
+    pub fn get(&self, x: usize, z: usize) -> i32 {
+        self.heights[z * 16 + x]
+    }
+
+    /// Returns the heights of all columns in the order of `z * 16 + x`
+    pub fn heights(&self) -> &[i32] {
+        &self.heights
+    }
+}
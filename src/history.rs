@@ -0,0 +1,292 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use indexmap::IndexMap;
+use quartz_nbt::{NbtCompound, NbtList};
+
+use crate::{
+    dat::read_dat,
+    items::{fingerprint, for_each_item, id_and_count},
+    players::INVENTORY_KEYS,
+    region::for_each_chunk_in_dir,
+    version::{GameVersion, DATA_VERSION_1_18},
+    world::dimension_path,
+};
+
+pub type Snapshot = IndexMap<String, u64>;
+
+/// Number of items per stack [`fingerprint`](crate::items::fingerprint)
+pub type Fingerprints = IndexMap<String, u64>;
+
+/// A directory of count-items snapshots named by their unix timestamp, each with an optional
+/// `<timestamp>.fingerprints.json` next to it
+pub struct SnapshotStore {
+    dir: PathBuf,
+}
+
+impl SnapshotStore {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, io::Error> {
+        fs::create_dir_all(&dir)?;
+        Ok(SnapshotStore {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Returns the timestamps of all stored snapshots in ascending order
+    pub fn timestamps(&self) -> Result<Vec<u64>, io::Error> {
+        let mut timestamps: Vec<u64> = fs::read_dir(&self.dir)?
+            .flatten()
+            .filter_map(|entry| {
+                let filename = entry.file_name();
+                filename.to_str()?.strip_suffix(".json")?.parse().ok()
+            })
+            .collect();
+        timestamps.sort_unstable();
+        Ok(timestamps)
+    }
+
+    pub fn load(&self, timestamp: u64) -> Result<Snapshot, io::Error> {
+        let json = fs::read_to_string(self.path(timestamp))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Loads the stack fingerprints recorded with a snapshot, if any
+    pub fn load_fingerprints(&self, timestamp: u64) -> Result<Option<Fingerprints>, io::Error> {
+        match fs::read_to_string(self.fingerprints_path(timestamp)) {
+            Ok(json) => Ok(Some(serde_json::from_str(&json)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Loads all snapshots in ascending order of their timestamps
+    pub fn load_all(&self) -> Result<Vec<(u64, Snapshot)>, io::Error> {
+        self.timestamps()?
+            .into_iter()
+            .map(|timestamp| Ok((timestamp, self.load(timestamp)?)))
+            .collect()
+    }
+
+    /// Stores the snapshot and the stack fingerprints, if given, under the current time and
+    /// returns its timestamp. Fails with `AlreadyExists` instead of overwriting if the newest
+    /// snapshot isn't older than that.
+    pub fn record(
+        &self,
+        snapshot: &Snapshot,
+        fingerprints: Option<&Fingerprints>,
+    ) -> Result<u64, io::Error> {
+        let timestamp = unix_now();
+        if let Some(&newest) = self.timestamps()?.last() {
+            if newest >= timestamp {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("there already is a snapshot at {}", newest),
+                ));
+            }
+        }
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(self.path(timestamp))?;
+        file.write_all(serde_json::to_string_pretty(snapshot)?.as_bytes())?;
+        if let Some(fingerprints) = fingerprints {
+            fs::write(
+                self.fingerprints_path(timestamp),
+                serde_json::to_string_pretty(fingerprints)?,
+            )?;
+        }
+        Ok(timestamp)
+    }
+
+    /// Removes all but the newest `keep` snapshots and those older than `max_age`
+    pub fn prune(
+        &self,
+        keep: Option<usize>,
+        max_age: Option<Duration>,
+    ) -> Result<Vec<u64>, io::Error> {
+        let timestamps = self.timestamps()?;
+        let now = unix_now();
+
+        let mut removed = Vec::new();
+        for (i, &timestamp) in timestamps.iter().enumerate() {
+            let newer = timestamps.len() - i - 1;
+            let too_many = keep.is_some_and(|keep| newer >= keep);
+            let too_old =
+                max_age.is_some_and(|max_age| now.saturating_sub(timestamp) > max_age.as_secs());
+            if too_many || too_old {
+                fs::remove_file(self.path(timestamp))?;
+                match fs::remove_file(self.fingerprints_path(timestamp)) {
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                    _ => {}
+                }
+                removed.push(timestamp);
+            }
+        }
+
+        Ok(removed)
+    }
+
+    fn path(&self, timestamp: u64) -> PathBuf {
+        self.dir.join(format!("{}.json", timestamp))
+    }
+
+    fn fingerprints_path(&self, timestamp: u64) -> PathBuf {
+        self.dir.join(format!("{}.fingerprints.json", timestamp))
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Counts the items and stack fingerprints in the containers of all chunks of the dimensions and
+/// in the inventories and ender chests of all players
+pub fn scan(
+    world_path: &Path,
+    dimensions: &[String],
+) -> Result<(Snapshot, Fingerprints), io::Error> {
+    let mut counts = Snapshot::new();
+    let mut fingerprints = Fingerprints::new();
+    let mut add = |items: &NbtList| {
+        for_each_item(items, &mut |item| {
+            let (id, count) = id_and_count(item);
+            *counts.entry(id.to_string()).or_default() += count;
+            if let Some(fingerprint) = fingerprint(item) {
+                *fingerprints.entry(fingerprint).or_default() += count;
+            }
+        })
+    };
+
+    for dim_name in dimensions {
+        let dim_path = dimension_path(world_path, dim_name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unknown dimension {}", dim_name),
+            )
+        })?;
+        if !dim_path.join("region").exists() {
+            continue;
+        }
+        for_each_chunk_in_dir(&dim_path.join("region"), |_, _, chunk| {
+            let version = GameVersion::of(&chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+            let block_entities = match version
+                .level(&chunk)
+                .and_then(|level| level.get::<_, &NbtList>(version.block_entities_key()).ok())
+            {
+                Some(block_entities) => block_entities,
+                None => return,
+            };
+            for block_entity in block_entities.iter_map::<&NbtCompound>().flatten() {
+                if let Ok(items) = block_entity.get::<_, &NbtList>("Items") {
+                    add(items);
+                }
+            }
+        })?;
+    }
+
+    if let Ok(entries) = fs::read_dir(world_path.join("playerdata")) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "dat") {
+                continue;
+            }
+            let player = match read_dat(&path) {
+                Ok(player) => player,
+                Err(e) => {
+                    eprintln!("failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            for key in INVENTORY_KEYS {
+                if let Ok(items) = player.get::<_, &NbtList>(*key) {
+                    add(items);
+                }
+            }
+        }
+    }
+
+    Ok((counts, fingerprints))
+}
+
+#[derive(Debug, Clone)]
+pub struct Anomaly {
+    pub id: String,
+    pub previous: u64,
+    pub current: u64,
+    /// Deviation of the latest change from the earlier changes in standard deviations,
+    /// if there are enough earlier snapshots to tell
+    pub z_score: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnomalyThresholds {
+    /// Minimum absolute increase of an item count
+    pub min_increase: u64,
+    /// Minimum relative increase in percent, items that didn't exist before always pass
+    pub min_increase_percent: f64,
+    /// Minimum z-score, only checked with at least three earlier changes
+    pub min_z_score: f64,
+}
+
+/// Flags items whose count increased unusually between the last two snapshots
+pub fn find_anomalies(snapshots: &[Snapshot], thresholds: &AnomalyThresholds) -> Vec<Anomaly> {
+    let (current, earlier) = match snapshots.split_last() {
+        Some((current, earlier)) if !earlier.is_empty() => (current, earlier),
+        _ => return Vec::new(),
+    };
+    let previous = &earlier[earlier.len() - 1];
+
+    let mut anomalies = Vec::new();
+    for (id, &count) in current {
+        let previous_count = previous.get(id).copied().unwrap_or(0);
+        if count < previous_count + thresholds.min_increase {
+            continue;
+        }
+
+        if previous_count > 0 {
+            let percent = (count - previous_count) as f64 / previous_count as f64 * 100.0;
+            if percent < thresholds.min_increase_percent {
+                continue;
+            }
+        }
+
+        let deltas: Vec<f64> = earlier
+            .windows(2)
+            .map(|pair| {
+                pair[1].get(id).copied().unwrap_or(0) as f64
+                    - pair[0].get(id).copied().unwrap_or(0) as f64
+            })
+            .collect();
+
+        let z_score = if deltas.len() >= 3 {
+            let mean = deltas.iter().sum::<f64>() / deltas.len() as f64;
+            let variance =
+                deltas.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / deltas.len() as f64;
+            let delta = count as f64 - previous_count as f64;
+            Some((delta - mean) / variance.sqrt().max(1.0))
+        } else {
+            None
+        };
+
+        if z_score.is_some_and(|z_score| z_score < thresholds.min_z_score) {
+            continue;
+        }
+
+        anomalies.push(Anomaly {
+            id: id.clone(),
+            previous: previous_count,
+            current: count,
+            z_score,
+        });
+    }
+
+    anomalies.sort_by_key(|anomaly| std::cmp::Reverse(anomaly.current - anomaly.previous));
+    anomalies
+}
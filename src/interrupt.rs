@@ -0,0 +1,26 @@
+use std::{
+    process,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Exit code of a run stopped by Ctrl-C, as a shell reports a process killed by SIGINT
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Makes the first Ctrl-C or SIGTERM only set a flag, checked with [`interrupted`], so that a
+/// command can finish the chunk or region file it is working on and close its outputs instead
+/// of leaving them truncated. A second one exits immediately.
+pub fn handle_interrupts() -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            process::exit(EXIT_INTERRUPTED);
+        }
+        eprintln!("interrupted, finishing the work in progress, press Ctrl-C again to exit now");
+    })
+}
+
+/// Whether the command should stop at the next point it can do so cleanly
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
@@ -0,0 +1,359 @@
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+
+use crate::{
+    snbt::write_sorted_compound,
+    text::{plain_text, tag_text},
+    version::GameVersion,
+};
+
+/// Items that can't be obtained in survival, without the `minecraft:` namespace
+pub const ILLEGAL_ITEMS: &[&str] = &[
+    "barrier",
+    "bedrock",
+    "budding_amethyst",
+    "chain_command_block",
+    "command_block",
+    "command_block_minecart",
+    "debug_stick",
+    "end_portal_frame",
+    "jigsaw",
+    "knowledge_book",
+    "light",
+    "petrified_oak_slab",
+    "reinforced_deepslate",
+    "repeating_command_block",
+    "spawner",
+    "structure_block",
+    "structure_void",
+];
+
+/// Calls `f` for every item in the list and, recursively, for the items inside shulker boxes and
+/// other containers, guessing the format of each item with [`GameVersion::of_item`]
+pub fn for_each_item<F: FnMut(&NbtCompound)>(items: &NbtList, f: &mut F) {
+    for item in stacks(items) {
+        f(item);
+        if let Some(contents) = contents(item) {
+            for_each_item(contents, f);
+        }
+    }
+}
+
+/// Like [`for_each_item`] with the items in the format of the given version
+pub fn for_each_item_as<F: FnMut(&NbtCompound)>(items: &NbtList, version: GameVersion, f: &mut F) {
+    for item in stacks(items) {
+        f(item);
+        if let Some(contents) = contents_as(item, version) {
+            for_each_item_as(contents, version, f);
+        }
+    }
+}
+
+/// Iterates the items of a list, unwrapping the `{slot, item}` entries of container items since
+/// 1.20.5
+pub fn stacks(items: &NbtList) -> impl Iterator<Item = &NbtCompound> {
+    items.iter_map::<&NbtCompound>().flatten().map(stack)
+}
+
+/// Returns the item of an entry of an item list, which is the entry itself unless it is a
+/// `{slot, item}` entry of a container item since 1.20.5
+pub fn stack(entry: &NbtCompound) -> &NbtCompound {
+    match entry.get::<_, &NbtCompound>("item") {
+        Ok(item) if !entry.contains_key("id") => item,
+        _ => entry,
+    }
+}
+
+pub fn stack_mut(entry: &mut NbtCompound) -> &mut NbtCompound {
+    if entry.contains_key("id") || entry.get::<_, &NbtCompound>("item").is_err() {
+        return entry;
+    }
+    entry.get_mut("item").unwrap()
+}
+
+/// The items inside of a container item like a shulker box. The entries are wrapped as
+/// `{slot, item}` since 1.20.5, see [`stacks`].
+pub fn contents(item: &NbtCompound) -> Option<&NbtList> {
+    contents_as(item, GameVersion::of_item(item))
+}
+
+pub fn contents_as(item: &NbtCompound, version: GameVersion) -> Option<&NbtList> {
+    let (last, path) = version.item_contents_path().split_last()?;
+    let mut compound = item;
+    for key in path {
+        compound = compound.get(*key).ok()?;
+    }
+    compound.get(*last).ok()
+}
+
+/// Mutable access to the items inside of a container item
+pub fn contents_mut(item: &mut NbtCompound) -> Option<&mut NbtList> {
+    let version = GameVersion::of_item(item);
+    contents_mut_as(item, version)
+}
+
+pub fn contents_mut_as(item: &mut NbtCompound, version: GameVersion) -> Option<&mut NbtList> {
+    let (last, path) = version.item_contents_path().split_last()?;
+    let mut compound = item;
+    for key in path {
+        compound = compound.get_mut(*key).ok()?;
+    }
+    compound.get_mut(*last).ok()
+}
+
+/// Returns the item's id and count, which is 0 if missing before 1.20.5 and 1 since
+pub fn id_and_count(item: &NbtCompound) -> (&str, u64) {
+    id_and_count_as(item, GameVersion::of_item(item))
+}
+
+pub fn id_and_count_as(item: &NbtCompound, version: GameVersion) -> (&str, u64) {
+    let id = item.get("id").unwrap_or_default();
+    let count = match item.inner().get(version.item_count_key()) {
+        Some(NbtTag::Byte(count)) => *count as u8 as u64,
+        Some(NbtTag::Int(count)) => (*count).max(0) as u64,
+        Some(_) => 0,
+        None if version.item_components() => 1,
+        None => 0,
+    };
+    (id, count)
+}
+
+/// Returns an id for the exact contents of a stack like `minecraft:diamond_sword#1a2b3c4d`, the
+/// CRC32 of its SNBT without the count and slot, so that copies of the same stack share it. Items
+/// without additional data get none, since their ids already tell them apart.
+pub fn fingerprint(item: &NbtCompound) -> Option<String> {
+    let version = GameVersion::of_item(item);
+    match item.get::<_, &NbtCompound>(version.item_data_key()) {
+        Ok(data) if !data.is_empty() => {}
+        _ => return None,
+    }
+
+    let mut item = item.clone();
+    item.inner_mut().remove(version.item_count_key());
+    item.inner_mut().remove("Slot");
+    let mut snbt = Vec::new();
+    write_sorted_compound(&mut snbt, &item).ok()?;
+    let (id, _) = id_and_count(&item);
+    Some(format!("{}#{:08x}", id, crc32fast::hash(&snbt)))
+}
+
+/// Formats an item stack like `5 minecraft:emerald`, or returns None for air
+pub fn format_stack(item: &NbtCompound) -> Option<String> {
+    let (id, count) = id_and_count(item);
+    if id.is_empty() || id == "minecraft:air" {
+        return None;
+    }
+    Some(format!("{} {}", count.max(1), id))
+}
+
+/// Returns the custom name of an item as a JSON text component, from `tag.display.Name` before
+/// 1.20.5 and the `minecraft:custom_name` component since
+pub fn custom_name(item: &NbtCompound) -> Option<&str> {
+    let version = GameVersion::of_item(item);
+    let data = item.get::<_, &NbtCompound>(version.item_data_key()).ok()?;
+    if version.item_components() {
+        return data.get::<_, &str>("minecraft:custom_name").ok();
+    }
+    data.get::<_, &NbtCompound>("display")
+        .and_then(|display| display.get::<_, &str>("Name"))
+        .ok()
+}
+
+/// Returns the enchantments stored in an enchanted book with their levels, from
+/// `tag.StoredEnchantments` before 1.20.5 and the `minecraft:stored_enchantments` component since
+pub fn stored_enchantments(item: &NbtCompound) -> Vec<(&str, i16)> {
+    stored_enchantments_as(item, GameVersion::of_item(item))
+}
+
+pub fn stored_enchantments_as(item: &NbtCompound, version: GameVersion) -> Vec<(&str, i16)> {
+    let data = match item.get::<_, &NbtCompound>(version.item_data_key()) {
+        Ok(data) => data,
+        Err(_) => return Vec::new(),
+    };
+    let level = |tag: &NbtTag| match *tag {
+        NbtTag::Short(level) => Some(level),
+        NbtTag::Int(level) => Some(level as i16),
+        _ => None,
+    };
+
+    if version.item_components() {
+        // the levels are nested in `levels` before 1.21.5
+        let component = match data.get::<_, &NbtCompound>("minecraft:stored_enchantments") {
+            Ok(component) => component,
+            Err(_) => return Vec::new(),
+        };
+        let levels = component
+            .get::<_, &NbtCompound>("levels")
+            .unwrap_or(component);
+        return levels
+            .inner()
+            .iter()
+            .filter_map(|(id, tag)| Some((id.as_str(), level(tag)?)))
+            .collect();
+    }
+
+    let enchantments = match data.get::<_, &NbtList>("StoredEnchantments") {
+        Ok(enchantments) => enchantments,
+        Err(_) => return Vec::new(),
+    };
+    enchantments
+        .iter_map::<&NbtCompound>()
+        .flatten()
+        .filter_map(|enchantment| {
+            let id = enchantment.get::<_, &str>("id").ok()?;
+            Some((id, level(enchantment.inner().get("lvl")?)?))
+        })
+        .collect()
+}
+
+/// Returns the id of the map a filled map shows, from `Damage` before 1.13, `tag.map` before
+/// 1.20.5 and the `minecraft:map_id` component since, or None for other items
+pub fn map_id(item: &NbtCompound) -> Option<i32> {
+    let (id, _) = id_and_count(item);
+    if id != "minecraft:filled_map" {
+        return None;
+    }
+    let version = GameVersion::of_item(item);
+    let key = match version.item_components() {
+        true => "minecraft:map_id",
+        false => "map",
+    };
+    let id = item
+        .get::<_, &NbtCompound>(version.item_data_key())
+        .and_then(|data| data.get::<_, i32>(key));
+    match id {
+        Ok(id) => Some(id),
+        Err(_) => item.get::<_, i16>("Damage").ok().map(i32::from),
+    }
+}
+
+/// The text of a written book or a book and quill
+#[derive(Debug, Clone, Default)]
+pub struct Book {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    /// The pages as plain text
+    pub pages: Vec<String>,
+}
+
+/// Reads the text of a written book or a book and quill, from `tag` before 1.20.5 and the
+/// `written_book_content` or `writable_book_content` component since, or returns None for other
+/// items
+pub fn book_content(item: &NbtCompound) -> Option<Book> {
+    let (id, _) = id_and_count(item);
+    // only the pages of written books are text components, a book and quill has plain text
+    let (written, component) = match id {
+        "minecraft:written_book" => (true, "minecraft:written_book_content"),
+        "minecraft:writable_book" => (false, "minecraft:writable_book_content"),
+        _ => return None,
+    };
+    let string = |tag: &NbtTag| match tag {
+        NbtTag::String(text) => Some(text.clone()),
+        _ => None,
+    };
+
+    let version = GameVersion::of_item(item);
+    if !version.item_components() {
+        let tag = match item.get::<_, &NbtCompound>(version.item_data_key()) {
+            Ok(tag) => tag,
+            Err(_) => return Some(Book::default()),
+        };
+        let pages = tag
+            .get::<_, &NbtList>("pages")
+            .map(|pages| {
+                pages
+                    .iter_map::<&str>()
+                    .flatten()
+                    .map(|page| match written {
+                        true => plain_text(page),
+                        false => page.to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        return Some(Book {
+            title: tag.inner().get("title").and_then(string),
+            author: tag.inner().get("author").and_then(string),
+            pages,
+        });
+    }
+
+    let content = match item
+        .get::<_, &NbtCompound>(version.item_data_key())
+        .and_then(|components| components.get::<_, &NbtCompound>(component))
+    {
+        Ok(content) => content,
+        Err(_) => return Some(Book::default()),
+    };
+    // pages and titles can be filtered for chat filtering, then the text is stored in `raw`
+    let raw = |tag: &'_ NbtTag| -> Option<NbtTag> {
+        match tag {
+            NbtTag::Compound(filterable) => filterable.inner().get("raw").cloned(),
+            tag => Some(tag.clone()),
+        }
+    };
+    let pages = content
+        .get::<_, &NbtList>("pages")
+        .map(|pages| {
+            pages
+                .as_ref()
+                .iter()
+                .filter_map(raw)
+                .filter_map(|page| match written {
+                    true => Some(tag_text(&page)),
+                    false => string(&page),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(Book {
+        title: content
+            .inner()
+            .get("title")
+            .and_then(raw)
+            .as_ref()
+            .and_then(string),
+        author: content.inner().get("author").and_then(string),
+        pages,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(snbt: &str) -> NbtCompound {
+        quartz_nbt::snbt::parse(snbt).unwrap()
+    }
+
+    #[test]
+    fn fingerprint_ignores_count_and_slot() {
+        let renamed = r#"components: {"minecraft:custom_name": '"Duped"'}"#;
+        let a = fingerprint(&item(&format!(
+            r#"{{id: "minecraft:diamond", count: 64, Slot: 1b, {}}}"#,
+            renamed
+        )));
+        let b = fingerprint(&item(&format!(
+            r#"{{id: "minecraft:diamond", count: 3, {}}}"#,
+            renamed
+        )));
+        assert!(a.as_ref().unwrap().starts_with("minecraft:diamond#"));
+        assert_eq!(a, b);
+
+        let other = fingerprint(&item(
+            r#"{id: "minecraft:diamond", count: 64, components: {"minecraft:custom_name": '"Other"'}}"#,
+        ));
+        assert_ne!(a, other);
+    }
+
+    #[test]
+    fn items_without_data_have_no_fingerprint() {
+        assert_eq!(
+            fingerprint(&item(r#"{id: "minecraft:diamond", count: 5}"#)),
+            None
+        );
+        assert_eq!(
+            fingerprint(&item(r#"{id: "minecraft:diamond", Count: 5b, tag: {}}"#)),
+            None
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+
+use crate::region::RegionFile;
+
+#[derive(thiserror::Error, Debug)]
+pub enum JournalError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("line {0}: {1}")]
+    Invalid(usize, String),
+}
+
+/// A JSON lines file recording the previous content of every chunk and `.dat` file a command
+/// changes, so that [`undo`] can put it back. Paths are stored relative to the world directory.
+pub struct Journal {
+    file: File,
+    world_path: PathBuf,
+    command: String,
+}
+
+impl Journal {
+    /// Opens a journal for appending the changes of a command to the world
+    pub fn open(path: &Path, world_path: &Path, command: &str) -> Result<Self, JournalError> {
+        Ok(Journal {
+            file: OpenOptions::new().create(true).append(true).open(path)?,
+            world_path: world_path.to_path_buf(),
+            command: command.to_string(),
+        })
+    }
+
+    fn append(&mut self, mut entry: Value) -> Result<(), JournalError> {
+        entry["command"] = json!(self.command);
+        entry["time"] = json!(SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs());
+        writeln!(self.file, "{}", entry)?;
+        Ok(())
+    }
+
+    fn relative<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(&self.world_path).unwrap_or(path)
+    }
+
+    /// Records the current payload of a chunk, or that it doesn't exist, before it is written
+    /// or deleted
+    pub fn record_chunk(
+        &mut self,
+        region_path: &Path,
+        region_file: &mut RegionFile,
+        index: usize,
+    ) -> Result<(), JournalError> {
+        let before = region_file.read_payload(index)?;
+        self.append(json!({
+            "file": self.relative(region_path),
+            "chunk": index,
+            "before": before.as_deref().map(to_hex),
+        }))
+    }
+
+    /// Records the current content of a file, or that it doesn't exist, before it is written
+    pub fn record_file(&mut self, path: &Path) -> Result<(), JournalError> {
+        let before = match fs::read(path) {
+            Ok(buf) => Some(to_hex(&buf)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        self.append(json!({
+            "file": self.relative(path),
+            "before": before,
+        }))
+    }
+}
+
+/// A change read back from a journal
+struct Entry {
+    path: PathBuf,
+    chunk: Option<usize>,
+    before: Option<Vec<u8>>,
+}
+
+/// Reverts the changes recorded in a journal, newest first, and returns their number. All
+/// entries are checked before anything is written.
+pub fn undo(journal_path: &Path, world_path: &Path) -> Result<usize, JournalError> {
+    let content = fs::read_to_string(journal_path)?;
+    let mut entries = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = |message: &str| JournalError::Invalid(i + 1, message.to_string());
+        let entry: Value = serde_json::from_str(line)?;
+        let path = world_path.join(entry["file"].as_str().ok_or_else(|| invalid("no file"))?);
+        let chunk = match &entry["chunk"] {
+            Value::Null => None,
+            chunk => match chunk.as_u64() {
+                Some(index) if index < 1024 => Some(index as usize),
+                _ => return Err(invalid("invalid chunk index")),
+            },
+        };
+        let before = match &entry["before"] {
+            Value::Null => None,
+            Value::String(hex) => Some(from_hex(hex).ok_or_else(|| invalid("invalid hex"))?),
+            _ => return Err(invalid("invalid before")),
+        };
+        entries.push(Entry {
+            path,
+            chunk,
+            before,
+        });
+    }
+
+    for entry in entries.iter().rev() {
+        match entry.chunk {
+            Some(index) => {
+                let file = OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .open(&entry.path)?;
+                let mut region_file = RegionFile::new(file)?;
+                match &entry.before {
+                    Some(payload) => region_file.write_chunk(index, payload)?,
+                    None => region_file.delete_chunk(index)?,
+                }
+            }
+            None => match &entry.before {
+                Some(buf) => fs::write(&entry.path, buf)?,
+                None => fs::remove_file(&entry.path)?,
+            },
+        }
+    }
+    Ok(entries.len())
+}
+
+fn to_hex(buf: &[u8]) -> String {
+    buf.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
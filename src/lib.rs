@@ -1 +1,38 @@
+pub mod aggregate;
+pub mod anonymize;
+pub mod backup;
+pub mod banners;
+pub mod biomes;
+pub mod blocks;
+pub mod categories;
+pub mod checksums;
+pub mod chunk;
+pub mod claims;
+pub mod dat;
+pub mod entity_index;
+pub mod forced;
+pub mod format;
+pub mod heightmaps;
+pub mod history;
+pub mod interrupt;
+pub mod items;
+pub mod journal;
+pub mod light;
+pub mod location;
+pub mod nbt_json;
+pub mod nbt_path;
+pub mod players;
+pub mod png;
+pub mod poi;
+pub mod redact;
 pub mod region;
+pub mod region_cache;
+pub mod relocate;
+pub mod snbt;
+pub mod symbol;
+pub mod text;
+pub mod text_index;
+pub mod uuid;
+pub mod validate;
+pub mod version;
+pub mod world;
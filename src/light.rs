@@ -0,0 +1,36 @@
+use quartz_nbt::{NbtCompound, NbtList};
+
+/// The `BlockLight` or `SkyLight` nibble arrays of all sections of a chunk
+pub struct ChunkLight<'a> {
+    sections: Vec<(i32, &'a [i8])>,
+}
+
+impl<'a> ChunkLight<'a> {
+    /// Reads the light arrays with the given key, `BlockLight` or `SkyLight`
+    pub fn from_chunk(chunk: &'a NbtCompound, key: &str) -> Self {
+        let sections = chunk
+            .get::<_, &NbtList>("sections")
+            .map(|sections| {
+                sections
+                    .iter_map::<&NbtCompound>()
+                    .flatten()
+                    .filter_map(|section| {
+                        let y = section.get::<_, i8>("Y").ok()? as i32;
+                        let data: &[i8] = section.get(key).ok()?;
+                        (data.len() == 2048).then_some((y, data))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        ChunkLight { sections }
+    }
+
+    /// Returns the light level at chunk local x and z and absolute y, or None if the section has
+    /// no light data
+    pub fn get(&self, x: usize, y: i32, z: usize) -> Option<u8> {
+        let section_y = y.div_euclid(16);
+        let (_, data) = self.sections.iter().find(|(y, _)| *y == section_y)?;
+        let index = (y.rem_euclid(16) as usize * 16 + z) * 16 + x;
+        Some((data[index / 2] as u8 >> (index % 2 * 4)) & 0xf)
+    }
+}
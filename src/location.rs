@@ -0,0 +1,150 @@
+use std::io::{self, Write};
+
+use serde_json::json;
+
+use crate::redact::Redaction;
+
+/// Something found at a position in the world
+#[derive(Debug, Clone)]
+pub struct Location {
+    pub dimension: String,
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    /// What kind of thing this is, e.g. the container's id
+    pub kind: String,
+    pub id: String,
+    pub count: u64,
+    pub extra: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocationFormat {
+    GeoJson,
+    Csv,
+}
+
+/// Writes locations in any of the supported formats. The coordinates are redacted by the
+/// [global](Redaction::global) policy.
+pub enum LocationWriter<W: Write> {
+    GeoJson(GeoJsonWriter<W>),
+    Csv(CsvWriter<W>),
+}
+
+impl<W: Write> LocationWriter<W> {
+    pub fn new(format: LocationFormat, writer: W) -> Result<Self, io::Error> {
+        Ok(match format {
+            LocationFormat::GeoJson => LocationWriter::GeoJson(GeoJsonWriter::new(writer)?),
+            LocationFormat::Csv => LocationWriter::Csv(CsvWriter::new(writer)?),
+        })
+    }
+
+    pub fn write(&mut self, location: &Location) -> Result<(), io::Error> {
+        match self {
+            LocationWriter::GeoJson(writer) => writer.write(location),
+            LocationWriter::Csv(writer) => writer.write(location),
+        }
+    }
+
+    pub fn finish(self) -> Result<(), io::Error> {
+        match self {
+            LocationWriter::GeoJson(writer) => writer.finish(),
+            LocationWriter::Csv(writer) => writer.finish(),
+        }
+    }
+}
+
+/// Streams locations as a GeoJSON feature collection using world x/z as coordinates
+pub struct GeoJsonWriter<W: Write> {
+    writer: W,
+    redaction: &'static Redaction,
+    first: bool,
+}
+
+impl<W: Write> GeoJsonWriter<W> {
+    pub fn new(mut writer: W) -> Result<Self, io::Error> {
+        let redaction = global_redaction()?;
+        write!(writer, r#"{{"type":"FeatureCollection","features":["#)?;
+        Ok(GeoJsonWriter {
+            writer,
+            redaction,
+            first: true,
+        })
+    }
+
+    pub fn write(&mut self, location: &Location) -> Result<(), io::Error> {
+        let (x, z) = self.redaction.coordinates(location.x, location.z);
+        let feature = json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Point",
+                "coordinates": [x, z],
+            },
+            "properties": {
+                "dimension": location.dimension,
+                "y": location.y,
+                "type": location.kind,
+                "id": location.id,
+                "count": location.count,
+                "extra": location.extra,
+            },
+        });
+        if !self.first {
+            write!(self.writer, ",")?;
+        }
+        self.first = false;
+        write!(self.writer, "\n{}", feature)
+    }
+
+    pub fn finish(mut self) -> Result<(), io::Error> {
+        writeln!(self.writer, "\n]}}")?;
+        self.writer.flush()
+    }
+}
+
+/// Streams locations as CSV rows with a header
+pub struct CsvWriter<W: Write> {
+    writer: W,
+    redaction: &'static Redaction,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(mut writer: W) -> Result<Self, io::Error> {
+        let redaction = global_redaction()?;
+        writeln!(writer, "dimension,x,y,z,type,id,count,extra")?;
+        Ok(CsvWriter { writer, redaction })
+    }
+
+    pub fn write(&mut self, location: &Location) -> Result<(), io::Error> {
+        let (x, z) = self.redaction.coordinates(location.x, location.z);
+        writeln!(
+            self.writer,
+            "{},{},{},{},{},{},{},{}",
+            csv_field(&location.dimension),
+            x,
+            location.y,
+            z,
+            csv_field(&location.kind),
+            csv_field(&location.id),
+            location.count,
+            csv_field(&location.extra),
+        )
+    }
+
+    pub fn finish(mut self) -> Result<(), io::Error> {
+        self.writer.flush()
+    }
+}
+
+fn global_redaction() -> Result<&'static Redaction, io::Error> {
+    Redaction::global().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// Quotes a CSV field if it contains a separator, quote or newline
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
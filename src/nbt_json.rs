@@ -0,0 +1,62 @@
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+use serde_json::{Map, Number, Value};
+
+/// Converts NBT to JSON, losing the distinction between number types
+pub fn nbt_to_json(tag: &NbtTag) -> Value {
+    match tag {
+        NbtTag::Byte(value) => Value::from(*value),
+        NbtTag::Short(value) => Value::from(*value),
+        NbtTag::Int(value) => Value::from(*value),
+        NbtTag::Long(value) => Value::from(*value),
+        NbtTag::Float(value) => Value::from(*value),
+        NbtTag::Double(value) => Value::from(*value),
+        NbtTag::String(value) => Value::from(value.as_str()),
+        NbtTag::ByteArray(values) => Value::from(values.clone()),
+        NbtTag::IntArray(values) => Value::from(values.clone()),
+        NbtTag::LongArray(values) => Value::from(values.clone()),
+        NbtTag::List(list) => Value::Array(list.as_ref().iter().map(nbt_to_json).collect()),
+        NbtTag::Compound(compound) => {
+            let mut entries: Vec<_> = compound.inner().iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, tag)| (key.clone(), nbt_to_json(tag)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+    }
+}
+
+/// Converts JSON to NBT using ints, longs and doubles for numbers. Fails on `null`.
+pub fn json_to_nbt(value: &Value) -> Option<NbtTag> {
+    Some(match value {
+        Value::Null => return None,
+        Value::Bool(value) => NbtTag::Byte(*value as i8),
+        Value::Number(number) => number_to_nbt(number),
+        Value::String(value) => NbtTag::String(value.clone()),
+        Value::Array(values) => NbtTag::List(
+            values
+                .iter()
+                .map(json_to_nbt)
+                .collect::<Option<NbtList>>()?,
+        ),
+        Value::Object(map) => {
+            let mut compound = NbtCompound::new();
+            for (key, value) in map {
+                compound.insert(key, json_to_nbt(value)?);
+            }
+            NbtTag::Compound(compound)
+        }
+    })
+}
+
+fn number_to_nbt(number: &Number) -> NbtTag {
+    match number.as_i64() {
+        Some(value) => match i32::try_from(value) {
+            Ok(value) => NbtTag::Int(value),
+            Err(_) => NbtTag::Long(value),
+        },
+        None => NbtTag::Double(number.as_f64().unwrap_or_default()),
+    }
+}
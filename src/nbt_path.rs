@@ -0,0 +1,159 @@
+use std::str::FromStr;
+
+use quartz_nbt::{NbtCompound, NbtTag};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Key(String),
+    AnyKey,
+    Index(usize),
+    AnyIndex,
+}
+
+/// A path into NBT data like `block_entities[*].Items[0]."minecraft:custom_name"`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NbtPath {
+    pub segments: Vec<Segment>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum NbtPathError {
+    #[error("unexpected character {0:?} at {1}")]
+    UnexpectedChar(char, usize),
+    #[error("unexpected end of path")]
+    UnexpectedEnd,
+    #[error("invalid index {0:?}")]
+    InvalidIndex(String),
+}
+
+impl FromStr for NbtPath {
+    type Err = NbtPathError;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let mut segments = Vec::new();
+        let mut chars = str.char_indices().peekable();
+
+        let mut expect_key = true;
+        while let Some(&(pos, char)) = chars.peek() {
+            match char {
+                '[' => {
+                    chars.next();
+                    let mut index = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, ']')) => break,
+                            Some((_, char)) => index.push(char),
+                            None => return Err(NbtPathError::UnexpectedEnd),
+                        }
+                    }
+                    segments.push(match index.as_str() {
+                        "*" => Segment::AnyIndex,
+                        _ => Segment::Index(
+                            index
+                                .parse()
+                                .map_err(|_| NbtPathError::InvalidIndex(index))?,
+                        ),
+                    });
+                    expect_key = false;
+                }
+                '.' if !expect_key => {
+                    chars.next();
+                    expect_key = true;
+                }
+                '"' if expect_key => {
+                    chars.next();
+                    let mut key = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '"')) => break,
+                            Some((_, '\\')) => match chars.next() {
+                                Some((_, char)) => key.push(char),
+                                None => return Err(NbtPathError::UnexpectedEnd),
+                            },
+                            Some((_, char)) => key.push(char),
+                            None => return Err(NbtPathError::UnexpectedEnd),
+                        }
+                    }
+                    segments.push(Segment::Key(key));
+                    expect_key = false;
+                }
+                '*' if expect_key => {
+                    chars.next();
+                    segments.push(Segment::AnyKey);
+                    expect_key = false;
+                }
+                '.' | '"' | '*' | ']' => return Err(NbtPathError::UnexpectedChar(char, pos)),
+                _ if expect_key => {
+                    let mut key = String::new();
+                    while let Some(&(_, char)) = chars.peek() {
+                        if matches!(char, '.' | '[' | ']' | '"') {
+                            break;
+                        }
+                        key.push(char);
+                        chars.next();
+                    }
+                    segments.push(Segment::Key(key));
+                    expect_key = false;
+                }
+                _ => return Err(NbtPathError::UnexpectedChar(char, pos)),
+            }
+        }
+
+        if expect_key && !segments.is_empty() {
+            return Err(NbtPathError::UnexpectedEnd);
+        }
+
+        Ok(NbtPath { segments })
+    }
+}
+
+impl NbtPath {
+    /// Returns all tags matching the path
+    pub fn query<'a>(&self, root: &'a NbtCompound) -> Vec<&'a NbtTag> {
+        let mut matches = Vec::new();
+        query_compound(root, &self.segments, &mut matches);
+        matches
+    }
+}
+
+fn query_compound<'a>(
+    compound: &'a NbtCompound,
+    segments: &[Segment],
+    matches: &mut Vec<&'a NbtTag>,
+) {
+    match segments.first() {
+        Some(Segment::Key(key)) => {
+            if let Some(tag) = compound.inner().get(key) {
+                query_tag(tag, &segments[1..], matches);
+            }
+        }
+        Some(Segment::AnyKey) => {
+            for tag in compound.inner().values() {
+                query_tag(tag, &segments[1..], matches);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn query_tag<'a>(tag: &'a NbtTag, segments: &[Segment], matches: &mut Vec<&'a NbtTag>) {
+    if segments.is_empty() {
+        matches.push(tag);
+        return;
+    }
+
+    match (tag, &segments[0]) {
+        (NbtTag::Compound(compound), _) => query_compound(compound, segments, matches),
+        (NbtTag::List(list), Segment::Index(index)) => {
+            if let Some(tag) = list.as_ref().get(*index) {
+                query_tag(tag, &segments[1..], matches);
+            }
+        }
+        (NbtTag::List(list), Segment::AnyIndex) => {
+            for tag in list.as_ref() {
+                query_tag(tag, &segments[1..], matches);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,145 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use quartz_nbt::{io::NbtIoError, NbtCompound};
+use serde_json::Value;
+
+use crate::dat::{read_dat, write_dat};
+
+/// Reads the UUID to name mapping from a server's `usercache.json`
+pub fn read_usercache(path: &Path) -> Result<HashMap<String, String>, io::Error> {
+    let entries: Vec<Value> = serde_json::from_str(&fs::read_to_string(path)?)?;
+    Ok(entries
+        .iter()
+        .filter_map(|entry| {
+            Some((
+                entry.get("uuid")?.as_str()?.to_string(),
+                entry.get("name")?.as_str()?.to_string(),
+            ))
+        })
+        .collect())
+}
+
+/// Looks for `usercache.json` next to the world directory, where the server keeps it
+pub fn find_usercache(world_path: &Path) -> HashMap<String, String> {
+    let path = match world_path.canonicalize() {
+        Ok(path) => path.with_file_name("usercache.json"),
+        Err(_) => return HashMap::new(),
+    };
+    read_usercache(&path).unwrap_or_default()
+}
+
+/// Returns the UUIDs and paths of all playerdata files of a world, sorted by UUID
+pub fn playerdata_files(world_path: &Path) -> Result<Vec<(String, PathBuf)>, io::Error> {
+    uuid_files(&world_path.join("playerdata"), "dat")
+}
+
+/// Returns the UUIDs and paths of all stats files of a world, sorted by UUID
+pub fn stats_files(world_path: &Path) -> Result<Vec<(String, PathBuf)>, io::Error> {
+    uuid_files(&world_path.join("stats"), "json")
+}
+
+/// Returns the UUIDs and paths of all advancements files of a world, sorted by UUID
+pub fn advancements_files(world_path: &Path) -> Result<Vec<(String, PathBuf)>, io::Error> {
+    uuid_files(&world_path.join("advancements"), "json")
+}
+
+/// Lists the files of a directory with one file per player, named by UUID
+fn uuid_files(dir: &Path, extension: &str) -> Result<Vec<(String, PathBuf)>, io::Error> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == extension) {
+            if let Some(uuid) = path.file_stem().and_then(|stem| stem.to_str()) {
+                files.push((uuid.to_string(), path.clone()));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Returns a statistic from a stats file, like `minecraft:deaths` in `minecraft:custom`, or 0
+/// if the player doesn't have it
+pub fn stat(stats: &Value, category: &str, key: &str) -> u64 {
+    stats
+        .get("stats")
+        .and_then(|stats| stats.get(category))
+        .and_then(|category| category.get(key))
+        .and_then(Value::as_u64)
+        .unwrap_or_default()
+}
+
+/// Returns the play time in ticks from a stats file
+pub fn play_time(stats: &Value) -> Option<u64> {
+    let custom = stats.get("stats")?.get("minecraft:custom")?;
+    // renamed from play_one_minute in 1.17
+    custom
+        .get("minecraft:play_time")
+        .or_else(|| custom.get("minecraft:play_one_minute"))?
+        .as_u64()
+}
+
+/// Parts of the playerdata that make up a player's items
+pub const INVENTORY_KEYS: &[&str] = &["Inventory", "EnderItems"];
+
+/// A directory of inventory snapshots of all players, stored as gzipped NBT named by their unix
+/// timestamp and mapping each player's UUID to their `Inventory` and `EnderItems`
+pub struct InventoryStore {
+    dir: PathBuf,
+}
+
+impl InventoryStore {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, io::Error> {
+        fs::create_dir_all(&dir)?;
+        Ok(InventoryStore {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Returns the timestamps of all stored snapshots in ascending order
+    pub fn timestamps(&self) -> Result<Vec<u64>, io::Error> {
+        let mut timestamps: Vec<u64> = fs::read_dir(&self.dir)?
+            .flatten()
+            .filter_map(|entry| {
+                let filename = entry.file_name();
+                filename.to_str()?.strip_suffix(".dat")?.parse().ok()
+            })
+            .collect();
+        timestamps.sort_unstable();
+        Ok(timestamps)
+    }
+
+    pub fn load(&self, timestamp: u64) -> Result<NbtCompound, NbtIoError> {
+        read_dat(&self.path(timestamp))
+    }
+
+    /// Stores the inventories under the current time and returns its timestamp
+    pub fn record(&self, inventories: &NbtCompound) -> Result<u64, NbtIoError> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        write_dat(&self.path(timestamp), inventories)?;
+        Ok(timestamp)
+    }
+
+    fn path(&self, timestamp: u64) -> PathBuf {
+        self.dir.join(format!("{}.dat", timestamp))
+    }
+}
+
+/// Copies the inventory parts of a playerdata compound
+pub fn inventory_of(player: &NbtCompound) -> NbtCompound {
+    let mut inventory = NbtCompound::new();
+    for key in INVENTORY_KEYS {
+        if let Some(tag) = player.inner().get(*key) {
+            inventory.insert(*key, tag.clone());
+        }
+    }
+    inventory
+}
@@ -0,0 +1,41 @@
+use std::io::{self, Write};
+
+use byteorder::{WriteBytesExt, BE};
+use flate2::{write::ZlibEncoder, Compression};
+
+/// Writes an 8 bit RGB image as PNG, with the pixels given row by row
+pub fn write_png(
+    mut writer: impl Write,
+    width: usize,
+    height: usize,
+    rgb: &[u8],
+) -> Result<(), io::Error> {
+    assert_eq!(rgb.len(), width * height * 3, "wrong number of pixels");
+    writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+    let mut header = Vec::new();
+    header.write_u32::<BE>(width as u32)?;
+    header.write_u32::<BE>(height as u32)?;
+    // bit depth 8, color type RGB, default compression, filter and no interlacing
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    write_chunk(&mut writer, b"IHDR", &header)?;
+
+    // each row starts with the filter type, 0 for none
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    for row in rgb.chunks(width * 3) {
+        encoder.write_all(&[0])?;
+        encoder.write_all(row)?;
+    }
+    write_chunk(&mut writer, b"IDAT", &encoder.finish()?)?;
+    write_chunk(&mut writer, b"IEND", &[])
+}
+
+fn write_chunk(mut writer: impl Write, kind: &[u8; 4], data: &[u8]) -> Result<(), io::Error> {
+    writer.write_u32::<BE>(data.len() as u32)?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(kind);
+    hasher.update(data);
+    writer.write_u32::<BE>(hasher.finalize())
+}
@@ -0,0 +1,138 @@
+use std::{collections::HashMap, io, path::Path};
+
+use serde::Deserialize;
+
+use crate::region::{deserialize_chunk, region_coords, region_files, ChunkError, RegionFile};
+
+/// A chunk of the `poi` folder, which uses the same region files as the terrain but holds the
+/// points of interest of each section, like the beds, workstations and portals villagers and
+/// the portal search look for
+#[derive(Debug, Clone, Deserialize)]
+pub struct PoiChunk {
+    #[serde(rename = "DataVersion")]
+    pub data_version: Option<i32>,
+    /// The sections by their y as a string, which is how they are keyed in the file
+    #[serde(rename = "Sections", default)]
+    pub sections: HashMap<String, PoiSection>,
+}
+
+impl PoiChunk {
+    /// Returns the sections with their y, ordered from the bottom
+    pub fn sections(&self) -> Vec<(i32, &PoiSection)> {
+        let mut sections: Vec<_> = self
+            .sections
+            .iter()
+            .filter_map(|(y, section)| Some((y.parse().ok()?, section)))
+            .collect();
+        sections.sort_by_key(|&(y, _)| y);
+        sections
+    }
+
+    /// Iterates over the records of all sections
+    pub fn records(&self) -> impl Iterator<Item = &PoiRecord> {
+        self.sections.values().flat_map(|section| &section.records)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PoiSection {
+    /// Whether the records are up to date. The game rebuilds sections that aren't from their
+    /// blocks when they are loaded.
+    #[serde(rename = "Valid", default)]
+    pub valid: bool,
+    #[serde(rename = "Records", default)]
+    pub records: Vec<PoiRecord>,
+}
+
+/// A single point of interest
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PoiRecord {
+    /// The type, like `minecraft:home` for beds, `minecraft:nether_portal` or a workstation
+    /// like `minecraft:librarian`
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub pos: [i32; 3],
+    /// How many more villagers can claim it, so 0 for a claimed bed or workstation
+    #[serde(default)]
+    pub free_tickets: i32,
+}
+
+/// A region file of the `poi` folder
+pub struct PoiRegion {
+    region_file: RegionFile,
+    x: i32,
+    z: i32,
+}
+
+impl PoiRegion {
+    /// Opens a region file, which has to be named after its region coordinates
+    pub fn open(path: &Path) -> Result<Self, io::Error> {
+        let (x, z) = region_coords(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not named like a region file", path.display()),
+            )
+        })?;
+        Ok(PoiRegion {
+            region_file: RegionFile::open(path)?,
+            x,
+            z,
+        })
+    }
+
+    /// The region coordinates
+    pub fn coords(&self) -> (i32, i32) {
+        (self.x, self.z)
+    }
+
+    /// Reads the chunk at an index of `z * 32 + x` in chunks relative to the region
+    pub fn read_chunk(&mut self, index: usize) -> Result<Option<PoiChunk>, ChunkError> {
+        match self.region_file.read_payload(index)? {
+            Some(payload) => Ok(Some(deserialize_chunk(&payload)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Decodes all chunks and passes them with their chunk coordinates. Chunks that fail to
+    /// decode are reported and skipped.
+    pub fn for_each_chunk(
+        &mut self,
+        mut func: impl FnMut(i32, i32, PoiChunk),
+    ) -> Result<(), io::Error> {
+        let (region_x, region_z) = (self.x, self.z);
+        self.region_file.for_each_chunk(|(index, _, buf)| {
+            let chunk_x = region_x * 32 + (index % 32) as i32;
+            let chunk_z = region_z * 32 + (index / 32) as i32;
+            match deserialize_chunk(buf) {
+                Ok(chunk) => func(chunk_x, chunk_z, chunk),
+                Err(e) => eprintln!("failed to read POI chunk {} {}: {}", chunk_x, chunk_z, e),
+            }
+        })
+    }
+}
+
+/// Decodes the POI chunks of all region files in a directory, like
+/// [`for_each_chunk_in_dir`](crate::region::for_each_chunk_in_dir) does for terrain
+pub fn for_each_poi_chunk_in_dir(
+    dir: &Path,
+    mut func: impl FnMut(i32, i32, PoiChunk),
+) -> Result<(), io::Error> {
+    for (_, _, path) in region_files(dir)? {
+        PoiRegion::open(&path)?.for_each_chunk(&mut func)?;
+    }
+    Ok(())
+}
+
+/// Reads the records of a type from all region files in a directory
+pub fn find_records(dir: &Path, kind: &str) -> Result<Vec<PoiRecord>, io::Error> {
+    let mut records = Vec::new();
+    for_each_poi_chunk_in_dir(dir, |_, _, chunk| {
+        records.extend(
+            chunk
+                .records()
+                .filter(|record| record.kind == kind)
+                .cloned(),
+        );
+    })?;
+    Ok(records)
+}
@@ -0,0 +1,240 @@
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs, io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use quartz_nbt::{NbtCompound, NbtTag};
+use serde::Serialize;
+use serde_json::Value;
+
+/// The environment variable naming the redaction config that all tools enforce
+pub const REDACTION_ENV: &str = "WORLD_STATISTICS_REDACTION";
+
+#[derive(thiserror::Error, Debug)]
+pub enum RedactionError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("{0}: {1}")]
+    Invalid(PathBuf, String),
+}
+
+/// What reports must not reveal when they are shared. The policy is read from the YAML file
+/// named by [`REDACTION_ENV`], e.g.
+///
+/// ```yaml
+/// hide_seed: true
+/// radius: 2000
+/// players: [Notch, 853c80ef-3c37-49fd-aa49-938b674adae6]
+/// ```
+///
+/// and enforced where the tools write their output, so that one file governs every report.
+#[derive(Debug, Clone, Default)]
+pub struct Redaction {
+    /// Leave out the world seed. Defaults to true once a config is given.
+    pub hide_seed: bool,
+    /// Round block coordinates farther than this from 0, 0 down to the corner of their region
+    pub radius: Option<u32>,
+    /// Lowercase names and UUIDs of players to leave out entirely
+    players: HashSet<String>,
+}
+
+static GLOBAL: OnceLock<Redaction> = OnceLock::new();
+
+impl Redaction {
+    /// Returns the policy of the config named by [`REDACTION_ENV`], or one that redacts nothing
+    /// if the variable isn't set. The config is only read once.
+    pub fn global() -> Result<&'static Redaction, RedactionError> {
+        if let Some(redaction) = GLOBAL.get() {
+            return Ok(redaction);
+        }
+        let redaction = match env::var_os(REDACTION_ENV) {
+            Some(path) => Redaction::read(Path::new(&path))?,
+            None => Redaction::default(),
+        };
+        Ok(GLOBAL.get_or_init(|| redaction))
+    }
+
+    pub fn read(path: &Path) -> Result<Self, RedactionError> {
+        let data: Value = serde_yaml::from_str(&fs::read_to_string(path)?)?;
+        let invalid = |message: &str| RedactionError::Invalid(path.to_path_buf(), message.into());
+        let hide_seed = match &data["hide_seed"] {
+            Value::Null => true,
+            value => value
+                .as_bool()
+                .ok_or_else(|| invalid("hide_seed is not a bool"))?,
+        };
+        let radius = match &data["radius"] {
+            Value::Null => None,
+            value => Some(
+                value
+                    .as_u64()
+                    .and_then(|radius| radius.try_into().ok())
+                    .ok_or_else(|| invalid("radius is not a number of blocks"))?,
+            ),
+        };
+        let players = match &data["players"] {
+            Value::Null => HashSet::new(),
+            value => value
+                .as_array()
+                .ok_or_else(|| invalid("players is not a list"))?
+                .iter()
+                .map(|player| {
+                    player
+                        .as_str()
+                        .map(str::to_lowercase)
+                        .ok_or_else(|| invalid("players must be names or UUIDs"))
+                })
+                .collect::<Result<_, _>>()?,
+        };
+        Ok(Redaction {
+            hide_seed,
+            radius,
+            players,
+        })
+    }
+
+    /// Whether the name or UUID is of a player that is to be left out
+    pub fn hides_player(&self, player: &str) -> bool {
+        !self.players.is_empty() && self.players.contains(&player.to_lowercase())
+    }
+
+    /// Whether the player is hidden by their UUID or by the name the usercache has for them.
+    /// Tools that pseudonymize players check this before, as the pseudonyms no longer match.
+    pub fn hides_uuid(&self, uuid: &str, names: &HashMap<String, String>) -> bool {
+        self.hides_player(uuid) || names.get(uuid).is_some_and(|name| self.hides_player(name))
+    }
+
+    /// Rounds the x and z block coordinates down to the corner of their region if they are
+    /// beyond the radius
+    pub fn coordinates(&self, x: i32, z: i32) -> (i32, i32) {
+        match self.radius {
+            Some(radius) if (x as f64).hypot(z as f64) > radius as f64 => (x & !511, z & !511),
+            _ => (x, z),
+        }
+    }
+
+    /// Like [`coordinates`](Self::coordinates) for chunk coordinates
+    pub fn chunk_coordinates(&self, chunk_x: i32, chunk_z: i32) -> (i32, i32) {
+        let (x, z) = self.coordinates(chunk_x * 16, chunk_z * 16);
+        (x >> 4, z >> 4)
+    }
+
+    /// Redacts a JSON report: seeds are removed, `pos` and `block` (`[x, y, z]` or `[x, z]`),
+    /// `positions` and `chunk` (`[x, z]`) coordinates are rounded, and entries keyed by, list items mentioning or
+    /// strings naming a hidden player are removed
+    pub fn json(&self, value: &mut Value) {
+        match value {
+            Value::Object(object) => {
+                object.retain(|key, _| !self.is_seed(key) && !self.hides_player(key));
+                for (key, value) in object.iter_mut() {
+                    match key.as_str() {
+                        "pos" | "block" if json_xz(value).is_some() => self.json_pos(value),
+                        "positions" => value
+                            .as_array_mut()
+                            .into_iter()
+                            .flatten()
+                            .for_each(|pos| self.json_pos(pos)),
+                        "chunk" => match json_xz(value) {
+                            Some((x, z)) => {
+                                let (x, z) = self.chunk_coordinates(x, z);
+                                set_json_xz(value, x, z);
+                            }
+                            None => self.json(value),
+                        },
+                        _ => self.json(value),
+                    }
+                }
+            }
+            Value::Array(values) => {
+                values.retain(|value| !self.mentions_player(value));
+                values.iter_mut().for_each(|value| self.json(value));
+            }
+            Value::String(string) if self.hides_player(string) => *value = Value::Null,
+            _ => {}
+        }
+    }
+
+    /// Removes the seed from NBT, e.g. a level.dat or a chunk dumped by a tool
+    pub fn nbt(&self, compound: &mut NbtCompound) {
+        if !self.hide_seed {
+            return;
+        }
+        compound.inner_mut().retain(|key, _| !self.is_seed(key));
+        for tag in compound.inner_mut().values_mut() {
+            self.nbt_tag(tag);
+        }
+    }
+
+    fn nbt_tag(&self, tag: &mut NbtTag) {
+        match tag {
+            NbtTag::Compound(compound) => self.nbt(compound),
+            NbtTag::List(list) => list
+                .inner_mut()
+                .iter_mut()
+                .for_each(|tag| self.nbt_tag(tag)),
+            _ => {}
+        }
+    }
+
+    fn is_seed(&self, key: &str) -> bool {
+        self.hide_seed && matches!(key, "seed" | "Seed" | "RandomSeed")
+    }
+
+    fn json_pos(&self, value: &mut Value) {
+        if let Some((x, z)) = json_xz(value) {
+            let (x, z) = self.coordinates(x, z);
+            set_json_xz(value, x, z);
+        }
+    }
+
+    /// Whether a list item is a hidden player or an object with one as a direct value
+    fn mentions_player(&self, value: &Value) -> bool {
+        match value {
+            Value::String(string) => self.hides_player(string),
+            Value::Object(object) => object.values().any(|value| {
+                value
+                    .as_str()
+                    .is_some_and(|string| self.hides_player(string))
+            }),
+            _ => false,
+        }
+    }
+}
+
+/// The first and last number of an `[x, z]` or `[x, y, z]` array
+fn json_xz(value: &Value) -> Option<(i32, i32)> {
+    match value.as_array()?.as_slice() {
+        [x, z] | [x, _, z] => Some((x.as_i64()? as i32, z.as_i64()? as i32)),
+        _ => None,
+    }
+}
+
+fn set_json_xz(value: &mut Value, x: i32, z: i32) {
+    if let Some(values) = value.as_array_mut() {
+        let last = values.len() - 1;
+        values[0] = x.into();
+        values[last] = z.into();
+    }
+}
+
+/// Prints a JSON report redacted by the [global](Redaction::global) policy
+pub fn print_json<T: Serialize>(value: &T) -> Result<(), RedactionError> {
+    let mut value = serde_json::to_value(value)?;
+    Redaction::global()?.json(&mut value);
+    println!("{}", serde_json::to_string_pretty(&value)?);
+    Ok(())
+}
+
+/// Like [`print_json`] on a single line, for tools that print one JSON object per result
+pub fn print_json_line<T: Serialize>(value: &T) -> Result<(), RedactionError> {
+    let mut value = serde_json::to_value(value)?;
+    Redaction::global()?.json(&mut value);
+    println!("{}", value);
+    Ok(())
+}
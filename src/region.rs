@@ -1,108 +1,1064 @@
 use std::{
-    fs::File,
-    io::{self, Read, Seek, SeekFrom},
+    fs::{self, File},
+    io::{self, Cursor, Read, Seek, SeekFrom, Write},
+    ops::{Deref, Range},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
-use byteorder::{ReadBytesExt, BE};
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
 use quartz_nbt::NbtCompound;
+use serde::de::DeserializeOwned;
 
-pub struct RegionFile {
-    file: File,
-    offsets: [u32; 1024],
+use crate::{checksums::checksum, world::ChunkSelection};
+
+/// Where a chunk is stored in a region file, as the first sector and number of sectors of 4096
+/// bytes each. Locations that point into the header or have no sectors can't be constructed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChunkLocation {
+    sector_offset: u32,
+    sector_count: u8,
+}
+
+impl ChunkLocation {
+    pub const SECTOR_SIZE: u64 = 4096;
+    /// The largest sector offset that fits into the 24 bits of a header entry
+    pub const MAX_SECTOR_OFFSET: u32 = 0xff_ffff;
+
+    /// Returns None if the location overlaps the two header sectors, has no sectors or doesn't
+    /// fit into a header entry
+    pub fn new(sector_offset: u32, sector_count: u8) -> Option<Self> {
+        ((2..=Self::MAX_SECTOR_OFFSET).contains(&sector_offset) && sector_count > 0).then_some(
+            ChunkLocation {
+                sector_offset,
+                sector_count,
+            },
+        )
+    }
+
+    /// Parses an entry of the region file header. Empty entries and invalid ones are both
+    /// returned as None, which is also how the game treats them.
+    pub fn from_header(entry: u32) -> Option<Self> {
+        Self::new(entry >> 8, entry as u8)
+    }
+
+    /// Encodes the location as an entry of the region file header
+    pub fn to_header(self) -> u32 {
+        (self.sector_offset << 8) | self.sector_count as u32
+    }
+
+    pub fn sector_offset(self) -> u32 {
+        self.sector_offset
+    }
+
+    pub fn sector_count(self) -> u8 {
+        self.sector_count
+    }
+
+    /// The first sector after the chunk
+    pub fn sector_end(self) -> u32 {
+        self.sector_offset + self.sector_count as u32
+    }
+
+    /// Position of the chunk in the file in bytes
+    pub fn byte_offset(self) -> u64 {
+        self.sector_offset as u64 * Self::SECTOR_SIZE
+    }
+
+    /// Size of the chunk's sectors in bytes, including the length prefix and padding
+    pub fn byte_len(self) -> u64 {
+        self.sector_count as u64 * Self::SECTOR_SIZE
+    }
+}
+
+/// A region file read from any seekable reader, like a file, an in-memory buffer or an archive
+/// entry. Writing chunks also needs the reader to implement [`Write`].
+pub struct RegionFile<R = File> {
+    file: R,
+    locations: [Option<ChunkLocation>; 1024],
+    timestamps: [u32; 1024],
+    verify_writes: bool,
+    /// Path of the region file, needed to find chunks stored in external `.mcc` files
+    path: Option<PathBuf>,
 }
 
-impl RegionFile {
-    pub fn new(mut file: File) -> Result<Self, io::Error> {
+/// Set in the compression type of chunks that are too large for the region file and stored in
+/// a `c.<x>.<z>.mcc` file next to it instead
+const EXTERNAL_FLAG: u8 = 0x80;
+
+impl RegionFile<File> {
+    /// Opens a region file for reading
+    pub fn open(path: &Path) -> Result<Self, io::Error> {
+        Ok(Self::new(File::open(path)?)?.with_path(path))
+    }
+}
+
+impl<R: Read + Seek> RegionFile<R> {
+    pub fn new(mut file: R) -> Result<Self, io::Error> {
         let mut header_buf = [0; 8192];
         file.read_exact(&mut header_buf)?;
 
-        let mut offsets = [0; 1024];
+        let mut locations = [None; 1024];
+        let mut timestamps = [0; 1024];
         for i in 0..1024 {
-            offsets[i] = u32::from_be_bytes(header_buf[i * 4..][..4].try_into().unwrap());
+            locations[i] = ChunkLocation::from_header(u32::from_be_bytes(
+                header_buf[i * 4..][..4].try_into().unwrap(),
+            ));
+            timestamps[i] = u32::from_be_bytes(header_buf[4096 + i * 4..][..4].try_into().unwrap());
         }
 
-        Ok(RegionFile { file, offsets })
+        Ok(RegionFile {
+            file,
+            locations,
+            timestamps,
+            verify_writes: true,
+            path: None,
+        })
     }
 
+    /// Returns the underlying reader
+    pub fn into_inner(self) -> R {
+        self.file
+    }
+
+    /// Sets the path of the region file, so that chunks stored in external `.mcc` files next to
+    /// it can be read. [`open`](RegionFile::open) sets it already.
+    pub fn with_path(mut self, path: &Path) -> Self {
+        self.path = Some(path.to_path_buf());
+        self
+    }
+
+    /// Reads a chunk stored in an external `.mcc` file and returns it as payload like any other
+    fn external_payload(&self, index: usize, compression_type: u8) -> Result<Vec<u8>, io::Error> {
+        let path = self
+            .path
+            .as_deref()
+            .and_then(|path| {
+                let (region_x, region_z) = region_coords(path)?;
+                Some(path.with_file_name(format!(
+                    "c.{}.{}.mcc",
+                    region_x * 32 + (index % 32) as i32,
+                    region_z * 32 + (index / 32) as i32
+                )))
+            })
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "chunk is stored in an external .mcc file, but the region file path is unknown",
+                )
+            })?;
+        let mut payload = vec![compression_type & !EXTERNAL_FLAG];
+        File::open(&path)?.read_to_end(&mut payload)?;
+        Ok(payload)
+    }
+
+    /// Returns a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        &self.file
+    }
+
+    pub fn contains_chunk(&self, index: usize) -> bool {
+        self.locations[index].is_some()
+    }
+
+    /// Where the chunk is stored, or None if it doesn't exist
+    pub fn location(&self, index: usize) -> Option<ChunkLocation> {
+        self.locations[index]
+    }
+
+    /// When the chunk was last saved as unix timestamp in seconds, or 0 if it doesn't exist
+    pub fn timestamp(&self, index: usize) -> u32 {
+        self.timestamps[index]
+    }
+
+    /// Reads all chunks in the order they are stored in and passes their index, timestamp and
+    /// payload
     pub fn for_each_chunk(
         &mut self,
-        mut func: impl FnMut((usize, &[u8])),
+        mut func: impl FnMut((usize, u32, &[u8])),
     ) -> Result<(), io::Error> {
-        let mut offsets = self.offsets.clone();
+        let mut chunks = self.chunks();
+        while let Some(batch) = chunks.next_batch()? {
+            for i in batch {
+                func(chunks.entry(i)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns an iterator over all chunks in the order they are stored in. Chunks that are
+    /// stored close to each other are read together, like with [`for_each_chunk`](Self::for_each_chunk).
+    pub fn chunks(&mut self) -> Chunks<'_, R> {
+        let mut locations = self.locations;
         let mut indices: Vec<usize> = (0..1024).collect();
-        indices.sort_by_key(|&i| offsets[i]);
-        offsets.sort();
+        indices.sort_by_key(|&i| locations[i]);
+        locations.sort();
 
-        let mut buf = Vec::new();
+        Chunks {
+            region: self,
+            locations,
+            indices,
+            next: 0,
+            batch: 0..0,
+            batch_start: 0,
+            last_sector: 0,
+            buf: Vec::new(),
+            external: Vec::new(),
+            failed: false,
+        }
+    }
+
+    /// Reads the payload (compression type followed by the compressed data) of a single chunk,
+    /// or None if the chunk doesn't exist
+    pub fn read_payload(&mut self, index: usize) -> Result<Option<Vec<u8>>, io::Error> {
+        let Some(location) = self.locations[index] else {
+            return Ok(None);
+        };
+
+        self.file.seek(SeekFrom::Start(location.byte_offset()))?;
+        let len = self.file.read_u32::<BE>()? as usize;
+        if len as u64 + 4 > location.byte_len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "chunk length exceeds its sectors",
+            ));
+        }
+
+        let mut buf = vec![0; len];
+        self.file.read_exact(&mut buf)?;
+        if is_external(&buf) {
+            return self.external_payload(index, buf[0]).map(Some);
+        }
+        Ok(Some(buf))
+    }
+
+    /// Reads the payload of the chunk at region local coordinates from 0 to 31 by seeking to it
+    /// directly, without reading the rest of the file. Other coordinates are an
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) error.
+    pub fn read_chunk_at(
+        &mut self,
+        local_x: usize,
+        local_z: usize,
+    ) -> Result<Option<Vec<u8>>, io::Error> {
+        self.read_payload(local_index(local_x, local_z)?)
+    }
 
-        let mut i = 0;
-        let mut last_sector = 2;
-        while i < 1024 {
-            if offsets[i] == 0 {
-                i += 1;
-                continue;
+    /// Like [`read_chunk_at`](Self::read_chunk_at) for several chunks, read in the order they
+    /// are stored in. The payloads are returned in the order of the coordinates.
+    pub fn read_chunks_at(
+        &mut self,
+        coords: &[(usize, usize)],
+    ) -> Result<Vec<Option<Vec<u8>>>, io::Error> {
+        let indices = coords
+            .iter()
+            .map(|&(local_x, local_z)| local_index(local_x, local_z))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut order: Vec<usize> = (0..indices.len()).collect();
+        order.sort_by_key(|&i| self.locations[indices[i]]);
+        let mut payloads = vec![None; indices.len()];
+        for i in order {
+            payloads[i] = self.read_payload(indices[i])?;
+        }
+        Ok(payloads)
+    }
+}
+
+/// Index of a chunk in the region file header from region local coordinates
+fn local_index(local_x: usize, local_z: usize) -> Result<usize, io::Error> {
+    if local_x >= 32 || local_z >= 32 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "region local chunk coordinates {} {} are outside of 0 to 31",
+                local_x, local_z
+            ),
+        ));
+    }
+    Ok(local_z * 32 + local_x)
+}
+
+impl<T: AsRef<[u8]>> RegionFile<Cursor<T>> {
+    /// Returns where the payload of a chunk is in the buffer, or None if the chunk doesn't exist.
+    /// Unlike [`read_payload`](RegionFile::read_payload) this doesn't copy the payload.
+    pub fn payload_range(&self, index: usize) -> Result<Option<Range<usize>>, io::Error> {
+        let Some(location) = self.locations[index] else {
+            return Ok(None);
+        };
+        let buf = self.file.get_ref().as_ref();
+        let start = location.byte_offset() as usize;
+        let mut sectors = buf.get(start..).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "chunk sectors are outside of the file",
+            )
+        })?;
+        let len = sectors.read_u32::<BE>()? as usize;
+        if len as u64 + 4 > location.byte_len() || len > sectors.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "chunk length exceeds its sectors",
+            ));
+        }
+        Ok(Some(start + 4..start + 4 + len))
+    }
+
+    /// Returns the index, timestamp and payload range of all chunks in the order they are stored
+    /// in. Chunks stored in external `.mcc` files only have their compression type in the region
+    /// file, see [`is_external`].
+    pub fn payload_ranges(
+        &self,
+    ) -> impl Iterator<Item = (usize, u32, Result<Range<usize>, io::Error>)> + '_ {
+        let mut indices: Vec<usize> = (0..1024)
+            .filter(|&index| self.locations[index].is_some())
+            .collect();
+        indices.sort_by_key(|&index| self.locations[index]);
+        indices.into_iter().map(|index| {
+            let range = self.payload_range(index).transpose().unwrap();
+            (index, self.timestamps[index], range)
+        })
+    }
+}
+
+/// A memory mapped file that can be shared between threads
+#[cfg(feature = "memmap2")]
+#[derive(Clone)]
+pub struct SharedMmap(std::sync::Arc<memmap2::Mmap>);
+
+#[cfg(feature = "memmap2")]
+impl AsRef<[u8]> for SharedMmap {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "memmap2")]
+impl RegionFile<Cursor<SharedMmap>> {
+    /// Maps a region file into memory, so that chunk payloads can be passed around as
+    /// [`Payload::Mapped`] without copying them
+    pub fn map(path: &Path) -> Result<Self, io::Error> {
+        let file = File::open(path)?;
+        // SAFETY: the world mustn't be written to while it's read, like with the other readers.
+        // Changes to the file may show up in the payloads, but can't cause undefined behavior
+        // as the mapping is only read as bytes.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(Self::new(Cursor::new(SharedMmap(std::sync::Arc::new(mmap))))?.with_path(path))
+    }
+}
+
+/// A chunk payload that owns its bytes or is a part of a memory mapped region file
+#[derive(Clone)]
+pub enum Payload {
+    Owned(Vec<u8>),
+    #[cfg(feature = "memmap2")]
+    Mapped(SharedMmap, Range<usize>),
+}
+
+impl Deref for Payload {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Payload::Owned(buf) => buf,
+            #[cfg(feature = "memmap2")]
+            Payload::Mapped(mmap, range) => &mmap.as_ref()[range.clone()],
+        }
+    }
+}
+
+/// A chunk read by [`RegionFile::chunks`]
+#[derive(Debug, Clone)]
+pub struct ChunkEntry {
+    pub index: usize,
+    /// Region local x coordinate from 0 to 31
+    pub local_x: usize,
+    /// Region local z coordinate from 0 to 31
+    pub local_z: usize,
+    /// When the chunk was last saved as unix timestamp in seconds
+    pub timestamp: u32,
+    /// Compression type followed by the compressed data
+    pub payload: Vec<u8>,
+}
+
+/// Iterator over the chunks of a region file, returned by [`RegionFile::chunks`]. It stops
+/// after the first error.
+pub struct Chunks<'a, R> {
+    region: &'a mut RegionFile<R>,
+    locations: [Option<ChunkLocation>; 1024],
+    indices: Vec<usize>,
+    next: usize,
+    batch: Range<usize>,
+    batch_start: u32,
+    last_sector: u32,
+    buf: Vec<u8>,
+    /// Payload of the last chunk read from an external file
+    external: Vec<u8>,
+    failed: bool,
+}
+
+impl<R: Read + Seek> Chunks<'_, R> {
+    /// Reads the next run of chunks that are stored close to each other into the buffer and
+    /// returns their positions in the sorted locations
+    fn next_batch(&mut self) -> Result<Option<Range<usize>>, io::Error> {
+        let locations = &self.locations;
+        let mut i = self.next;
+        while i < 1024 && locations[i].is_none() {
+            i += 1;
+        }
+        let Some(first) = locations.get(i).copied().flatten() else {
+            self.next = 1024;
+            return Ok(None);
+        };
+
+        // locations are sorted by their sector offset, but chunks in broken files may overlap
+        let sector_start = first.sector_offset();
+        let mut sector_end = first.sector_end();
+        let mut j = i + 1;
+        while let Some(location) = locations.get(j).copied().flatten() {
+            let empty = location.sector_offset().saturating_sub(sector_end);
+            let sector_count = location.sector_end().max(sector_end) - sector_start;
+            if sector_count + empty > 16 {
+                break;
             }
+            sector_end = sector_end.max(location.sector_end());
+            j += 1;
+        }
+
+        let len = (sector_end - sector_start) as u64 * ChunkLocation::SECTOR_SIZE;
+
+        let file = &mut self.region.file;
+        if sector_start != self.last_sector {
+            file.seek(SeekFrom::Start(first.byte_offset()))?;
+        }
+        self.last_sector = sector_end;
 
-            let sector_start = offsets[i] >> 8;
-            let mut j = i + 1;
+        self.buf.clear();
+        self.buf.reserve(len as usize);
+        Read::by_ref(file).take(len).read_to_end(&mut self.buf)?;
 
-            while j < 1024 {
-                let empty = (offsets[j] >> 8) - ((offsets[j - 1] >> 8) + (offsets[j - 1] & 0xff));
-                let sector_count = (offsets[j] >> 8) + (offsets[j] & 0xff) - sector_start;
-                if sector_count + empty > 16 {
-                    break;
+        self.next = j;
+        self.batch_start = sector_start;
+        Ok(Some(i..j))
+    }
+
+    /// Returns the index, timestamp and payload of a chunk in the current batch. The sectors
+    /// may be cut off at the end of the file, but the payload has to be complete.
+    fn entry(&mut self, i: usize) -> Result<(usize, u32, &[u8]), io::Error> {
+        let location = self.locations[i].unwrap();
+        let start = (location.sector_offset() - self.batch_start) as usize
+            * ChunkLocation::SECTOR_SIZE as usize;
+        let end = (start + location.byte_len() as usize).min(self.buf.len());
+        let mut buf = self.buf.get(start..end).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "chunk sectors are outside of the file",
+            )
+        })?;
+        let len = buf.read_u32::<BE>()? as usize;
+        let payload = buf.get(..len).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "chunk length exceeds its sectors",
+            )
+        })?;
+        let index = self.indices[i];
+        let timestamp = self.region.timestamps[index];
+        if is_external(payload) {
+            self.external = self.region.external_payload(index, payload[0])?;
+            return Ok((index, timestamp, &self.external));
+        }
+        Ok((index, timestamp, payload))
+    }
+}
+
+impl<R: Read + Seek> Iterator for Chunks<'_, R> {
+    type Item = Result<ChunkEntry, io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        if self.batch.is_empty() {
+            match self.next_batch() {
+                Ok(Some(batch)) => self.batch = batch,
+                Ok(None) => return None,
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
                 }
-                j += 1;
             }
+        }
+        let i = self.batch.next().unwrap();
+        let result = self.entry(i).map(|(index, timestamp, payload)| ChunkEntry {
+            index,
+            local_x: index % 32,
+            local_z: index / 32,
+            timestamp,
+            payload: payload.to_vec(),
+        });
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
+
+impl<R: Read + Write + Seek> RegionFile<R> {
+    /// Like [`new`](Self::new), but writes an empty header first if the file is empty, e.g.
+    /// because it was just created. The file has to be opened for writing.
+    pub fn create(mut file: R) -> Result<Self, io::Error> {
+        if file.seek(SeekFrom::End(0))? == 0 {
+            file.write_all(&[0; 8192])?;
+        }
+        file.seek(SeekFrom::Start(0))?;
+        Self::new(file)
+    }
 
-            let sector_end = (offsets[j - 1] >> 8) + (offsets[j - 1] & 0xff);
-            let len = (sector_end - sector_start) as u64 * 4096;
+    /// Writes a chunk payload (compression type followed by the compressed data) at the given
+    /// index. The chunk's sectors are reused if it fits, otherwise it is written to the first
+    /// gap large enough or appended to the file. The chunk is read back and its checksum
+    /// compared unless turned off with [`set_verify_writes`](Self::set_verify_writes). The file
+    /// has to be opened for writing.
+    pub fn write_chunk(&mut self, index: usize, payload: &[u8]) -> Result<(), io::Error> {
+        let sector_count = u8::try_from(
+            (payload.len() as u64 + 4).div_ceil(ChunkLocation::SECTOR_SIZE),
+        )
+        .map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "chunk is too large to be stored in the region file",
+            )
+        })?;
 
-            let sector_offset = sector_start - last_sector;
-            if sector_offset > 0 {
-                self.file
-                    .seek(SeekFrom::Current(sector_offset as i64 * 4096))?;
+        let location = match self.locations[index] {
+            Some(location) if sector_count <= location.sector_count() => {
+                ChunkLocation::new(location.sector_offset(), sector_count)
+            }
+            _ => {
+                let file_sectors = self
+                    .file
+                    .seek(SeekFrom::End(0))?
+                    .div_ceil(ChunkLocation::SECTOR_SIZE) as u32;
+                self.allocate(sector_count, file_sectors)
             }
-            last_sector = sector_end;
+        }
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "region file is full"))?;
 
-            buf.clear();
-            buf.reserve(len as usize);
-            self.file.by_ref().take(len).read_to_end(&mut buf)?;
+        let mut buf = Vec::with_capacity(location.byte_len() as usize);
+        buf.write_u32::<BE>(payload.len() as u32)?;
+        buf.extend_from_slice(payload);
+        buf.resize(location.byte_len() as usize, 0);
 
-            for i in i..j {
-                let start = ((offsets[i] >> 8) - sector_start) as usize * 4096;
-                let mut buf = &buf[start..];
-                let len = buf.read_u32::<BE>()? as usize;
-                func((indices[i], &buf[..len]));
+        self.file.seek(SeekFrom::Start(location.byte_offset()))?;
+        self.file.write_all(&buf)?;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as u32;
+        self.set_header(index, Some(location), timestamp)?;
+
+        if self.verify_writes {
+            let written = self.read_payload(index)?;
+            if written.as_deref().map(checksum) != Some(checksum(payload)) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "chunk payload differs after writing it",
+                ));
             }
+        }
+        Ok(())
+    }
+
+    /// Finds the first free sectors large enough for a chunk. The sectors still in use by the
+    /// chunk being replaced aren't free until the header points elsewhere. Appends to the file if
+    /// no gap between the chunks is large enough.
+    fn allocate(&self, sector_count: u8, file_sectors: u32) -> Option<ChunkLocation> {
+        let mut used: Vec<ChunkLocation> = self.locations.iter().flatten().copied().collect();
+        used.sort();
 
-            i = j;
+        let mut sector_offset = 2;
+        for location in &used {
+            if location.sector_offset() >= sector_offset + sector_count as u32 {
+                return ChunkLocation::new(sector_offset, sector_count);
+            }
+            sector_offset = sector_offset.max(location.sector_end());
         }
+        ChunkLocation::new(sector_offset.max(file_sectors), sector_count)
+    }
+
+    /// Whether every chunk is read back after writing it and its checksum compared, failing the
+    /// write if the payload doesn't match. On by default.
+    pub fn set_verify_writes(&mut self, verify_writes: bool) {
+        self.verify_writes = verify_writes;
+    }
 
+    /// Removes a chunk from the header so that it is regenerated on the next load.
+    /// The file has to be opened for writing.
+    pub fn delete_chunk(&mut self, index: usize) -> Result<(), io::Error> {
+        self.set_header(index, None, 0)
+    }
+
+    fn set_header(
+        &mut self,
+        index: usize,
+        location: Option<ChunkLocation>,
+        timestamp: u32,
+    ) -> Result<(), io::Error> {
+        self.file.seek(SeekFrom::Start(index as u64 * 4))?;
+        self.file
+            .write_u32::<BE>(location.map_or(0, ChunkLocation::to_header))?;
+        self.file.seek(SeekFrom::Start(4096 + index as u64 * 4))?;
+        self.file.write_u32::<BE>(timestamp)?;
+        self.locations[index] = location;
+        self.timestamps[index] = timestamp;
         Ok(())
     }
 }
 
+/// Creates or updates a region file on disk, encoding the chunks written to it with one
+/// compression
+pub struct RegionWriter {
+    region_file: RegionFile,
+    compression: Compression,
+}
+
+impl RegionWriter {
+    /// Opens the region file at the path for writing, creating it and its directory if they
+    /// don't exist yet
+    pub fn open(path: &Path) -> Result<Self, io::Error> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        Ok(RegionWriter {
+            region_file: RegionFile::create(file)?.with_path(path),
+            compression: Compression::default(),
+        })
+    }
+
+    /// Sets the compression of the chunks written with [`write_chunk`](Self::write_chunk)
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Encodes and writes a chunk at the given index
+    pub fn write_chunk(&mut self, index: usize, chunk: &NbtCompound) -> Result<(), ChunkError> {
+        let payload = encode_chunk_with(chunk, self.compression)?;
+        Ok(self.region_file.write_chunk(index, &payload)?)
+    }
+
+    /// Writes an already encoded payload at the given index
+    pub fn write_payload(&mut self, index: usize, payload: &[u8]) -> Result<(), io::Error> {
+        self.region_file.write_chunk(index, payload)
+    }
+
+    /// Removes a chunk so that it is regenerated on the next load
+    pub fn delete_chunk(&mut self, index: usize) -> Result<(), io::Error> {
+        self.region_file.delete_chunk(index)
+    }
+
+    /// The region file being written, e.g. to read chunks before replacing them
+    pub fn region_file(&mut self) -> &mut RegionFile {
+        &mut self.region_file
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum ChunkError {
     #[error("invalid compression type {0}")]
     InvalidCompressionType(u8),
+    #[error("chunk is stored in an external .mcc file")]
+    External,
+    #[error("unsupported custom compression algorithm {0}")]
+    UnsupportedCompression(String),
     #[error(transparent)]
     Io(#[from] io::Error),
     #[error(transparent)]
     NbtIo(#[from] quartz_nbt::io::NbtIoError),
 }
 
-pub fn read_chunk(mut buf: &[u8]) -> Result<NbtCompound, ChunkError> {
+/// Compression of a chunk payload, stored as its first byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    Gzip = 1,
+    /// What the game writes
+    #[default]
+    Zlib = 2,
+    /// Only read by the game since 24w04a (1.20.5)
+    None = 3,
+}
+
+impl Compression {
+    /// Returns None for unknown compression types. Both 0 and 3 mean uncompressed.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 | 3 => Some(Compression::None),
+            1 => Some(Compression::Gzip),
+            2 => Some(Compression::Zlib),
+            _ => None,
+        }
+    }
+
+    fn flavor(self) -> quartz_nbt::io::Flavor {
+        match self {
+            Compression::Gzip => quartz_nbt::io::Flavor::GzCompressed,
+            Compression::Zlib => quartz_nbt::io::Flavor::ZlibCompressed,
+            Compression::None => quartz_nbt::io::Flavor::Uncompressed,
+        }
+    }
+}
+
+/// Whether a payload is only the compression type of a chunk stored in an external `.mcc` file,
+/// which [`RegionFile::read_payload`] reads instead
+pub fn is_external(payload: &[u8]) -> bool {
+    payload
+        .first()
+        .is_some_and(|&compression_type| compression_type & EXTERNAL_FLAG != 0)
+}
+
+pub fn read_chunk(buf: &[u8]) -> Result<NbtCompound, ChunkError> {
+    decode_chunk(buf, |mut data, flavor| {
+        Ok(quartz_nbt::io::read_nbt(&mut data, flavor)?.0)
+    })
+}
+
+/// Deserializes a chunk payload into a type like [`Chunk`](crate::chunk::Chunk) instead of an
+/// [`NbtCompound`]
+pub fn deserialize_chunk<T: DeserializeOwned>(buf: &[u8]) -> Result<T, ChunkError> {
+    decode_chunk(buf, |data, flavor| {
+        Ok(quartz_nbt::serde::deserialize(data, flavor)?.0)
+    })
+}
+
+/// Decompresses the chunk if the NBT reader can't and passes the NBT data with its flavor to
+/// `read`
+fn decode_chunk<T>(
+    mut buf: &[u8],
+    read: impl FnOnce(&[u8], quartz_nbt::io::Flavor) -> Result<T, quartz_nbt::io::NbtIoError>,
+) -> Result<T, ChunkError> {
     let compression_type = buf.read_u8()?;
-    Ok(quartz_nbt::io::read_nbt(
-        &mut buf,
-        match compression_type {
-            0 => quartz_nbt::io::Flavor::Uncompressed,
-            1 => quartz_nbt::io::Flavor::GzCompressed,
-            2 => quartz_nbt::io::Flavor::ZlibCompressed,
-            t => return Err(ChunkError::InvalidCompressionType(t)),
-        },
-    )?
-    .0)
+    if compression_type & EXTERNAL_FLAG != 0 {
+        return Err(ChunkError::External);
+    }
+    let data = match compression_type {
+        4 => decompress_lz4(buf)?,
+        127 => decompress_custom(buf)?,
+        _ => {
+            let compression = Compression::from_id(compression_type)
+                .ok_or(ChunkError::InvalidCompressionType(compression_type))?;
+            return Ok(read(buf, compression.flavor())?);
+        }
+    };
+    Ok(read(&data, quartz_nbt::io::Flavor::Uncompressed)?)
+}
+
+/// Decompresses the LZ4 compressed chunks (compression type 4) the game writes since 1.20.5 if
+/// configured to. They are in the block format of lz4-java's `LZ4BlockOutputStream`, a
+/// sequence of blocks with a header each, ended by an empty block.
+fn decompress_lz4(mut buf: &[u8]) -> Result<Vec<u8>, io::Error> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let mut data = Vec::new();
+    loop {
+        let mut magic = [0; 8];
+        buf.read_exact(&mut magic)?;
+        if &magic != b"LZ4Block" {
+            return Err(invalid("invalid LZ4 block magic"));
+        }
+        let method = buf.read_u8()? & 0xf0;
+        let compressed_len = buf.read_u32::<byteorder::LE>()? as usize;
+        let len = buf.read_u32::<byteorder::LE>()? as usize;
+        // checksum of the decompressed block
+        buf.read_u32::<byteorder::LE>()?;
+        if len == 0 {
+            return Ok(data);
+        }
+
+        let block = buf
+            .get(..compressed_len)
+            .ok_or_else(|| invalid("LZ4 block exceeds the chunk"))?;
+        buf = &buf[compressed_len..];
+        match method {
+            0x10 => data.extend_from_slice(block),
+            0x20 => data.extend_from_slice(
+                &lz4_flex::block::decompress(block, len)
+                    .map_err(|e| invalid(&format!("invalid LZ4 block: {}", e)))?,
+            ),
+            _ => return Err(invalid("unknown LZ4 block compression method")),
+        }
+    }
+}
+
+/// Decompresses a chunk with a custom compression (type 127), which third-party servers can
+/// write since 24w04a. The name of the algorithm comes first, like `minecraft:zstd`, of which
+/// only zstd is supported.
+fn decompress_custom(mut buf: &[u8]) -> Result<Vec<u8>, ChunkError> {
+    let len = buf.read_u16::<BE>()? as usize;
+    let name = buf.get(..len).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "compression algorithm name exceeds the chunk",
+        )
+    })?;
+    let name = String::from_utf8_lossy(name);
+    buf = &buf[len..];
+    match name.rsplit(':').next() {
+        Some("zstd") => Ok(zstd::decode_all(buf)?),
+        _ => Err(ChunkError::UnsupportedCompression(name.into_owned())),
+    }
+}
+
+/// Encodes a chunk as a zlib compressed region file payload
+pub fn encode_chunk(chunk: &NbtCompound) -> Result<Vec<u8>, ChunkError> {
+    encode_chunk_with(chunk, Compression::Zlib)
+}
+
+/// Encodes a chunk as a region file payload with the given compression
+pub fn encode_chunk_with(
+    chunk: &NbtCompound,
+    compression: Compression,
+) -> Result<Vec<u8>, ChunkError> {
+    let mut buf = vec![compression as u8];
+    quartz_nbt::io::write_nbt(&mut buf, None, chunk, compression.flavor())?;
+    Ok(buf)
+}
+
+/// Returns the region coordinates of a chunk and its index inside the region file
+pub fn chunk_position(chunk_x: i32, chunk_z: i32) -> (i32, i32, usize) {
+    (
+        chunk_x >> 5,
+        chunk_z >> 5,
+        ((chunk_x & 31) + (chunk_z & 31) * 32) as usize,
+    )
+}
+
+/// Parses the region coordinates from a `r.<x>.<z>.mca` file name
+pub fn region_coords(path: &Path) -> Option<(i32, i32)> {
+    let mut split = path.file_name()?.to_str()?.split('.');
+    let coords = match (split.next(), split.next(), split.next(), split.next()) {
+        (Some("r"), Some(x), Some(z), Some("mca")) => (x.parse().ok()?, z.parse().ok()?),
+        _ => return None,
+    };
+    split.next().is_none().then_some(coords)
+}
+
+/// Lists the region files in a directory with their region coordinates
+pub fn region_files(dir: &Path) -> Result<Vec<(i32, i32, PathBuf)>, io::Error> {
+    Ok(fs::read_dir(dir)?
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let (x, z) = region_coords(&path)?;
+            Some((x, z, path))
+        })
+        .collect())
+}
+
+/// Decodes all chunks of the region files in a directory and passes them with their chunk
+/// coordinates. Chunks that fail to decode are reported and skipped, and so are region files
+/// that can't be read, like the empty or truncated ones a crash can leave behind.
+pub fn for_each_chunk_in_dir(
+    dir: &Path,
+    mut func: impl FnMut(i32, i32, NbtCompound),
+) -> Result<(), io::Error> {
+    let mut skipped = 0;
+    for (region_x, region_z, path) in region_files(dir)? {
+        let result = for_each_chunk_in_region(&path, |index, buf| {
+            let chunk_x = region_x * 32 + (index % 32) as i32;
+            let chunk_z = region_z * 32 + (index / 32) as i32;
+            match read_chunk(buf) {
+                Ok(chunk) => func(chunk_x, chunk_z, chunk),
+                Err(e) => eprintln!("failed to read chunk {} {}: {}", chunk_x, chunk_z, e),
+            }
+        });
+        if !result {
+            skipped += 1;
+        }
+    }
+    report_skipped_regions(dir, skipped);
+    Ok(())
+}
+
+/// Passes the index and payload of every chunk in a region file. Returns false after printing a
+/// notice if the file can't be read.
+fn for_each_chunk_in_region(path: &Path, mut func: impl FnMut(usize, &[u8])) -> bool {
+    let result = RegionFile::open(path)
+        .and_then(|mut region_file| region_file.for_each_chunk(|(index, _, buf)| func(index, buf)));
+    match result {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!(
+                "notice: skipping unreadable region file {}: {}",
+                path.display(),
+                e
+            );
+            false
+        }
+    }
+}
+
+fn report_skipped_regions(dir: &Path, skipped: usize) {
+    if skipped > 0 {
+        eprintln!(
+            "{} unreadable region files skipped in {}",
+            skipped,
+            dir.display()
+        );
+    }
+}
+
+/// Like [`for_each_chunk_in_dir`] but only decodes the chunks in the selection
+pub fn for_each_chunk_in_selection(
+    dir: &Path,
+    selection: &ChunkSelection,
+    mut func: impl FnMut(i32, i32, NbtCompound),
+) -> Result<(), io::Error> {
+    let mut skipped = 0;
+    for (region_x, region_z, path) in region_files(dir)? {
+        if region_x < selection.min.0 >> 5
+            || region_x > selection.max.0 >> 5
+            || region_z < selection.min.1 >> 5
+            || region_z > selection.max.1 >> 5
+        {
+            continue;
+        }
+        let result = for_each_chunk_in_region(&path, |index, buf| {
+            let chunk_x = region_x * 32 + (index % 32) as i32;
+            let chunk_z = region_z * 32 + (index / 32) as i32;
+            if !selection.contains(chunk_x, chunk_z) {
+                return;
+            }
+            match read_chunk(buf) {
+                Ok(chunk) => func(chunk_x, chunk_z, chunk),
+                Err(e) => eprintln!("failed to read chunk {} {}: {}", chunk_x, chunk_z, e),
+            }
+        });
+        if !result {
+            skipped += 1;
+        }
+    }
+    report_skipped_regions(dir, skipped);
+    Ok(())
+}
+
+/// Decodes the chunks of all region files in a directory in parallel, yielding them with their
+/// chunk coordinates. Errors reading a whole region file are yielded at the coordinates of its
+/// first chunk.
+#[cfg(feature = "rayon")]
+pub fn par_chunks(
+    dir: &Path,
+) -> Result<
+    impl rayon::iter::ParallelIterator<Item = (i32, i32, Result<NbtCompound, ChunkError>)>,
+    io::Error,
+> {
+    use rayon::{iter::Either, prelude::*};
+
+    Ok(region_files(dir)?
+        .into_par_iter()
+        .flat_map(|(region_x, region_z, path)| {
+            let result = File::open(&path)
+                .and_then(RegionFile::new)
+                .map(|region_file| region_file.with_path(&path))
+                .and_then(|mut region_file| region_file.chunks().collect::<Result<Vec<_>, _>>());
+            let chunks = match result {
+                Ok(chunks) => chunks,
+                Err(e) => {
+                    return Either::Left(rayon::iter::once((
+                        region_x * 32,
+                        region_z * 32,
+                        Err(e.into()),
+                    )))
+                }
+            };
+
+            Either::Right(chunks.into_par_iter().map(move |chunk| {
+                let chunk_x = region_x * 32 + chunk.local_x as i32;
+                let chunk_z = region_z * 32 + chunk.local_z as i32;
+                (chunk_x, chunk_z, read_chunk(&chunk.payload))
+            }))
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_header_entries_have_no_location() {
+        assert_eq!(ChunkLocation::from_header(0), None);
+        // an offset without sectors is as empty as no entry at all
+        assert_eq!(ChunkLocation::from_header(5 << 8), None);
+        assert_eq!(ChunkLocation::new(5, 0), None);
+    }
+
+    #[test]
+    fn locations_in_the_header_are_rejected() {
+        assert_eq!(ChunkLocation::new(0, 1), None);
+        assert_eq!(ChunkLocation::new(1, 1), None);
+        assert_eq!(ChunkLocation::from_header((1 << 8) | 1), None);
+        assert!(ChunkLocation::new(2, 1).is_some());
+    }
+
+    #[test]
+    fn maximum_offset_and_count_round_trip() {
+        let location = ChunkLocation::new(ChunkLocation::MAX_SECTOR_OFFSET, u8::MAX).unwrap();
+        assert_eq!(location.to_header(), u32::MAX);
+        assert_eq!(ChunkLocation::from_header(u32::MAX), Some(location));
+        assert_eq!(
+            location.sector_end(),
+            ChunkLocation::MAX_SECTOR_OFFSET + 255
+        );
+        assert_eq!(
+            location.byte_offset(),
+            ChunkLocation::MAX_SECTOR_OFFSET as u64 * 4096
+        );
+        assert_eq!(location.byte_len(), 255 * 4096);
+    }
+
+    #[test]
+    fn offsets_beyond_24_bits_are_rejected() {
+        assert_eq!(
+            ChunkLocation::new(ChunkLocation::MAX_SECTOR_OFFSET + 1, 1),
+            None
+        );
+    }
+
+    #[test]
+    fn header_entries_round_trip() {
+        let entry = (0x01_0203 << 8) | 4;
+        let location = ChunkLocation::from_header(entry).unwrap();
+        assert_eq!(location.sector_offset(), 0x01_0203);
+        assert_eq!(location.sector_count(), 4);
+        assert_eq!(location.to_header(), entry);
+    }
+
+    #[test]
+    fn compression_types_0_and_3_are_uncompressed() {
+        assert_eq!(Compression::from_id(0), Some(Compression::None));
+        assert_eq!(Compression::from_id(3), Some(Compression::None));
+        // an empty compound with an empty name
+        for compression_type in [0, 3] {
+            let chunk = read_chunk(&[compression_type, 10, 0, 0, 0]).unwrap();
+            assert!(chunk.is_empty());
+        }
+        assert!(matches!(
+            read_chunk(&[5, 10, 0, 0, 0]),
+            Err(ChunkError::InvalidCompressionType(5))
+        ));
+    }
+
+    #[test]
+    fn coordinates_outside_the_region_are_invalid_input() {
+        let mut region_file = RegionFile::new(Cursor::new(vec![0; 8192])).unwrap();
+        assert_eq!(region_file.read_chunk_at(31, 31).unwrap(), None);
+        for (local_x, local_z) in [(32, 0), (0, 32)] {
+            let e = region_file.read_chunk_at(local_x, local_z).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        }
+        let e = region_file.read_chunks_at(&[(0, 0), (40, 0)]).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+    }
 }
@@ -0,0 +1,162 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::File,
+    io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use quartz_nbt::NbtCompound;
+
+use crate::region::{chunk_position, read_chunk, ChunkError, RegionFile};
+
+type SharedRegion = Arc<Mutex<RegionFile>>;
+
+/// Keeps the most recently used region files of a directory open, so that looking up chunks
+/// doesn't reopen the file and reparse its header every time. Can be shared between threads.
+pub struct RegionCache {
+    dir: PathBuf,
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+struct CacheState {
+    /// Open region files, or None for regions that don't exist
+    regions: HashMap<(i32, i32), Option<SharedRegion>>,
+    /// Least recently used first
+    order: VecDeque<(i32, i32)>,
+}
+
+impl CacheState {
+    /// Returns the cached region and marks it as the most recently used, or None if it isn't
+    /// cached
+    fn touch(&mut self, key: (i32, i32)) -> Option<Option<SharedRegion>> {
+        let region = self.regions.get(&key).cloned()?;
+        if let Some(i) = self.order.iter().position(|k| *k == key) {
+            self.order.remove(i);
+        }
+        self.order.push_back(key);
+        Some(region)
+    }
+}
+
+impl RegionCache {
+    /// Creates a cache for the region files in `dir` that keeps up to `capacity` of them open
+    pub fn new(dir: impl Into<PathBuf>, capacity: usize) -> Self {
+        RegionCache {
+            dir: dir.into(),
+            capacity: capacity.max(1),
+            state: Mutex::new(CacheState {
+                regions: HashMap::new(),
+                order: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Returns the region file, opening it if it isn't cached, or None if it doesn't exist. The
+    /// file is opened without holding the lock, so that other threads can use the cached regions
+    /// meanwhile.
+    pub fn region(&self, region_x: i32, region_z: i32) -> Result<Option<SharedRegion>, io::Error> {
+        let key = (region_x, region_z);
+        if let Some(region) = self.state.lock().unwrap().touch(key) {
+            return Ok(region);
+        }
+
+        let path = self.dir.join(format!("r.{}.{}.mca", region_x, region_z));
+        let region = match File::open(&path) {
+            Ok(file) => Some(Arc::new(Mutex::new(
+                RegionFile::new(file)?.with_path(&path),
+            ))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+
+        let mut state = self.state.lock().unwrap();
+        // another thread may have opened the region meanwhile, whose copy is kept
+        if let Some(region) = state.touch(key) {
+            return Ok(region);
+        }
+        if state.order.len() >= self.capacity {
+            if let Some(evicted) = state.order.pop_front() {
+                state.regions.remove(&evicted);
+            }
+        }
+        state.regions.insert(key, region.clone());
+        state.order.push_back(key);
+        Ok(region)
+    }
+
+    /// Reads and decodes a chunk, or returns None if it doesn't exist
+    pub fn read_chunk(
+        &self,
+        chunk_x: i32,
+        chunk_z: i32,
+    ) -> Result<Option<NbtCompound>, ChunkError> {
+        let (region_x, region_z, index) = chunk_position(chunk_x, chunk_z);
+        let region = match self.region(region_x, region_z)? {
+            Some(region) => region,
+            None => return Ok(None),
+        };
+        let payload = region.lock().unwrap().read_payload(index)?;
+        payload.map(|payload| read_chunk(&payload)).transpose()
+    }
+
+    /// Drops all cached region files, e.g. after they were modified on disk
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.regions.clear();
+        state.order.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, OpenOptions};
+
+    use quartz_nbt::compound;
+
+    use super::*;
+    use crate::region::encode_chunk;
+
+    fn write_region(dir: &std::path::Path, region_x: i32, region_z: i32) {
+        let path = dir.join(format!("r.{}.{}.mca", region_x, region_z));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .unwrap();
+        let mut region_file = RegionFile::create(file).unwrap();
+        let chunk = compound! { "xPos": region_x * 32, "zPos": region_z * 32 };
+        region_file
+            .write_chunk(0, &encode_chunk(&chunk).unwrap())
+            .unwrap();
+    }
+
+    #[test]
+    fn regions_are_cached_up_to_the_capacity() {
+        let dir = std::env::temp_dir().join(format!("region-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for region_x in 0..3 {
+            write_region(&dir, region_x, 0);
+        }
+        let cache = RegionCache::new(&dir, 2);
+
+        let chunk = cache.read_chunk(32, 0).unwrap().unwrap();
+        assert_eq!(chunk.get::<_, i32>("xPos").unwrap(), 32);
+        assert!(cache.read_chunk(33, 0).unwrap().is_none());
+        assert!(cache.read_chunk(-1, 0).unwrap().is_none());
+
+        let first = cache.region(0, 0).unwrap().unwrap();
+        assert!(Arc::ptr_eq(&first, &cache.region(0, 0).unwrap().unwrap()));
+        // region 1 0 is evicted as the least recently used
+        cache.region(2, 0).unwrap();
+        assert_eq!(cache.state.lock().unwrap().order, [(0, 0), (2, 0)]);
+        assert!(Arc::ptr_eq(&first, &cache.region(0, 0).unwrap().unwrap()));
+
+        cache.clear();
+        assert!(!Arc::ptr_eq(&first, &cache.region(0, 0).unwrap().unwrap()));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,112 @@
+use quartz_nbt::{NbtCompound, NbtTag};
+
+use crate::version::{GameVersion, DATA_VERSION_1_18};
+
+/// Moves a terrain chunk by a number of chunks: its position, the positions of its block
+/// entities, scheduled ticks and, before 1.17, entities. Structure starts and references are
+/// cleared, since they point to other chunks which aren't necessarily moved along.
+pub fn relocate_chunk(chunk: &mut NbtCompound, offset: (i32, i32)) {
+    let version = GameVersion::of(chunk).unwrap_or(GameVersion::new(DATA_VERSION_1_18));
+    let level = match version.level_mut(chunk) {
+        Some(level) => level,
+        None => return,
+    };
+    let (dx, dz) = (offset.0 * 16, offset.1 * 16);
+
+    add_int(level, "xPos", offset.0);
+    add_int(level, "zPos", offset.1);
+
+    let (ticks_keys, structures_key) = if version.nested_block_states() {
+        (["block_ticks", "fluid_ticks"], "structures")
+    } else {
+        (["TileTicks", "LiquidTicks"], "Structures")
+    };
+    for key in [version.block_entities_key()].into_iter().chain(ticks_keys) {
+        for compound in compounds_mut(level, key) {
+            add_int(compound, "x", dx);
+            add_int(compound, "z", dz);
+        }
+    }
+    if version.entities_in_chunk() {
+        for entity in compounds_mut(level, "Entities") {
+            relocate_entity(entity, (dx, dz));
+        }
+    }
+
+    if let Some(NbtTag::Compound(structures)) = level.inner_mut().get_mut(structures_key) {
+        for tag in structures.inner_mut().values_mut() {
+            if let NbtTag::Compound(compound) = tag {
+                compound.inner_mut().clear();
+            }
+        }
+    }
+}
+
+/// Moves a chunk of the `entities` folder by a number of chunks
+pub fn relocate_entities_chunk(chunk: &mut NbtCompound, offset: (i32, i32)) {
+    if let Some(NbtTag::IntArray(pos)) = chunk.inner_mut().get_mut("Position") {
+        if pos.len() == 2 {
+            pos[0] += offset.0;
+            pos[1] += offset.1;
+        }
+    }
+    for entity in compounds_mut(chunk, "Entities") {
+        relocate_entity(entity, (offset.0 * 16, offset.1 * 16));
+    }
+}
+
+/// Moves a chunk of the `poi` folder by a number of chunks
+pub fn relocate_poi_chunk(chunk: &mut NbtCompound, offset: (i32, i32)) {
+    let sections = match chunk.get_mut::<_, &mut NbtCompound>("Sections") {
+        Ok(sections) => sections,
+        Err(_) => return,
+    };
+    for section in sections.inner_mut().values_mut() {
+        if let NbtTag::Compound(section) = section {
+            for record in compounds_mut(section, "Records") {
+                if let Some(NbtTag::IntArray(pos)) = record.inner_mut().get_mut("pos") {
+                    if pos.len() == 3 {
+                        pos[0] += offset.0 * 16;
+                        pos[2] += offset.1 * 16;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Moves an entity and its passengers by a number of blocks, including the block an item
+/// frame or painting hangs on
+fn relocate_entity(entity: &mut NbtCompound, offset: (i32, i32)) {
+    if let Some(NbtTag::List(pos)) = entity.inner_mut().get_mut("Pos") {
+        if let [NbtTag::Double(x), _, NbtTag::Double(z)] = pos.inner_mut().as_mut_slice() {
+            *x += offset.0 as f64;
+            *z += offset.1 as f64;
+        }
+    }
+    add_int(entity, "TileX", offset.0);
+    add_int(entity, "TileZ", offset.1);
+    for passenger in compounds_mut(entity, "Passengers") {
+        relocate_entity(passenger, offset);
+    }
+}
+
+fn add_int(compound: &mut NbtCompound, key: &str, delta: i32) {
+    if let Some(NbtTag::Int(value)) = compound.inner_mut().get_mut(key) {
+        *value += delta;
+    }
+}
+
+fn compounds_mut<'a>(
+    compound: &'a mut NbtCompound,
+    key: &str,
+) -> impl Iterator<Item = &'a mut NbtCompound> {
+    let tags = match compound.inner_mut().get_mut(key) {
+        Some(NbtTag::List(list)) => list.inner_mut().as_mut_slice(),
+        _ => &mut [],
+    };
+    tags.iter_mut().filter_map(|tag| match tag {
+        NbtTag::Compound(compound) => Some(compound),
+        _ => None,
+    })
+}
@@ -0,0 +1,110 @@
+use std::io::{self, Write};
+
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+
+/// Writes a compound as compact SNBT, producing the same output as `NbtCompound::to_snbt` but
+/// without allocating a string per value
+pub fn write_compound<W: Write>(writer: &mut W, compound: &NbtCompound) -> io::Result<()> {
+    write_compound_keys(writer, compound, false)
+}
+
+/// Writes a compound as compact SNBT with the keys of all nested compounds sorted, so that equal
+/// compounds always produce the same output regardless of the order they were read in
+pub fn write_sorted_compound<W: Write>(writer: &mut W, compound: &NbtCompound) -> io::Result<()> {
+    write_compound_keys(writer, compound, true)
+}
+
+/// Writes a tag as compact SNBT
+pub fn write_tag<W: Write>(writer: &mut W, tag: &NbtTag) -> io::Result<()> {
+    write_tag_keys(writer, tag, false)
+}
+
+fn write_compound_keys<W: Write>(
+    writer: &mut W,
+    compound: &NbtCompound,
+    sorted: bool,
+) -> io::Result<()> {
+    let mut entries: Vec<_> = compound.inner().iter().collect();
+    if sorted {
+        entries.sort_unstable_by_key(|(key, _)| *key);
+    }
+    writer.write_all(b"{")?;
+    for (i, (key, value)) in entries.into_iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        write_string(writer, key)?;
+        writer.write_all(b":")?;
+        write_tag_keys(writer, value, sorted)?;
+    }
+    writer.write_all(b"}")
+}
+
+fn write_tag_keys<W: Write>(writer: &mut W, tag: &NbtTag, sorted: bool) -> io::Result<()> {
+    match tag {
+        NbtTag::Byte(value) => write!(writer, "{}B", value),
+        NbtTag::Short(value) => write!(writer, "{}S", value),
+        NbtTag::Int(value) => write!(writer, "{}", value),
+        NbtTag::Long(value) => write!(writer, "{}L", value),
+        NbtTag::Float(value) => write!(writer, "{}F", value),
+        NbtTag::Double(value) => write!(writer, "{}D", value),
+        NbtTag::ByteArray(values) => write_array(writer, "B", values),
+        NbtTag::String(value) => write_string(writer, value),
+        NbtTag::List(list) => write_list(writer, list, sorted),
+        NbtTag::Compound(compound) => write_compound_keys(writer, compound, sorted),
+        NbtTag::IntArray(values) => write_array(writer, "I", values),
+        NbtTag::LongArray(values) => write_array(writer, "L", values),
+    }
+}
+
+fn write_list<W: Write>(writer: &mut W, list: &NbtList, sorted: bool) -> io::Result<()> {
+    writer.write_all(b"[")?;
+    for (i, tag) in list.as_ref().iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        write_tag_keys(writer, tag, sorted)?;
+    }
+    writer.write_all(b"]")
+}
+
+fn write_array<W: Write, T: std::fmt::Display>(
+    writer: &mut W,
+    type_specifier: &str,
+    values: &[T],
+) -> io::Result<()> {
+    write!(writer, "[{};", type_specifier)?;
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        write!(writer, "{}", value)?;
+    }
+    writer.write_all(b"]")
+}
+
+/// Writes a string or key, quoted and escaped only if needed
+fn write_string<W: Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    if !NbtTag::should_quote(value) {
+        return writer.write_all(value.as_bytes());
+    }
+
+    let quote = if value.contains('"') { b'\'' } else { b'"' };
+    writer.write_all(&[quote])?;
+    let mut start = 0;
+    for (i, byte) in value.bytes().enumerate() {
+        let escaped: &[u8] = match byte {
+            b'\n' => b"\\n",
+            b'\r' => b"\\r",
+            b'\t' => b"\\t",
+            b'\\' => b"\\\\",
+            _ if byte == quote => &[b'\\', quote],
+            _ => continue,
+        };
+        writer.write_all(&value.as_bytes()[start..i])?;
+        writer.write_all(escaped)?;
+        start = i + 1;
+    }
+    writer.write_all(&value.as_bytes()[start..])?;
+    writer.write_all(&[quote])
+}
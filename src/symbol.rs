@@ -0,0 +1,78 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{OnceLock, RwLock},
+};
+
+/// An interned string such as an item, block or entity id. Each distinct string is stored once
+/// for the whole process, so symbols are copied, compared and hashed as a `u32` instead of
+/// allocating a `String` at every step of a scan.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol(u32);
+
+#[derive(Default)]
+struct Interner {
+    strings: Vec<&'static str>,
+    symbols: HashMap<&'static str, Symbol>,
+}
+
+fn interner() -> &'static RwLock<Interner> {
+    static INTERNER: OnceLock<RwLock<Interner>> = OnceLock::new();
+    INTERNER.get_or_init(Default::default)
+}
+
+impl Symbol {
+    /// Returns the symbol of a string, adding it to the interner if it's new. Interned strings
+    /// are never freed, which is fine for ids but not for arbitrary text.
+    pub fn intern(string: &str) -> Symbol {
+        if let Some(&symbol) = interner().read().unwrap().symbols.get(string) {
+            return symbol;
+        }
+        let mut interner = interner().write().unwrap();
+        // another thread may have added it between releasing the read and taking the write lock
+        if let Some(&symbol) = interner.symbols.get(string) {
+            return symbol;
+        }
+        let string: &'static str = Box::leak(string.into());
+        let symbol = Symbol(interner.strings.len() as u32);
+        interner.strings.push(string);
+        interner.symbols.insert(string, symbol);
+        symbol
+    }
+
+    /// Returns the interned string
+    pub fn as_str(self) -> &'static str {
+        interner().read().unwrap().strings[self.0 as usize]
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(string: &str) -> Self {
+        Symbol::intern(string)
+    }
+}
+
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders symbols by their strings, not by when they were interned
+impl Ord for Symbol {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
@@ -0,0 +1,72 @@
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+use serde_json::Value;
+
+use crate::nbt_json::nbt_to_json;
+
+/// Converts a JSON text component like a CustomName to plain text, falling back to the raw
+/// string if it isn't valid JSON
+pub fn plain_text(json: &str) -> String {
+    match serde_json::from_str::<Value>(json) {
+        Ok(value) => {
+            let mut text = String::new();
+            append_text(&value, &mut text);
+            text
+        }
+        Err(_) => json.to_string(),
+    }
+}
+
+/// Converts a text component to plain text, whether it is stored as a JSON string or, since
+/// 1.21.5, as NBT
+pub fn tag_text(tag: &NbtTag) -> String {
+    match tag {
+        NbtTag::String(json) => plain_text(json),
+        tag => {
+            let mut text = String::new();
+            append_text(&nbt_to_json(tag), &mut text);
+            text
+        }
+    }
+}
+
+/// Returns the lines of text on a sign, from the front and back since 1.20 and from `Text1` to
+/// `Text4` before, or None if the block entity isn't a sign
+pub fn sign_lines(block_entity: &NbtCompound) -> Option<Vec<String>> {
+    if let Ok(front) = block_entity.get::<_, &NbtCompound>("front_text") {
+        let mut lines = Vec::new();
+        for side in [Ok(front), block_entity.get::<_, &NbtCompound>("back_text")] {
+            if let Ok(messages) = side.and_then(|side| side.get::<_, &NbtList>("messages")) {
+                lines.extend(messages.as_ref().iter().map(tag_text));
+            }
+        }
+        return Some(lines);
+    }
+    let lines: Vec<String> = ["Text1", "Text2", "Text3", "Text4"]
+        .iter()
+        .filter_map(|key| block_entity.get::<_, &str>(*key).ok())
+        .map(plain_text)
+        .collect();
+    (!lines.is_empty()).then_some(lines)
+}
+
+fn append_text(value: &Value, text: &mut String) {
+    match value {
+        Value::String(str) => text.push_str(str),
+        Value::Array(components) => {
+            for component in components {
+                append_text(component, text);
+            }
+        }
+        Value::Object(component) => {
+            if let Some(Value::String(str)) = component.get("text") {
+                text.push_str(str);
+            } else if let Some(Value::String(key)) = component.get("translate") {
+                text.push_str(key);
+            }
+            if let Some(extra) = component.get("extra") {
+                append_text(extra, text);
+            }
+        }
+        _ => {}
+    }
+}
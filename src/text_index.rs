@@ -0,0 +1,197 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Text written on a sign or in a book, with where it was found
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TextEntry {
+    /// `sign` or `book`
+    pub kind: String,
+    /// The file it was found in, relative to the world directory
+    pub file: String,
+    pub dimension: Option<String>,
+    pub pos: Option<[i32; 3]>,
+    /// UUID of the player whose inventory the book is in
+    pub player: Option<String>,
+    /// The container, entity or inventory the book is in
+    #[serde(rename = "in")]
+    pub container: Option<String>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    /// The lines of a sign or the pages of a book, separated by newlines
+    pub text: String,
+}
+
+/// An inverted index from the words of sign and book text to where they appear, kept in a JSON
+/// file between runs so that only the files modified since the last run have to be scanned
+/// again. Paths are stored relative to the world directory.
+pub struct TextIndex {
+    path: PathBuf,
+    world_path: PathBuf,
+    data: IndexData,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct IndexData {
+    /// Modification time of each scanned file in milliseconds since the unix epoch
+    files: BTreeMap<String, u64>,
+    next_id: u64,
+    entries: BTreeMap<u64, TextEntry>,
+    /// The ids of the entries each word appears in
+    words: BTreeMap<String, BTreeSet<u64>>,
+}
+
+impl TextIndex {
+    /// Loads the index written by a previous run, or starts an empty one if the file doesn't
+    /// exist yet
+    pub fn open(path: &Path, world_path: &Path) -> Result<Self, io::Error> {
+        let data = match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => IndexData::default(),
+            Err(e) => return Err(e),
+        };
+        Ok(TextIndex {
+            path: path.to_path_buf(),
+            world_path: world_path.to_path_buf(),
+            data,
+        })
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.world_path)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Forgets everything, to scan the whole world again
+    pub fn clear(&mut self) {
+        self.data = IndexData::default();
+    }
+
+    /// Whether the file was scanned since it was last modified
+    pub fn is_current(&self, path: &Path) -> bool {
+        let recorded = self.data.files.get(&self.relative(path));
+        recorded.is_some() && recorded.copied() == modified(path)
+    }
+
+    /// Replaces the entries found in a file by a previous scan with the ones found now
+    pub fn update_file(&mut self, path: &Path, entries: Vec<TextEntry>) {
+        let file = self.relative(path);
+        self.remove_entries(&file);
+        for mut entry in entries {
+            entry.file = file.clone();
+            let id = self.data.next_id;
+            self.data.next_id += 1;
+            for word in words(&entry.text) {
+                self.data.words.entry(word).or_default().insert(id);
+            }
+            self.data.entries.insert(id, entry);
+        }
+        if let Some(modified) = modified(path) {
+            self.data.files.insert(file, modified);
+        }
+    }
+
+    /// Removes the entries of files that were deleted since they were scanned
+    pub fn remove_missing(&mut self) {
+        let missing: Vec<String> = self
+            .data
+            .files
+            .keys()
+            .filter(|file| !self.world_path.join(file).exists())
+            .cloned()
+            .collect();
+        for file in missing {
+            self.remove_entries(&file);
+            self.data.files.remove(&file);
+        }
+    }
+
+    fn remove_entries(&mut self, file: &str) {
+        if !self.data.files.contains_key(file) {
+            return;
+        }
+        let ids: Vec<u64> = self
+            .data
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.file == file)
+            .map(|(&id, _)| id)
+            .collect();
+        for id in ids {
+            let entry = self.data.entries.remove(&id).unwrap();
+            for word in words(&entry.text) {
+                if let Some(ids) = self.data.words.get_mut(&word) {
+                    ids.remove(&id);
+                    if ids.is_empty() {
+                        self.data.words.remove(&word);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Number of indexed signs and books
+    pub fn len(&self) -> usize {
+        self.data.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.entries.is_empty()
+    }
+
+    /// Returns the entries containing all words of the query regardless of case. Words ending
+    /// with `*` match all words starting with them.
+    pub fn search(&self, query: &str) -> Vec<&TextEntry> {
+        let mut matches: Option<BTreeSet<u64>> = None;
+        for part in query.split_whitespace() {
+            let prefix = part.ends_with('*');
+            let part_words = words(part);
+            for (i, word) in part_words.iter().enumerate() {
+                let ids: BTreeSet<u64> = if prefix && i == part_words.len() - 1 {
+                    self.data
+                        .words
+                        .range(word.clone()..)
+                        .take_while(|(other, _)| other.starts_with(word.as_str()))
+                        .flat_map(|(_, ids)| ids.iter().copied())
+                        .collect()
+                } else {
+                    self.data.words.get(word).cloned().unwrap_or_default()
+                };
+                matches = Some(match matches {
+                    Some(matches) => matches.intersection(&ids).copied().collect(),
+                    None => ids,
+                });
+            }
+        }
+        matches
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|id| self.data.entries.get(id))
+            .collect()
+    }
+
+    /// Writes the index back to the file
+    pub fn save(&self) -> Result<(), io::Error> {
+        fs::write(&self.path, serde_json::to_string(&self.data)?)
+    }
+}
+
+/// Splits text into lowercase words of letters and digits, the way it is indexed
+pub fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn modified(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as u64)
+}
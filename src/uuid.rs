@@ -0,0 +1,27 @@
+/// Formats a UUID stored as four ints (as used since 1.16) in the hyphenated form
+pub fn format_uuid(ints: &[i32]) -> Option<String> {
+    let ints: &[i32; 4] = ints.try_into().ok()?;
+    let value = ints
+        .iter()
+        .fold(0u128, |value, &int| value << 32 | int as u32 as u128);
+    let hex = format!("{:032x}", value);
+    Some(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    ))
+}
+
+/// Formats a UUID stored as two longs (as used before 1.16) in the hyphenated form
+pub fn format_uuid_longs(most: i64, least: i64) -> String {
+    format_uuid(&[
+        (most >> 32) as i32,
+        most as i32,
+        (least >> 32) as i32,
+        least as i32,
+    ])
+    .unwrap()
+}
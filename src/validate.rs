@@ -0,0 +1,810 @@
+use std::fmt;
+
+use quartz_nbt::{NbtCompound, NbtList, NbtTag};
+
+use crate::version::GameVersion;
+
+/// A part of a chunk that doesn't match what the game expects
+#[derive(Debug, Clone)]
+pub struct Violation {
+    /// NBT path to the offending tag
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// How closely chunks have to follow the format of their DataVersion. Performance mods like
+/// Carpet, Lithium or C2ME and server software like Paper write chunks the game loads fine but
+/// that differ from what it writes itself. Tags in a different order are accepted in every mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strictness {
+    /// Only report what the game fails to load or loads wrong. Numbers of another type than
+    /// expected, e.g. a long `xPos` or an int section `Y`, heightmaps that aren't long arrays,
+    /// which the game recomputes, and entities chunks without `Position` are accepted.
+    Lenient,
+    /// Report missing required tags and tags of another type than the game writes
+    #[default]
+    Normal,
+    /// Also report tags the game doesn't write, e.g. `Paper.*` or data of mods, and missing
+    /// `LastUpdate`, `InhabitedTime`, `Heightmaps` and block entities, which the game always
+    /// writes but doesn't need
+    Strict,
+}
+
+/// Tags the game writes into terrain chunks or their `Level` compound, in any version
+const VANILLA_CHUNK_TAGS: &[&str] = &[
+    "DataVersion",
+    "Level",
+    "xPos",
+    "yPos",
+    "zPos",
+    "Status",
+    "LastUpdate",
+    "InhabitedTime",
+    "isLightOn",
+    "sections",
+    "Sections",
+    "block_entities",
+    "TileEntities",
+    "Entities",
+    "Heightmaps",
+    "HeightMap",
+    "Biomes",
+    "block_ticks",
+    "fluid_ticks",
+    "TileTicks",
+    "LiquidTicks",
+    "ToBeTicked",
+    "LiquidsToBeTicked",
+    "PostProcessing",
+    "structures",
+    "Structures",
+    "UpgradeData",
+    "blending_data",
+    "below_zero_retrogen",
+    "CarvingMasks",
+    "carving_mask",
+    "Lights",
+    "entities",
+    "TerrainPopulated",
+    "LightPopulated",
+    "V",
+];
+
+/// Tags the game writes into entities chunks
+const VANILLA_ENTITIES_CHUNK_TAGS: &[&str] = &["DataVersion", "Position", "Entities"];
+
+struct Validator {
+    violations: Vec<Violation>,
+    strictness: Strictness,
+}
+
+impl Validator {
+    fn new(strictness: Strictness) -> Self {
+        Validator {
+            violations: Vec::new(),
+            strictness,
+        }
+    }
+
+    fn report(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.violations.push(Violation {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Returns the tag if it exists and reports it otherwise
+    fn require<'a>(
+        &mut self,
+        compound: &'a NbtCompound,
+        path: &str,
+        key: &str,
+    ) -> Option<&'a NbtTag> {
+        let tag = compound.inner().get(key);
+        if tag.is_none() {
+            self.report(join(path, key), "missing");
+        }
+        tag
+    }
+
+    /// Requires an int, or any number when lenient like the game which converts them
+    fn require_int(&mut self, compound: &NbtCompound, path: &str, key: &str) -> Option<i32> {
+        match self.require(compound, path, key)? {
+            NbtTag::Int(value) => Some(*value),
+            tag if self.strictness == Strictness::Lenient && number(tag).is_some() => {
+                number(tag).map(|value| value as i32)
+            }
+            tag => {
+                self.report(
+                    join(path, key),
+                    format!("expected int, found {}", type_name(tag)),
+                );
+                None
+            }
+        }
+    }
+
+    fn require_compound<'a>(
+        &mut self,
+        compound: &'a NbtCompound,
+        path: &str,
+        key: &str,
+    ) -> Option<&'a NbtCompound> {
+        match self.require(compound, path, key)? {
+            NbtTag::Compound(value) => Some(value),
+            tag => {
+                self.report(
+                    join(path, key),
+                    format!("expected compound, found {}", type_name(tag)),
+                );
+                None
+            }
+        }
+    }
+
+    /// Requires a list whose elements are all compounds. Empty lists may have any element type.
+    fn require_compound_list<'a>(
+        &mut self,
+        compound: &'a NbtCompound,
+        path: &str,
+        key: &str,
+    ) -> Option<Vec<&'a NbtCompound>> {
+        let list = match self.require(compound, path, key)? {
+            NbtTag::List(list) => list,
+            tag => {
+                self.report(
+                    join(path, key),
+                    format!("expected list, found {}", type_name(tag)),
+                );
+                return None;
+            }
+        };
+        self.compound_list(list, &join(path, key))
+    }
+
+    fn compound_list<'a>(&mut self, list: &'a NbtList, path: &str) -> Option<Vec<&'a NbtCompound>> {
+        let mut compounds = Vec::with_capacity(list.len());
+        for (i, tag) in list.as_ref().iter().enumerate() {
+            match tag {
+                NbtTag::Compound(compound) => compounds.push(compound),
+                tag => {
+                    self.report(
+                        format!("{}[{}]", path, i),
+                        format!("expected compound, found {}", type_name(tag)),
+                    );
+                    return None;
+                }
+            }
+        }
+        Some(compounds)
+    }
+
+    fn require_string<'a>(
+        &mut self,
+        compound: &'a NbtCompound,
+        path: &str,
+        key: &str,
+    ) -> Option<&'a str> {
+        match self.require(compound, path, key)? {
+            NbtTag::String(value) => Some(value),
+            tag => {
+                self.report(
+                    join(path, key),
+                    format!("expected string, found {}", type_name(tag)),
+                );
+                None
+            }
+        }
+    }
+
+    /// Reports the tags the game doesn't write when strict
+    fn unknown_tags(&mut self, compound: &NbtCompound, path: &str, known: &[&str]) {
+        if self.strictness != Strictness::Strict {
+            return;
+        }
+        for key in compound.inner().keys() {
+            if !known.contains(&key.as_str()) {
+                self.report(join(path, key), "not written by the game, e.g. by a mod");
+            }
+        }
+    }
+
+    /// Reports tags the game always writes but doesn't need when strict
+    fn expect(&mut self, compound: &NbtCompound, path: &str, key: &str) {
+        if self.strictness == Strictness::Strict && !compound.contains_key(key) {
+            self.report(
+                join(path, key),
+                "missing, although the game always writes it",
+            );
+        }
+    }
+
+    /// Checks a paletted container: the long array has to have the length implied by the
+    /// palette size and all indices have to be inside the palette
+    fn paletted(
+        &mut self,
+        data_path: String,
+        palette_len: usize,
+        data: Option<&NbtTag>,
+        entries: usize,
+        min_bits: usize,
+        spanning: bool,
+    ) {
+        let data = match data {
+            Some(NbtTag::LongArray(data)) => data,
+            Some(tag) => {
+                self.report(
+                    data_path,
+                    format!("expected long array, found {}", type_name(tag)),
+                );
+                return;
+            }
+            None if palette_len <= 1 => return,
+            None => {
+                self.report(data_path, "missing with more than one palette entry");
+                return;
+            }
+        };
+        if palette_len == 0 {
+            self.report(data_path, "palette is empty");
+            return;
+        }
+
+        let bits = bits_for(palette_len).max(min_bits);
+        let expected_len = if spanning {
+            (entries * bits).div_ceil(64)
+        } else {
+            entries.div_ceil(64 / bits)
+        };
+        if data.len() != expected_len {
+            self.report(
+                data_path,
+                format!(
+                    "expected {} longs for {} palette entries, found {}",
+                    expected_len,
+                    palette_len,
+                    data.len()
+                ),
+            );
+            return;
+        }
+
+        let mask = (1u64 << bits) - 1;
+        let invalid = (0..entries)
+            .filter(|&i| {
+                let value = if spanning {
+                    let bit = i * bits;
+                    let mut value = data[bit / 64] as u64 >> (bit % 64);
+                    if bit % 64 + bits > 64 {
+                        value |= (data[bit / 64 + 1] as u64) << (64 - bit % 64);
+                    }
+                    value & mask
+                } else {
+                    let per_long = 64 / bits;
+                    (data[i / per_long] as u64 >> (i % per_long * bits)) & mask
+                };
+                value as usize >= palette_len
+            })
+            .count();
+        if invalid > 0 {
+            self.report(
+                data_path,
+                format!(
+                    "{} indices outside of the palette of {}",
+                    invalid, palette_len
+                ),
+            );
+        }
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn bits_for(palette_len: usize) -> usize {
+    (usize::BITS - palette_len.saturating_sub(1).leading_zeros()) as usize
+}
+
+fn number(tag: &NbtTag) -> Option<f64> {
+    match *tag {
+        NbtTag::Byte(value) => Some(value as f64),
+        NbtTag::Short(value) => Some(value as f64),
+        NbtTag::Int(value) => Some(value as f64),
+        NbtTag::Long(value) => Some(value as f64),
+        NbtTag::Float(value) => Some(value as f64),
+        NbtTag::Double(value) => Some(value),
+        _ => None,
+    }
+}
+
+fn type_name(tag: &NbtTag) -> &'static str {
+    match tag {
+        NbtTag::Byte(_) => "byte",
+        NbtTag::Short(_) => "short",
+        NbtTag::Int(_) => "int",
+        NbtTag::Long(_) => "long",
+        NbtTag::Float(_) => "float",
+        NbtTag::Double(_) => "double",
+        NbtTag::ByteArray(_) => "byte array",
+        NbtTag::String(_) => "string",
+        NbtTag::List(_) => "list",
+        NbtTag::Compound(_) => "compound",
+        NbtTag::IntArray(_) => "int array",
+        NbtTag::LongArray(_) => "long array",
+    }
+}
+
+/// Checks a terrain chunk against the format of its DataVersion, or of `assume_version` if
+/// given. `position` is where the chunk is stored in the region file, which has to match its own
+/// coordinates.
+pub fn validate_chunk(
+    chunk: &NbtCompound,
+    position: (i32, i32),
+    assume_version: Option<GameVersion>,
+    strictness: Strictness,
+) -> Vec<Violation> {
+    let mut v = Validator::new(strictness);
+    let version = match assume_version {
+        Some(version) => version,
+        None => match v.require_int(chunk, "", "DataVersion") {
+            Some(data_version) => GameVersion::new(data_version),
+            None => return v.violations,
+        },
+    };
+
+    let path = version.level_path();
+    let level = if path.is_empty() {
+        chunk
+    } else {
+        match v.require_compound(chunk, "", path) {
+            Some(level) => level,
+            None => return v.violations,
+        }
+    };
+    if !path.is_empty() {
+        v.unknown_tags(chunk, "", &["DataVersion", path]);
+    }
+    v.unknown_tags(level, path, VANILLA_CHUNK_TAGS);
+    for key in ["LastUpdate", "InhabitedTime", "Heightmaps"] {
+        v.expect(level, path, key);
+    }
+    v.expect(level, path, version.block_entities_key());
+
+    let x = v.require_int(level, path, "xPos");
+    let z = v.require_int(level, path, "zPos");
+    if let (Some(x), Some(z)) = (x, z) {
+        if (x, z) != position {
+            v.report(
+                join(path, "xPos"),
+                format!(
+                    "chunk {} {} is stored at {} {}",
+                    x, z, position.0, position.1
+                ),
+            );
+        }
+    }
+    v.require_string(level, path, "Status");
+
+    let sections_key = version.sections_key();
+    let sections_path = join(path, sections_key);
+    if let Some(sections) = v.require_compound_list(level, path, sections_key) {
+        for (i, section) in sections.into_iter().enumerate() {
+            let section_path = format!("{}[{}]", sections_path, i);
+            match section.inner().get("Y") {
+                Some(NbtTag::Byte(_)) => {}
+                Some(tag) if strictness == Strictness::Lenient && number(tag).is_some() => {}
+                Some(tag) => v.report(
+                    join(&section_path, "Y"),
+                    format!("expected byte, found {}", type_name(tag)),
+                ),
+                None => v.report(join(&section_path, "Y"), "missing"),
+            }
+            if version.nested_block_states() {
+                validate_section(&mut v, section, &section_path);
+            } else {
+                validate_legacy_section(&mut v, section, &section_path, version);
+            }
+        }
+    }
+
+    let block_entities_key = version.block_entities_key();
+    if level.contains_key(block_entities_key) {
+        let block_entities_path = join(path, block_entities_key);
+        if let Some(block_entities) = v.require_compound_list(level, path, block_entities_key) {
+            for (i, block_entity) in block_entities.into_iter().enumerate() {
+                let block_entity_path = format!("{}[{}]", block_entities_path, i);
+                v.require_string(block_entity, &block_entity_path, "id");
+                let bx = v.require_int(block_entity, &block_entity_path, "x");
+                v.require_int(block_entity, &block_entity_path, "y");
+                let bz = v.require_int(block_entity, &block_entity_path, "z");
+                if let (Some(bx), Some(bz)) = (bx, bz) {
+                    if (bx >> 4, bz >> 4) != position {
+                        v.report(block_entity_path, "outside of the chunk");
+                    }
+                }
+            }
+        }
+    }
+
+    // the game recomputes heightmaps it can't read
+    if let Some(NbtTag::Compound(heightmaps)) = level
+        .inner()
+        .get("Heightmaps")
+        .filter(|_| strictness != Strictness::Lenient)
+    {
+        for (name, tag) in heightmaps.inner() {
+            let heightmap_path = join(&join(path, "Heightmaps"), name);
+            // the length depends on the height of the dimension
+            if !matches!(tag, NbtTag::LongArray(_)) {
+                v.report(
+                    heightmap_path,
+                    format!("expected long array, found {}", type_name(tag)),
+                );
+            }
+        }
+    }
+
+    v.violations
+}
+
+fn validate_section(v: &mut Validator, section: &NbtCompound, path: &str) {
+    if let Some(NbtTag::Compound(block_states)) = section.inner().get("block_states") {
+        let block_states_path = join(path, "block_states");
+        if let Some(palette) = v.require_compound_list(block_states, &block_states_path, "palette")
+        {
+            for (i, state) in palette.iter().enumerate() {
+                v.require_string(
+                    state,
+                    &format!("{}.palette[{}]", block_states_path, i),
+                    "Name",
+                );
+            }
+            v.paletted(
+                join(&block_states_path, "data"),
+                palette.len(),
+                block_states.inner().get("data"),
+                4096,
+                4,
+                false,
+            );
+        }
+    }
+
+    if let Some(NbtTag::Compound(biomes)) = section.inner().get("biomes") {
+        let biomes_path = join(path, "biomes");
+        let palette_len = match v.require(biomes, &biomes_path, "palette") {
+            Some(NbtTag::List(palette)) => {
+                if palette
+                    .as_ref()
+                    .iter()
+                    .any(|tag| !matches!(tag, NbtTag::String(_)))
+                {
+                    v.report(join(&biomes_path, "palette"), "expected a list of strings");
+                }
+                palette.len()
+            }
+            Some(tag) => {
+                v.report(
+                    join(&biomes_path, "palette"),
+                    format!("expected list, found {}", type_name(tag)),
+                );
+                return;
+            }
+            None => return,
+        };
+        v.paletted(
+            join(&biomes_path, "data"),
+            palette_len,
+            biomes.inner().get("data"),
+            64,
+            1,
+            false,
+        );
+    }
+}
+
+fn validate_legacy_section(
+    v: &mut Validator,
+    section: &NbtCompound,
+    path: &str,
+    version: GameVersion,
+) {
+    let palette = match section.inner().get("Palette") {
+        Some(NbtTag::List(palette)) => palette,
+        Some(tag) => {
+            v.report(
+                join(path, "Palette"),
+                format!("expected list, found {}", type_name(tag)),
+            );
+            return;
+        }
+        // sections with only light data have no blocks
+        None => return,
+    };
+    let palette_path = join(path, "Palette");
+    if let Some(palette) = v.compound_list(palette, &palette_path) {
+        for (i, state) in palette.iter().enumerate() {
+            v.require_string(state, &format!("{}[{}]", palette_path, i), "Name");
+        }
+        v.paletted(
+            join(path, "BlockStates"),
+            palette.len(),
+            section.inner().get("BlockStates"),
+            4096,
+            4,
+            version.spanning_block_states(),
+        );
+    }
+}
+
+/// Checks an entities chunk, stored in the `entities` folder since 1.17
+pub fn validate_entities_chunk(
+    chunk: &NbtCompound,
+    position: (i32, i32),
+    strictness: Strictness,
+) -> Vec<Violation> {
+    let mut v = Validator::new(strictness);
+    v.require_int(chunk, "", "DataVersion");
+    v.unknown_tags(chunk, "", VANILLA_ENTITIES_CHUNK_TAGS);
+
+    // the game only logs a warning for a missing position
+    let position_tag = match strictness {
+        Strictness::Lenient => chunk.inner().get("Position"),
+        _ => v.require(chunk, "", "Position"),
+    };
+    match position_tag {
+        Some(NbtTag::IntArray(pos)) if pos.len() == 2 && (pos[0], pos[1]) != position => {
+            let message = format!(
+                "chunk {} {} is stored at {} {}",
+                pos[0], pos[1], position.0, position.1
+            );
+            v.report("Position", message);
+        }
+        Some(NbtTag::IntArray(pos)) if pos.len() == 2 => {}
+        Some(_) => v.report("Position", "expected an int array of 2"),
+        None => {}
+    }
+
+    if let Some(entities) = v.require_compound_list(chunk, "", "Entities") {
+        for (i, entity) in entities.into_iter().enumerate() {
+            validate_entity(&mut v, entity, &format!("Entities[{}]", i));
+        }
+    }
+
+    v.violations
+}
+
+fn validate_entity(v: &mut Validator, entity: &NbtCompound, path: &str) {
+    v.require_string(entity, path, "id");
+    match v.require(entity, path, "Pos") {
+        Some(NbtTag::List(pos))
+            if pos.len() == 3
+                && pos
+                    .as_ref()
+                    .iter()
+                    .all(|tag| matches!(tag, NbtTag::Double(_))) => {}
+        Some(_) => v.report(join(path, "Pos"), "expected a list of 3 doubles"),
+        None => {}
+    }
+    match v.require(entity, path, "UUID") {
+        Some(NbtTag::IntArray(uuid)) if uuid.len() == 4 => {}
+        Some(_) => v.report(join(path, "UUID"), "expected an int array of 4"),
+        None => {}
+    }
+    if let Some(NbtTag::List(passengers)) = entity.inner().get("Passengers") {
+        let passengers_path = join(path, "Passengers");
+        if let Some(passengers) = v.compound_list(passengers, &passengers_path) {
+            for (i, passenger) in passengers.into_iter().enumerate() {
+                validate_entity(v, passenger, &format!("{}[{}]", passengers_path, i));
+            }
+        }
+    }
+}
+
+/// A change made by `repair_block_states`
+#[derive(Debug, Clone)]
+pub struct Repair {
+    /// Y of the section
+    pub section: i8,
+    pub message: String,
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "section {}: {}", self.section, self.message)
+    }
+}
+
+/// Repairs the block states of a chunk in the 1.18 format. Sections whose palette or data is
+/// inconsistent are filled with air, and palette entries for which `replacement` returns a
+/// block are replaced with it, e.g. blocks of removed mods.
+pub fn repair_block_states(
+    chunk: &mut NbtCompound,
+    replacement: impl Fn(&str) -> Option<String>,
+) -> Vec<Repair> {
+    let mut repairs = Vec::new();
+    let sections = match chunk.get_mut::<_, &mut NbtList>("sections") {
+        Ok(sections) => sections,
+        Err(_) => return repairs,
+    };
+    for section in sections.inner_mut() {
+        let section = match section {
+            NbtTag::Compound(section) => section,
+            _ => continue,
+        };
+        let y = section.get::<_, i8>("Y").unwrap_or_default();
+        let block_states = match section.get_mut::<_, &mut NbtCompound>("block_states") {
+            Ok(block_states) => block_states,
+            Err(_) => continue,
+        };
+
+        let mut v = Validator::new(Strictness::Normal);
+        if let Some(palette) = v.require_compound_list(block_states, "", "palette") {
+            for (i, state) in palette.iter().enumerate() {
+                v.require_string(state, &format!("palette[{}]", i), "Name");
+            }
+            v.paletted(
+                "data".to_string(),
+                palette.len(),
+                block_states.inner().get("data"),
+                4096,
+                4,
+                false,
+            );
+        }
+        if let Some(violation) = v.violations.first() {
+            let mut air = NbtCompound::new();
+            air.insert("Name", "minecraft:air");
+            let mut cleared = NbtCompound::new();
+            cleared.insert("palette", NbtList::from(vec![air]));
+            *block_states = cleared;
+            repairs.push(Repair {
+                section: y,
+                message: format!("filled with air because {}", violation),
+            });
+            continue;
+        }
+
+        let palette = match block_states.get_mut::<_, &mut NbtList>("palette") {
+            Ok(palette) => palette,
+            Err(_) => continue,
+        };
+        for state in palette.inner_mut() {
+            let state = match state {
+                NbtTag::Compound(state) => state,
+                _ => continue,
+            };
+            let name = state.get::<_, &str>("Name").unwrap_or_default().to_string();
+            if let Some(new_name) = replacement(&name) {
+                let mut new_state = NbtCompound::new();
+                new_state.insert("Name", new_name.as_str());
+                *state = new_state;
+                repairs.push(Repair {
+                    section: y,
+                    message: format!("replaced {} with {}", name, new_name),
+                });
+            }
+        }
+    }
+    repairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: [Strictness; 3] = [Strictness::Lenient, Strictness::Normal, Strictness::Strict];
+
+    /// A 1.18 chunk with every tag the validator looks at, in the order the game writes them
+    const CHUNK: &str = r#"{
+        DataVersion: 2860, xPos: 1, zPos: 2, yPos: -4, Status: "minecraft:full",
+        LastUpdate: 10L, InhabitedTime: 20L, Heightmaps: {WORLD_SURFACE: [L; 0L]},
+        sections: [{Y: 0b, block_states: {palette: [{Name: "minecraft:stone"}]}}],
+        block_entities: [{id: "minecraft:chest", x: 16, y: 64, z: 32}]
+    }"#;
+
+    fn chunk(snbt: &str) -> NbtCompound {
+        quartz_nbt::snbt::parse(snbt).unwrap()
+    }
+
+    /// The violations of each mode, from lenient to strict
+    fn violations(chunk: &NbtCompound) -> [Vec<String>; 3] {
+        MODES.map(|strictness| {
+            validate_chunk(chunk, (1, 2), None, strictness)
+                .iter()
+                .map(|violation| violation.path.clone())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn vanilla_chunk_is_valid_in_every_mode() {
+        assert!(violations(&chunk(CHUNK)).iter().all(Vec::is_empty));
+    }
+
+    #[test]
+    fn reordered_tags_are_valid_in_every_mode() {
+        let reordered = chunk(
+            r#"{
+                block_entities: [{z: 32, y: 64, x: 16, id: "minecraft:chest"}],
+                sections: [{block_states: {palette: [{Name: "minecraft:stone"}]}, Y: 0b}],
+                Heightmaps: {WORLD_SURFACE: [L; 0L]}, InhabitedTime: 20L, LastUpdate: 10L,
+                Status: "minecraft:full", yPos: -4, zPos: 2, xPos: 1, DataVersion: 2860
+            }"#,
+        );
+        assert!(violations(&reordered).iter().all(Vec::is_empty));
+    }
+
+    #[test]
+    fn missing_optional_tags_are_only_strict_violations() {
+        const OPTIONAL: [&str; 4] = [
+            "LastUpdate",
+            "InhabitedTime",
+            "Heightmaps",
+            "block_entities",
+        ];
+        let mut missing = chunk(CHUNK);
+        for key in OPTIONAL {
+            missing.inner_mut().remove(key);
+        }
+        let [lenient, normal, strict] = violations(&missing);
+        assert!(lenient.is_empty());
+        assert!(normal.is_empty());
+        assert_eq!(strict, OPTIONAL);
+    }
+
+    #[test]
+    fn extra_tags_are_only_strict_violations() {
+        let mut extra = chunk(CHUNK);
+        extra.insert("Paper.AsyncLoad", 1i8);
+        extra.insert("c2me:version", 3);
+        let [lenient, normal, mut strict] = violations(&extra);
+        assert!(lenient.is_empty());
+        assert!(normal.is_empty());
+        // extra tags are reported in the order of the compound's map
+        strict.sort();
+        assert_eq!(strict, ["Paper.AsyncLoad", "c2me:version"]);
+    }
+
+    #[test]
+    fn other_number_types_are_only_accepted_when_lenient() {
+        let mut retyped = chunk(CHUNK);
+        retyped.insert("xPos", 1i64);
+        let [lenient, normal, strict] = violations(&retyped);
+        assert!(lenient.is_empty());
+        assert_eq!(normal, ["xPos"]);
+        assert_eq!(strict, ["xPos"]);
+    }
+
+    #[test]
+    fn missing_required_tags_are_violations_in_every_mode() {
+        let mut missing = chunk(CHUNK);
+        missing.inner_mut().remove("Status");
+        for violations in violations(&missing) {
+            assert_eq!(violations, ["Status"]);
+        }
+    }
+
+    #[test]
+    fn entities_chunk_without_position_is_accepted_when_lenient() {
+        let entities = chunk(r#"{DataVersion: 2860, Entities: []}"#);
+        let violations = MODES.map(|strictness| {
+            validate_entities_chunk(&entities, (1, 2), strictness)
+                .iter()
+                .map(|violation| violation.path.clone())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(violations, [vec![], vec!["Position"], vec!["Position"]]);
+    }
+}
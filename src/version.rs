@@ -0,0 +1,234 @@
+use std::{fmt, str::FromStr};
+
+use quartz_nbt::NbtCompound;
+
+/// DataVersion of 1.13, which replaced numeric block ids with named block states
+pub const DATA_VERSION_1_13: i32 = 1451;
+
+/// DataVersion of 1.15, which made biomes three dimensional
+pub const DATA_VERSION_1_15: i32 = 2225;
+
+/// DataVersion of 1.16, since which block states don't span multiple longs
+pub const DATA_VERSION_1_16: i32 = 2529;
+
+/// DataVersion of 1.17, which moved entities into the `entities` folder
+pub const DATA_VERSION_1_17: i32 = 2724;
+
+/// DataVersion of 1.18, which moved the chunk data out of `Level` and changed the section format
+pub const DATA_VERSION_1_18: i32 = 2844;
+
+/// DataVersion of 1.20.5, which replaced the `tag` of items with components
+pub const DATA_VERSION_1_20_5: i32 = 3837;
+
+/// Releases that can be given by name instead of a DataVersion
+const RELEASES: &[(&str, i32)] = &[
+    ("1.13", 1519),
+    ("1.14", 1952),
+    ("1.15", 2225),
+    ("1.16", 2566),
+    ("1.17", 2724),
+    ("1.18", 2860),
+    ("1.19", 3105),
+    ("1.20", 3463),
+    ("1.20.5", 3837),
+    ("1.21", 3953),
+];
+
+/// How the biomes of a chunk are stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BiomeEncoding {
+    /// One numeric id per block column in `Level.Biomes`
+    Columns,
+    /// One numeric id per 4x4x4 cell in `Level.Biomes`
+    Cells,
+    /// A palette of biome names per section in `sections[].biomes`
+    Palette,
+}
+
+/// The differences in how chunks, entities and items are stored between game versions, selected
+/// by DataVersion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GameVersion {
+    pub data_version: i32,
+}
+
+impl GameVersion {
+    pub fn new(data_version: i32) -> Self {
+        GameVersion { data_version }
+    }
+
+    /// Reads the DataVersion of a chunk, entities chunk or `.dat` file
+    pub fn of(compound: &NbtCompound) -> Option<Self> {
+        compound.get::<_, i32>("DataVersion").ok().map(Self::new)
+    }
+
+    /// Returns the compound holding the chunk data, which is the chunk itself since 1.18
+    pub fn level<'a>(&self, chunk: &'a NbtCompound) -> Option<&'a NbtCompound> {
+        if self.data_version >= DATA_VERSION_1_18 {
+            Some(chunk)
+        } else {
+            chunk.get("Level").ok()
+        }
+    }
+
+    pub fn level_mut<'a>(&self, chunk: &'a mut NbtCompound) -> Option<&'a mut NbtCompound> {
+        if self.data_version >= DATA_VERSION_1_18 {
+            Some(chunk)
+        } else {
+            chunk.get_mut("Level").ok()
+        }
+    }
+
+    /// NBT path of the compound returned by [`level`](Self::level)
+    pub fn level_path(&self) -> &'static str {
+        if self.data_version >= DATA_VERSION_1_18 {
+            ""
+        } else {
+            "Level"
+        }
+    }
+
+    pub fn sections_key(&self) -> &'static str {
+        if self.data_version >= DATA_VERSION_1_18 {
+            "sections"
+        } else {
+            "Sections"
+        }
+    }
+
+    pub fn block_entities_key(&self) -> &'static str {
+        if self.data_version >= DATA_VERSION_1_18 {
+            "block_entities"
+        } else {
+            "TileEntities"
+        }
+    }
+
+    /// Key of the block state palette in a section, or in its `block_states` since 1.18
+    pub fn palette_key(&self) -> &'static str {
+        if self.data_version >= DATA_VERSION_1_18 {
+            "palette"
+        } else {
+            "Palette"
+        }
+    }
+
+    /// Key of the block state indices in a section, or in its `block_states` since 1.18
+    pub fn block_states_data_key(&self) -> &'static str {
+        if self.data_version >= DATA_VERSION_1_18 {
+            "data"
+        } else {
+            "BlockStates"
+        }
+    }
+
+    /// Whether blocks are stored as a palette of named block states instead of numeric ids
+    pub fn named_block_states(&self) -> bool {
+        self.data_version >= DATA_VERSION_1_13
+    }
+
+    /// Whether the block states are nested in `block_states` of the section
+    pub fn nested_block_states(&self) -> bool {
+        self.data_version >= DATA_VERSION_1_18
+    }
+
+    /// Whether indices of block states may span two longs
+    pub fn spanning_block_states(&self) -> bool {
+        self.data_version < DATA_VERSION_1_16
+    }
+
+    /// Whether entities are stored in `Level.Entities` of the terrain chunk instead of the
+    /// `entities` folder
+    pub fn entities_in_chunk(&self) -> bool {
+        self.data_version < DATA_VERSION_1_17
+    }
+
+    pub fn biome_encoding(&self) -> BiomeEncoding {
+        if self.data_version >= DATA_VERSION_1_18 {
+            BiomeEncoding::Palette
+        } else if self.data_version >= DATA_VERSION_1_15 {
+            BiomeEncoding::Cells
+        } else {
+            BiomeEncoding::Columns
+        }
+    }
+
+    /// Guesses the format of an item stored without a DataVersion, like the items printed by
+    /// dump-items. Before 1.20.5 items always have a `Count` and their data in `tag`.
+    pub fn of_item(item: &NbtCompound) -> Self {
+        if item.contains_key("Count") || item.contains_key("tag") {
+            GameVersion::new(DATA_VERSION_1_18)
+        } else {
+            GameVersion::new(DATA_VERSION_1_20_5)
+        }
+    }
+
+    /// Whether the data of items is stored in components instead of an NBT `tag`
+    pub fn item_components(&self) -> bool {
+        self.data_version >= DATA_VERSION_1_20_5
+    }
+
+    /// Path from an item to the list of items inside of it, like the contents of a shulker box.
+    /// The list holds the items wrapped as `{slot, item}` since 1.20.5.
+    pub fn item_contents_path(&self) -> &'static [&'static str] {
+        if self.item_components() {
+            &["components", "minecraft:container"]
+        } else {
+            &["tag", "BlockEntityTag", "Items"]
+        }
+    }
+
+    /// Key of an item's stack size, a byte before 1.20.5 and an int since
+    pub fn item_count_key(&self) -> &'static str {
+        if self.data_version >= DATA_VERSION_1_20_5 {
+            "count"
+        } else {
+            "Count"
+        }
+    }
+
+    /// Whether the game reads chunks stored uncompressed (compression type 3), which it does
+    /// since 24w04a
+    pub fn uncompressed_chunks(&self) -> bool {
+        self.data_version >= DATA_VERSION_1_20_5
+    }
+
+    /// Key of an item's additional data like enchantments and custom names
+    pub fn item_data_key(&self) -> &'static str {
+        if self.data_version >= DATA_VERSION_1_20_5 {
+            "components"
+        } else {
+            "tag"
+        }
+    }
+}
+
+impl fmt::Display for GameVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DataVersion {}", self.data_version)
+    }
+}
+
+/// Parses a DataVersion like `2975`, or a release like `1.18.2` which is mapped to the latest
+/// listed release not newer than it
+impl FromStr for GameVersion {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        if let Ok(data_version) = str.parse() {
+            return Ok(GameVersion::new(data_version));
+        }
+        let parse_release = |str: &str| -> Option<Vec<u32>> {
+            str.split('.').map(|part| part.parse().ok()).collect()
+        };
+        let release = parse_release(str)
+            .filter(|release| release.len() >= 2 && release[0] == 1)
+            .ok_or_else(|| format!("expected a DataVersion or release, found {}", str))?;
+        RELEASES
+            .iter()
+            .rev()
+            .find(|(name, _)| parse_release(name).is_some_and(|listed| listed <= release))
+            .map(|&(_, data_version)| GameVersion::new(data_version))
+            .ok_or_else(|| format!("releases before {} are not supported", RELEASES[0].0))
+    }
+}
@@ -0,0 +1,425 @@
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    region::{read_chunk, RegionFile},
+    version::GameVersion,
+};
+
+/// Returns the directory of a dimension inside the world directory: overworld, nether, end, or
+/// a dimension added by a datapack or mod, given by its id like `mymod:mining` or by its
+/// directory like `dimensions/mymod/mining`
+pub fn dimension_path(world_path: &Path, dim_name: &str) -> Option<PathBuf> {
+    Some(world_path.join(match dim_name {
+        "overworld" | "minecraft:overworld" => "",
+        "nether" | "minecraft:the_nether" => "DIM-1",
+        "end" | "minecraft:the_end" => "DIM1",
+        _ => return custom_dimension_path(dim_name).map(|path| world_path.join(path)),
+    }))
+}
+
+/// Returns `dimensions/<namespace>/<path>` for the id or directory of a custom dimension
+fn custom_dimension_path(dim_name: &str) -> Option<PathBuf> {
+    let (namespace, path) = match dim_name.strip_prefix("dimensions/") {
+        Some(dir) => dir.split_once('/')?,
+        None => dim_name.split_once(':')?,
+    };
+    // resource location characters, which also keeps the path inside the world directory
+    let valid = |part: &str| {
+        part.chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "_-./".contains(c))
+            && part
+                .split('/')
+                .all(|segment| !matches!(segment, "" | "." | ".."))
+    };
+    (valid(namespace) && !namespace.contains('/') && valid(path))
+        .then(|| Path::new("dimensions").join(namespace).join(path))
+}
+
+/// Lists the ids of the dimensions added by datapacks and mods, found by their directories in
+/// `dimensions/<namespace>/<path>`
+pub fn custom_dimensions(world_path: &Path) -> Vec<String> {
+    fn visit(dir: &Path, id: &str, dimensions: &mut Vec<String>) {
+        let mut entries: Vec<_> = match fs::read_dir(dir) {
+            Ok(entries) => entries.flatten().map(|entry| entry.path()).collect(),
+            Err(_) => return,
+        };
+        entries.sort();
+        let is_dimension = [Source::Region, Source::Entities, Source::Poi]
+            .iter()
+            .any(|source| dir.join(source.folder()).is_dir());
+        if is_dimension && !id.ends_with(':') {
+            dimensions.push(id.to_string());
+        }
+        for path in entries {
+            let name = match path.file_name().and_then(|name| name.to_str()) {
+                Some(name) if path.is_dir() => name,
+                _ => continue,
+            };
+            if ["region", "entities", "poi", "data"].contains(&name) {
+                continue;
+            }
+            let id = if id.ends_with(':') {
+                format!("{}{}", id, name)
+            } else {
+                format!("{}/{}", id, name)
+            };
+            visit(&path, &id, dimensions);
+        }
+    }
+
+    let mut dimensions = Vec::new();
+    let Ok(namespaces) = fs::read_dir(world_path.join("dimensions")) else {
+        return dimensions;
+    };
+    let mut namespaces: Vec<_> = namespaces.flatten().map(|entry| entry.path()).collect();
+    namespaces.sort();
+    for dir in namespaces {
+        if let Some(namespace) = dir.file_name().and_then(|name| name.to_str()) {
+            visit(&dir, &format!("{}:", namespace), &mut dimensions);
+        }
+    }
+    dimensions
+}
+
+/// A block position given as `x,y,z`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockPos(pub [i32; 3]);
+
+impl FromStr for BlockPos {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let coords = str
+            .split(',')
+            .map(|coord| coord.trim().parse::<i32>().map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(BlockPos(coords.try_into().map_err(|_| "expected x,y,z")?))
+    }
+}
+
+/// A single chunk `x,z` or a box of chunks `x1,z1..x2,z2`
+#[derive(Debug, Clone)]
+pub struct ChunkSelection {
+    pub min: (i32, i32),
+    pub max: (i32, i32),
+}
+
+impl FromStr for ChunkSelection {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let parse_coords = |str: &str| -> Result<(i32, i32), String> {
+            let (x, z) = str
+                .split_once(',')
+                .ok_or_else(|| "expected chunk coordinates as x,z".to_string())?;
+            let parse = |str: &str| str.trim().parse().map_err(|e| format!("{}", e));
+            Ok((parse(x)?, parse(z)?))
+        };
+
+        let (a, b) = match str.split_once("..") {
+            Some((a, b)) => (parse_coords(a)?, parse_coords(b)?),
+            None => (parse_coords(str)?, parse_coords(str)?),
+        };
+
+        Ok(ChunkSelection {
+            min: (a.0.min(b.0), a.1.min(b.1)),
+            max: (a.0.max(b.0), a.1.max(b.1)),
+        })
+    }
+}
+
+impl ChunkSelection {
+    pub fn contains(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        (self.min.0..=self.max.0).contains(&chunk_x) && (self.min.1..=self.max.1).contains(&chunk_z)
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = (i32, i32)> {
+        let (min, max) = (self.min, self.max);
+        (min.0..=max.0).flat_map(move |x| (min.1..=max.1).map(move |z| (x, z)))
+    }
+}
+
+/// The dimensions every world has, by the names the tools take
+pub const DIMENSIONS: &[&str] = &["overworld", "nether", "end"];
+
+/// A world directory, which finds the files and folders of the world and its dimensions
+#[derive(Debug, Clone)]
+pub struct World {
+    path: PathBuf,
+}
+
+impl World {
+    /// Fails if the directory has neither a level.dat nor a region folder, as it's then most
+    /// likely not a world
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, io::Error> {
+        let world = World { path: path.into() };
+        if !world.level_dat().exists() && !world.path.join("region").is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} has no level.dat or region folder", world.path.display()),
+            ));
+        }
+        Ok(world)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn level_dat(&self) -> PathBuf {
+        self.path.join("level.dat")
+    }
+
+    /// Returns a dimension by name (overworld, nether, end or the id of a custom dimension),
+    /// whether it exists or not
+    pub fn dimension(&self, name: &str) -> Option<Dimension> {
+        Some(Dimension {
+            name: name.to_string(),
+            path: dimension_path(&self.path, name)?,
+        })
+    }
+
+    /// Returns the dimensions that have a region, entities or poi folder, including those
+    /// added by datapacks and mods
+    pub fn dimensions(&self) -> impl Iterator<Item = Dimension> + '_ {
+        let vanilla = DIMENSIONS.iter().filter_map(|name| {
+            self.dimension(name).filter(|dimension| {
+                [Source::Region, Source::Entities, Source::Poi]
+                    .into_iter()
+                    .any(|source| dimension.has(source))
+            })
+        });
+        let custom = custom_dimensions(&self.path)
+            .into_iter()
+            .filter_map(|name| self.dimension(&name));
+        vanilla.chain(custom)
+    }
+
+    /// Returns the UUIDs and paths of the players' `.dat` files, sorted by UUID
+    pub fn playerdata(&self) -> Result<Vec<(String, PathBuf)>, io::Error> {
+        crate::players::playerdata_files(&self.path)
+    }
+
+    /// Returns the UUIDs and paths of the players' statistics, sorted by UUID
+    pub fn stats(&self) -> Result<Vec<(String, PathBuf)>, io::Error> {
+        crate::players::stats_files(&self.path)
+    }
+
+    /// Returns the UUIDs and paths of the players' advancements, sorted by UUID
+    pub fn advancements(&self) -> Result<Vec<(String, PathBuf)>, io::Error> {
+        crate::players::advancements_files(&self.path)
+    }
+}
+
+/// A dimension of a world, whose directory holds its region, entities and poi folders
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dimension {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl Dimension {
+    /// Returns the path of the region, entities or poi folder
+    pub fn folder(&self, source: Source) -> PathBuf {
+        self.path.join(source.folder())
+    }
+
+    pub fn has(&self, source: Source) -> bool {
+        self.folder(source).is_dir()
+    }
+
+    /// Lists the region files of the region, entities or poi folder with their region
+    /// coordinates
+    pub fn region_files(&self, source: Source) -> Result<Vec<(i32, i32, PathBuf)>, io::Error> {
+        crate::region::region_files(&self.folder(source))
+    }
+}
+
+/// A kind of data stored in its own folder of a world or dimension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Region,
+    Entities,
+    Poi,
+    Playerdata,
+}
+
+impl Source {
+    pub fn folder(&self) -> &'static str {
+        match self {
+            Source::Region => "region",
+            Source::Entities => "entities",
+            Source::Poi => "poi",
+            Source::Playerdata => "playerdata",
+        }
+    }
+}
+
+/// Which folders of a world are present, since copies of worlds often lack the entities, POI or
+/// playerdata
+#[derive(Debug, Clone)]
+pub struct Sources {
+    pub playerdata: bool,
+    /// Dimension name and the folders present in it
+    pub dimensions: Vec<(String, Vec<Source>)>,
+    /// Dimensions without an entities folder whose entities are in the region files, as before
+    /// 1.17
+    pub entities_in_chunks: Vec<String>,
+}
+
+impl Sources {
+    /// Checks which folders of the world and the given dimensions exist
+    pub fn detect(world_path: &Path, dimensions: &[String]) -> Self {
+        let dimensions: Vec<(String, Vec<Source>)> = dimensions
+            .iter()
+            .map(|dim_name| {
+                let present = dimension_path(world_path, dim_name)
+                    .map(|dim_path| {
+                        [Source::Region, Source::Entities, Source::Poi]
+                            .into_iter()
+                            .filter(|source| dim_path.join(source.folder()).is_dir())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                (dim_name.clone(), present)
+            })
+            .collect();
+        let entities_in_chunks = dimensions
+            .iter()
+            .filter(|(_, present)| {
+                present.contains(&Source::Region) && !present.contains(&Source::Entities)
+            })
+            .filter(|(dim_name, _)| {
+                dimension_path(world_path, dim_name)
+                    .is_some_and(|dim_path| entities_in_region(&dim_path.join("region")))
+            })
+            .map(|(dim_name, _)| dim_name.clone())
+            .collect();
+        Sources {
+            playerdata: world_path.join("playerdata").is_dir(),
+            dimensions,
+            entities_in_chunks,
+        }
+    }
+
+    /// Whether the folder exists in the dimension, or in the world for playerdata
+    pub fn has(&self, dim_name: &str, source: Source) -> bool {
+        if source == Source::Playerdata {
+            return self.playerdata;
+        }
+        self.dimensions
+            .iter()
+            .any(|(name, present)| name == dim_name && present.contains(&source))
+    }
+
+    /// Prints a notice for each missing folder with the parts of the analysis that are skipped
+    /// because of it, given per source
+    pub fn notify_missing(&self, skipped: &[(Source, &str)]) {
+        for &(source, what) in skipped {
+            if source == Source::Playerdata {
+                if !self.playerdata {
+                    eprintln!("notice: no playerdata folder, skipping {}", what);
+                }
+                continue;
+            }
+            for (dim_name, _) in &self.dimensions {
+                if source == Source::Entities && self.entities_in_chunks.contains(dim_name) {
+                    continue;
+                }
+                if !self.has(dim_name, source) {
+                    eprintln!(
+                        "notice: no {} folder in {}, skipping {}",
+                        source.folder(),
+                        dim_name,
+                        what
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Whether the first chunk found in a region folder predates 1.17 and holds its entities
+fn entities_in_region(region_path: &Path) -> bool {
+    for (_, _, path) in crate::region::region_files(region_path).unwrap_or_default() {
+        let mut region_file = match RegionFile::open(&path) {
+            Ok(region_file) => region_file,
+            Err(_) => continue,
+        };
+        for index in 0..1024 {
+            if let Ok(Some(payload)) = region_file.read_payload(index) {
+                if let Ok(chunk) = read_chunk(&payload) {
+                    return GameVersion::of(&chunk).is_some_and(|v| v.entities_in_chunk());
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Whether another process holds the lock on the world's `session.lock`, which the game and
+/// servers do while the world is open
+pub fn session_locked(world_path: &Path) -> Result<bool, io::Error> {
+    let file = match File::open(world_path.join("session.lock")) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    is_locked(&file)
+}
+
+/// Java locks files with `fcntl`, which doesn't see `flock` locks on Linux, so ask for the
+/// conflicting lock directly
+#[cfg(unix)]
+fn is_locked(file: &File) -> Result<bool, io::Error> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut lock: libc::flock = unsafe { std::mem::zeroed() };
+    lock.l_type = libc::F_WRLCK as _;
+    lock.l_whence = libc::SEEK_SET as _;
+    if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GETLK, &mut lock) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(i32::from(lock.l_type) != libc::F_UNLCK)
+}
+
+#[cfg(not(unix))]
+fn is_locked(file: &File) -> Result<bool, io::Error> {
+    match file.try_lock_shared() {
+        Ok(()) => Ok(false),
+        Err(std::fs::TryLockError::WouldBlock) => Ok(true),
+        Err(std::fs::TryLockError::Error(e)) => Err(e),
+    }
+}
+
+/// Prints a warning if the world is open in a running game or server, since the region files
+/// may change while they are read
+pub fn warn_if_open(world_path: &Path) {
+    if session_locked(world_path).unwrap_or(false) {
+        eprintln!(
+            "WARNING: the world is open in a running game or server, results may be inconsistent"
+        );
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("the world is open in a running game or server, stop it first or pass --force")]
+pub struct WorldOpen;
+
+/// Fails if the world is open in a running game or server, which would overwrite or corrupt
+/// the changes, unless `force` is set
+pub fn ensure_closed(world_path: &Path, force: bool) -> Result<(), WorldOpen> {
+    if !session_locked(world_path).unwrap_or(false) {
+        return Ok(());
+    }
+    if !force {
+        return Err(WorldOpen);
+    }
+    eprintln!("WARNING: the world is open in a running game or server, writing anyway");
+    Ok(())
+}
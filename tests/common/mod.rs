@@ -0,0 +1,94 @@
+#![allow(dead_code)]
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+/// A copy of a world from `fixtures/` in a temporary directory, removed when dropped, so that
+/// tests can change it
+pub struct TempWorld(PathBuf);
+
+impl TempWorld {
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn to_str(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for TempWorld {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+pub fn fixture_copy(version: &str, test: &str) -> TempWorld {
+    let target = std::env::temp_dir().join(format!(
+        "world-statistics-{}-{}-{}",
+        test,
+        version,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&target);
+    copy_dir(&fixture(version), &target).unwrap();
+    TempWorld(target)
+}
+
+pub fn fixture(version: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(version)
+}
+
+/// The versions of the worlds in `fixtures/`
+pub fn fixture_versions() -> Vec<String> {
+    let mut versions: Vec<String> =
+        fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures"))
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+    versions.sort();
+    versions
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &to.join(entry.file_name()))?;
+        } else {
+            fs::copy(entry.path(), to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads every file below a directory with its path, to check that a tool didn't change any
+pub fn snapshot(dir: &Path) -> Vec<(PathBuf, Vec<u8>)> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir).unwrap().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                let content = fs::read(&path).unwrap();
+                files.push((path, content));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Runs a tool, given by its `CARGO_BIN_EXE_<name>` path, with the arguments
+pub fn run(bin: &str, args: &[&str]) -> Output {
+    Command::new(bin).args(args).output().unwrap()
+}
@@ -0,0 +1,493 @@
+//! Runs the tools on the miniature world of every version in `fixtures/`, each with a chest of
+//! 5 diamonds and a pig in chunk 0 0, to check that they pick the format of each DataVersion
+
+mod common;
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use common::{fixture, fixture_copy, fixture_versions, run};
+use quartz_nbt::{NbtCompound, NbtList};
+use world_statistics::{
+    dat::write_dat,
+    region::{encode_chunk, read_chunk, RegionFile},
+    world::Sources,
+};
+
+fn stdout(bin: &str, args: &[&str]) -> String {
+    let output = run(bin, args);
+    assert!(
+        output.status.success(),
+        "{} {:?} exited with {}: {}",
+        bin,
+        args,
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn compat_check_passes_on_all_fixtures() {
+    let fixtures = fixture("");
+    let output = stdout(
+        env!("CARGO_BIN_EXE_compat-check"),
+        &[fixtures.to_str().unwrap()],
+    );
+    assert!(!output.contains("failed"), "{}", output);
+}
+
+#[test]
+fn dump_items_finds_the_diamonds() {
+    for version in fixture_versions() {
+        let world = fixture(&version);
+        let items = stdout(
+            env!("CARGO_BIN_EXE_dump-items"),
+            &[world.to_str().unwrap(), "overworld"],
+        );
+        assert_eq!(items.lines().count(), 1, "{}: {}", version, items);
+        assert!(
+            items.contains("minecraft:diamond"),
+            "{}: {}",
+            version,
+            items
+        );
+
+        let csv = stdout(
+            env!("CARGO_BIN_EXE_dump-items"),
+            &["--format", "csv", world.to_str().unwrap(), "overworld"],
+        );
+        assert_eq!(
+            csv.lines().nth(1),
+            Some("overworld,1,1,1,minecraft:chest,minecraft:diamond,5,"),
+            "{}",
+            version
+        );
+
+        let mut count_items = Command::new(env!("CARGO_BIN_EXE_count-items"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        count_items
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(items.as_bytes())
+            .unwrap();
+        let output = count_items.wait_with_output().unwrap();
+        assert!(output.status.success(), "{}", version);
+        let counts: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(counts["minecraft:diamond"], 5, "{}", version);
+    }
+}
+
+#[test]
+fn dump_items_reads_playerdata_with_the_dimensions() {
+    let world = fixture_copy("1.18", "dump-items-playerdata");
+    std::fs::create_dir(world.path().join("playerdata")).unwrap();
+    let player: NbtCompound = quartz_nbt::snbt::parse(
+        r#"{Pos: [10.5d, 64.0d, -3.5d], Inventory: [{Slot: 0b, id: "minecraft:diamond", Count: 2b}]}"#,
+    )
+    .unwrap();
+    write_dat(
+        &world
+            .path()
+            .join("playerdata/853c80ef-3c37-49fd-aa49-938b674adae6.dat"),
+        &player,
+    )
+    .unwrap();
+
+    let csv = stdout(
+        env!("CARGO_BIN_EXE_dump-items"),
+        &["--format", "csv", world.to_str(), "playerdata", "overworld"],
+    );
+    let mut rows: Vec<_> = csv.lines().skip(1).collect();
+    rows.sort();
+    assert_eq!(
+        rows,
+        [
+            "overworld,1,1,1,minecraft:chest,minecraft:diamond,5,",
+            "overworld,10,64,-4,minecraft:player,minecraft:diamond,2,",
+        ]
+    );
+}
+
+#[test]
+fn extract_chunk_reads_the_chest() {
+    for version in fixture_versions() {
+        let world = fixture(&version);
+        let world = world.to_str().unwrap();
+        let snbt = stdout(
+            env!("CARGO_BIN_EXE_extract-chunk"),
+            &[world, "overworld", "0", "0"],
+        );
+        assert!(snbt.contains("minecraft:chest"), "{}: {}", version, snbt);
+
+        let output = run(
+            env!("CARGO_BIN_EXE_extract-chunk"),
+            &[world, "overworld", "1", "0"],
+        );
+        assert!(!output.status.success(), "{}", version);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("chunk does not exist"));
+    }
+}
+
+#[test]
+fn merge_conflicts_finds_the_overlapping_chunk() {
+    let source = fixture("1.18");
+    let target = fixture("1.20.5");
+    let conflicts = |offset_x: &str| {
+        let output = stdout(
+            env!("CARGO_BIN_EXE_merge-conflicts"),
+            &[
+                "--offset-x",
+                offset_x,
+                source.to_str().unwrap(),
+                target.to_str().unwrap(),
+                "overworld",
+            ],
+        );
+        let report: serde_json::Value = serde_json::from_str(&output).unwrap();
+        report["overworld"]["conflicts"].as_u64().unwrap()
+    };
+    assert_eq!(conflicts("0"), 1);
+    assert_eq!(conflicts("-1"), 0);
+    assert_eq!(conflicts("32"), 0);
+}
+
+#[test]
+fn validate_chunks_accepts_the_fixtures() {
+    // compat-check reports validation as unsupported before 1.13
+    for version in fixture_versions()
+        .into_iter()
+        .filter(|version| version != "1.12")
+    {
+        // the fixtures have no nether or end, which are skipped with a notice
+        let world = fixture(&version);
+        let output = run(
+            env!("CARGO_BIN_EXE_validate-chunks"),
+            &[world.to_str().unwrap()],
+        );
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            output.status.success(),
+            "{}: {}{}",
+            version,
+            String::from_utf8_lossy(&output.stdout),
+            stderr
+        );
+        assert!(
+            stderr.contains("notice: no region folder in nether"),
+            "{}",
+            stderr
+        );
+        assert!(stderr.contains("chunks are invalid"), "{}", stderr);
+    }
+}
+
+#[test]
+fn sources_find_the_entities_in_old_chunks() {
+    for version in fixture_versions() {
+        let world = fixture(&version);
+        let sources = Sources::detect(&world, &["overworld".to_string()]);
+        // entities moved into their own folder in 1.17
+        let old = ["1.12", "1.13", "1.15", "1.16"].contains(&version.as_str());
+        assert_eq!(
+            sources
+                .entities_in_chunks
+                .contains(&"overworld".to_string()),
+            old,
+            "{}",
+            version
+        );
+    }
+}
+
+#[test]
+fn readers_run_on_every_version() {
+    for version in fixture_versions() {
+        let world = fixture(&version);
+        let world = world.to_str().unwrap();
+
+        let report: serde_json::Value =
+            serde_json::from_str(&stdout(env!("CARGO_BIN_EXE_report"), &[world, "overworld"]))
+                .unwrap();
+        assert_eq!(report["overworld"]["chunks"]["chunks"], 1, "{}", version);
+        assert_eq!(
+            report["overworld"]["items"]["minecraft:diamond"], 5,
+            "{}",
+            version
+        );
+        assert_eq!(
+            report["overworld"]["entities"]["minecraft:pig"], 1,
+            "{}",
+            version
+        );
+
+        let stacks = stdout(
+            env!("CARGO_BIN_EXE_entity-stacks"),
+            &["--min-height", "1", world, "overworld"],
+        );
+        assert_eq!(
+            stacks, "overworld 8 1 8 height 1 entities 1: minecraft:pig\n",
+            "{}",
+            version
+        );
+
+        let raid_stats: serde_json::Value = serde_json::from_str(&stdout(
+            env!("CARGO_BIN_EXE_raid-stats"),
+            &["--item", "diamond", world, "overworld"],
+        ))
+        .unwrap();
+        assert_eq!(raid_stats["storage"], 5, "{}", version);
+        assert_eq!(raid_stats["raiders"], serde_json::json!({}), "{}", version);
+
+        // nothing in the fixtures is modded, rare or lagging, which a misread chunk would change
+        let residue = stdout(env!("CARGO_BIN_EXE_mod-residue"), &[world]);
+        assert_eq!(residue.trim(), "{}", "{}", version);
+        let rare_items: serde_json::Value =
+            serde_json::from_str(&stdout(env!("CARGO_BIN_EXE_rare-items"), &[world])).unwrap();
+        for (category, distribution) in rare_items.as_object().unwrap() {
+            assert_eq!(distribution["total"], 0, "{}: {}", version, category);
+        }
+        let lag = stdout(env!("CARGO_BIN_EXE_lag-score"), &[world]);
+        assert!(lag.is_empty(), "{}: {}", version, lag);
+        let banned = stdout(env!("CARGO_BIN_EXE_banned-items"), &[world]);
+        assert!(banned.is_empty(), "{}: {}", version, banned);
+    }
+}
+
+#[test]
+fn banned_items_finds_the_diamonds() {
+    for version in fixture_versions() {
+        let world = fixture(&version);
+        let output = run(
+            env!("CARGO_BIN_EXE_banned-items"),
+            &["--items", "diamond", world.to_str().unwrap(), "overworld"],
+        );
+        assert!(output.status.success(), "{}", version);
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "overworld 1 1 1 minecraft:diamond 5\n",
+            "{}",
+            version
+        );
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("found 5 items"), "{}: {}", version, stderr);
+    }
+}
+
+#[test]
+fn banned_items_removes_items_inside_shulker_boxes() {
+    let world = fixture_copy("1.20.5", "banned-items-shulker");
+    let path = world.path().join("region/r.0.0.mca");
+    let mut region_file = RegionFile::new(
+        std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap(),
+    )
+    .unwrap();
+    let mut chunk = read_chunk(&region_file.read_payload(0).unwrap().unwrap()).unwrap();
+    let shulker_box: NbtCompound = quartz_nbt::snbt::parse(
+        r#"{Slot: 2b, id: "minecraft:shulker_box", count: 1, components: {
+            "minecraft:container": [{slot: 0, item: {id: "minecraft:bedrock", count: 3}}]
+        }}"#,
+    )
+    .unwrap();
+    let block_entities = chunk.get_mut::<_, &mut NbtList>("block_entities").unwrap();
+    let chest = block_entities
+        .iter_mut_map::<&mut NbtCompound>()
+        .flatten()
+        .find(|block_entity| block_entity.get::<_, &str>("id").ok() == Some("minecraft:chest"))
+        .unwrap();
+    chest
+        .get_mut::<_, &mut NbtList>("Items")
+        .unwrap()
+        .push(shulker_box);
+    region_file
+        .write_chunk(0, &encode_chunk(&chunk).unwrap())
+        .unwrap();
+
+    let banned_items = |args: &[&str]| {
+        let output = run(
+            env!("CARGO_BIN_EXE_banned-items"),
+            &[args, &[world.to_str(), "overworld"]].concat(),
+        );
+        assert!(output.status.success());
+        (
+            String::from_utf8(output.stdout).unwrap(),
+            String::from_utf8(output.stderr).unwrap(),
+        )
+    };
+    let (found, _) = banned_items(&[]);
+    assert_eq!(found, "overworld 1 1 1 minecraft:bedrock 3\n");
+    let (_, stderr) = banned_items(&["--remove"]);
+    assert!(stderr.contains("removed 3 items"), "{}", stderr);
+    let (found, stderr) = banned_items(&[]);
+    assert!(found.is_empty(), "{}", found);
+    assert!(stderr.contains("found 0 items"), "{}", stderr);
+    let (found, _) = banned_items(&["--items", "diamond"]);
+    assert_eq!(found, "overworld 1 1 1 minecraft:diamond 5\n");
+}
+
+#[test]
+fn anonymize_refuses_to_run_without_a_salt() {
+    let world = fixture("1.18");
+    let output = Command::new(env!("CARGO_BIN_EXE_leaderboard"))
+        .args(["--anonymize", world.to_str().unwrap()])
+        .env_remove("WORLD_STATISTICS_ANONYMIZE_SALT")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("needs a secret salt"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_leaderboard"))
+        .args(["--anonymize", world.to_str().unwrap()])
+        .env("WORLD_STATISTICS_ANONYMIZE_SALT", "secret")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+}
+
+#[test]
+fn history_scan_records_the_diamonds() {
+    for version in fixture_versions() {
+        let store = std::env::temp_dir().join(format!(
+            "world-statistics-history-{}-{}",
+            version,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&store);
+        let world = fixture(&version);
+        stdout(
+            env!("CARGO_BIN_EXE_history"),
+            &[
+                store.to_str().unwrap(),
+                "scan",
+                world.to_str().unwrap(),
+                "overworld",
+            ],
+        );
+        let csv = stdout(
+            env!("CARGO_BIN_EXE_history"),
+            &[store.to_str().unwrap(), "export", "diamond"],
+        );
+        let row = csv.lines().nth(1).unwrap_or_default();
+        assert!(row.ends_with(",5"), "{}: {}", version, csv);
+        let timestamp = row.split(',').next().unwrap();
+        assert!(
+            store
+                .join(format!("{}.fingerprints.json", timestamp))
+                .exists(),
+            "{}",
+            version
+        );
+
+        // a snapshot that isn't older than the current time is never overwritten
+        std::fs::write(store.join("99999999999.json"), "{}").unwrap();
+        let output = run(
+            env!("CARGO_BIN_EXE_history"),
+            &[
+                store.to_str().unwrap(),
+                "scan",
+                world.to_str().unwrap(),
+                "overworld",
+            ],
+        );
+        assert!(!output.status.success(), "{}", version);
+        assert_eq!(
+            std::fs::read_to_string(store.join("99999999999.json")).unwrap(),
+            "{}"
+        );
+        std::fs::remove_dir_all(&store).unwrap();
+    }
+}
+
+#[test]
+fn purge_entities_removes_the_pig() {
+    for version in fixture_versions() {
+        let world = fixture_copy(&version, "purge-entities");
+        let output = run(
+            env!("CARGO_BIN_EXE_purge-entities"),
+            &["--types", "pig", "--apply", world.to_str(), "overworld"],
+        );
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "{}: {}", version, stderr);
+        assert!(
+            stderr.contains("removed 1 entities"),
+            "{}: {}",
+            version,
+            stderr
+        );
+
+        let report: serde_json::Value = serde_json::from_str(&stdout(
+            env!("CARGO_BIN_EXE_report"),
+            &[world.to_str(), "overworld"],
+        ))
+        .unwrap();
+        assert_eq!(
+            report["overworld"]["entities"],
+            serde_json::json!({}),
+            "{}",
+            version
+        );
+        assert_eq!(
+            report["overworld"]["items"]["minecraft:diamond"], 5,
+            "{}",
+            version
+        );
+    }
+}
+
+#[test]
+fn purge_entities_reads_string_owners() {
+    let world = fixture_copy("1.16", "purge-entities-owner");
+    std::fs::create_dir(world.path().join("playerdata")).unwrap();
+    let missing_owner = || {
+        let output = run(
+            env!("CARGO_BIN_EXE_purge-entities"),
+            &["--missing-owner", world.to_str(), "overworld"],
+        );
+        assert!(output.status.success());
+        String::from_utf8(output.stderr).unwrap()
+    };
+    // the pig isn't tamed
+    assert!(missing_owner().contains("would remove 0 entities"));
+
+    let path = world.path().join("region/r.0.0.mca");
+    let mut region_file = RegionFile::new(
+        std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap(),
+    )
+    .unwrap();
+    let mut chunk = read_chunk(&region_file.read_payload(0).unwrap().unwrap()).unwrap();
+    let pig = chunk
+        .get_mut::<_, &mut NbtCompound>("Level")
+        .unwrap()
+        .get_mut::<_, &mut NbtList>("Entities")
+        .unwrap()
+        .get_mut::<&mut NbtCompound>(0)
+        .unwrap();
+    pig.insert("OwnerUUID", "853C80EF-3C37-49FD-AA49-938B674ADAE6");
+    region_file
+        .write_chunk(0, &encode_chunk(&chunk).unwrap())
+        .unwrap();
+    assert!(missing_owner().contains("would remove 1 entities"));
+
+    std::fs::write(
+        world
+            .path()
+            .join("playerdata/853c80ef-3c37-49fd-aa49-938b674adae6.dat"),
+        [],
+    )
+    .unwrap();
+    assert!(missing_owner().contains("would remove 0 entities"));
+}
@@ -0,0 +1,63 @@
+//! `--dry-run` leaves the world unchanged, and `undo` reverts what a tool recorded with
+//! `--journal`
+
+mod common;
+
+use common::{fixture_copy, run, snapshot};
+
+#[test]
+fn banned_items_dry_run_leaves_world_unchanged() {
+    let world = fixture_copy("1.18", "banned-items-dry-run");
+    let before = snapshot(world.path());
+    let output = run(
+        env!("CARGO_BIN_EXE_banned-items"),
+        &[
+            "--items",
+            "diamond",
+            "--remove",
+            "--dry-run",
+            world.to_str(),
+            "overworld",
+        ],
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("would remove 5 items"));
+    assert!(snapshot(world.path()) == before);
+}
+
+#[test]
+fn undo_puts_back_removed_banned_items() {
+    let world = fixture_copy("1.18", "banned-items-undo");
+    let journal = world.path().join("removed.jsonl");
+    let find = || {
+        run(
+            env!("CARGO_BIN_EXE_banned-items"),
+            &["--items", "diamond", world.to_str(), "overworld"],
+        )
+        .stdout
+    };
+    let found = find();
+    assert!(!found.is_empty());
+
+    let output = run(
+        env!("CARGO_BIN_EXE_banned-items"),
+        &[
+            "--items",
+            "diamond",
+            "--remove",
+            "--journal",
+            journal.to_str().unwrap(),
+            world.to_str(),
+            "overworld",
+        ],
+    );
+    assert!(output.status.success());
+    assert!(find().is_empty());
+
+    let output = run(
+        env!("CARGO_BIN_EXE_undo"),
+        &[journal.to_str().unwrap(), world.to_str()],
+    );
+    assert!(output.status.success());
+    assert_eq!(find(), found);
+}
@@ -0,0 +1,170 @@
+//! The tools only read a world that is open in a running game or server, and the ones that write
+//! refuse to unless `--force` is given
+
+#![cfg(unix)]
+
+mod common;
+
+use std::{
+    fs::{self, OpenOptions},
+    os::unix::io::AsRawFd,
+    path::Path,
+};
+
+use common::{fixture_copy, run, snapshot};
+use quartz_nbt::NbtCompound;
+use world_statistics::{dat::write_dat, world::session_locked};
+
+/// A child process holding the lock on a world's `session.lock` like a running game, which
+/// `fcntl` only reports to other processes
+struct LockHolder {
+    pid: libc::pid_t,
+}
+
+impl LockHolder {
+    fn new(world: &Path) -> Self {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(world.join("session.lock"))
+            .unwrap();
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+
+        // SAFETY: the child only makes async-signal-safe calls until it is killed
+        match unsafe { libc::fork() } {
+            -1 => panic!("fork failed"),
+            0 => unsafe {
+                let mut lock: libc::flock = std::mem::zeroed();
+                lock.l_type = libc::F_WRLCK as _;
+                lock.l_whence = libc::SEEK_SET as _;
+                libc::fcntl(file.as_raw_fd(), libc::F_SETLKW, &lock);
+                libc::write(fds[1], b"l".as_ptr().cast(), 1);
+                loop {
+                    libc::pause();
+                }
+            },
+            pid => {
+                let mut buf = [0u8; 1];
+                assert_eq!(unsafe { libc::read(fds[0], buf.as_mut_ptr().cast(), 1) }, 1);
+                unsafe {
+                    libc::close(fds[0]);
+                    libc::close(fds[1]);
+                }
+                LockHolder { pid }
+            }
+        }
+    }
+}
+
+impl Drop for LockHolder {
+    fn drop(&mut self) {
+        unsafe {
+            libc::kill(self.pid, libc::SIGKILL);
+            libc::waitpid(self.pid, std::ptr::null_mut(), 0);
+        }
+    }
+}
+
+#[test]
+fn held_lock_is_detected() {
+    let world = fixture_copy("1.18", "held-lock");
+    assert!(!session_locked(world.path()).unwrap());
+    let holder = LockHolder::new(world.path());
+    assert!(session_locked(world.path()).unwrap());
+    drop(holder);
+    assert!(!session_locked(world.path()).unwrap());
+}
+
+#[test]
+fn readers_warn_and_leave_an_open_world_unchanged() {
+    let world = fixture_copy("1.18", "open-readers");
+    let world_str = world.to_str();
+    // history-from-backups only measures worlds with a level.dat
+    let level: NbtCompound = quartz_nbt::snbt::parse("{Data: {LastPlayed: 0L}}").unwrap();
+    write_dat(&world.path().join("level.dat"), &level).unwrap();
+    // a directory of backups with the open world among them
+    let backups = world.path().with_extension("backups");
+    let _ = fs::remove_dir_all(&backups);
+    fs::create_dir(&backups).unwrap();
+    std::os::unix::fs::symlink(world.path(), backups.join("live")).unwrap();
+    let store = world.path().with_extension("history");
+    let _ = fs::remove_dir_all(&store);
+
+    let _holder = LockHolder::new(world.path());
+    let before = snapshot(world.path());
+
+    for (bin, args) in [
+        (
+            env!("CARGO_BIN_EXE_dump-items"),
+            vec![world_str, "overworld"],
+        ),
+        (
+            env!("CARGO_BIN_EXE_validate-chunks"),
+            vec![world_str, "overworld"],
+        ),
+        (env!("CARGO_BIN_EXE_mod-residue"), vec![world_str]),
+        (
+            env!("CARGO_BIN_EXE_history"),
+            vec![store.to_str().unwrap(), "scan", world_str, "overworld"],
+        ),
+        (
+            env!("CARGO_BIN_EXE_diff-frames"),
+            vec![world_str, world_str, "overworld"],
+        ),
+        (env!("CARGO_BIN_EXE_playtime"), vec![world_str]),
+        (
+            env!("CARGO_BIN_EXE_history-from-backups"),
+            vec![backups.to_str().unwrap(), "overworld"],
+        ),
+    ] {
+        let output = run(bin, &args);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "{}: {}", bin, stderr);
+        assert!(
+            stderr.contains("WARNING: the world is open"),
+            "{}: {}",
+            bin,
+            stderr
+        );
+    }
+
+    assert!(
+        snapshot(world.path()) == before,
+        "a reader changed the world"
+    );
+    fs::remove_dir_all(&backups).unwrap();
+    fs::remove_dir_all(&store).unwrap();
+}
+
+#[test]
+fn writers_refuse_an_open_world_without_force() {
+    let world = fixture_copy("1.18", "open-writers");
+    let world_str = world.to_str();
+    let holder = LockHolder::new(world.path());
+    let before = snapshot(world.path());
+
+    let output = run(
+        env!("CARGO_BIN_EXE_delete-chunks"),
+        &[world_str, "overworld", "0,0"],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("pass --force"));
+    assert!(
+        snapshot(world.path()) == before,
+        "delete-chunks wrote without --force"
+    );
+
+    let output = run(
+        env!("CARGO_BIN_EXE_delete-chunks"),
+        &["--force", world_str, "overworld", "0,0"],
+    );
+    assert!(output.status.success());
+    assert!(
+        snapshot(world.path()) != before,
+        "delete-chunks didn't write with --force"
+    );
+    drop(holder);
+}